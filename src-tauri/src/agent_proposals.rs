@@ -0,0 +1,385 @@
+//! Pending agent write proposals, held for review in the app.
+//!
+//! While MCP writes are in propose mode, the write tools that can propose
+//! validate a change exactly as they would write it and then record the tool
+//! call here instead. Like agent-write markers, proposals live in an owner-only
+//! spool beside app configuration, one file each, so they never show up in a
+//! Forge and concurrent MCP processes never rewrite shared state.
+//!
+//! Accepting a proposal replays its call through the MCP tools with writes
+//! allowed, as the client that proposed it. Every check runs again against the
+//! note as it is then: a note created in the meantime refuses a proposed
+//! `create_note`, and a `write_note` made stale by later edits keeps those
+//! edits as a conflict copy, just as a direct write with `baseHash` would.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::agent_writes::{
+    ensure_spool_dir, normalized_client, now_ms, unique_stem, valid_rel_path,
+};
+use crate::persist::write_atomic;
+
+/// Tools whose calls a proposal may record. `create_note_from_template` is
+/// recorded as the `create_note` it expands to, so accepting it later cannot
+/// change what was reviewed.
+const PROPOSAL_TOOLS: [&str; 3] = ["create_note", "append_to_daily_note", "write_note"];
+const MAX_PROPOSAL_BYTES: u64 = 16 * 1024 * 1024;
+const SPOOL_NAME: &str = "agent proposal spool";
+const NOT_FOUND: &str = "Proposal not found; it may already have been accepted or rejected";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredProposal {
+    forge_root: String,
+    rel_path: String,
+    tool: String,
+    arguments: Value,
+    client: Option<String>,
+    proposed_at_ms: u64,
+}
+
+/// A pending proposal as the app lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentProposal {
+    pub(crate) id: String,
+    pub(crate) rel_path: String,
+    pub(crate) tool: String,
+    pub(crate) client: Option<String>,
+    pub(crate) proposed_at_ms: u64,
+}
+
+/// What accepting a proposal would change, against the note as it is now.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentProposalDiff {
+    pub(crate) diff: String,
+    pub(crate) content_hash: String,
+    /// The note changed after the proposal was made, so accepting it keeps
+    /// the current version as a conflict copy.
+    pub(crate) conflict: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AcceptedProposal {
+    pub(crate) rel_path: String,
+    pub(crate) conflict_copy: Option<String>,
+}
+
+/// Resolve the global proposal spool without depending on an initialized Tauri app.
+pub(crate) fn spool_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("Moldavite").join("agent-proposals"))
+}
+
+/// Record a tool call for review and return the proposal id. The caller has
+/// already validated the call as a dry run.
+pub(crate) fn record_at(
+    spool: &Path,
+    forge_root: &Path,
+    rel_path: &str,
+    tool: &str,
+    arguments: &Value,
+    client: Option<&str>,
+) -> Result<String, String> {
+    let proposal = StoredProposal {
+        forge_root: forge_root.to_string_lossy().into_owned(),
+        rel_path: rel_path.to_string(),
+        tool: tool.to_string(),
+        arguments: arguments.clone(),
+        client: normalized_client(client),
+        proposed_at_ms: now_ms(),
+    };
+    if !proposal_is_valid(&proposal) {
+        return Err("Invalid agent proposal".to_string());
+    }
+    ensure_spool_dir(spool, SPOOL_NAME)?;
+    let json = serde_json::to_vec(&proposal)
+        .map_err(|error| format!("Failed to encode agent proposal: {error}"))?;
+    if json.len() as u64 > MAX_PROPOSAL_BYTES {
+        return Err("The proposed change is too large to hold for review".to_string());
+    }
+    let id = unique_stem();
+    write_atomic(&spool.join(format!("{id}.json")), &json, Some(0o600))?;
+    Ok(id)
+}
+
+/// Pending proposals for one Forge, oldest first. Unreadable entries are
+/// skipped and malformed ones removed; listing must never fail the app.
+pub(crate) fn list_from(spool: &Path, forge_root: &Path) -> Vec<AgentProposal> {
+    let Ok(entries) = fs::read_dir(spool) else {
+        return Vec::new();
+    };
+    let forge_root = forge_root.to_string_lossy();
+    let mut proposals: Vec<AgentProposal> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let id = path
+                .file_name()?
+                .to_str()?
+                .strip_suffix(".json")
+                .filter(|id| valid_id(id))?
+                .to_string();
+            let proposal = match read_proposal(&path) {
+                Ok(Some(proposal)) => proposal,
+                Ok(None) => {
+                    let _ = fs::remove_file(&path);
+                    return None;
+                }
+                Err(_) => return None,
+            };
+            (proposal.forge_root == forge_root).then_some(AgentProposal {
+                id,
+                rel_path: proposal.rel_path,
+                tool: proposal.tool,
+                client: proposal.client,
+                proposed_at_ms: proposal.proposed_at_ms,
+            })
+        })
+        .collect();
+    proposals.sort_by(|a, b| (a.proposed_at_ms, &a.id).cmp(&(b.proposed_at_ms, &b.id)));
+    proposals
+}
+
+/// Diff a proposal against the note on disk by replaying it as a dry run.
+pub(crate) fn diff_at<F>(
+    spool: &Path,
+    forge_root: &Path,
+    id: &str,
+    replay: F,
+) -> Result<AgentProposalDiff, String>
+where
+    F: FnOnce(&Path, &str, &Value, Option<&str>) -> Result<Value, String>,
+{
+    let proposal = load(&proposal_path(spool, id)?, forge_root)?;
+    let mut arguments = proposal.arguments;
+    arguments["dryRun"] = Value::Bool(true);
+    let preview = replay(
+        forge_root,
+        &proposal.tool,
+        &arguments,
+        proposal.client.as_deref(),
+    )?;
+    Ok(AgentProposalDiff {
+        diff: preview["diff"].as_str().unwrap_or_default().to_string(),
+        content_hash: preview["contentHash"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        conflict: preview["conflict"].as_bool().unwrap_or(false),
+    })
+}
+
+/// Apply a proposal. It is claimed first, so two accepts of the same proposal
+/// cannot both apply it; a failed replay puts it back for another try.
+pub(crate) fn accept_at<F>(
+    spool: &Path,
+    forge_root: &Path,
+    id: &str,
+    replay: F,
+) -> Result<AcceptedProposal, String>
+where
+    F: FnOnce(&Path, &str, &Value, Option<&str>) -> Result<Value, String>,
+{
+    let path = proposal_path(spool, id)?;
+    let proposal = load(&path, forge_root)?;
+    let claimed = spool.join(format!("{id}.accepting"));
+    fs::rename(&path, &claimed).map_err(|_| NOT_FOUND.to_string())?;
+    match replay(
+        forge_root,
+        &proposal.tool,
+        &proposal.arguments,
+        proposal.client.as_deref(),
+    ) {
+        Ok(result) => {
+            let _ = fs::remove_file(&claimed);
+            Ok(AcceptedProposal {
+                rel_path: proposal.rel_path,
+                conflict_copy: result["conflictCopy"].as_str().map(str::to_string),
+            })
+        }
+        Err(error) => {
+            let _ = fs::rename(&claimed, &path);
+            Err(error)
+        }
+    }
+}
+
+pub(crate) fn reject_at(spool: &Path, forge_root: &Path, id: &str) -> Result<(), String> {
+    let path = proposal_path(spool, id)?;
+    load(&path, forge_root)?;
+    fs::remove_file(&path).map_err(|_| NOT_FOUND.to_string())
+}
+
+fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .bytes()
+            .all(|byte| byte == b'-' || byte.is_ascii_hexdigit())
+}
+
+fn proposal_path(spool: &Path, id: &str) -> Result<PathBuf, String> {
+    if !valid_id(id) {
+        return Err("Invalid proposal id".to_string());
+    }
+    Ok(spool.join(format!("{id}.json")))
+}
+
+fn proposal_is_valid(proposal: &StoredProposal) -> bool {
+    !proposal.forge_root.is_empty()
+        && valid_rel_path(&proposal.rel_path)
+        && PROPOSAL_TOOLS.contains(&proposal.tool.as_str())
+        && proposal.arguments.is_object()
+        && proposal.client.as_deref().map_or(true, |client| {
+            normalized_client(Some(client)).as_deref() == Some(client)
+        })
+}
+
+/// `Ok(None)` for an entry that is not a valid proposal file.
+fn read_proposal(path: &Path) -> Result<Option<StoredProposal>, String> {
+    let metadata = fs::symlink_metadata(path).map_err(|_| NOT_FOUND.to_string())?;
+    if !metadata.is_file() || metadata.len() > MAX_PROPOSAL_BYTES {
+        return Ok(None);
+    }
+    let raw = fs::read(path).map_err(|error| format!("Failed to read proposal: {error}"))?;
+    Ok(serde_json::from_slice::<StoredProposal>(&raw)
+        .ok()
+        .filter(proposal_is_valid))
+}
+
+/// Read one proposal, which must belong to `forge_root`: the app only acts
+/// on proposals for the Forge it has open.
+fn load(path: &Path, forge_root: &Path) -> Result<StoredProposal, String> {
+    match read_proposal(path)? {
+        Some(proposal) if proposal.forge_root == forge_root.to_string_lossy() => Ok(proposal),
+        _ => Err(NOT_FOUND.to_string()),
+    }
+}
+
+fn required_spool() -> Result<PathBuf, String> {
+    spool_dir().ok_or_else(|| "No configuration folder for agent proposals".to_string())
+}
+
+#[tauri::command]
+pub(crate) fn list_agent_proposals() -> Vec<AgentProposal> {
+    let Some(spool) = spool_dir() else {
+        return Vec::new();
+    };
+    list_from(&spool, &crate::paths::get_notes_dir())
+}
+
+#[tauri::command]
+pub(crate) fn get_agent_proposal_diff(id: String) -> Result<AgentProposalDiff, String> {
+    diff_at(
+        &required_spool()?,
+        &crate::paths::get_notes_dir(),
+        &id,
+        crate::mcp::replay_proposal,
+    )
+}
+
+#[tauri::command]
+pub(crate) fn accept_agent_proposal(id: String) -> Result<AcceptedProposal, String> {
+    accept_at(
+        &required_spool()?,
+        &crate::paths::get_notes_dir(),
+        &id,
+        crate::mcp::replay_proposal,
+    )
+}
+
+#[tauri::command]
+pub(crate) fn reject_agent_proposal(id: String) -> Result<(), String> {
+    reject_at(&required_spool()?, &crate::paths::get_notes_dir(), &id)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "moldavite-agent-proposals-{tag}-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn unused_replay(_: &Path, _: &str, _: &Value, _: Option<&str>) -> Result<Value, String> {
+        panic!("replay must not run")
+    }
+
+    #[test]
+    fn proposals_are_owner_only_and_scoped_to_their_forge() {
+        let temp = temp_dir("scoped");
+        let spool = temp.join("spool");
+        let (forge, other) = (temp.join("forge"), temp.join("other"));
+        let call = json!({ "path": "notes/a.md", "content": "new" });
+        let id = record_at(
+            &spool,
+            &forge,
+            "notes/a.md",
+            "create_note",
+            &call,
+            Some(" Agent "),
+        )
+        .unwrap();
+        record_at(&spool, &other, "notes/b.md", "create_note", &call, None).unwrap();
+        assert!(record_at(&spool, &forge, "notes/a.md", "trash_note", &call, None).is_err());
+        assert!(record_at(&spool, &forge, "../a.md", "create_note", &call, None).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(spool.join(format!("{id}.json")))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let listed = list_from(&spool, &forge);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, id);
+        assert_eq!(listed[0].client.as_deref(), Some("Agent"));
+        assert_eq!(reject_at(&spool, &other, &id), Err(NOT_FOUND.to_string()));
+        assert!(accept_at(&spool, &other, &id, unused_replay).is_err());
+        reject_at(&spool, &forge, &id).unwrap();
+        assert!(list_from(&spool, &forge).is_empty());
+        assert_eq!(list_from(&spool, &other).len(), 1);
+        let _ = fs::remove_dir_all(temp);
+    }
+
+    #[test]
+    fn ids_cannot_leave_the_spool_and_malformed_entries_are_dropped() {
+        let temp = temp_dir("malformed");
+        let spool = temp.join("spool");
+        fs::create_dir_all(&spool).unwrap();
+        write_atomic(&spool.join("abc.json"), b"{not json", Some(0o600)).unwrap();
+        write_atomic(&temp.join("outside.json"), b"{}", Some(0o600)).unwrap();
+
+        assert!(list_from(&spool, &temp).is_empty());
+        assert!(!spool.join("abc.json").exists());
+        for id in ["../outside", "", "ABC/../x", "abc.json"] {
+            assert_eq!(
+                reject_at(&spool, &temp, id),
+                Err("Invalid proposal id".to_string())
+            );
+        }
+        assert!(temp.join("outside.json").exists());
+        let _ = fs::remove_dir_all(temp);
+    }
+}
@@ -0,0 +1,596 @@
+//! Pre-write snapshots of notes changed through MCP, for undoing an agent.
+//!
+//! Every note write an MCP session makes first records what the note held
+//! before (or that it did not exist) and a hash of what the agent wrote. The
+//! snapshots live in an owner-only spool beside app configuration, one file
+//! per write like agent-write markers, and are grouped by the session that
+//! made them. Restoring a session's changes puts back the note as it was
+//! before that session's first write to it; a note edited after the agent's
+//! last write keeps that version as a conflict copy, as saves over an external
+//! edit do.
+//!
+//! A rename or move is recorded as a snapshot of the note at its new path
+//! that names the old one, and the notes whose links it rewrote are
+//! snapshotted like any other write. A note's snapshots follow it across such
+//! moves, and restoring it also puts it back where the session found it.
+//! Trashing is not snapshotted; trashed notes come back from the Trash.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::agent_writes::{
+    ensure_spool_dir, normalized_client, now_ms, unique_stem, valid_rel_path,
+};
+use crate::commands::notes::{restore_note_snapshot, sha256_hex};
+use crate::persist::write_atomic;
+
+/// Snapshots are kept this long, then pruned by later writes.
+const SNAPSHOT_TTL_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+const MAX_SNAPSHOT_BYTES: u64 = 32 * 1024 * 1024;
+const SPOOL_NAME: &str = "agent snapshot spool";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    forge_root: String,
+    rel_path: String,
+    session_id: String,
+    client: Option<String>,
+    taken_at_ms: u64,
+    /// The raw note before the write, or `None` when the write created it.
+    before: Option<String>,
+    /// SHA-256 of the raw note the write produced.
+    written_hash: String,
+    /// Where a renamed or moved note was before; the move left its content
+    /// alone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    moved_from: Option<String>,
+}
+
+/// One MCP session's writes in a Forge, as the app lists them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentSession {
+    pub(crate) session_id: String,
+    pub(crate) client: Option<String>,
+    pub(crate) first_write_ms: u64,
+    pub(crate) last_write_ms: u64,
+    pub(crate) changes: Vec<AgentChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentChange {
+    pub(crate) rel_path: String,
+    pub(crate) writes: usize,
+    /// The session created the note, so restoring it removes the note.
+    pub(crate) created: bool,
+    /// Where the note was before the session renamed or moved it.
+    pub(crate) renamed_from: Option<String>,
+    pub(crate) last_write_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RestoredNote {
+    pub(crate) rel_path: String,
+    pub(crate) removed: bool,
+    /// Where a renamed or moved note was put back.
+    pub(crate) moved_back_to: Option<String>,
+    pub(crate) conflict_copy: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+/// Resolve the global snapshot spool without depending on an initialized Tauri app.
+pub(crate) fn spool_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("Moldavite").join("agent-snapshots"))
+}
+
+/// Snapshot `rel_path` before a session writes `raw_content` to it, and
+/// return the snapshot file so a failed write can remove it. Unlike
+/// attribution markers this is not best-effort: a write that could not be
+/// undone is refused.
+pub(crate) fn record_at(
+    spool: &Path,
+    forge_root: &Path,
+    rel_path: &str,
+    session_id: &str,
+    client: Option<&str>,
+    raw_content: &str,
+) -> Result<PathBuf, String> {
+    if !valid_rel_path(rel_path) || !valid_session_id(session_id) {
+        return Err("Invalid agent snapshot".to_string());
+    }
+    let before = match fs::read_to_string(forge_root.join(rel_path)) {
+        Ok(before) => Some(before),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(format!("Failed to snapshot note before writing: {error}")),
+    };
+    save_at(
+        spool,
+        Snapshot {
+            forge_root: forge_root.to_string_lossy().into_owned(),
+            rel_path: rel_path.to_string(),
+            session_id: session_id.to_string(),
+            client: normalized_client(client),
+            taken_at_ms: now_ms(),
+            before,
+            written_hash: sha256_hex(raw_content),
+            moved_from: None,
+        },
+    )
+}
+
+/// Record that a session renamed or moved the note at `from` to `to`, once
+/// the move is done, so restoring the session can move it back.
+pub(crate) fn record_move_at(
+    spool: &Path,
+    forge_root: &Path,
+    from: &str,
+    to: &str,
+    session_id: &str,
+    client: Option<&str>,
+) -> Result<PathBuf, String> {
+    if !valid_rel_path(from) || !valid_rel_path(to) || !valid_session_id(session_id) {
+        return Err("Invalid agent snapshot".to_string());
+    }
+    let raw = fs::read_to_string(forge_root.join(to))
+        .map_err(|error| format!("Failed to snapshot moved note: {error}"))?;
+    save_at(
+        spool,
+        Snapshot {
+            forge_root: forge_root.to_string_lossy().into_owned(),
+            rel_path: to.to_string(),
+            session_id: session_id.to_string(),
+            client: normalized_client(client),
+            taken_at_ms: now_ms(),
+            written_hash: sha256_hex(&raw),
+            before: Some(raw),
+            moved_from: Some(from.to_string()),
+        },
+    )
+}
+
+fn save_at(spool: &Path, snapshot: Snapshot) -> Result<PathBuf, String> {
+    ensure_spool_dir(spool, SPOOL_NAME)?;
+    prune_spool_at(spool, snapshot.taken_at_ms);
+    let json = serde_json::to_vec(&snapshot)
+        .map_err(|error| format!("Failed to encode agent snapshot: {error}"))?;
+    let path = spool.join(format!("{}.json", unique_stem()));
+    write_atomic(&path, &json, Some(0o600))?;
+    Ok(path)
+}
+
+/// Sessions that changed notes in `forge_root`, most recent first.
+pub(crate) fn list_from(spool: &Path, forge_root: &Path) -> Vec<AgentSession> {
+    let mut sessions: Vec<AgentSession> = grouped(spool, forge_root)
+        .into_iter()
+        .map(|(session_id, notes)| {
+            let snapshots = || notes.values().flatten().map(|(_, snapshot)| snapshot);
+            AgentSession {
+                client: snapshots().find_map(|snapshot| snapshot.client.clone()),
+                first_write_ms: snapshots().map(|s| s.taken_at_ms).min().unwrap_or_default(),
+                last_write_ms: snapshots().map(|s| s.taken_at_ms).max().unwrap_or_default(),
+                changes: notes
+                    .iter()
+                    .map(|(rel_path, snapshots)| AgentChange {
+                        rel_path: rel_path.clone(),
+                        writes: snapshots
+                            .iter()
+                            .filter(|(_, snapshot)| snapshot.moved_from.is_none())
+                            .count(),
+                        created: snapshots[0].1.before.is_none(),
+                        renamed_from: renamed_from(rel_path, snapshots).map(str::to_string),
+                        last_write_ms: snapshots[snapshots.len() - 1].1.taken_at_ms,
+                    })
+                    .collect(),
+                session_id,
+            }
+        })
+        .collect();
+    sessions
+        .sort_by(|a, b| (b.last_write_ms, &b.session_id).cmp(&(a.last_write_ms, &a.session_id)));
+    sessions
+}
+
+/// Restore every note a session changed, or only `paths`. Each note is
+/// restored on its own: one that fails, say because it is now locked, is
+/// reported and keeps its snapshots while the rest go ahead.
+pub(crate) fn restore_at(
+    spool: &Path,
+    forge_root: &Path,
+    session_id: &str,
+    paths: Option<&[String]>,
+) -> Result<Vec<RestoredNote>, String> {
+    let Some(notes) = grouped(spool, forge_root).remove(session_id) else {
+        return Err("No changes recorded for this agent session".to_string());
+    };
+    let mut restored = Vec::new();
+    for (rel_path, snapshots) in notes {
+        if paths.is_some_and(|paths| !paths.contains(&rel_path)) {
+            continue;
+        }
+        let first = &snapshots[0].1;
+        let last = &snapshots[snapshots.len() - 1].1;
+        // A note the session created is removed wherever it ended up.
+        let moved_back_to = renamed_from(&rel_path, &snapshots).filter(|_| first.before.is_some());
+        let result = match moved_back_to {
+            Some(from) => restore_moved_note(
+                forge_root,
+                &rel_path,
+                from,
+                &last.written_hash,
+                first.before.as_deref(),
+            ),
+            None => restore_note(
+                forge_root,
+                &rel_path,
+                &last.written_hash,
+                first.before.as_deref(),
+            ),
+        };
+        if result.is_ok() {
+            for (file, _) in &snapshots {
+                let _ = fs::remove_file(file);
+            }
+        }
+        let moved_back_to = moved_back_to.map(str::to_string);
+        restored.push(match result {
+            Ok(conflict_copy) => RestoredNote {
+                rel_path,
+                removed: first.before.is_none(),
+                moved_back_to,
+                conflict_copy,
+                error: None,
+            },
+            Err(error) => RestoredNote {
+                rel_path,
+                removed: false,
+                moved_back_to: None,
+                conflict_copy: None,
+                error: Some(error),
+            },
+        });
+    }
+    Ok(restored)
+}
+
+/// Put `before` back as the Forge note `rel_path`, or remove the note when
+/// `before` is `None`, keeping a version that no longer hashes to
+/// `written_hash` as a conflict copy. Symlinked notes are refused.
+pub(crate) fn restore_note(
+    forge_root: &Path,
+    rel_path: &str,
+    written_hash: &str,
+    before: Option<&str>,
+) -> Result<Option<String>, String> {
+    let path = forge_root.join(rel_path);
+    if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        return Err("Refusing to restore a symlinked note".to_string());
+    }
+    let parent = path
+        .parent()
+        .ok_or_else(|| "Invalid note path".to_string())?;
+    if before.is_some() && !parent.is_dir() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to recreate note folder: {error}"))?;
+    }
+    if parent.is_dir() {
+        crate::validation::validate_path_within_base(&path, forge_root)?;
+    }
+    restore_note_snapshot(&path, written_hash, before)
+}
+
+/// Restore a note the session renamed or moved, then move it back to `from`.
+/// Nothing is touched if another note has taken `from` since.
+fn restore_moved_note(
+    forge_root: &Path,
+    rel_path: &str,
+    from: &str,
+    written_hash: &str,
+    before: Option<&str>,
+) -> Result<Option<String>, String> {
+    let origin = forge_root.join(from);
+    let mut locked = origin.clone().into_os_string();
+    locked.push(".locked");
+    if fs::symlink_metadata(&origin).is_ok() || fs::symlink_metadata(&locked).is_ok() {
+        return Err(format!(
+            "{from} exists again, so the note can't be moved back"
+        ));
+    }
+    let conflict_copy = restore_note(forge_root, rel_path, written_hash, before)?;
+    let parent = origin
+        .parent()
+        .ok_or_else(|| "Invalid note path".to_string())?;
+    fs::create_dir_all(parent)
+        .map_err(|error| format!("Failed to recreate note folder: {error}"))?;
+    crate::validation::validate_path_within_base(&origin, forge_root)?;
+    fs::rename(forge_root.join(rel_path), &origin)
+        .map_err(|error| format!("Failed to move the note back: {error}"))?;
+    Ok(conflict_copy)
+}
+
+/// Where the session first found a note it renamed or moved, unless the note
+/// has come back to that path.
+fn renamed_from<'a>(rel_path: &str, snapshots: &'a [(PathBuf, Snapshot)]) -> Option<&'a str> {
+    snapshots
+        .iter()
+        .find_map(|(_, snapshot)| snapshot.moved_from.as_deref())
+        .filter(|from| *from != rel_path)
+}
+
+fn valid_session_id(session_id: &str) -> bool {
+    !session_id.is_empty()
+        && session_id
+            .bytes()
+            .all(|byte| byte == b'-' || byte.is_ascii_hexdigit())
+}
+
+fn snapshot_is_valid(snapshot: &Snapshot) -> bool {
+    !snapshot.forge_root.is_empty()
+        && valid_rel_path(&snapshot.rel_path)
+        && valid_session_id(&snapshot.session_id)
+        && snapshot.written_hash.len() == 64
+        && snapshot.moved_from.as_deref().map_or(true, valid_rel_path)
+}
+
+/// `None` for an entry that is not a valid snapshot file, which is removed.
+fn read_snapshot(path: &Path) -> Option<Snapshot> {
+    if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+        return None;
+    }
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_SNAPSHOT_BYTES {
+        return None;
+    }
+    let snapshot = fs::read(path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<Snapshot>(&raw).ok())
+        .filter(snapshot_is_valid);
+    if snapshot.is_none() {
+        let _ = fs::remove_file(path);
+    }
+    snapshot
+}
+
+/// A Forge's snapshots by session, then note, each in write order. A note's
+/// snapshots are filed under the path its last rename or move gave it.
+#[allow(clippy::type_complexity)]
+fn grouped(
+    spool: &Path,
+    forge_root: &Path,
+) -> BTreeMap<String, BTreeMap<String, Vec<(PathBuf, Snapshot)>>> {
+    let mut sessions: BTreeMap<String, Vec<(PathBuf, Snapshot)>> = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(spool) else {
+        return BTreeMap::new();
+    };
+    let forge_root = forge_root.to_string_lossy();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(snapshot) = read_snapshot(&path) else {
+            continue;
+        };
+        if snapshot.forge_root != forge_root {
+            continue;
+        }
+        sessions
+            .entry(snapshot.session_id.clone())
+            .or_default()
+            .push((path, snapshot));
+    }
+    sessions
+        .into_iter()
+        .map(|(session_id, mut snapshots)| {
+            snapshots.sort_by(|(a_path, a), (b_path, b)| {
+                (a.taken_at_ms, a_path).cmp(&(b.taken_at_ms, b_path))
+            });
+            let mut notes: BTreeMap<String, Vec<(PathBuf, Snapshot)>> = BTreeMap::new();
+            for (path, snapshot) in snapshots {
+                let mut earlier = snapshot
+                    .moved_from
+                    .as_ref()
+                    .and_then(|from| notes.remove(from))
+                    .unwrap_or_default();
+                let history = notes.entry(snapshot.rel_path.clone()).or_default();
+                history.append(&mut earlier);
+                history.push((path, snapshot));
+            }
+            (session_id, notes)
+        })
+        .collect()
+}
+
+/// Remove snapshots past their TTL by file age, without reading them.
+fn prune_spool_at(spool: &Path, current_ms: u64) {
+    let Ok(entries) = fs::read_dir(spool) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) else {
+            continue;
+        };
+        let modified_ms = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |age| age.as_millis() as u64);
+        if current_ms.saturating_sub(modified_ms) > SNAPSHOT_TTL_MS {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[tauri::command]
+pub(crate) fn list_agent_sessions() -> Vec<AgentSession> {
+    let Some(spool) = spool_dir() else {
+        return Vec::new();
+    };
+    list_from(&spool, &crate::paths::get_notes_dir())
+}
+
+/// Undo a session's changes to `paths`, or to every note it changed.
+#[tauri::command]
+pub(crate) fn restore_agent_changes(
+    session_id: String,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<RestoredNote>, String> {
+    let spool =
+        spool_dir().ok_or_else(|| "No configuration folder for agent snapshots".to_string())?;
+    restore_at(
+        &spool,
+        &crate::paths::get_notes_dir(),
+        &session_id,
+        paths.as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    fn temp_forge(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "moldavite-agent-snapshots-{tag}-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(root.join("notes")).unwrap();
+        root
+    }
+
+    /// Write `raw` the way an MCP session would: snapshot first.
+    fn agent_write(spool: &Path, forge: &Path, rel: &str, session: &str, raw: &str) {
+        record_at(spool, forge, rel, session, Some("Agent"), raw).unwrap();
+        fs::write(forge.join(rel), raw).unwrap();
+    }
+
+    #[test]
+    fn restore_returns_notes_to_before_the_sessions_first_write() {
+        let forge = temp_forge("restore");
+        let spool = forge.with_extension("spool");
+        fs::write(forge.join("notes/plan.md"), "---\ncolor: red\n---\nmine\n").unwrap();
+        fs::write(forge.join("notes/edited.md"), "original").unwrap();
+        agent_write(&spool, &forge, "notes/plan.md", "a1", "first");
+        agent_write(&spool, &forge, "notes/plan.md", "a1", "second");
+        agent_write(&spool, &forge, "notes/new.md", "a1", "created");
+        agent_write(&spool, &forge, "notes/edited.md", "a1", "agent");
+        agent_write(&spool, &forge, "notes/plan.md", "b2", "other session");
+        fs::write(forge.join("notes/edited.md"), "human fix").unwrap();
+
+        let sessions = list_from(&spool, &forge);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "b2");
+        let session = &sessions[1];
+        assert_eq!(session.client.as_deref(), Some("Agent"));
+        let changes: Vec<(&str, usize, bool)> = session
+            .changes
+            .iter()
+            .map(|change| (change.rel_path.as_str(), change.writes, change.created))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("notes/edited.md", 1, false),
+                ("notes/new.md", 1, true),
+                ("notes/plan.md", 2, false)
+            ]
+        );
+        assert!(list_from(&spool, &forge.join("notes")).is_empty());
+
+        let only = ["notes/new.md".to_string(), "notes/edited.md".to_string()];
+        let restored = restore_at(&spool, &forge, "a1", Some(&only)).unwrap();
+        assert_eq!(restored.len(), 2);
+        let copy = restored[0].conflict_copy.as_deref().unwrap();
+        assert_eq!(
+            fs::read_to_string(forge.join("notes").join(copy)).unwrap(),
+            "human fix"
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/edited.md")).unwrap(),
+            "original"
+        );
+        assert!(restored[1].removed);
+        assert!(!forge.join("notes/new.md").exists());
+
+        // plan.md was rewritten by another session since, so restoring keeps
+        // that version as a conflict copy.
+        let restored = restore_at(&spool, &forge, "a1", None).unwrap();
+        assert_eq!(restored.len(), 1);
+        let copy = restored[0].conflict_copy.as_deref().unwrap();
+        assert_eq!(
+            fs::read_to_string(forge.join("notes").join(copy)).unwrap(),
+            "other session"
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/plan.md")).unwrap(),
+            "---\ncolor: red\n---\nmine\n"
+        );
+        assert!(restore_at(&spool, &forge, "a1", None).is_err());
+        assert_eq!(list_from(&spool, &forge).len(), 1);
+        let _ = fs::remove_dir_all(&forge);
+        let _ = fs::remove_dir_all(&spool);
+    }
+
+    #[test]
+    fn moved_notes_go_back_only_to_a_free_path() {
+        let forge = temp_forge("moved");
+        let spool = forge.with_extension("spool");
+        fs::write(forge.join("notes/a.md"), "mine").unwrap();
+        agent_write(&spool, &forge, "notes/b.md", "d4", "created");
+        for (from, to) in [("notes/a.md", "notes/c.md"), ("notes/b.md", "notes/d.md")] {
+            fs::rename(forge.join(from), forge.join(to)).unwrap();
+            record_move_at(&spool, &forge, from, to, "d4", Some("Agent")).unwrap();
+        }
+        fs::write(forge.join("notes/a.md"), "a new a").unwrap();
+
+        let restored = restore_at(&spool, &forge, "d4", None).unwrap();
+        assert_eq!(
+            restored[0].error.as_deref(),
+            Some("notes/a.md exists again, so the note can't be moved back")
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/c.md")).unwrap(),
+            "mine"
+        );
+        // A note the session created is removed, not moved back.
+        assert!(restored[1].removed && restored[1].moved_back_to.is_none());
+        assert!(!forge.join("notes/b.md").exists() && !forge.join("notes/d.md").exists());
+
+        fs::remove_file(forge.join("notes/a.md")).unwrap();
+        let restored = restore_at(&spool, &forge, "d4", None).unwrap();
+        assert_eq!(restored[0].moved_back_to.as_deref(), Some("notes/a.md"));
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/a.md")).unwrap(),
+            "mine"
+        );
+        assert!(record_move_at(&spool, &forge, "../a.md", "notes/a.md", "d4", None).is_err());
+        let _ = fs::remove_dir_all(&forge);
+        let _ = fs::remove_dir_all(&spool);
+    }
+
+    #[test]
+    fn locked_notes_are_reported_and_keep_their_snapshots() {
+        let forge = temp_forge("locked");
+        let spool = forge.with_extension("spool");
+        agent_write(&spool, &forge, "notes/a.md", "c3", "agent");
+        agent_write(&spool, &forge, "notes/b.md", "c3", "agent");
+        fs::write(forge.join("notes/a.md.locked"), "sealed").unwrap();
+
+        let restored = restore_at(&spool, &forge, "c3", None).unwrap();
+        assert_eq!(restored[0].error.as_deref(), Some("Note is locked"));
+        assert!(restored[1].error.is_none() && restored[1].removed);
+        let remaining = list_from(&spool, &forge);
+        assert_eq!(remaining[0].changes.len(), 1);
+        assert_eq!(remaining[0].changes[0].rel_path, "notes/a.md");
+        assert!(record_at(&spool, &forge, "../a.md", "c3", None, "x").is_err());
+        assert!(record_at(&spool, &forge, "notes/a.md", "../c3", None, "x").is_err());
+        let _ = fs::remove_dir_all(&forge);
+        let _ = fs::remove_dir_all(&spool);
+    }
+}
@@ -44,7 +44,7 @@ pub(crate) fn spool_dir() -> Option<PathBuf> {
     dirs::config_dir().map(|dir| dir.join("Moldavite").join("agent-writes"))
 }
 
-fn now_ms() -> u64 {
+pub(crate) fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -53,23 +53,28 @@ fn now_ms() -> u64 {
         .unwrap_or(u64::MAX)
 }
 
-fn marker_filename() -> String {
+/// A spool file stem no other write, in this or another process, will pick.
+pub(crate) fn unique_stem() -> String {
     let nanos = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
     let counter = MARKER_COUNTER.fetch_add(1, Ordering::Relaxed);
-    format!("{nanos:x}-{:x}-{counter:x}.json", std::process::id())
+    format!("{nanos:x}-{:x}-{counter:x}", std::process::id())
+}
+
+fn marker_filename() -> String {
+    format!("{}.json", unique_stem())
 }
 
-fn normalized_client(client: Option<&str>) -> Option<String> {
+pub(crate) fn normalized_client(client: Option<&str>) -> Option<String> {
     client
         .map(str::trim)
         .filter(|name| !name.is_empty())
         .map(|name| name.chars().take(MAX_CLIENT_CHARS).collect())
 }
 
-fn valid_rel_path(rel_path: &str) -> bool {
+pub(crate) fn valid_rel_path(rel_path: &str) -> bool {
     let Some((top, rest)) = rel_path.split_once('/') else {
         return false;
     };
@@ -87,27 +92,28 @@ fn valid_content_hash(content_hash: &str) -> bool {
     content_hash.len() == 64 && content_hash.bytes().all(|byte| byte.is_ascii_hexdigit())
 }
 
-fn ensure_spool_dir(path: &Path) -> Result<(), String> {
+/// Create or check an owner-only spool folder; `what` names it in errors.
+pub(crate) fn ensure_spool_dir(path: &Path, what: &str) -> Result<(), String> {
     match fs::symlink_metadata(path) {
         Ok(metadata) if metadata.file_type().is_symlink() => {
-            return Err("Refusing a symlinked agent-write spool".to_string());
+            return Err(format!("Refusing a symlinked {what}"));
         }
         Ok(metadata) if !metadata.is_dir() => {
-            return Err("Agent-write spool is not a directory".to_string());
+            return Err(format!("The {what} is not a directory"));
         }
         Ok(_) => {}
         Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
             fs::create_dir_all(path)
-                .map_err(|error| format!("Failed to create agent-write spool: {error}"))?;
+                .map_err(|error| format!("Failed to create {what}: {error}"))?;
         }
-        Err(error) => return Err(format!("Failed to inspect agent-write spool: {error}")),
+        Err(error) => return Err(format!("Failed to inspect {what}: {error}")),
     }
 
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
         fs::set_permissions(path, fs::Permissions::from_mode(0o700))
-            .map_err(|error| format!("Failed to secure agent-write spool: {error}"))?;
+            .map_err(|error| format!("Failed to secure {what}: {error}"))?;
     }
     Ok(())
 }
@@ -182,7 +188,7 @@ fn record_marker_at(
     if !valid_rel_path(rel_path) {
         return Err("Invalid agent-write note path".to_string());
     }
-    ensure_spool_dir(spool)?;
+    ensure_spool_dir(spool, "agent-write spool")?;
     // MCP can outlive the window, so writes also opportunistically remove stale
     // markers rather than relying only on frontend consumption to clean the spool.
     prune_spool_at(spool, written_at_ms);
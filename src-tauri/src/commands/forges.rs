@@ -201,6 +201,8 @@ pub(crate) fn set_active_forge(
     tauri::async_runtime::spawn_blocking(move || {
         idx.rebuild_from_disk();
     });
+    // Searches scan the new Forge until its full-text index is reconciled.
+    crate::search_index::open(crate::paths::get_notes_dir());
     // Swap the semantic index over to the new Forge (async, no-op if the
     // feature is disabled).
     crate::commands::semantic::on_forge_switched(app.clone());
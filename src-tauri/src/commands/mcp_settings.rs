@@ -1,10 +1,17 @@
 //! GUI-facing discovery and write-consent settings for the built-in MCP server.
 //!
-//! Write access is persisted as an explicit opt-in and defaults to false. The
-//! running MCP tool context re-reads this value per request, so changing it takes
-//! effect without restarting an existing client session.
+//! Write access is persisted as an explicit opt-in and defaults to off; the
+//! third mode, propose, queues agent writes for review instead. The running MCP
+//! tool context re-reads this value per request, so changing it takes effect
+//! without restarting an existing client session. The bearer token for
+//! the opt-in HTTP transport lives in the OS credential store, never in config.
+//! Per-client policies are re-read the same way and only ever narrow access.
 
-use crate::persist::{read_config, write_config};
+use std::collections::BTreeMap;
+
+use crate::persist::{read_config, read_mcp_client_policies, write_config};
+use crate::secrets::KeychainSecretStore;
+use crate::types::{McpClientPolicy, McpWriteMode};
 
 #[tauri::command]
 pub(crate) fn get_app_binary_path() -> Result<String, String> {
@@ -13,14 +20,62 @@ pub(crate) fn get_app_binary_path() -> Result<String, String> {
         .map_err(|error| format!("Failed to locate the Moldavite binary: {error}"))
 }
 
+/// Whether agents write directly; proposing does not count.
 #[tauri::command]
 pub(crate) fn get_mcp_writes_enabled() -> bool {
-    read_config().mcp_writes_enabled.unwrap_or(false)
+    read_config().mcp_write_mode() == McpWriteMode::Direct
 }
 
 #[tauri::command]
 pub(crate) fn set_mcp_writes_enabled(enabled: bool) -> Result<(), String> {
+    set_mcp_write_mode(if enabled {
+        McpWriteMode::Direct
+    } else {
+        McpWriteMode::Off
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_mcp_write_mode() -> McpWriteMode {
+    read_config().mcp_write_mode()
+}
+
+#[tauri::command]
+pub(crate) fn set_mcp_write_mode(mode: McpWriteMode) -> Result<(), String> {
+    let mut config = read_config();
+    config.mcp_write_mode = Some(mode);
+    config.mcp_writes_enabled = Some(mode == McpWriteMode::Direct);
+    write_config(&config)
+}
+
+/// Bearer token that clients of `--mcp-http` must send, created on first use.
+#[tauri::command]
+pub(crate) fn get_mcp_http_token() -> Result<String, String> {
+    crate::mcp::bearer_token_with(&KeychainSecretStore)
+}
+
+/// Issue a new HTTP bearer token. Servers already running keep the old one
+/// until they are restarted.
+#[tauri::command]
+pub(crate) fn rotate_mcp_http_token() -> Result<String, String> {
+    crate::mcp::rotate_bearer_token_with(&KeychainSecretStore)
+}
+
+/// Per-client policies keyed by MCP `clientInfo.name`, with `"*"` as the
+/// fallback. Fails when the stored policies are malformed, as the server does.
+#[tauri::command]
+pub(crate) fn get_mcp_client_policies() -> Result<BTreeMap<String, McpClientPolicy>, String> {
+    read_mcp_client_policies()
+}
+
+#[tauri::command]
+pub(crate) fn set_mcp_client_policies(
+    policies: BTreeMap<String, McpClientPolicy>,
+) -> Result<(), String> {
+    if policies.keys().any(|name| name.trim().is_empty()) {
+        return Err("MCP client policy names must not be empty".to_string());
+    }
     let mut config = read_config();
-    config.mcp_writes_enabled = Some(enabled);
+    config.mcp_client_policies = policies;
     write_config(&config)
 }
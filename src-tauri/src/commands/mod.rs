@@ -16,6 +16,8 @@ pub mod mcp_settings;
 pub mod misc;
 pub mod notes;
 pub mod plugins;
+pub mod replace;
+pub mod saved_searches;
 pub mod root_files;
 pub mod search;
 pub mod semantic;
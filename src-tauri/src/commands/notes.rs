@@ -108,7 +108,7 @@ fn is_date_stem(stem: &str) -> bool {
 }
 
 /// True when a weekly-note stem is an actual ISO week ("2024-W52").
-fn is_week_stem(stem: &str) -> bool {
+pub(crate) fn is_week_stem(stem: &str) -> bool {
     let b = stem.as_bytes();
     b.len() == 8
         && b[..4].iter().all(u8::is_ascii_digit)
@@ -167,24 +167,33 @@ fn preserve_conflict_copy_unlocked(
     new_content: &str,
     stamp: &str,
 ) -> Result<Option<(String, String)>, String> {
-    let Some(base) = base_hash else {
-        // Caller didn't opt in to conflict detection — legacy behavior.
+    let Some((raw, disk_body)) = conflicting_disk_version(path, base_hash, new_content) else {
         return Ok(None);
     };
+    let (conflict_path, conflict_name) = conflict_copy_destination(path, stamp)?;
+    // Preserve the disk version byte-for-byte (frontmatter included).
+    write_atomic(&conflict_path, raw.as_bytes(), Some(0o600))?;
+    Ok(Some((conflict_name, disk_body)))
+}
+
+/// The disk version a save would preserve as a conflict copy, as
+/// `(raw, body)`, or `None` when the save cannot conflict.
+fn conflicting_disk_version(
+    path: &Path,
+    base_hash: Option<&str>,
+    new_content: &str,
+) -> Option<(String, String)> {
+    // Caller didn't opt in to conflict detection — legacy behavior.
+    let base = base_hash?;
     // A missing file can't conflict — the save simply creates it.
-    let Ok(raw) = fs::read_to_string(path) else {
-        return Ok(None);
-    };
+    let raw = fs::read_to_string(path).ok()?;
     let disk_body = frontmatter::parse_note(&raw).body;
     // No conflict when the disk still matches what the frontend last read,
     // or when the incoming save is identical to the disk content anyway.
     if sha256_hex(&disk_body) == base || disk_body == new_content {
-        return Ok(None);
+        return None;
     }
-    let (conflict_path, conflict_name) = conflict_copy_destination(path, stamp)?;
-    // Preserve the disk version byte-for-byte (frontmatter included).
-    write_atomic(&conflict_path, raw.as_bytes(), Some(0o600))?;
-    Ok(Some((conflict_name, disk_body)))
+    Some((raw, disk_body))
 }
 
 #[cfg(test)]
@@ -296,6 +305,32 @@ where
     ensure_note_is_writable(path)?;
     let stamp = chrono::Local::now().format("%Y-%m-%d %H%M").to_string();
     let conflict = preserve_conflict_copy_unlocked(path, base_hash, content, &stamp)?;
+    let (_, serialized) = serialized_save(path, content, color);
+    write(path, &serialized)?;
+    Ok(conflict)
+}
+
+/// Dry-run counterpart of [`save_note_with_conflict_using`]: the same lock
+/// and checks, but nothing is written. Returns whether a conflict copy would
+/// be made, the current disk content, and the content the save would write.
+pub(crate) fn preview_note_save(
+    path: &Path,
+    base_hash: Option<&str>,
+    content: &str,
+    color: Option<&str>,
+) -> Result<(bool, String, String), String> {
+    let _guard = conflict_copy_lock()
+        .lock()
+        .map_err(|_| "Conflict-copy lock poisoned".to_string())?;
+    ensure_note_is_writable(path)?;
+    let conflict = conflicting_disk_version(path, base_hash, content).is_some();
+    let (existing, serialized) = serialized_save(path, content, color);
+    Ok((conflict, existing, serialized))
+}
+
+/// The current disk content and the note a save of `content` would write:
+/// existing frontmatter is kept and `color` applied on top of it.
+fn serialized_save(path: &Path, content: &str, color: Option<&str>) -> (String, String) {
     let existing = fs::read_to_string(path).unwrap_or_default();
     let parsed_existing = frontmatter::parse_note(&existing);
     let resolved_color = match color {
@@ -304,10 +339,67 @@ where
         None => parsed_existing.color.as_deref(),
     };
     let serialized = frontmatter::serialize_note(resolved_color, &parsed_existing.extra, content);
-    write(path, &serialized)?;
+    (existing, serialized)
+}
+
+/// Undo an agent write: put `raw` back as the whole note, frontmatter
+/// included, or remove the note when `raw` is `None` because the agent
+/// created it. A disk version that no longer hashes to `written_hash` — the
+/// raw file the agent wrote — was edited since, and is kept as a conflict copy
+/// first. Returns that copy's filename.
+pub(crate) fn restore_note_snapshot(
+    path: &Path,
+    written_hash: &str,
+    raw: Option<&str>,
+) -> Result<Option<String>, String> {
+    let _guard = conflict_copy_lock()
+        .lock()
+        .map_err(|_| "Conflict-copy lock poisoned".to_string())?;
+    ensure_note_is_writable(path)?;
+    let conflict = match fs::read_to_string(path) {
+        Ok(current) if sha256_hex(&current) != written_hash && Some(current.as_str()) != raw => {
+            let stamp = chrono::Local::now().format("%Y-%m-%d %H%M").to_string();
+            let (conflict_path, conflict_name) = conflict_copy_destination(path, &stamp)?;
+            write_atomic(&conflict_path, current.as_bytes(), Some(0o600))?;
+            Some(conflict_name)
+        }
+        Ok(_) => None,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(format!("Failed to read note: {error}")),
+    };
+    match raw {
+        Some(raw) => write_atomic(path, raw.as_bytes(), Some(0o600))?,
+        None => match fs::remove_file(path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                return Err(format!("Failed to remove note: {error}"));
+            }
+            _ => {}
+        },
+    }
     Ok(conflict)
 }
 
+/// Overwrite a whole note, frontmatter included, with `raw` only while it
+/// still hashes to `expected_hash` — the raw file as the caller last read it.
+/// A note changed since is left alone and reported, never conflict-copied:
+/// the caller's edit was derived from content that no longer exists.
+pub(crate) fn replace_note_if_unchanged(
+    path: &Path,
+    expected_hash: &str,
+    raw: &str,
+) -> Result<(), String> {
+    let _guard = conflict_copy_lock()
+        .lock()
+        .map_err(|_| "Conflict-copy lock poisoned".to_string())?;
+    ensure_note_is_writable(path)?;
+    let current =
+        fs::read_to_string(path).map_err(|error| format!("Failed to read note: {error}"))?;
+    if sha256_hex(&current) != expected_hash {
+        return Err("Note changed since the preview".to_string());
+    }
+    write_atomic(path, raw.as_bytes(), Some(0o600))
+}
+
 fn ensure_note_is_writable(path: &Path) -> Result<(), String> {
     let mut locked_name = path.as_os_str().to_os_string();
     locked_name.push(".locked");
@@ -864,12 +956,12 @@ pub(crate) fn export_single_note(
     Ok(destination)
 }
 
-fn note_ref_stem(filename: &str) -> &str {
+pub(crate) fn note_ref_stem(filename: &str) -> &str {
     let name = filename.rsplit('/').next().unwrap_or(filename);
     name.strip_suffix(".md").unwrap_or(name)
 }
 
-fn rename_note_in(
+pub(crate) fn rename_note_in(
     dir: &Path,
     old_filename: &str,
     new_filename: &str,
@@ -963,15 +1055,18 @@ fn rewrite_inbound_links(old_stem: &str, new_stem: &str, index: &Arc<BacklinksIn
         new_stem,
         index,
         None,
+        &mut |path, rewritten| write_atomic(path, rewritten.as_bytes(), Some(0o600)),
     );
 }
 
-fn rewrite_inbound_links_in_roots(
+/// `write` saves each rewritten note, so a caller can snapshot it first.
+pub(crate) fn rewrite_inbound_links_in_roots(
     roots: &[PathBuf],
     old_stem: &str,
     new_stem: &str,
     index: &Arc<BacklinksIndex>,
     resolver: Option<&crate::backlinks_index::Resolver>,
+    write: &mut dyn FnMut(&Path, &str) -> Result<(), String>,
 ) {
     for root in roots {
         if !root.exists() {
@@ -995,7 +1090,7 @@ fn rewrite_inbound_links_in_roots(
             else {
                 continue;
             };
-            if let Err(e) = write_atomic(path, rewritten.as_bytes(), Some(0o600)) {
+            if let Err(e) = write(path, &rewritten) {
                 log::warn!("rename: failed to rewrite links in {:?}: {}", path, e);
                 continue;
             }
@@ -1080,7 +1175,7 @@ pub(crate) fn move_note(
     Ok(format!("notes/{}", new_relative_path))
 }
 
-fn move_note_in(
+pub(crate) fn move_note_in(
     standalone_dir: &Path,
     note_path: &str,
     to_folder: Option<&str>,
@@ -1621,6 +1716,7 @@ mod tests {
             note_ref_stem("Projects/q3-planning.md"),
             &index,
             Some(&crate::wiki::note_name_to_filename),
+            &mut |path, rewritten| write_atomic(path, rewritten.as_bytes(), Some(0o600)),
         );
 
         assert_eq!(
@@ -1964,6 +2060,7 @@ mod tests {
             "日本語ノート",
             &index,
             Some(&crate::wiki::note_name_to_filename),
+            &mut |path, rewritten| write_atomic(path, rewritten.as_bytes(), Some(0o600)),
         );
         let elapsed = started.elapsed();
         for i in 0..550 {
@@ -0,0 +1,817 @@
+//! Forge-wide find and replace, previewed before it writes and undoable after.
+//!
+//! A preview lists every unlocked note in scope that the replace would change,
+//! with its replacement count, a unified diff, and the hash of the raw file it
+//! was computed from. Applying writes only the notes picked from a preview, and
+//! only those still hashing as previewed; the rest are reported as skipped.
+//! Frontmatter is left byte-for-byte alone unless the request includes it, and
+//! locked notes are never read or written.
+//!
+//! Before anything is written, an apply records a batch holding every note it
+//! is about to change as it was, in an owner-only spool beside app
+//! configuration. Undoing the batch puts those notes back; one edited since the
+//! replace keeps that version as a conflict copy, as undoing an agent does.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use regex::{NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::agent_snapshots::{restore_note, RestoredNote};
+use crate::agent_writes::{ensure_spool_dir, now_ms, unique_stem, valid_rel_path};
+use crate::backlinks_index::BacklinksIndex;
+use crate::commands::notes::{replace_note_if_unchanged, sha256_hex};
+use crate::commands::search::{build_regex, classify_note_path, visible_files};
+use crate::persist::write_atomic;
+use crate::text_diff::unified_diff;
+
+/// Batches are kept this long, then pruned by later applies.
+const BATCH_TTL_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+const MAX_BATCH_BYTES: u64 = 256 * 1024 * 1024;
+const SPOOL_NAME: &str = "replace batch spool";
+
+/// What to replace, and where. The same request is sent to preview and apply.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplaceRequest {
+    pub(crate) find: String,
+    /// With `regex`, `$1` or `${name}` insert capture groups; otherwise the
+    /// text is inserted as is.
+    pub(crate) replace: String,
+    #[serde(default)]
+    pub(crate) regex: bool,
+    #[serde(default)]
+    pub(crate) case_sensitive: bool,
+    #[serde(default)]
+    pub(crate) whole_word: bool,
+    /// A Forge-relative folder such as `notes/Projects` or `daily` to limit
+    /// the replace to; the whole Forge when `None`.
+    #[serde(default)]
+    pub(crate) folder: Option<String>,
+    /// Replace inside frontmatter too, not only in note bodies.
+    #[serde(default)]
+    pub(crate) include_frontmatter: bool,
+}
+
+/// A note the replace would change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NotePreview {
+    pub(crate) rel_path: String,
+    pub(crate) replacements: usize,
+    /// SHA-256 of the raw file the preview was computed from; apply writes
+    /// the note only while it still matches.
+    pub(crate) file_hash: String,
+    pub(crate) diff: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplacePreview {
+    pub(crate) notes: Vec<NotePreview>,
+    /// Locked notes in scope, which are never searched or changed.
+    pub(crate) locked: Vec<String>,
+}
+
+/// A note picked from a preview, with the hash the preview gave it.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PickedNote {
+    pub(crate) rel_path: String,
+    pub(crate) file_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppliedNote {
+    pub(crate) rel_path: String,
+    pub(crate) replacements: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SkippedNote {
+    pub(crate) rel_path: String,
+    pub(crate) reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplaceOutcome {
+    /// Undoes the notes in `applied`; `None` when nothing was written.
+    pub(crate) batch_id: Option<String>,
+    pub(crate) applied: Vec<AppliedNote>,
+    pub(crate) skipped: Vec<SkippedNote>,
+}
+
+/// An applied replace in a Forge, as the app lists it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplaceBatchSummary {
+    pub(crate) batch_id: String,
+    pub(crate) applied_at_ms: u64,
+    pub(crate) find: String,
+    pub(crate) replace: String,
+    pub(crate) notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Batch {
+    forge_root: String,
+    applied_at_ms: u64,
+    find: String,
+    replace: String,
+    notes: Vec<BatchNote>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchNote {
+    rel_path: String,
+    /// The raw note before the replace.
+    before: String,
+    /// SHA-256 of the raw note the replace wrote.
+    written_hash: String,
+}
+
+/// A request compiled for matching.
+struct Replacer {
+    regex: Regex,
+    replace: String,
+    expand: bool,
+    include_frontmatter: bool,
+}
+
+impl Replacer {
+    fn new(request: &ReplaceRequest) -> Result<Replacer, String> {
+        if request.find.is_empty() {
+            return Err("Enter text to find".to_string());
+        }
+        let source = if request.regex {
+            request.find.clone()
+        } else {
+            regex::escape(&request.find)
+        };
+        Ok(Replacer {
+            regex: build_regex(&source, request.case_sensitive, request.whole_word)?,
+            replace: request.replace.clone(),
+            expand: request.regex,
+            include_frontmatter: request.include_frontmatter,
+        })
+    }
+
+    /// `raw` with every match replaced, and how many there were, or `None`
+    /// when the replace would leave it unchanged.
+    fn apply(&self, raw: &str) -> Option<(String, usize)> {
+        let start = if self.include_frontmatter {
+            0
+        } else {
+            crate::frontmatter::body_offset(raw)
+        };
+        let (kept, searched) = raw.split_at(start);
+        let count = self.regex.find_iter(searched).count();
+        if count == 0 {
+            return None;
+        }
+        let replaced = if self.expand {
+            self.regex.replace_all(searched, self.replace.as_str())
+        } else {
+            self.regex.replace_all(searched, NoExpand(&self.replace))
+        };
+        (replaced != searched).then(|| (format!("{kept}{replaced}"), count))
+    }
+}
+
+/// The Forge-relative folder a request is limited to, checked to be a real
+/// folder of notes inside the Forge.
+fn scope_folder(forge_root: &Path, folder: Option<&str>) -> Result<Option<String>, String> {
+    let Some(folder) = folder.map(|folder| folder.trim_matches('/')) else {
+        return Ok(None);
+    };
+    let mut parts = folder.split('/');
+    let valid = matches!(parts.next(), Some("daily" | "weekly" | "notes"))
+        && parts.all(|part| !part.is_empty() && !part.starts_with('.'));
+    let path = forge_root.join(folder);
+    if !valid
+        || !path.is_dir()
+        || crate::validation::validate_path_within_base(&path, forge_root).is_err()
+    {
+        return Err(format!("No such folder: {folder}"));
+    }
+    Ok(Some(folder.to_string()))
+}
+
+fn in_scope(rel_path: &str, scope: Option<&str>) -> bool {
+    scope.map_or(true, |folder| {
+        rel_path
+            .strip_prefix(folder)
+            .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Preview `request` over the notes of `forge_root`, sorted by path.
+pub(crate) fn preview_in(
+    forge_root: &Path,
+    request: &ReplaceRequest,
+) -> Result<ReplacePreview, String> {
+    let replacer = Replacer::new(request)?;
+    let scope = scope_folder(forge_root, request.folder.as_deref())?;
+    let root = scope.as_deref().map_or_else(
+        || forge_root.to_path_buf(),
+        |folder| forge_root.join(folder),
+    );
+    let trash_dir = forge_root.join(".trash");
+    let mut preview = ReplacePreview {
+        notes: Vec::new(),
+        locked: Vec::new(),
+    };
+    for path in visible_files(&root, &trash_dir) {
+        let Some((rel_path, ..)) = classify_note_path(forge_root, &path) else {
+            continue;
+        };
+        if let Some(note) = rel_path.strip_suffix(".locked") {
+            if note.ends_with(".md") {
+                preview.locked.push(note.to_string());
+            }
+            continue;
+        }
+        if !rel_path.ends_with(".md") {
+            continue;
+        }
+        let Ok(raw) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some((replaced, replacements)) = replacer.apply(&raw) else {
+            continue;
+        };
+        preview.notes.push(NotePreview {
+            diff: unified_diff(
+                &raw,
+                &replaced,
+                &format!("a/{rel_path}"),
+                &format!("b/{rel_path}"),
+            ),
+            file_hash: sha256_hex(&raw),
+            replacements,
+            rel_path,
+        });
+    }
+    preview.notes.sort_by(|a, b| a.rel_path.cmp(&b.rel_path));
+    preview.locked.sort();
+    Ok(preview)
+}
+
+/// Apply `request` to the `picked` notes of `forge_root`, recording the batch
+/// in `spool` first. A note that is locked, changed since its preview, or
+/// otherwise can't be written is skipped on its own while the rest go ahead.
+pub(crate) fn apply_at(
+    spool: &Path,
+    forge_root: &Path,
+    request: &ReplaceRequest,
+    picked: &[PickedNote],
+) -> Result<ReplaceOutcome, String> {
+    let replacer = Replacer::new(request)?;
+    let scope = scope_folder(forge_root, request.folder.as_deref())?;
+    let mut skipped = Vec::new();
+    let mut skip = |rel_path: &str, reason: &str| {
+        skipped.push(SkippedNote {
+            rel_path: rel_path.to_string(),
+            reason: reason.to_string(),
+        })
+    };
+    let mut planned = Vec::new();
+    for note in picked {
+        let rel_path = note.rel_path.as_str();
+        if !valid_rel_path(rel_path) || !in_scope(rel_path, scope.as_deref()) {
+            skip(rel_path, "Not a note in the replace's scope");
+            continue;
+        }
+        let path = forge_root.join(rel_path);
+        if fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+            skip(rel_path, "Refusing to write a symlinked note");
+            continue;
+        }
+        let raw = match fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                skip(rel_path, "Note no longer exists");
+                continue;
+            }
+            Err(error) => {
+                skip(rel_path, &format!("Failed to read note: {error}"));
+                continue;
+            }
+        };
+        if sha256_hex(&raw) != note.file_hash {
+            skip(rel_path, "Note changed since the preview");
+            continue;
+        }
+        let Some((replaced, replacements)) = replacer.apply(&raw) else {
+            skip(rel_path, "Nothing to replace");
+            continue;
+        };
+        planned.push((path, note, raw, replaced, replacements));
+    }
+    if planned.is_empty() {
+        return Ok(ReplaceOutcome {
+            batch_id: None,
+            applied: Vec::new(),
+            skipped,
+        });
+    }
+
+    ensure_spool_dir(spool, SPOOL_NAME)?;
+    let applied_at_ms = now_ms();
+    prune_spool_at(spool, applied_at_ms);
+    let batch_id = unique_stem();
+    let batch_path = spool.join(format!("{batch_id}.json"));
+    let mut batch = Batch {
+        forge_root: forge_root.to_string_lossy().into_owned(),
+        applied_at_ms,
+        find: request.find.clone(),
+        replace: request.replace.clone(),
+        notes: planned
+            .iter()
+            .map(|(_, note, raw, replaced, _)| BatchNote {
+                rel_path: note.rel_path.clone(),
+                before: raw.clone(),
+                written_hash: sha256_hex(replaced),
+            })
+            .collect(),
+    };
+    // A replace that could not be undone is refused.
+    write_batch(&batch_path, &batch)?;
+
+    let mut applied = Vec::new();
+    for (path, note, raw, replaced, replacements) in &planned {
+        match replace_note_if_unchanged(path, &sha256_hex(raw), replaced) {
+            Ok(()) => applied.push(AppliedNote {
+                rel_path: note.rel_path.clone(),
+                replacements: *replacements,
+            }),
+            Err(error) => skip(&note.rel_path, &error),
+        }
+    }
+    let batch_id = if applied.is_empty() {
+        let _ = fs::remove_file(&batch_path);
+        None
+    } else {
+        if applied.len() < batch.notes.len() {
+            batch
+                .notes
+                .retain(|note| applied.iter().any(|a| a.rel_path == note.rel_path));
+            write_batch(&batch_path, &batch)?;
+        }
+        Some(batch_id)
+    };
+    Ok(ReplaceOutcome {
+        batch_id,
+        applied,
+        skipped,
+    })
+}
+
+/// Applied replaces in `forge_root` that can still be undone, most recent first.
+pub(crate) fn list_batches_from(spool: &Path, forge_root: &Path) -> Vec<ReplaceBatchSummary> {
+    let Ok(entries) = fs::read_dir(spool) else {
+        return Vec::new();
+    };
+    let forge_root = forge_root.to_string_lossy();
+    let mut batches: Vec<ReplaceBatchSummary> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            let batch_id = path.file_stem()?.to_str()?.to_string();
+            let batch = read_batch(&path)?;
+            (batch.forge_root == forge_root).then(|| ReplaceBatchSummary {
+                batch_id,
+                applied_at_ms: batch.applied_at_ms,
+                find: batch.find,
+                replace: batch.replace,
+                notes: batch.notes.into_iter().map(|note| note.rel_path).collect(),
+            })
+        })
+        .collect();
+    batches.sort_by(|a, b| (b.applied_at_ms, &b.batch_id).cmp(&(a.applied_at_ms, &a.batch_id)));
+    batches
+}
+
+/// Undo a batch, restoring each note on its own: one that fails, say because
+/// it is now locked, is reported and stays in the batch while the rest go
+/// ahead. The batch is gone once every note is restored.
+pub(crate) fn undo_at(
+    spool: &Path,
+    forge_root: &Path,
+    batch_id: &str,
+) -> Result<Vec<RestoredNote>, String> {
+    let path = spool.join(format!("{batch_id}.json"));
+    let batch = valid_batch_id(batch_id)
+        .then(|| read_batch(&path))
+        .flatten()
+        .filter(|batch| batch.forge_root == forge_root.to_string_lossy())
+        .ok_or_else(|| "No such replace to undo".to_string())?;
+    let mut remaining = batch.clone();
+    remaining.notes.clear();
+    let mut restored = Vec::new();
+    for note in batch.notes {
+        let result = restore_note(
+            forge_root,
+            &note.rel_path,
+            &note.written_hash,
+            Some(&note.before),
+        );
+        restored.push(match result {
+            Ok(conflict_copy) => RestoredNote {
+                rel_path: note.rel_path,
+                removed: false,
+                moved_back_to: None,
+                conflict_copy,
+                error: None,
+            },
+            Err(error) => {
+                remaining.notes.push(note.clone());
+                RestoredNote {
+                    rel_path: note.rel_path,
+                    removed: false,
+                    moved_back_to: None,
+                    conflict_copy: None,
+                    error: Some(error),
+                }
+            }
+        });
+    }
+    if remaining.notes.is_empty() {
+        let _ = fs::remove_file(&path);
+    } else {
+        write_batch(&path, &remaining)?;
+    }
+    Ok(restored)
+}
+
+fn valid_batch_id(batch_id: &str) -> bool {
+    !batch_id.is_empty()
+        && batch_id
+            .bytes()
+            .all(|byte| byte == b'-' || byte.is_ascii_hexdigit())
+}
+
+fn batch_is_valid(batch: &Batch) -> bool {
+    !batch.forge_root.is_empty()
+        && batch
+            .notes
+            .iter()
+            .all(|note| valid_rel_path(&note.rel_path) && note.written_hash.len() == 64)
+}
+
+fn write_batch(path: &Path, batch: &Batch) -> Result<(), String> {
+    let json = serde_json::to_vec(batch)
+        .map_err(|error| format!("Failed to encode replace batch: {error}"))?;
+    write_atomic(path, &json, Some(0o600))
+}
+
+/// `None` for an entry that is not a valid batch file, which is removed.
+fn read_batch(path: &Path) -> Option<Batch> {
+    if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+        return None;
+    }
+    let metadata = fs::symlink_metadata(path).ok()?;
+    if !metadata.is_file() || metadata.len() > MAX_BATCH_BYTES {
+        return None;
+    }
+    let batch = fs::read(path)
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<Batch>(&raw).ok())
+        .filter(batch_is_valid);
+    if batch.is_none() {
+        let _ = fs::remove_file(path);
+    }
+    batch
+}
+
+/// Remove batches past their TTL by file age, without reading them.
+fn prune_spool_at(spool: &Path, current_ms: u64) {
+    let Ok(entries) = fs::read_dir(spool) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) else {
+            continue;
+        };
+        let modified_ms = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |age| age.as_millis() as u64);
+        if current_ms.saturating_sub(modified_ms) > BATCH_TTL_MS {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Resolve the global batch spool without depending on an initialized Tauri app.
+fn spool_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join("Moldavite").join("replace-batches"))
+        .ok_or_else(|| "No configuration folder for replace batches".to_string())
+}
+
+/// Refresh the backlinks and search indexes for notes a replace or its undo
+/// rewrote. The watcher tells open editors.
+fn reindex(forge_root: &Path, rel_paths: &[&str], index: &BacklinksIndex) {
+    for rel_path in rel_paths {
+        let path = forge_root.join(rel_path);
+        if let (Ok(raw), Some(name)) = (
+            fs::read_to_string(&path),
+            path.file_name().and_then(|name| name.to_str()),
+        ) {
+            index.update_note(name, &crate::frontmatter::parse_note(&raw).body);
+        }
+        crate::semantic::note_changed(rel_path);
+    }
+}
+
+#[tauri::command]
+pub(crate) fn preview_replace(request: ReplaceRequest) -> Result<ReplacePreview, String> {
+    preview_in(&crate::paths::get_notes_dir(), &request)
+}
+
+/// Write the `notes` picked from a preview of the same `request`.
+#[tauri::command]
+pub(crate) fn apply_replace(
+    request: ReplaceRequest,
+    notes: Vec<PickedNote>,
+    index: State<'_, Arc<BacklinksIndex>>,
+) -> Result<ReplaceOutcome, String> {
+    let forge_root = crate::paths::get_notes_dir();
+    let outcome = apply_at(&spool_dir()?, &forge_root, &request, &notes)?;
+    let written: Vec<&str> = outcome
+        .applied
+        .iter()
+        .map(|note| note.rel_path.as_str())
+        .collect();
+    reindex(&forge_root, &written, &index);
+    Ok(outcome)
+}
+
+#[tauri::command]
+pub(crate) fn list_replace_batches() -> Vec<ReplaceBatchSummary> {
+    let Ok(spool) = spool_dir() else {
+        return Vec::new();
+    };
+    list_batches_from(&spool, &crate::paths::get_notes_dir())
+}
+
+#[tauri::command]
+pub(crate) fn undo_replace(
+    batch_id: String,
+    index: State<'_, Arc<BacklinksIndex>>,
+) -> Result<Vec<RestoredNote>, String> {
+    let forge_root = crate::paths::get_notes_dir();
+    let restored = undo_at(&spool_dir()?, &forge_root, &batch_id)?;
+    let written: Vec<&str> = restored
+        .iter()
+        .filter(|note| note.error.is_none())
+        .map(|note| note.rel_path.as_str())
+        .collect();
+    reindex(&forge_root, &written, &index);
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    fn temp_forge(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "moldavite-replace-{tag}-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        for dir in ["daily", "notes/Projects", ".trash"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        root
+    }
+
+    fn request(find: &str, replace: &str) -> ReplaceRequest {
+        ReplaceRequest {
+            find: find.to_string(),
+            replace: replace.to_string(),
+            regex: false,
+            case_sensitive: false,
+            whole_word: false,
+            folder: None,
+            include_frontmatter: false,
+        }
+    }
+
+    fn picked(preview: &ReplacePreview) -> Vec<PickedNote> {
+        preview
+            .notes
+            .iter()
+            .map(|note| PickedNote {
+                rel_path: note.rel_path.clone(),
+                file_hash: note.file_hash.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn preview_matches_bodies_in_scope_and_skips_locked_notes() {
+        let forge = temp_forge("preview");
+        fs::write(
+            forge.join("notes/plan.md"),
+            "---\nstatus: draft\n---\nA draft, a Draft, drafted.\n",
+        )
+        .unwrap();
+        fs::write(forge.join("notes/Projects/roadmap.md"), "draft $5\n").unwrap();
+        fs::write(forge.join("notes/Projects/secret.md.locked"), "draft").unwrap();
+        fs::write(forge.join("daily/2026-01-01.md"), "no match\n").unwrap();
+        fs::write(forge.join(".trash/old.md"), "draft").unwrap();
+
+        let whole_word = ReplaceRequest {
+            whole_word: true,
+            ..request("draft", "final $1")
+        };
+        let preview = preview_in(&forge, &whole_word).unwrap();
+        let counts: Vec<(&str, usize)> = preview
+            .notes
+            .iter()
+            .map(|note| (note.rel_path.as_str(), note.replacements))
+            .collect();
+        assert_eq!(
+            counts,
+            [("notes/Projects/roadmap.md", 1), ("notes/plan.md", 2)]
+        );
+        assert_eq!(preview.locked, ["notes/Projects/secret.md"]);
+        // Frontmatter is untouched and the replacement is literal.
+        assert!(preview.notes[1]
+            .diff
+            .contains("+A final $1, a final $1, drafted.\n"));
+        assert!(!preview.notes[1].diff.contains("+status"));
+
+        let with_frontmatter = ReplaceRequest {
+            case_sensitive: true,
+            include_frontmatter: true,
+            folder: Some("notes".to_string()),
+            ..request("draft", "final")
+        };
+        let preview = preview_in(&forge, &with_frontmatter).unwrap();
+        assert_eq!(preview.notes[1].replacements, 3);
+        assert!(preview.notes[1].diff.contains("+status: final\n"));
+
+        let regex = ReplaceRequest {
+            regex: true,
+            folder: Some("notes/Projects/".to_string()),
+            ..request(r"(\w+) \$(\d)", "$2 ${1}s")
+        };
+        let preview = preview_in(&forge, &regex).unwrap();
+        assert_eq!(preview.notes.len(), 1);
+        assert!(preview.notes[0].diff.contains("+5 drafts\n"));
+
+        for folder in ["../notes", "notes/.trash", "templates", "notes/Missing"] {
+            let scoped = ReplaceRequest {
+                folder: Some(folder.to_string()),
+                ..request("draft", "final")
+            };
+            assert!(preview_in(&forge, &scoped).is_err(), "{folder}");
+        }
+        assert!(preview_in(&forge, &request("", "x")).is_err());
+        let _ = fs::remove_dir_all(&forge);
+    }
+
+    #[test]
+    fn whole_word_literals_may_start_or_end_with_punctuation() {
+        let forge = temp_forge("punctuation");
+        fs::write(
+            forge.join("notes/code.md"),
+            "C++ and ObjC++; call foo() or bar.foo()\n",
+        )
+        .unwrap();
+
+        for (find, replace, line) in [
+            ("C++", "Rust", "+Rust and ObjC++; call foo() or bar.foo()\n"),
+            (
+                "foo()",
+                "run()",
+                "+C++ and ObjC++; call run() or bar.run()\n",
+            ),
+        ] {
+            let whole_word = ReplaceRequest {
+                whole_word: true,
+                ..request(find, replace)
+            };
+            let preview = preview_in(&forge, &whole_word).unwrap();
+            assert_eq!(preview.notes.len(), 1, "{find}");
+            assert!(preview.notes[0].diff.contains(line), "{find}");
+        }
+        let _ = fs::remove_dir_all(&forge);
+    }
+
+    #[test]
+    fn apply_writes_unchanged_picks_and_undo_restores_them() {
+        let forge = temp_forge("apply");
+        let spool = forge.with_extension("spool");
+        fs::write(forge.join("notes/a.md"), "---\ntag: old\n---\nold text\n").unwrap();
+        fs::write(forge.join("notes/b.md"), "old b\n").unwrap();
+        fs::write(forge.join("notes/c.md"), "old c\n").unwrap();
+        fs::write(forge.join("daily/2026-01-01.md"), "old day\n").unwrap();
+
+        let replace = request("old", "new");
+        let preview = preview_in(&forge, &replace).unwrap();
+        assert_eq!(preview.notes.len(), 4);
+        // c.md changes after the preview, and the daily note isn't picked.
+        fs::write(forge.join("notes/c.md"), "old c, edited\n").unwrap();
+        let mut notes = picked(&preview);
+        notes.retain(|note| !note.rel_path.starts_with("daily/"));
+        notes.push(PickedNote {
+            rel_path: "../outside.md".to_string(),
+            file_hash: String::new(),
+        });
+
+        let outcome = apply_at(&spool, &forge, &replace, &notes).unwrap();
+        let applied: Vec<&str> = outcome
+            .applied
+            .iter()
+            .map(|note| note.rel_path.as_str())
+            .collect();
+        assert_eq!(applied, ["notes/a.md", "notes/b.md"]);
+        let skipped: Vec<(&str, &str)> = outcome
+            .skipped
+            .iter()
+            .map(|note| (note.rel_path.as_str(), note.reason.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            [
+                ("notes/c.md", "Note changed since the preview"),
+                ("../outside.md", "Not a note in the replace's scope")
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/a.md")).unwrap(),
+            "---\ntag: old\n---\nnew text\n"
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("daily/2026-01-01.md")).unwrap(),
+            "old day\n"
+        );
+
+        let batches = list_batches_from(&spool, &forge);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].batch_id, outcome.batch_id.clone().unwrap());
+        assert_eq!(batches[0].notes, ["notes/a.md", "notes/b.md"]);
+        assert!(list_batches_from(&spool, &forge.join("notes")).is_empty());
+
+        // b.md is edited after the replace, so undoing keeps that version.
+        fs::write(forge.join("notes/b.md"), "new b, edited\n").unwrap();
+        let restored = undo_at(&spool, &forge, &batches[0].batch_id).unwrap();
+        assert!(restored.iter().all(|note| note.error.is_none()));
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/a.md")).unwrap(),
+            "---\ntag: old\n---\nold text\n"
+        );
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/b.md")).unwrap(),
+            "old b\n"
+        );
+        let copy = restored[1].conflict_copy.as_deref().unwrap();
+        assert_eq!(
+            fs::read_to_string(forge.join("notes").join(copy)).unwrap(),
+            "new b, edited\n"
+        );
+        assert!(list_batches_from(&spool, &forge).is_empty());
+        assert!(undo_at(&spool, &forge, &batches[0].batch_id).is_err());
+        assert!(undo_at(&spool, &forge, "../escape").is_err());
+        let _ = fs::remove_dir_all(&forge);
+        let _ = fs::remove_dir_all(&spool);
+    }
+
+    #[test]
+    fn apply_skips_notes_locked_after_the_preview() {
+        let forge = temp_forge("locked");
+        let spool = forge.with_extension("spool");
+        fs::write(forge.join("notes/a.md"), "old\n").unwrap();
+        let replace = request("old", "new");
+        let notes = picked(&preview_in(&forge, &replace).unwrap());
+        fs::write(forge.join("notes/a.md.locked"), "ciphertext").unwrap();
+
+        let outcome = apply_at(&spool, &forge, &replace, &notes).unwrap();
+        assert_eq!(outcome.batch_id, None);
+        assert_eq!(outcome.skipped[0].reason, "Note is locked");
+        assert_eq!(
+            fs::read_to_string(forge.join("notes/a.md")).unwrap(),
+            "old\n"
+        );
+        assert!(list_batches_from(&spool, &forge).is_empty());
+        let _ = fs::remove_dir_all(&forge);
+        let _ = fs::remove_dir_all(&spool);
+    }
+}
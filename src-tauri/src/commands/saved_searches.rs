@@ -0,0 +1,448 @@
+//! Named saved searches, stored per Forge, with cached result counts.
+//!
+//! Each saved search is a JSON file in the Forge's `searches` folder, named by
+//! a slug of the search's name as template files are, and written atomically.
+//! A search keeps its query, mode, and match options, so running it later
+//! answers exactly as the search box did when it was saved. Queries are parsed
+//! when saved, so a saved search never fails on syntax.
+//!
+//! Listing reports how many notes each search finds. Counts are cached in
+//! memory per Forge and dropped whenever the Forge watcher sees a note change,
+//! so a list between edits doesn't re-run every search.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::commands::search::{search_in_mode, SearchQuery};
+use crate::paths::get_notes_dir;
+use crate::semantic::SemanticHit;
+use crate::templates_data::generate_template_id;
+use crate::types::{ContentMatch, SaveSearchInput, SavedSearch, SearchMode};
+use crate::validation::{is_safe_existing_filename, is_safe_filename, validate_path_within_base};
+
+/// Most results a count includes; a search finding more is counted as this.
+pub(crate) const COUNT_LIMIT: u32 = 500;
+const MAX_NAME_CHARS: usize = 200;
+
+#[derive(Default)]
+struct CountCache {
+    /// Bumped by every invalidation, so a count computed across one is
+    /// dropped rather than cached.
+    generations: HashMap<PathBuf, u64>,
+    counts: HashMap<(PathBuf, String), usize>,
+}
+
+fn count_cache() -> &'static Mutex<CountCache> {
+    static CACHE: OnceLock<Mutex<CountCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(CountCache::default()))
+}
+
+/// Forget every cached count for `forge_root`. The Forge watcher calls this
+/// for each note change it sees, the app's own writes included.
+pub(crate) fn invalidate_counts(forge_root: &Path) {
+    let Ok(mut cache) = count_cache().lock() else {
+        return;
+    };
+    *cache
+        .generations
+        .entry(forge_root.to_path_buf())
+        .or_default() += 1;
+    cache.counts.retain(|(root, _), _| root != forge_root);
+}
+
+fn forget_count(forge_root: &Path, id: &str) {
+    if let Ok(mut cache) = count_cache().lock() {
+        cache
+            .counts
+            .remove(&(forge_root.to_path_buf(), id.to_string()));
+    }
+}
+
+/// The saved searches folder of a Forge. A symlinked folder is refused, as
+/// it could reach outside the Forge.
+pub(crate) fn searches_dir(forge_root: &Path) -> Result<PathBuf, String> {
+    let dir = forge_root.join("searches");
+    if fs::symlink_metadata(&dir).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        return Err("Refusing to use a symlinked searches folder".to_string());
+    }
+    Ok(dir)
+}
+
+fn validated_search_path(dir: &Path, id: &str, existing: bool) -> Result<PathBuf, String> {
+    let safe = if existing {
+        is_safe_existing_filename(id)
+    } else {
+        is_safe_filename(id)
+    };
+    if !safe || generate_template_id(id) != id {
+        return Err("Invalid saved search name".to_string());
+    }
+    let path = dir.join(format!("{id}.json"));
+    if dir.exists() {
+        validate_path_within_base(&path, dir)
+            .map_err(|_| "Invalid saved search name".to_string())?;
+    }
+    Ok(path)
+}
+
+/// Every saved search in `dir`, by name. Unreadable or malformed files are
+/// skipped, and so are symlinks.
+pub(crate) fn list_saved_searches_in(dir: &Path) -> Vec<SavedSearch> {
+    let mut searches: Vec<SavedSearch> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|entry| {
+            let search: SavedSearch =
+                serde_json::from_str(&fs::read_to_string(entry.path()).ok()?).ok()?;
+            // The file name is the id; a copied file keeps its own.
+            let stem = entry.path().file_stem()?.to_str()?.to_string();
+            Some(SavedSearch { id: stem, ..search })
+        })
+        .collect();
+    searches.sort_by(|a, b| (a.name.to_lowercase(), &a.id).cmp(&(b.name.to_lowercase(), &b.id)));
+    searches
+}
+
+/// The saved search whose id is `name`, or whose name is `name` ignoring case.
+pub(crate) fn find_saved_search_in(dir: &Path, name: &str) -> Result<SavedSearch, String> {
+    let searches = list_saved_searches_in(dir);
+    let names: Vec<&str> = searches.iter().map(|search| search.name.as_str()).collect();
+    if let Some(search) = searches
+        .iter()
+        .find(|search| search.id == name || search.name.to_lowercase() == name.to_lowercase())
+    {
+        return Ok(search.clone());
+    }
+    Err(if names.is_empty() {
+        format!("No saved search named '{name}'; this Forge has none")
+    } else {
+        format!(
+            "No saved search named '{name}'. Saved searches: {}",
+            names.join(", ")
+        )
+    })
+}
+
+/// Check `input` and build the search it saves as `id`.
+fn saved_search(id: String, input: SaveSearchInput) -> Result<SavedSearch, String> {
+    let name = input.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_CHARS {
+        return Err(format!(
+            "Saved search names must be 1 to {MAX_NAME_CHARS} characters"
+        ));
+    }
+    let query = input.query.trim();
+    let parses = match input.mode {
+        // Semantic search reads the text as it is, without operators.
+        SearchMode::Semantic => !query.is_empty(),
+        SearchMode::Keyword | SearchMode::Hybrid => {
+            SearchQuery::parse(query, input.options)?.is_some()
+        }
+    };
+    if !parses {
+        return Err("Enter a query to save".to_string());
+    }
+    Ok(SavedSearch {
+        id,
+        name: name.to_string(),
+        query: query.to_string(),
+        mode: input.mode,
+        options: input.options,
+        result_count: None,
+    })
+}
+
+/// Names are unique ignoring case, since MCP clients run searches by name.
+fn ensure_name_is_free(dir: &Path, search: &SavedSearch) -> Result<(), String> {
+    let name = search.name.to_lowercase();
+    if list_saved_searches_in(dir)
+        .iter()
+        .any(|other| other.id != search.id && other.name.to_lowercase() == name)
+    {
+        return Err(format!(
+            "A saved search named '{}' already exists",
+            search.name
+        ));
+    }
+    Ok(())
+}
+
+fn write_saved_search(path: &Path, search: &SavedSearch) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(search).map_err(|e| e.to_string())?;
+    crate::persist::write_atomic(path, json.as_bytes(), Some(0o600))
+}
+
+pub(crate) fn create_saved_search_in(
+    dir: &Path,
+    input: SaveSearchInput,
+) -> Result<SavedSearch, String> {
+    let id = generate_template_id(input.name.trim());
+    let path = validated_search_path(dir, &id, false)?;
+    if path.exists() {
+        return Err(format!(
+            "A saved search named '{}' already exists",
+            input.name.trim()
+        ));
+    }
+    let search = saved_search(id, input)?;
+    ensure_name_is_free(dir, &search)?;
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create searches folder: {e}"))?;
+    write_saved_search(&path, &search)?;
+    Ok(search)
+}
+
+/// Replace the name, query, mode, and options of the saved search `id`,
+/// which keeps its id.
+pub(crate) fn update_saved_search_in(
+    dir: &Path,
+    id: &str,
+    input: SaveSearchInput,
+) -> Result<SavedSearch, String> {
+    let path = validated_search_path(dir, id, true)?;
+    if !path.is_file() {
+        return Err(format!("Saved search '{id}' not found"));
+    }
+    let search = saved_search(id.to_string(), input)?;
+    ensure_name_is_free(dir, &search)?;
+    write_saved_search(&path, &search)?;
+    Ok(search)
+}
+
+pub(crate) fn delete_saved_search_in(dir: &Path, id: &str) -> Result<(), String> {
+    let path = validated_search_path(dir, id, true)?;
+    if path.is_file() {
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Run a saved search in `forge_root` as the search box would.
+pub(crate) fn run_saved_search_in(
+    forge_root: &Path,
+    search: &SavedSearch,
+    max_results: u32,
+    semantic: impl FnOnce(&str, usize) -> Result<Vec<SemanticHit>, String>,
+) -> Result<Vec<ContentMatch>, String> {
+    search_in_mode(
+        forge_root,
+        &forge_root.join(".trash"),
+        &search.query,
+        max_results,
+        search.mode,
+        search.options,
+        semantic,
+    )
+}
+
+/// Fill in each search's result count, from the cache when no note in
+/// `forge_root` has changed since it was counted. A search that can't run
+/// right now, such as a semantic one while the index builds, has no count.
+pub(crate) fn with_result_counts(
+    forge_root: &Path,
+    searches: Vec<SavedSearch>,
+    semantic: impl Fn(&str, usize) -> Result<Vec<SemanticHit>, String>,
+) -> Vec<SavedSearch> {
+    searches
+        .into_iter()
+        .map(|search| {
+            let key = (forge_root.to_path_buf(), search.id.clone());
+            let (cached, generation) = match count_cache().lock() {
+                Ok(cache) => (
+                    cache.counts.get(&key).copied(),
+                    cache.generations.get(forge_root).copied(),
+                ),
+                Err(_) => (None, None),
+            };
+            let result_count = cached.or_else(|| {
+                let count = run_saved_search_in(forge_root, &search, COUNT_LIMIT, &semantic)
+                    .ok()?
+                    .len();
+                if let Ok(mut cache) = count_cache().lock() {
+                    // A note changed while counting: the count may be stale.
+                    if cache.generations.get(forge_root).copied() == generation {
+                        cache.counts.insert(key, count);
+                    }
+                }
+                Some(count)
+            });
+            SavedSearch {
+                result_count,
+                ..search
+            }
+        })
+        .collect()
+}
+
+/// The active Forge's saved searches by name, each with its result count.
+#[tauri::command]
+pub(crate) fn list_saved_searches() -> Result<Vec<SavedSearch>, String> {
+    let forge_root = get_notes_dir();
+    let searches = list_saved_searches_in(&searches_dir(&forge_root)?);
+    Ok(with_result_counts(&forge_root, searches, |text, limit| {
+        crate::semantic::service().search(&forge_root, text, limit)
+    }))
+}
+
+#[tauri::command]
+pub(crate) fn create_saved_search(input: SaveSearchInput) -> Result<SavedSearch, String> {
+    create_saved_search_in(&searches_dir(&get_notes_dir())?, input)
+}
+
+#[tauri::command]
+pub(crate) fn update_saved_search(
+    id: String,
+    input: SaveSearchInput,
+) -> Result<SavedSearch, String> {
+    let forge_root = get_notes_dir();
+    let search = update_saved_search_in(&searches_dir(&forge_root)?, &id, input)?;
+    forget_count(&forge_root, &id);
+    Ok(search)
+}
+
+#[tauri::command]
+pub(crate) fn delete_saved_search(id: String) -> Result<(), String> {
+    let forge_root = get_notes_dir();
+    delete_saved_search_in(&searches_dir(&forge_root)?, &id)?;
+    forget_count(&forge_root, &id);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn run_saved_search(id: String, max_results: u32) -> Result<Vec<ContentMatch>, String> {
+    let forge_root = get_notes_dir();
+    let dir = searches_dir(&forge_root)?;
+    validated_search_path(&dir, &id, true)?;
+    let search = list_saved_searches_in(&dir)
+        .into_iter()
+        .find(|search| search.id == id)
+        .ok_or_else(|| format!("Saved search '{id}' not found"))?;
+    run_saved_search_in(&forge_root, &search, max_results, |text, limit| {
+        crate::semantic::service().search(&forge_root, text, limit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::SearchOptions;
+
+    use super::*;
+
+    fn temp_forge(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "moldavite-saved-searches-{tag}-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(root.join("notes/Projects")).unwrap();
+        root
+    }
+
+    fn input(name: &str, query: &str) -> SaveSearchInput {
+        SaveSearchInput {
+            name: name.to_string(),
+            query: query.to_string(),
+            mode: SearchMode::Keyword,
+            options: SearchOptions::default(),
+        }
+    }
+
+    fn no_semantic(_: &str, _: usize) -> Result<Vec<SemanticHit>, String> {
+        Err("Semantic search is not ready".to_string())
+    }
+
+    #[test]
+    fn saved_searches_round_trip_and_reject_bad_input() {
+        let forge = temp_forge("crud");
+        let dir = searches_dir(&forge).unwrap();
+        assert!(list_saved_searches_in(&dir).is_empty());
+
+        let todos =
+            create_saved_search_in(&dir, input(" Open TODOs ", "TODO folder:Projects")).unwrap();
+        assert_eq!(todos.id, "open-todos");
+        assert_eq!(todos.name, "Open TODOs");
+        let whole_word = SaveSearchInput {
+            options: SearchOptions {
+                whole_word: true,
+                ..SearchOptions::default()
+            },
+            ..input("Incidents", "tag:incident")
+        };
+        create_saved_search_in(&dir, whole_word).unwrap();
+        let names: Vec<String> = list_saved_searches_in(&dir)
+            .into_iter()
+            .map(|search| search.name)
+            .collect();
+        assert_eq!(names, ["Incidents", "Open TODOs"]);
+        // Counts are never stored.
+        let stored = fs::read_to_string(dir.join("open-todos.json")).unwrap();
+        assert!(!stored.contains("resultCount"));
+
+        assert!(create_saved_search_in(&dir, input("open todos", "x")).is_err());
+        assert!(create_saved_search_in(&dir, input("Broken", "(unclosed")).is_err());
+        assert!(create_saved_search_in(&dir, input("Empty", "  ")).is_err());
+        assert!(create_saved_search_in(&dir, input("!!!", "x")).is_err());
+        assert!(update_saved_search_in(&dir, "../escape", input("x", "x")).is_err());
+        assert!(update_saved_search_in(&dir, "incidents", input("Open TODOs", "x")).is_err());
+
+        let renamed = SaveSearchInput {
+            mode: SearchMode::Hybrid,
+            ..input("Incidents this month", "tag:incident modified:>=2026-10-01")
+        };
+        let updated = update_saved_search_in(&dir, "incidents", renamed).unwrap();
+        assert_eq!(updated.id, "incidents");
+        assert_eq!(
+            find_saved_search_in(&dir, "INCIDENTS THIS MONTH").unwrap(),
+            updated
+        );
+        assert_eq!(find_saved_search_in(&dir, "open-todos").unwrap(), todos);
+        let missing = find_saved_search_in(&dir, "nope").unwrap_err();
+        assert!(
+            missing.contains("Incidents this month, Open TODOs"),
+            "{missing}"
+        );
+
+        delete_saved_search_in(&dir, "incidents").unwrap();
+        assert_eq!(list_saved_searches_in(&dir), [todos]);
+        let _ = fs::remove_dir_all(&forge);
+    }
+
+    #[test]
+    fn result_counts_are_cached_until_the_forge_changes() {
+        let forge = temp_forge("counts");
+        let dir = searches_dir(&forge).unwrap();
+        fs::write(forge.join("notes/Projects/a.md"), "TODO ship\n").unwrap();
+        fs::write(forge.join("notes/b.md"), "TODO elsewhere\n").unwrap();
+        create_saved_search_in(&dir, input("Project TODOs", "TODO folder:Projects")).unwrap();
+        let semantic = SaveSearchInput {
+            mode: SearchMode::Semantic,
+            ..input("Related", "shipping plans")
+        };
+        create_saved_search_in(&dir, semantic).unwrap();
+
+        let counts = |forge: &Path| -> Vec<Option<usize>> {
+            with_result_counts(forge, list_saved_searches_in(&dir), no_semantic)
+                .into_iter()
+                .map(|search| search.result_count)
+                .collect()
+        };
+        assert_eq!(counts(&forge), [Some(1), None]);
+        fs::write(forge.join("notes/Projects/c.md"), "TODO test\n").unwrap();
+        // Until the watcher reports the change, the cached count stands.
+        assert_eq!(counts(&forge), [Some(1), None]);
+        invalidate_counts(&forge);
+        assert_eq!(counts(&forge), [Some(2), None]);
+
+        let search = find_saved_search_in(&dir, "Project TODOs").unwrap();
+        let results = run_saved_search_in(&forge, &search, 20, no_semantic).unwrap();
+        assert_eq!(results.len(), 2);
+        let _ = fs::remove_dir_all(&forge);
+    }
+}
@@ -4,13 +4,27 @@
 //! the frontend addressing contract: daily and weekly notes use bare filenames,
 //! while standalone notes retain their `notes/`-relative folder path. Snippets
 //! are Unicode-boundary safe and result counts are capped by the caller's limit.
+//! Queries are parsed once here for the app and MCP alike, and hits are ranked
+//! by BM25 with filename, heading, and tag boosts. Hybrid search fuses that
+//! ranking with the semantic index's.
 
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDate};
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
 use walkdir::WalkDir;
 
+use crate::frontmatter::ParsedNote;
 use crate::paths::{get_notes_dir, get_trash_dir};
-use crate::types::ContentMatch;
+use crate::semantic::SemanticHit;
+use crate::types::{ContentMatch, SearchMode, SearchOptions, SearchSignal};
 
 pub(crate) fn classify_note_path(
     notes_dir: &Path,
@@ -36,18 +50,18 @@ pub(crate) fn classify_note_path(
     }
 }
 
-pub(crate) fn build_snippet(line: &str, term_lower: &str, max_width: usize) -> String {
-    let line_lower = line.to_lowercase();
-    let idx = line_lower.find(term_lower).unwrap_or(0);
+/// The part of `line` a snippet shows: about `max_width` bytes centred on
+/// `matched`, widened to character boundaries.
+fn snippet_window(line: &str, matched: &Range<usize>, max_width: usize) -> Range<usize> {
     let half = max_width / 2;
     let start_byte = {
-        let mut s = idx.saturating_sub(half);
+        let mut s = matched.start.saturating_sub(half);
         while !line.is_char_boundary(s) && s > 0 {
             s -= 1;
         }
         s
     };
-    let end_target = (idx + term_lower.len() + half).min(line.len());
+    let end_target = (matched.end + half).min(line.len());
     let end_byte = {
         let mut e = end_target;
         while e < line.len() && !line.is_char_boundary(e) {
@@ -55,29 +69,66 @@ pub(crate) fn build_snippet(line: &str, term_lower: &str, max_width: usize) -> S
         }
         e
     };
-    let prefix = if start_byte > 0 { "…" } else { "" };
-    let suffix = if end_byte < line.len() { "…" } else { "" };
-    format!("{}{}{}", prefix, &line[start_byte..end_byte], suffix)
+    start_byte..end_byte
 }
 
-pub(crate) fn search_notes_content_in(
+/// A snippet of `line` around the byte span `matched` of it, with ellipses
+/// where the line was cut.
+pub(crate) fn build_snippet(line: &str, matched: Range<usize>, max_width: usize) -> String {
+    let window = snippet_window(line, &matched, max_width);
+    let prefix = if window.start > 0 { "…" } else { "" };
+    let suffix = if window.end < line.len() { "…" } else { "" };
+    format!("{}{}{}", prefix, &line[window], suffix)
+}
+
+/// [`build_snippet`] around `matched`, and where each of `spans` shows in the
+/// snippet as UTF-16 offsets, the units the frontend slices strings in.
+fn highlighted_snippet(
+    line: &str,
+    matched: Range<usize>,
+    spans: &[Range<usize>],
+    max_width: usize,
+) -> (String, Vec<[usize; 2]>) {
+    let window = snippet_window(line, &matched, max_width);
+    let lead = usize::from(window.start > 0);
+    let units = |range: Range<usize>| line[range].encode_utf16().count();
+    let highlights = spans
+        .iter()
+        .filter(|span| span.start < window.end && span.end > window.start)
+        .map(|span| {
+            let start = span.start.max(window.start);
+            let end = span.end.min(window.end);
+            let from = lead + units(window.start..start);
+            [from, from + units(start..end)]
+        })
+        .collect();
+    (build_snippet(line, matched, max_width), highlights)
+}
+
+/// Every visible unlocked Markdown file under `notes_dir`, skipping the trash
+/// and hidden directories and never following symlinks.
+pub(crate) fn visible_markdown_files<'a>(
     notes_dir: &Path,
-    trash_dir: &Path,
-    query: &str,
-    max_results: u32,
-) -> Vec<ContentMatch> {
-    let query = query.trim();
-    if query.is_empty() {
-        return Vec::new();
-    }
-    let term_lower = query.to_lowercase();
-    let cap = max_results.clamp(1, 500) as usize;
-    let mut results: Vec<ContentMatch> = Vec::new();
+    trash_dir: &'a Path,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    visible_files(notes_dir, trash_dir).filter(|path| {
+        // Only unlocked markdown files
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| name.ends_with(".md"))
+    })
+}
 
-    let walker = WalkDir::new(notes_dir)
+/// Every visible file under `notes_dir`, locked notes included, skipping the
+/// trash and hidden directories and never following symlinks.
+pub(crate) fn visible_files<'a>(
+    notes_dir: &Path,
+    trash_dir: &'a Path,
+) -> impl Iterator<Item = PathBuf> + 'a {
+    WalkDir::new(notes_dir)
         .follow_links(false)
         .into_iter()
-        .filter_entry(|entry| {
+        .filter_entry(move |entry| {
             // Skip the trash directory entirely
             if entry.path() == trash_dir {
                 return false;
@@ -92,90 +143,1547 @@ pub(crate) fn search_notes_content_in(
                 return false;
             }
             true
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+}
+
+/// Read one note and report where `term_lower` occurs in its body, or `None`
+/// when it doesn't (or the file can't be read or addressed).
+pub(crate) fn match_note(notes_dir: &Path, path: &Path, term_lower: &str) -> Option<NoteHit> {
+    let matcher = TermMatcher::new(term_lower, SearchOptions::default());
+    NoteFacts::read(notes_dir, path, SearchOptions::default())?
+        .hit(&Expr::Text(term_lower.to_string()), &[matcher])
+}
+
+/// Where the first match in a note is.
+struct FirstMatch {
+    line_number: usize,
+    snippet: String,
+    /// Where each match on the line shows in `snippet`, in UTF-16 offsets.
+    highlights: Vec<[usize; 2]>,
+    /// Each match on the line, in byte offsets into it.
+    spans: Vec<[usize; 2]>,
+}
+
+/// Occurrences of each of `terms` in `content`, and where the first of them
+/// is.
+fn locate_terms(
+    content: &str,
+    terms: &[TermMatcher],
+    options: SearchOptions,
+) -> (Vec<u32>, Option<FirstMatch>) {
+    let mut occurrences = vec![0u32; terms.len()];
+    let mut first = None;
+    for (idx, line) in content.lines().enumerate() {
+        let folded = Folded::new(line, options.fold_diacritics);
+        let mut on_line: Vec<Range<usize>> = Vec::new();
+        for (term, total) in terms.iter().zip(occurrences.iter_mut()) {
+            let spans = term.find_in(&folded);
+            *total = total.saturating_add(spans.len() as u32);
+            if first.is_none() {
+                on_line.extend(spans);
+            }
+        }
+        if first.is_none() && !on_line.is_empty() {
+            // Centre on the first term found, and mark every term found.
+            let matched = on_line[0].clone();
+            on_line.sort_by_key(|span| span.start);
+            let (snippet, highlights) = highlighted_snippet(line, matched, &on_line, 120);
+            first = Some(FirstMatch {
+                line_number: idx + 1,
+                snippet,
+                highlights,
+                spans: on_line.iter().map(|span| [span.start, span.end]).collect(),
+            });
+        }
+    }
+    (occurrences, first)
+}
+
+// =============================================================================
+// MATCHING
+// =============================================================================
+
+// Text is compared folded: lowercased a character at a time, with final sigma
+// read as sigma, and with `fold_diacritics` also decomposed and stripped of its
+// combining marks, so "Café" reads as "cafe" and "Straße" as "strasse". A word
+// or phrase matches wherever its folded form occurs in the folded text. With
+// `fuzzy`, each of its words may also match a whole word a few edits away —
+// none for words of one or two characters, one up to five, two beyond — with
+// a swap of neighbouring characters counting as one edit. With `whole_word`, a
+// match must start and end at word boundaries, where `\b` in a regular
+// expression would match. With `regex`, the whole query is one regular
+// expression, matched case-insensitively against the folded text; the regex
+// engine runs in time linear in the text, and patterns are capped in length
+// and compiled size, so no pattern can hang a search. Every match maps back to
+// the span of the original text it folds from, so snippets centre on, and
+// highlights mark, what is actually there.
+
+/// Longest regular expression a search accepts, in bytes.
+const MAX_PATTERN_LEN: usize = 1_000;
+/// Memory a compiled regular expression and its lazy DFA may each use.
+const PATTERN_SIZE_LIMIT: usize = 1 << 20;
+/// Deepest nesting of groups and repetitions a regular expression may have.
+const PATTERN_NEST_LIMIT: u32 = 64;
+
+/// Text folded for matching, remembering where each byte of it came from.
+pub(crate) struct Folded {
+    pub(crate) text: String,
+    /// For each byte of `text`, the byte span of the original character it
+    /// folds from, followed by any characters folded away after it.
+    origin: Vec<(u32, u32)>,
+}
+
+impl Folded {
+    pub(crate) fn new(original: &str, diacritics: bool) -> Folded {
+        let mut text = String::with_capacity(original.len());
+        let mut origin = Vec::with_capacity(original.len());
+        let mut last_char = 0;
+        for (at, c) in original.char_indices() {
+            let span = (at as u32, (at + c.len_utf8()) as u32);
+            let before = text.len();
+            push_folded(c, diacritics, &mut text);
+            if text.len() > before {
+                last_char = before;
+                origin.resize(text.len(), span);
+            } else {
+                // A mark folded away belongs to the character before it.
+                for entry in &mut origin[last_char..] {
+                    entry.1 = span.1;
+                }
+            }
+        }
+        Folded { text, origin }
+    }
+
+    /// The original span the folded bytes `range` come from.
+    fn original(&self, range: Range<usize>) -> Range<usize> {
+        if range.is_empty() {
+            return 0..0;
+        }
+        self.origin[range.start].0 as usize..self.origin[range.end - 1].1 as usize
+    }
+}
+
+/// `text` folded for matching.
+pub(crate) fn fold(text: &str, diacritics: bool) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_folded(c, diacritics, &mut folded);
+    }
+    folded
+}
+
+fn push_folded(c: char, diacritics: bool, out: &mut String) {
+    let mut push = |c: char| {
+        for lower in c.to_lowercase() {
+            match lower {
+                'ς' => out.push('σ'),
+                _ if !diacritics => out.push(lower),
+                // Letters with strokes and ligatures don't decompose.
+                'ß' => out.push_str("ss"),
+                'æ' => out.push_str("ae"),
+                'œ' => out.push_str("oe"),
+                'ø' => out.push('o'),
+                'ł' => out.push('l'),
+                'đ' => out.push('d'),
+                'ı' => out.push('i'),
+                _ if is_combining_mark(lower) => {}
+                _ => out.push(lower),
+            }
+        }
+    };
+    if diacritics {
+        decompose_canonical(c, |part| {
+            if !is_combining_mark(part) {
+                push(part);
+            }
         });
+    } else {
+        push(c);
+    }
+}
 
-    for entry in walker.flatten() {
-        if !entry.file_type().is_file() {
-            continue;
+/// Compile a user's regular expression, matching case-insensitively unless
+/// `case_sensitive`, and only at word boundaries when `whole_word`. Patterns
+/// are capped in length and compiled size; errors say what is wrong with the
+/// pattern for the person who typed it.
+pub(crate) fn build_regex(
+    source: &str,
+    case_sensitive: bool,
+    whole_word: bool,
+) -> Result<Regex, String> {
+    if source.len() > MAX_PATTERN_LEN {
+        return Err(format!(
+            "Regular expression is longer than {MAX_PATTERN_LEN} characters"
+        ));
+    }
+    let build = |source: &str| {
+        RegexBuilder::new(source)
+            .case_insensitive(!case_sensitive)
+            .size_limit(PATTERN_SIZE_LIMIT)
+            .dfa_size_limit(PATTERN_SIZE_LIMIT)
+            .nest_limit(PATTERN_NEST_LIMIT)
+            .build()
+            .map_err(|err| match err {
+                regex::Error::CompiledTooBig(_) => "Regular expression is too complex".to_string(),
+                err => {
+                    // Syntax errors end with a line saying what is wrong.
+                    let message = err.to_string();
+                    let reason = message.lines().last().unwrap_or_default();
+                    format!(
+                        "Invalid regular expression: {}",
+                        reason.trim().trim_start_matches("error: ")
+                    )
+                }
+            })
+    };
+    // Checked alone first, so a stray `)` can't close the group that keeps a
+    // whole-word pattern together. The half boundaries only refuse a word
+    // character next to the match, so `C++` and `foo()` still match before a
+    // space or at the end of a line.
+    let regex = build(source)?;
+    if whole_word {
+        build(&format!(r"\b{{start-half}}(?:{source})\b{{end-half}}"))
+    } else {
+        Ok(regex)
+    }
+}
+
+/// A query's regular expression, compiled for folded text.
+#[derive(Debug, Clone)]
+struct Pattern(Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Pattern) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Pattern {
+    /// Compile `source` to match text folded with `options`. Errors say what
+    /// is wrong with it for the person who typed it.
+    fn compile(source: &str, options: SearchOptions) -> Result<Pattern, String> {
+        if options.fuzzy {
+            return Err("Typo tolerance can't be combined with a regular expression".to_string());
         }
-        let path = entry.path();
-        let filename = match path.file_name().and_then(|n| n.to_str()) {
-            Some(n) => n,
-            None => continue,
+        // Only letters beyond ASCII fold, so escapes such as \W keep their
+        // meaning.
+        let source: String = if options.fold_diacritics {
+            let mut folded = String::with_capacity(source.len());
+            for c in source.chars() {
+                if c.is_ascii() {
+                    folded.push(c);
+                } else {
+                    push_folded(c, true, &mut folded);
+                }
+            }
+            folded
+        } else {
+            source.to_string()
         };
-        // Only unlocked markdown files
-        if filename.ends_with(".md.locked") {
-            continue;
+        build_regex(&source, false, options.whole_word).map(Pattern)
+    }
+
+    /// Spans of the non-empty matches in `text`.
+    fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Range<usize>> + 'a {
+        self.0
+            .find_iter(text)
+            .map(|found| found.range())
+            .filter(|span| !span.is_empty())
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Whether `span` of `text` has no word character right before or after it,
+/// like the half boundaries `build_regex` puts around a whole-word pattern.
+fn at_word_boundaries(text: &str, span: &Range<usize>) -> bool {
+    let before = text[..span.start].chars().next_back();
+    let after = text[span.end..].chars().next();
+    !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+}
+
+/// Edits a query word may be off by when fuzzy.
+fn max_edits_of(word: &[char]) -> usize {
+    match word.len() {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Whether `a` turns into `b` with at most `limit` insertions, deletions,
+/// substitutions, and swaps of neighbouring characters.
+fn within_edits(a: &[char], b: &[char], limit: usize) -> bool {
+    if a.len().abs_diff(b.len()) > limit {
+        return false;
+    }
+    // Three rows of the optimal string alignment table.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut current = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(before[j - 2] + 1);
+            }
+            current[j] = best;
         }
-        if !filename.ends_with(".md") {
-            continue;
+        if current.iter().all(|&edits| edits > limit) {
+            return false;
         }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()] <= limit
+}
 
-        let Ok(raw) = fs::read_to_string(path) else { continue };
-        // Don't search YAML frontmatter — it would surface "color: red" as a
-        // hit when the user searches for "red".
-        let content = crate::frontmatter::parse_note(&raw).body;
-        let content_lower = content.to_lowercase();
-        if !content_lower.contains(&term_lower) {
-            continue;
+/// Byte spans of the words in `text`: maximal runs of alphanumeric
+/// characters, as [`terms_of`] splits it.
+fn word_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (at, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(at),
+            (false, Some(from)) => {
+                spans.push(from..at);
+                start = None;
+            }
+            _ => {}
         }
+    }
+    if let Some(from) = start {
+        spans.push(from..text.len());
+    }
+    spans
+}
 
-        let mut match_count: u32 = 0;
-        let mut first_line_number: usize = 0;
-        let mut first_snippet: Option<String> = None;
-        for (idx, line) in content.lines().enumerate() {
-            let line_lower = line.to_lowercase();
-            let occurrences = line_lower.matches(&term_lower).count() as u32;
-            if occurrences == 0 {
-                continue;
+/// One word or phrase of a query, or its regular expression, folded and
+/// ready to match.
+pub(crate) struct TermMatcher {
+    folded: String,
+    /// With `fuzzy`, the term's words and the edits each may be off by.
+    words: Vec<(Vec<char>, usize)>,
+    whole_word: bool,
+    pattern: Option<Pattern>,
+}
+
+impl TermMatcher {
+    pub(crate) fn new(term: &str, options: SearchOptions) -> TermMatcher {
+        let folded = fold(term, options.fold_diacritics);
+        let words = if options.fuzzy {
+            terms_of(&folded)
+                .map(|word| {
+                    let chars: Vec<char> = word.chars().collect();
+                    let limit = max_edits_of(&chars);
+                    (chars, limit)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        TermMatcher {
+            folded,
+            words,
+            whole_word: options.whole_word,
+            pattern: None,
+        }
+    }
+
+    fn pattern(pattern: Pattern) -> TermMatcher {
+        TermMatcher {
+            folded: pattern.0.as_str().to_string(),
+            words: Vec::new(),
+            whole_word: false,
+            pattern: Some(pattern),
+        }
+    }
+
+    /// Spans of the matches in `folded` that need no edits, in order.
+    fn exact_spans<'a>(&'a self, folded: &'a str) -> Box<dyn Iterator<Item = Range<usize>> + 'a> {
+        if let Some(pattern) = &self.pattern {
+            return Box::new(pattern.find_iter(folded));
+        }
+        if self.folded.is_empty() {
+            return Box::new(std::iter::empty());
+        }
+        Box::new(
+            folded
+                .match_indices(&self.folded)
+                .map(|(at, found)| at..at + found.len())
+                .filter(move |span| !self.whole_word || at_word_boundaries(folded, span)),
+        )
+    }
+
+    /// Whether text folded with the same options has a match.
+    pub(crate) fn is_in(&self, folded: &str) -> bool {
+        self.exact_spans(folded).next().is_some() || !self.fuzzy_spans(folded, true).is_empty()
+    }
+
+    /// The original spans of the matches in `folded`, in order and without
+    /// overlaps.
+    fn find_in(&self, folded: &Folded) -> Vec<Range<usize>> {
+        let mut spans: Vec<Range<usize>> = self.exact_spans(&folded.text).collect();
+        for near in self.fuzzy_spans(&folded.text, false) {
+            if !spans
+                .iter()
+                .any(|span| span.start < near.end && near.start < span.end)
+            {
+                spans.push(near);
             }
-            if first_snippet.is_none() {
-                first_line_number = idx + 1;
-                first_snippet = Some(build_snippet(line, &term_lower, 120));
+        }
+        spans.sort_by_key(|span| span.start);
+        spans
+            .into_iter()
+            .map(|span| folded.original(span))
+            .collect()
+    }
+
+    /// Runs of words in `folded` within edits of the term's words, or just
+    /// the first when `first_only`.
+    fn fuzzy_spans(&self, folded: &str, first_only: bool) -> Vec<Range<usize>> {
+        let mut spans = Vec::new();
+        if self.words.is_empty() {
+            return spans;
+        }
+        let found = word_spans(folded);
+        let chars: Vec<Vec<char>> = found
+            .iter()
+            .map(|span| folded[span.clone()].chars().collect())
+            .collect();
+        let mut at = 0;
+        while at + self.words.len() <= found.len() {
+            if self
+                .words
+                .iter()
+                .zip(&chars[at..])
+                .all(|((word, limit), candidate)| within_edits(word, candidate, *limit))
+            {
+                spans.push(found[at].start..found[at + self.words.len() - 1].end);
+                if first_only {
+                    break;
+                }
+                at += self.words.len();
+            } else {
+                at += 1;
             }
-            match_count = match_count.saturating_add(occurrences);
         }
+        spans
+    }
+
+    /// Whether `folded` has every word of the term, the test for whether a
+    /// note counts towards the term's document frequency. A term without
+    /// words counts where it occurs.
+    fn words_in(&self, folded: &str) -> bool {
+        if self.pattern.is_some() {
+            return self.is_in(folded);
+        }
+        let mut words = terms_of(&self.folded).peekable();
+        if words.peek().is_none() {
+            return !self.folded.is_empty() && folded.contains(&self.folded);
+        }
+        if self.words.is_empty() {
+            return words.all(|word| folded.contains(word));
+        }
+        let found: Vec<Vec<char>> = terms_of(folded)
+            .map(|word| word.chars().collect())
+            .collect();
+        words.zip(&self.words).all(|(word, (chars, limit))| {
+            folded.contains(word)
+                || found
+                    .iter()
+                    .any(|candidate| within_edits(chars, candidate, *limit))
+        })
+    }
+}
+
+/// Whether a word of a note, folded, matches the folded query word `word`:
+/// contains it, or with `fuzzy` is within edits of it.
+pub(crate) fn word_matches(found: &str, word: &str, fuzzy: bool) -> bool {
+    if found.contains(word) {
+        return true;
+    }
+    if !fuzzy {
+        return false;
+    }
+    let word: Vec<char> = word.chars().collect();
+    let limit = max_edits_of(&word);
+    limit > 0
+        && found.chars().count().abs_diff(word.len()) <= limit
+        && within_edits(&word, &found.chars().collect::<Vec<char>>(), limit)
+}
+
+// =============================================================================
+// RANKING
+// =============================================================================
+//
+// Hits are ranked with BM25 over note bodies. Each query word or phrase a note
+// contains adds its BM25 weight, multiplied up when the note's filename, one
+// of its headings, or one of its #tags contains it too. The statistics BM25
+// needs — how many notes there are, their average length in terms, and how
+// many notes have each query term — come from the full-text index when it is
+// ready and from the scan itself otherwise, counted the same way either way.
+// A note's length is its number of terms, the alphanumeric runs the index
+// splits bodies into, and a phrase counts as in a note when all of its words
+// are. Ties go to more occurrences, then filename, then path, so every caller
+// orders the same hits the same way.
+
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+const FILENAME_BOOST: f64 = 2.0;
+const HEADING_BOOST: f64 = 1.0;
+const TAG_BOOST: f64 = 0.5;
 
-        let Some(snippet) = first_snippet else { continue };
-        let Some((rel_path, is_daily, is_weekly, folder_path)) =
-            classify_note_path(notes_dir, path)
-        else {
+/// A term occurs in a heading line (`# Title` … `###### Title`).
+pub(crate) const IN_HEADING: u8 = 1;
+/// A term occurs in an inline #tag.
+pub(crate) const IN_TAG: u8 = 2;
+/// A term occurs in the filename, `.md` aside.
+pub(crate) const IN_FILENAME: u8 = 4;
+
+lazy_static! {
+    // Same rules as the editor's tag parser in src/lib/tags.ts.
+    static ref TAG_RE: Regex = Regex::new(r"#([a-zA-Z][a-zA-Z0-9-]*)").unwrap();
+    static ref URL_RE: Regex = Regex::new(r#"(?i)(https?://|www\.)[^\s<>"']+"#).unwrap();
+}
+
+/// The notes a search ranks against.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Corpus {
+    pub(crate) notes: u32,
+    /// Terms in all their bodies together.
+    pub(crate) terms: u64,
+}
+
+impl Corpus {
+    fn add(&mut self, body_lower: &str) {
+        self.notes += 1;
+        self.terms += u64::from(term_count(body_lower));
+    }
+}
+
+/// BM25 weight of one query term in one note, boosted by the `fields` it
+/// also occurs in. `notes_with_term` counts the notes of `corpus` that have it.
+pub(crate) fn bm25(
+    corpus: Corpus,
+    notes_with_term: u32,
+    occurrences: u32,
+    note_terms: u32,
+    fields: u8,
+) -> f64 {
+    if occurrences == 0 {
+        return 0.0;
+    }
+    let notes = f64::from(corpus.notes.max(notes_with_term));
+    let with_term = f64::from(notes_with_term);
+    let idf = (1.0 + (notes - with_term + 0.5) / (with_term + 0.5)).ln();
+    let relative_length = if corpus.terms == 0 {
+        1.0
+    } else {
+        f64::from(note_terms) * notes / corpus.terms as f64
+    };
+    let tf = f64::from(occurrences);
+    let saturated =
+        tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * (1.0 - BM25_B + BM25_B * relative_length));
+    let mut boost = 1.0;
+    for (field, weight) in [
+        (IN_FILENAME, FILENAME_BOOST),
+        (IN_HEADING, HEADING_BOOST),
+        (IN_TAG, TAG_BOOST),
+    ] {
+        if fields & field != 0 {
+            boost += weight;
+        }
+    }
+    idf * saturated * boost
+}
+
+/// Terms in already-lowercased text: maximal runs of alphanumeric characters.
+pub(crate) fn terms_of(lower: &str) -> impl Iterator<Item = &str> {
+    lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+}
+
+fn term_count(lower: &str) -> u32 {
+    terms_of(lower).count() as u32
+}
+
+/// Whether a note's filename, without `.md`, contains `term_lower`.
+pub(crate) fn filename_has(filename: &str, term_lower: &str) -> bool {
+    let stem = filename.strip_suffix(".md").unwrap_or(filename);
+    stem.to_lowercase().contains(term_lower)
+}
+
+pub(crate) fn is_heading(line: &str) -> bool {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|&c| c == '#').count();
+    let rest = &line[level..];
+    (1..=6).contains(&level) && rest.chars().next().map_or(true, char::is_whitespace)
+}
+
+/// The lowercase names of the inline #tags in `body`, as the editor finds
+/// them: URL fragments aren't tags.
+pub(crate) fn inline_tags(body: &str) -> HashSet<String> {
+    let body = URL_RE.replace_all(body, " ");
+    TAG_RE
+        .captures_iter(&body)
+        .map(|caps| caps[1].to_lowercase())
+        .collect()
+}
+
+/// A note that matched, waiting to be scored against the notes around it.
+pub(crate) struct NoteHit {
+    pub(crate) result: ContentMatch,
+    /// Terms in the body.
+    note_terms: u32,
+    /// Per positive query term: occurrences, and the fields it occurs in.
+    terms: Vec<(u32, u8)>,
+}
+
+impl NoteHit {
+    /// The result with its score, given how many notes of `corpus` have each
+    /// query term.
+    pub(crate) fn scored(mut self, corpus: Corpus, notes_with_term: &[u32]) -> ContentMatch {
+        self.result.score = self
+            .terms
+            .iter()
+            .zip(notes_with_term)
+            .map(|(&(occurrences, fields), &with_term)| {
+                bm25(corpus, with_term, occurrences, self.note_terms, fields)
+            })
+            .sum();
+        self.result
+    }
+}
+
+/// Result order: best score, then most matches, then filename, then path.
+pub(crate) fn compare_ranked(a: &ContentMatch, b: &ContentMatch) -> std::cmp::Ordering {
+    b.score
+        .total_cmp(&a.score)
+        .then_with(|| b.match_count.cmp(&a.match_count))
+        .then_with(|| a.filename.cmp(&b.filename))
+        .then_with(|| a.path.cmp(&b.path))
+}
+
+/// Sort into result order, keeping at most `cap`.
+pub(crate) fn rank_matches(results: &mut Vec<ContentMatch>, cap: usize) {
+    results.sort_by(compare_ranked);
+    results.truncate(cap);
+}
+
+// =============================================================================
+// QUERY LANGUAGE
+// =============================================================================
+//
+// Bare words and "quoted phrases" match note bodies as case-insensitive
+// substrings. Terms next to each other must all match; `OR` and `NOT` (upper
+// case, so prose like "salt and pepper" stays three words) and parentheses
+// combine them. `field:value` terms filter on what the note is rather than
+// what it says:
+//
+//   tag:idea                    inline #idea or a frontmatter `tags` entry
+//   folder:Projects             the folder or anything below it
+//   color:blue                  frontmatter color
+//   is:daily / is:weekly / is:standalone
+//   created:2026-07-01..2026-07-31, modified:>=2026-07-01
+//                               local days; `A..`, `..B`, `>`, `<`, `<=` too
+//   prop:status=done / prop:status
+//                               any other frontmatter property, or its presence
+//
+// A `name:` prefix that isn't one of these stays part of the word, so
+// searching for "TODO:" or "10:30" works as it always did.
+
+const FIELDS: [&str; 7] = [
+    "tag", "folder", "color", "is", "created", "modified", "prop",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Term {
+        field: Option<String>,
+        value: String,
+    },
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut depth = 0usize;
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&c) = chars.peek() else {
+            break;
+        };
+        // Parentheses group only where they can: `(` opening a word and `)`
+        // closing an open group. Anywhere else they are ordinary characters.
+        if c == '(' {
+            chars.next();
+            depth += 1;
+            tokens.push(Token::Open);
             continue;
+        }
+        if c == ')' && depth > 0 {
+            chars.next();
+            depth -= 1;
+            tokens.push(Token::Close);
+            continue;
+        }
+        let mut value = String::new();
+        let mut field = None;
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || (c == ')' && depth > 0) {
+                break;
+            }
+            chars.next();
+            match c {
+                '"' => {
+                    quoted = true;
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => value.push(c),
+                            None => return Err("Unclosed quote in search query".to_string()),
+                        }
+                    }
+                }
+                ':' if !quoted
+                    && field.is_none()
+                    && FIELDS.contains(&value.to_lowercase().as_str()) =>
+                {
+                    field = Some(value.to_lowercase());
+                    value.clear();
+                }
+                c => value.push(c),
+            }
+        }
+        tokens.push(match (quoted || field.is_some(), value.as_str()) {
+            (false, "AND") => Token::And,
+            (false, "OR") => Token::Or,
+            (false, "NOT") => Token::Not,
+            // An empty phrase constrains nothing.
+            (_, "") if field.is_none() => continue,
+            _ => Token::Term { field, value },
+        });
+    }
+    Ok(tokens)
+}
+
+/// Calendar days in local time, both ends inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DayRange {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+}
+
+impl DayRange {
+    fn parse(text: &str) -> Option<DayRange> {
+        let day = |text: &str| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok();
+        let (from, to) = if let Some(rest) = text.strip_prefix(">=") {
+            (Some(day(rest)?), None)
+        } else if let Some(rest) = text.strip_prefix('>') {
+            (Some(day(rest)?.succ_opt()?), None)
+        } else if let Some(rest) = text.strip_prefix("<=") {
+            (None, Some(day(rest)?))
+        } else if let Some(rest) = text.strip_prefix('<') {
+            (None, Some(day(rest)?.pred_opt()?))
+        } else if let Some((from, to)) = text.split_once("..") {
+            let bound = |text: &str| match text {
+                "" => Some(None),
+                text => day(text).map(Some),
+            };
+            (bound(from)?, bound(to)?)
+        } else {
+            let day = day(text)?;
+            (Some(day), Some(day))
+        };
+        if from.is_none() && to.is_none() {
+            return None;
+        }
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return None;
+            }
+        }
+        Some(DayRange { from, to })
+    }
+
+    fn contains(self, day: NaiveDate) -> bool {
+        self.from.map_or(true, |from| day >= from) && self.to.map_or(true, |to| day <= to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Filter {
+    Tag(String),
+    Folder(String),
+    Color(String),
+    Daily,
+    Weekly,
+    Standalone,
+    Created(DayRange),
+    Modified(DayRange),
+    Prop(String, Option<String>),
+}
+
+impl Filter {
+    fn parse(field: &str, value: &str) -> Result<Filter, String> {
+        let value = value.trim();
+        let required = |example: &str| {
+            if value.is_empty() {
+                Err(format!("{field}: needs a value, as in {example}"))
+            } else {
+                Ok(value.to_lowercase())
+            }
         };
+        let days = || {
+            DayRange::parse(value).ok_or_else(|| {
+                format!(
+                    "{field}: expects a date such as 2026-07-12, a range such as \
+                     2026-07-01..2026-07-31, or a bound such as >=2026-07-01"
+                )
+            })
+        };
+        Ok(match field {
+            "tag" => Filter::Tag(required("tag:idea")?.trim_start_matches('#').to_string()),
+            "folder" => Filter::Folder(required("folder:Projects")?.trim_matches('/').to_string()),
+            "color" => Filter::Color(required("color:blue")?),
+            "is" => match value.to_lowercase().as_str() {
+                "daily" => Filter::Daily,
+                "weekly" => Filter::Weekly,
+                "standalone" => Filter::Standalone,
+                _ => return Err("is: must be daily, weekly, or standalone".to_string()),
+            },
+            "created" => Filter::Created(days()?),
+            "modified" => Filter::Modified(days()?),
+            _ => {
+                let (key, wanted) = match value.split_once('=') {
+                    Some((key, wanted)) => (key.trim(), Some(wanted.trim().to_lowercase())),
+                    None => (value, None),
+                };
+                if key.is_empty() {
+                    return Err("prop: needs a property name, as in prop:status=done".to_string());
+                }
+                Filter::Prop(key.to_string(), wanted)
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    /// Lowercase text the body must contain.
+    Text(String),
+    /// A regular expression the body must have a match for.
+    Pattern(Pattern),
+    Filter(Filter),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
 
-        results.push(ContentMatch {
-            filename: filename.to_string(),
-            path: rel_path,
-            snippet,
-            line_number: first_line_number,
-            match_count,
+    fn starts_term(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Term { .. } | Token::Not | Token::Open)
+        )
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut branches = Vec::new();
+        loop {
+            if !self.starts_term() && self.peek() != Some(&Token::And) {
+                return Err("OR needs a term on both sides".to_string());
+            }
+            branches.push(self.and()?);
+            if self.peek() != Some(&Token::Or) {
+                break;
+            }
+            self.pos += 1;
+        }
+        Ok(if branches.len() == 1 {
+            branches.remove(0)
+        } else {
+            Expr::Or(branches)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut parts = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::Or | Token::Close) => break,
+                Some(Token::And) => {
+                    self.pos += 1;
+                    if parts.is_empty() || !self.starts_term() {
+                        return Err("AND needs a term on both sides".to_string());
+                    }
+                }
+                _ => parts.push(self.unary()?),
+            }
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Expr::And(parts)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        let token = self.tokens[self.pos].clone();
+        self.pos += 1;
+        match token {
+            Token::Not => {
+                if !self.starts_term() {
+                    return Err("NOT needs a term after it".to_string());
+                }
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            Token::Open => {
+                match self.peek() {
+                    Some(Token::Close) => {
+                        return Err("Empty parentheses in search query".to_string())
+                    }
+                    None => return Err("Missing closing parenthesis in search query".to_string()),
+                    _ => {}
+                }
+                let inner = self.or()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err("Missing closing parenthesis in search query".to_string());
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Token::Term { field: None, value } => Ok(Expr::Text(value.to_lowercase())),
+            Token::Term {
+                field: Some(field),
+                value,
+            } => Ok(Expr::Filter(Filter::parse(&field, &value)?)),
+            Token::And | Token::Or | Token::Close => {
+                Err("Unexpected operator in search query".to_string())
+            }
+        }
+    }
+}
+
+/// One note as a search reads it. What filters and ranking need beyond its
+/// body is worked out on first use.
+struct NoteFacts {
+    path: PathBuf,
+    filename: String,
+    rel_path: String,
+    is_daily: bool,
+    is_weekly: bool,
+    folder_path: Option<String>,
+    note: ParsedNote,
+    options: SearchOptions,
+    /// The body folded for matching.
+    folded: String,
+    inline_tags: OnceCell<HashSet<String>>,
+    tags: OnceCell<HashSet<String>>,
+    metadata: OnceCell<Option<fs::Metadata>>,
+}
+
+impl NoteFacts {
+    /// `None` when the file can't be read or isn't a note search addresses.
+    fn read(notes_dir: &Path, path: &Path, options: SearchOptions) -> Option<NoteFacts> {
+        let filename = path.file_name()?.to_str()?.to_string();
+        let (rel_path, is_daily, is_weekly, folder_path) = classify_note_path(notes_dir, path)?;
+        let raw = fs::read_to_string(path).ok()?;
+        let note = crate::frontmatter::parse_note(&raw);
+        Some(NoteFacts {
+            path: path.to_path_buf(),
+            filename,
+            rel_path,
             is_daily,
             is_weekly,
             folder_path,
-        });
+            folded: fold(&note.body, options.fold_diacritics),
+            note,
+            options,
+            inline_tags: OnceCell::new(),
+            tags: OnceCell::new(),
+            metadata: OnceCell::new(),
+        })
     }
 
-    results.sort_by(|a, b| {
-        b.match_count
-            .cmp(&a.match_count)
-            .then_with(|| a.filename.cmp(&b.filename))
-    });
-    results.truncate(cap);
-    results
+    fn inline_tags(&self) -> &HashSet<String> {
+        self.inline_tags
+            .get_or_init(|| inline_tags(&self.note.body))
+    }
+
+    /// Inline #tags and the frontmatter `tags` (or `tag`) list.
+    fn tags(&self) -> &HashSet<String> {
+        self.tags.get_or_init(|| {
+            let mut tags = self.inline_tags().clone();
+            for key in ["tags", "tag"] {
+                let listed: Vec<&str> = match self.note.extra.get(key) {
+                    Some(serde_yaml::Value::Sequence(items)) => {
+                        items.iter().filter_map(serde_yaml::Value::as_str).collect()
+                    }
+                    Some(serde_yaml::Value::String(text)) => text
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                tags.extend(
+                    listed
+                        .into_iter()
+                        .map(|tag| tag.trim().trim_start_matches('#').to_lowercase())
+                        .filter(|tag| !tag.is_empty()),
+                );
+            }
+            tags
+        })
+    }
+
+    /// The fields besides the body that `term` occurs in.
+    fn fields_of(&self, term: &TermMatcher) -> u8 {
+        let diacritics = self.options.fold_diacritics;
+        let mut fields = 0;
+        let stem = self.filename.strip_suffix(".md").unwrap_or(&self.filename);
+        if term.is_in(&fold(stem, diacritics)) {
+            fields |= IN_FILENAME;
+        }
+        if self
+            .folded
+            .lines()
+            .any(|line| is_heading(line) && term.is_in(line))
+        {
+            fields |= IN_HEADING;
+        }
+        if self
+            .inline_tags()
+            .iter()
+            .any(|tag| term.is_in(&fold(tag, diacritics)))
+        {
+            fields |= IN_TAG;
+        }
+        fields
+    }
+
+    /// The note as a hit for `expr`, with `terms` matching its positive text
+    /// terms, or `None` when it doesn't match.
+    fn hit(&self, expr: &Expr, terms: &[TermMatcher]) -> Option<NoteHit> {
+        expr.matches(self).then(|| self.found(terms))
+    }
+
+    /// The note as a hit for `terms`, whether or not it contains any of them.
+    fn found(&self, terms: &[TermMatcher]) -> NoteHit {
+        let (occurrences, first) = locate_terms(&self.note.body, terms, self.options);
+        // A note matched on filters alone is shown by its first line. The
+        // snippet is centred on the line's start, so it gets twice the width.
+        let first = first
+            .or_else(|| {
+                self.note
+                    .body
+                    .lines()
+                    .enumerate()
+                    .find(|(_, line)| !line.trim().is_empty())
+                    .map(|(idx, line)| FirstMatch {
+                        line_number: idx + 1,
+                        snippet: build_snippet(line.trim(), 0..0, 240),
+                        highlights: Vec::new(),
+                        spans: Vec::new(),
+                    })
+            })
+            .unwrap_or(FirstMatch {
+                line_number: 1,
+                snippet: String::new(),
+                highlights: Vec::new(),
+                spans: Vec::new(),
+            });
+        let match_count = occurrences
+            .iter()
+            .fold(0u32, |sum, &found| sum.saturating_add(found));
+        NoteHit {
+            result: ContentMatch {
+                filename: self.filename.clone(),
+                path: self.rel_path.clone(),
+                snippet: first.snippet,
+                line_number: first.line_number,
+                highlights: first.highlights,
+                spans: first.spans,
+                match_count,
+                score: 0.0,
+                is_daily: self.is_daily,
+                is_weekly: self.is_weekly,
+                folder_path: self.folder_path.clone(),
+                signals: Vec::new(),
+            },
+            note_terms: term_count(&self.folded),
+            terms: terms
+                .iter()
+                .zip(occurrences)
+                .map(|(term, found)| (found, if found > 0 { self.fields_of(term) } else { 0 }))
+                .collect(),
+        }
+    }
+
+    fn metadata(&self) -> Option<&fs::Metadata> {
+        self.metadata
+            .get_or_init(|| fs::metadata(&self.path).ok())
+            .as_ref()
+    }
+
+    /// The frontmatter `created` date, else when the file was created.
+    fn created(&self) -> Option<NaiveDate> {
+        let from_frontmatter = self
+            .note
+            .extra
+            .get("created")
+            .and_then(serde_yaml::Value::as_str)
+            .and_then(|text| {
+                let text = text.trim();
+                match DateTime::parse_from_rfc3339(text) {
+                    Ok(instant) => Some(instant.with_timezone(&Local).date_naive()),
+                    Err(_) => NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok(),
+                }
+            });
+        from_frontmatter.or_else(|| self.metadata()?.created().ok().map(local_day))
+    }
+
+    fn in_folder(&self, folder: &str) -> bool {
+        let dir = self
+            .rel_path
+            .rsplit_once('/')
+            .map_or("", |(dir, _)| dir)
+            .to_lowercase();
+        let within = |dir: &str| dir == folder || dir.starts_with(&format!("{folder}/"));
+        within(&dir) || dir.strip_prefix("notes/").is_some_and(within)
+    }
+
+    fn has_property(&self, key: &str, wanted: Option<&str>) -> bool {
+        let value = match self.note.extra.get(key) {
+            Some(value) => value,
+            None => match self
+                .note
+                .extra
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            {
+                Some((_, value)) => value,
+                None => return false,
+            },
+        };
+        let Some(wanted) = wanted else {
+            return true;
+        };
+        let is_wanted = |value: &serde_yaml::Value| {
+            let text = match value {
+                serde_yaml::Value::String(text) => text.to_lowercase(),
+                serde_yaml::Value::Number(number) => number.to_string(),
+                serde_yaml::Value::Bool(flag) => flag.to_string(),
+                serde_yaml::Value::Null => String::new(),
+                _ => return false,
+            };
+            text == wanted
+        };
+        match value {
+            serde_yaml::Value::Sequence(items) => items.iter().any(is_wanted),
+            value => is_wanted(value),
+        }
+    }
+}
+
+fn local_day(time: SystemTime) -> NaiveDate {
+    DateTime::<Local>::from(time).date_naive()
+}
+
+impl Expr {
+    fn matches(&self, facts: &NoteFacts) -> bool {
+        match self {
+            Expr::Text(text) => TermMatcher::new(text, facts.options).is_in(&facts.folded),
+            Expr::Pattern(pattern) => pattern.find_iter(&facts.folded).next().is_some(),
+            Expr::Filter(filter) => match filter {
+                Filter::Tag(tag) => facts.tags().contains(tag),
+                Filter::Folder(folder) => facts.in_folder(folder),
+                Filter::Color(color) => facts
+                    .note
+                    .color
+                    .as_deref()
+                    .is_some_and(|own| own.eq_ignore_ascii_case(color)),
+                Filter::Daily => facts.is_daily,
+                Filter::Weekly => facts.is_weekly,
+                Filter::Standalone => !facts.is_daily && !facts.is_weekly,
+                Filter::Created(range) => facts.created().is_some_and(|day| range.contains(day)),
+                Filter::Modified(range) => facts
+                    .metadata()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .is_some_and(|modified| range.contains(local_day(modified))),
+                Filter::Prop(key, wanted) => facts.has_property(key, wanted.as_deref()),
+            },
+            Expr::Not(inner) => !inner.matches(facts),
+            Expr::And(parts) => parts.iter().all(|part| part.matches(facts)),
+            Expr::Or(branches) => branches.iter().any(|branch| branch.matches(facts)),
+        }
+    }
+
+    /// Text terms that count towards a note's matches: every one not under a
+    /// NOT.
+    fn positive_terms<'a>(&'a self, terms: &mut Vec<&'a str>) {
+        match self {
+            Expr::Text(text) => {
+                if !terms.contains(&text.as_str()) {
+                    terms.push(text);
+                }
+            }
+            Expr::Pattern(pattern) => terms.push(pattern.0.as_str()),
+            Expr::And(parts) | Expr::Or(parts) => {
+                for part in parts {
+                    part.positive_terms(terms);
+                }
+            }
+            Expr::Filter(_) | Expr::Not(_) => {}
+        }
+    }
+
+    /// Clauses every matching note satisfies, each a set of texts of which
+    /// the note contains at least one. Empty when the text can't narrow the
+    /// search, as for a query of filters only.
+    fn required_terms(&self) -> Vec<Vec<String>> {
+        match self {
+            Expr::Text(text) => vec![vec![text.clone()]],
+            Expr::And(parts) => parts.iter().flat_map(Expr::required_terms).collect(),
+            Expr::Or(branches) => {
+                let mut union = Vec::new();
+                for branch in branches {
+                    match branch.required_terms().into_iter().next() {
+                        Some(clause) => union.extend(clause),
+                        None => return Vec::new(),
+                    }
+                }
+                vec![union]
+            }
+            Expr::Pattern(_) | Expr::Filter(_) | Expr::Not(_) => Vec::new(),
+        }
+    }
+}
+
+/// A parsed search query, run against one Forge at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchQuery {
+    expr: Expr,
+    options: SearchOptions,
+}
+
+impl SearchQuery {
+    /// Parse `text` to match its words and phrases as `options` say, or with
+    /// `regex` as one regular expression. `None` for a query with nothing to
+    /// search for. Errors describe what is wrong with the query in words
+    /// meant for the person who typed it.
+    pub(crate) fn parse(text: &str, options: SearchOptions) -> Result<Option<SearchQuery>, String> {
+        if options.regex {
+            let source = text.trim();
+            if source.is_empty() {
+                return Ok(None);
+            }
+            return Ok(Some(SearchQuery {
+                expr: Expr::Pattern(Pattern::compile(source, options)?),
+                options,
+            }));
+        }
+        let tokens = tokenize(text)?;
+        if tokens.is_empty() {
+            return Ok(None);
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err("Unmatched closing parenthesis in search query".to_string());
+        }
+        Ok(Some(SearchQuery { expr, options }))
+    }
+
+    /// A matcher for each of `terms`, the query's positive terms.
+    fn matchers(&self, terms: &[&str]) -> Vec<TermMatcher> {
+        match &self.expr {
+            Expr::Pattern(pattern) => vec![TermMatcher::pattern(pattern.clone())],
+            _ => terms
+                .iter()
+                .map(|term| TermMatcher::new(term, self.options))
+                .collect(),
+        }
+    }
+
+    /// Only words and phrases, all required — what semantic search can
+    /// answer in its place.
+    pub(crate) fn is_plain(&self) -> bool {
+        match &self.expr {
+            Expr::Text(_) => true,
+            Expr::And(parts) => parts.iter().all(|part| matches!(part, Expr::Text(_))),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn run(
+        &self,
+        notes_dir: &Path,
+        trash_dir: &Path,
+        max_results: u32,
+    ) -> Vec<ContentMatch> {
+        let cap = max_results.clamp(1, 500) as usize;
+        if let Expr::Text(term) = &self.expr {
+            // The Forge's full-text index answers when it is loaded and
+            // current; otherwise fall back to reading every note. Folded and
+            // fuzzy matches it can only narrow down.
+            if self.options == SearchOptions::default() {
+                if let Some(results) = crate::search_index::search(notes_dir, term, cap) {
+                    return results;
+                }
+            }
+        }
+        let mut terms = Vec::new();
+        self.expr.positive_terms(&mut terms);
+        let matchers = self.matchers(&terms);
+        let clauses = self.expr.required_terms();
+        let candidates = crate::search_index::candidate_paths(notes_dir, &clauses, self.options);
+        let statistics = crate::search_index::statistics(notes_dir, &terms, self.options);
+        let indexed = candidates.zip(statistics);
+        let (hits, corpus, notes_with_term) = match indexed {
+            Some((candidates, (corpus, notes_with_term))) => {
+                let hits = candidates
+                    .iter()
+                    .filter_map(|rel_path| {
+                        NoteFacts::read(notes_dir, &notes_dir.join(rel_path), self.options)
+                    })
+                    .filter_map(|note| note.hit(&self.expr, &matchers))
+                    .collect();
+                (hits, corpus, notes_with_term)
+            }
+            None => self.scan(notes_dir, trash_dir, &matchers),
+        };
+        let mut results: Vec<ContentMatch> = hits
+            .into_iter()
+            .map(|hit: NoteHit| hit.scored(corpus, &notes_with_term))
+            .collect();
+        rank_matches(&mut results, cap);
+        results
+    }
+
+    /// Read every note, gathering the ranking statistics on the way.
+    fn scan(
+        &self,
+        notes_dir: &Path,
+        trash_dir: &Path,
+        matchers: &[TermMatcher],
+    ) -> (Vec<NoteHit>, Corpus, Vec<u32>) {
+        let mut hits = Vec::new();
+        let mut corpus = Corpus::default();
+        let mut notes_with_term = vec![0u32; matchers.len()];
+        for path in visible_markdown_files(notes_dir, trash_dir) {
+            let Some(note) = NoteFacts::read(notes_dir, &path, self.options) else {
+                continue;
+            };
+            corpus.add(&note.folded);
+            for (term, count) in matchers.iter().zip(notes_with_term.iter_mut()) {
+                if term.words_in(&note.folded) {
+                    *count += 1;
+                }
+            }
+            hits.extend(note.hit(&self.expr, matchers));
+        }
+        (hits, corpus, notes_with_term)
+    }
+}
+
+// =============================================================================
+// HYBRID
+// =============================================================================
+
+// A hybrid search runs the keyword search and the semantic index on the same
+// query and fuses the two rankings by reciprocal rank: each ranking a note is
+// in adds 1 / (RRF_K + rank) to its score, so a note both searches find rises
+// above one only either finds, and an exact term the embedding misses, such as
+// a ticket ID, still makes the list. Only ranks are fused, since BM25 scores
+// and cosine similarities aren't on comparable scales. Each result names the
+// searches that found it; a note only semantic search found is shown by its
+// first line with a query word in it, else its first line. Semantic search
+// can't apply operators or filters, so a query with any is answered by
+// keyword search alone.
+
+/// Larger values flatten the lead the top ranks of each search get.
+const RRF_K: f64 = 60.0;
+/// Hits each search contributes, whatever the page size, so a note just
+/// past the page in one ranking can still be lifted by the other.
+const HYBRID_DEPTH: u32 = 50;
+
+fn reciprocal_rank(rank: usize) -> f64 {
+    1.0 / (RRF_K + rank as f64 + 1.0)
+}
+
+impl SearchQuery {
+    /// Run the query as a hybrid search. `semantic` returns the semantic
+    /// index's best hits for it, at most the count given; it isn't called for
+    /// a query with operators or filters.
+    pub(crate) fn run_hybrid(
+        &self,
+        notes_dir: &Path,
+        trash_dir: &Path,
+        max_results: u32,
+        semantic: impl FnOnce(usize) -> Result<Vec<SemanticHit>, String>,
+    ) -> Result<Vec<ContentMatch>, String> {
+        let depth = max_results.clamp(HYBRID_DEPTH, 500);
+        let mut fused = self.run(notes_dir, trash_dir, depth);
+        let mut by_path = HashMap::new();
+        for (rank, hit) in fused.iter_mut().enumerate() {
+            hit.score = reciprocal_rank(rank);
+            hit.signals = vec![SearchSignal::Keyword];
+            by_path.insert(hit.path.clone(), rank);
+        }
+        if self.is_plain() {
+            let mut terms = Vec::new();
+            self.expr.positive_terms(&mut terms);
+            let terms = self.matchers(&terms);
+            for (rank, hit) in semantic(depth as usize)?.iter().enumerate() {
+                if let Some(&at) = by_path.get(&hit.path) {
+                    fused[at].score += reciprocal_rank(rank);
+                    fused[at].signals.push(SearchSignal::Semantic);
+                } else if let Some(mut result) = semantic_match(notes_dir, hit, &terms) {
+                    result.score = reciprocal_rank(rank);
+                    fused.push(result);
+                }
+            }
+        }
+        rank_matches(&mut fused, max_results.clamp(1, 500) as usize);
+        Ok(fused)
+    }
+}
+
+/// A semantic hit shown the way keyword search would show its note, with
+/// `terms` the query words to find a snippet for. `None` when the note is
+/// gone or can't be addressed.
+pub(crate) fn semantic_match(
+    notes_dir: &Path,
+    hit: &SemanticHit,
+    terms: &[TermMatcher],
+) -> Option<ContentMatch> {
+    if !crate::semantic::is_valid_note_index_path(&hit.path) {
+        return None;
+    }
+    let mut result = NoteFacts::read(
+        notes_dir,
+        &notes_dir.join(&hit.path),
+        SearchOptions::default(),
+    )?
+    .found(terms)
+    .result;
+    // A note without the query's words is shown by the passage semantic
+    // search matched rather than by its first line.
+    if let (0, Some(passage)) = (result.match_count, &hit.passage) {
+        result.snippet = build_snippet(&passage.text, 0..0, 240);
+        result.line_number = passage.start_line;
+    }
+    result.signals = vec![SearchSignal::Semantic];
+    Some(result)
+}
+
+/// Answer `query` with the search `mode` names, keyword matches following
+/// `options`. `semantic` searches the semantic index for the given text,
+/// returning at most the count given.
+pub(crate) fn search_in_mode(
+    notes_dir: &Path,
+    trash_dir: &Path,
+    query: &str,
+    max_results: u32,
+    mode: SearchMode,
+    options: SearchOptions,
+    semantic: impl FnOnce(&str, usize) -> Result<Vec<SemanticHit>, String>,
+) -> Result<Vec<ContentMatch>, String> {
+    match mode {
+        SearchMode::Keyword => {
+            search_notes_content_in(notes_dir, trash_dir, query, max_results, options)
+        }
+        SearchMode::Semantic => {
+            let lower = query.to_lowercase();
+            let words: Vec<TermMatcher> = terms_of(&lower)
+                .map(|word| TermMatcher::new(word, SearchOptions::default()))
+                .collect();
+            Ok(semantic(query, max_results.clamp(1, 500) as usize)?
+                .iter()
+                .filter_map(|hit| {
+                    let mut result = semantic_match(notes_dir, hit, &words)?;
+                    result.score = f64::from(hit.score);
+                    Some(result)
+                })
+                .collect())
+        }
+        SearchMode::Hybrid => match SearchQuery::parse(query, options)? {
+            Some(parsed) => parsed.run_hybrid(notes_dir, trash_dir, max_results, |depth| {
+                semantic(query, depth)
+            }),
+            None => Ok(Vec::new()),
+        },
+    }
+}
+
+/// Parse `query` and run it with `options`. An empty query finds nothing; a
+/// malformed one is an error naming the problem.
+pub(crate) fn search_notes_content_in(
+    notes_dir: &Path,
+    trash_dir: &Path,
+    query: &str,
+    max_results: u32,
+    options: SearchOptions,
+) -> Result<Vec<ContentMatch>, String> {
+    Ok(match SearchQuery::parse(query, options)? {
+        Some(query) => query.run(notes_dir, trash_dir, max_results),
+        None => Vec::new(),
+    })
 }
 
 /// Full-text search across all unlocked markdown notes.
 ///
-/// Case-insensitive substring match. Skips `.md.locked` files and the
-/// internal `.trash` directory. Results are sorted by match count desc.
+/// Takes the query language described above, and a query that doesn't parse
+/// is an error. Words and phrases are case-insensitive substring matches.
+/// `options` can fold diacritics, allow typos, match whole words only, or
+/// read the query as a regular expression.
+/// Skips `.md.locked` files and the internal `.trash` directory. Results are
+/// ranked by BM25 score, as described above.
+/// Answered from the active Forge's full-text index once it is ready.
+/// `mode` picks semantic or hybrid search instead; both need the semantic
+/// index to be ready.
 #[tauri::command]
-pub(crate) fn search_notes_content(query: String, max_results: u32) -> Result<Vec<ContentMatch>, String> {
+pub(crate) fn search_notes_content(
+    query: String,
+    max_results: u32,
+    mode: Option<SearchMode>,
+    options: Option<SearchOptions>,
+) -> Result<Vec<ContentMatch>, String> {
     let notes_dir = get_notes_dir();
     let trash_dir = get_trash_dir();
-    Ok(search_notes_content_in(
+    search_in_mode(
         &notes_dir,
         &trash_dir,
         &query,
         max_results,
-    ))
+        mode.unwrap_or_default(),
+        options.unwrap_or_default(),
+        |text, limit| crate::semantic::service().search(&notes_dir, text, limit),
+    )
 }
@@ -19,11 +19,18 @@ use tauri::AppHandle;
 use tauri::Emitter;
 
 #[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
-use crate::paths::get_notes_dir;
+use crate::commands::search::search_in_mode;
+#[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
+use crate::paths::{get_notes_dir, get_trash_dir};
 use crate::persist::{read_config, write_config};
 use crate::semantic::{self, ModelInfo, SemanticHit};
 #[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
 use crate::semantic::{Embedder, Phase, CANCELLED};
+#[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
+use crate::semantic_ann;
+use crate::types::SearchMode;
+#[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
+use crate::types::SearchOptions;
 
 /// Snapshot of the semantic-search lifecycle for the frontend.
 #[derive(Debug, Serialize)]
@@ -156,15 +163,40 @@ pub(crate) fn semantic_set_enabled(enabled: bool, _app: AppHandle) -> Result<(),
 }
 
 /// Embed the query locally and return the `limit` most similar notes.
+/// `mode` runs a keyword or hybrid search instead, each hit carrying the
+/// snippet keyword search shows for it.
 #[tauri::command]
 #[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
-pub(crate) fn semantic_search(query: String, limit: u32) -> Result<Vec<SemanticHit>, String> {
-    semantic::service().search(&query, limit as usize)
+pub(crate) fn semantic_search(
+    query: String,
+    limit: u32,
+    mode: Option<SearchMode>,
+) -> Result<Vec<SemanticHit>, String> {
+    let notes_dir = get_notes_dir();
+    match mode.unwrap_or(SearchMode::Semantic) {
+        SearchMode::Semantic => semantic::service().search(&notes_dir, &query, limit as usize),
+        mode => Ok(search_in_mode(
+            &notes_dir,
+            &get_trash_dir(),
+            &query,
+            limit,
+            mode,
+            SearchOptions::default(),
+            |text, limit| semantic::service().search(&notes_dir, text, limit),
+        )?
+        .into_iter()
+        .map(SemanticHit::from)
+        .collect()),
+    }
 }
 
 #[tauri::command]
 #[cfg(all(target_os = "macos", target_arch = "x86_64"))]
-pub(crate) fn semantic_search(_query: String, _limit: u32) -> Result<Vec<SemanticHit>, String> {
+pub(crate) fn semantic_search(
+    _query: String,
+    _limit: u32,
+    _mode: Option<SearchMode>,
+) -> Result<Vec<SemanticHit>, String> {
     Err(semantic::UNSUPPORTED_MESSAGE.to_string())
 }
 
@@ -282,13 +314,20 @@ fn run_build(app: &AppHandle, force: bool) -> Result<usize, String> {
     // Phase 2: index build/reconcile.
     svc.set_phase(Phase::Indexing);
     let forge_root = get_notes_dir();
-    let existing = if force {
-        Vec::new()
+    let (existing, mut ann) = if force {
+        (Vec::new(), None)
     } else {
-        semantic::load_index(&forge_root, &model_id).unwrap_or_default()
+        (
+            semantic::load_index(&forge_root, &model_id).unwrap_or_default(),
+            semantic_ann::load_ann(&forge_root, &model_id),
+        )
     };
-    let entries =
-        semantic::reconcile_index(&forge_root, embedder.as_ref(), &existing, |done, total| {
+    let entries = semantic::reconcile_index(
+        &forge_root,
+        embedder.as_ref(),
+        &existing,
+        &mut ann,
+        |done, total| {
             // Throttle events on big vaults; always emit the final one.
             let step = (total / 50).max(1);
             if done % step == 0 || done == total {
@@ -297,13 +336,15 @@ fn run_build(app: &AppHandle, force: bool) -> Result<usize, String> {
             // `disable()` flips the phase; abort instead of finishing a
             // build the user just turned off.
             !matches!(semantic::service().phase(), Phase::Disabled)
-        })?;
+        },
+    )?;
     // A Forge switch mid-build means these entries belong to the old vault.
     if get_notes_dir() != forge_root {
         return Err(CANCELLED.to_string());
     }
     semantic::save_index(&forge_root, &entries, &model_id)?;
+    semantic_ann::save_ann(&forge_root, ann.as_ref(), &model_id)?;
     let indexed_count = entries.len();
-    semantic::service().replace_entries(entries);
+    semantic::service().replace_entries(entries, ann);
     Ok(indexed_count)
 }
@@ -40,16 +40,20 @@ fn validated_new_template_path(templates_dir: &Path, id: &str) -> Result<PathBuf
     Ok(path)
 }
 
-#[tauri::command]
-pub(crate) fn list_templates() -> Result<Vec<Template>, String> {
+/// Built-in templates followed by the user templates stored in `templates_dir`.
+/// Unreadable or malformed template files are skipped, and so are symlinks:
+/// the templates folder lives inside the Forge and must not reach outside it.
+pub(crate) fn list_templates_in(templates_dir: &Path) -> Vec<Template> {
     let mut templates = get_default_templates();
 
     // Load custom templates from disk
-    let templates_dir = get_templates_dir()?;
     if templates_dir.exists() {
-        if let Ok(entries) = fs::read_dir(&templates_dir) {
+        if let Ok(entries) = fs::read_dir(templates_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
+                if entry.file_type().is_ok_and(|kind| kind.is_symlink()) {
+                    continue;
+                }
                 if path.extension().is_some_and(|ext| ext == "json") {
                     if let Ok(content) = fs::read_to_string(&path) {
                         if let Ok(template) = serde_json::from_str::<Template>(&content) {
@@ -61,11 +65,11 @@ pub(crate) fn list_templates() -> Result<Vec<Template>, String> {
         }
     }
 
-    Ok(templates)
+    templates
 }
 
-#[tauri::command]
-pub(crate) fn get_template(id: String) -> Result<Template, String> {
+/// Look up a built-in template, then a user template in `templates_dir`.
+pub(crate) fn get_template_in(templates_dir: &Path, id: &str) -> Result<Template, String> {
     // Check default templates first
     let defaults = get_default_templates();
     if let Some(template) = defaults.into_iter().find(|t| t.id == id) {
@@ -73,11 +77,12 @@ pub(crate) fn get_template(id: String) -> Result<Template, String> {
     }
 
     // Check custom templates
-    ensure_templates_dir()?;
-    let templates_dir = get_templates_dir()?;
-    let template_path = validated_existing_template_path(&templates_dir, &id)?;
+    let template_path = validated_existing_template_path(templates_dir, id)?;
 
-    if template_path.exists() {
+    let is_regular_file = fs::symlink_metadata(&template_path)
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false);
+    if is_regular_file {
         let content = fs::read_to_string(&template_path).map_err(|e| e.to_string())?;
         let template: Template = serde_json::from_str(&content).map_err(|e| e.to_string())?;
         return Ok(template);
@@ -86,6 +91,17 @@ pub(crate) fn get_template(id: String) -> Result<Template, String> {
     Err(format!("Template '{}' not found", id))
 }
 
+#[tauri::command]
+pub(crate) fn list_templates() -> Result<Vec<Template>, String> {
+    Ok(list_templates_in(&get_templates_dir()?))
+}
+
+#[tauri::command]
+pub(crate) fn get_template(id: String) -> Result<Template, String> {
+    ensure_templates_dir()?;
+    get_template_in(&get_templates_dir()?, &id)
+}
+
 #[tauri::command]
 pub(crate) fn save_template(input: SaveTemplateInput) -> Result<Template, String> {
     ensure_templates_dir()?;
@@ -22,7 +22,7 @@ use crate::validation::{
     is_safe_existing_filename, is_safe_existing_note_path, validate_path_within_base,
 };
 
-fn next_trash_id() -> String {
+pub(crate) fn next_trash_id() -> String {
     static LAST_ID: AtomicI64 = AtomicI64::new(0);
     let now = chrono::Utc::now().timestamp_millis();
     let mut previous = LAST_ID.load(Ordering::Relaxed);
@@ -154,7 +154,7 @@ fn restore_item_on_disk(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn trash_note_on_disk(
+pub(crate) fn trash_note_on_disk(
     source_dir: &std::path::Path,
     trash_dir: &std::path::Path,
     filename: &str,
@@ -239,9 +239,15 @@ pub fn spawn(app: AppHandle, recent: Arc<RecentWrites>) -> Result<WatcherHandle,
                         if !is_relevant(&rel) {
                             continue;
                         }
+                        // Any note change can change what a saved search
+                        // finds, the app's own writes skipped below included.
+                        if rel.ends_with(".md") || rel.ends_with(".md.locked") {
+                            crate::commands::saved_searches::invalidate_counts(&root_for_thread);
+                        }
                         if recent_for_thread.matches_current_content(&path) {
                             continue;
                         }
+                        crate::search_index::note_changed(&root_for_thread, &rel);
                         let payload = ForgeChange {
                             kind: "modified".into(),
                             rel_path: rel,
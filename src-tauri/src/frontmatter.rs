@@ -19,6 +19,7 @@
 use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
+use serde_yaml::Mapping;
 
 /// Parsed view of a note file: structured frontmatter (if any) + body.
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -46,35 +47,19 @@ fn strip_bom(input: &str) -> &str {
     input.strip_prefix('\u{FEFF}').unwrap_or(input)
 }
 
-/// Parse a note's raw text into frontmatter + body. If the text does not
-/// begin with a `---` fence, the entire text is returned as the body.
-pub fn parse_note(raw: &str) -> ParsedNote {
-    let text = strip_bom(raw);
-
+/// Split a note into its raw YAML block and body, or `None` when the text does
+/// not open with a well-formed, closed `---` fence.
+fn split_frontmatter(text: &str) -> Option<(&str, &str)> {
     // Frontmatter must start at the very beginning of the file.
     if !text.starts_with("---") {
-        return ParsedNote {
-            color: None,
-            extra: BTreeMap::new(),
-            body: text.to_string(),
-        };
+        return None;
     }
 
-    // Skip the opening fence. We accept "---\n" and "---\r\n".
-    let after_open = match text.strip_prefix("---\r\n") {
-        Some(rest) => rest,
-        None => match text.strip_prefix("---\n") {
-            Some(rest) => rest,
-            None => {
-                // Malformed (no newline after the fence) — treat as body.
-                return ParsedNote {
-                    color: None,
-                    extra: BTreeMap::new(),
-                    body: text.to_string(),
-                };
-            }
-        },
-    };
+    // Skip the opening fence. We accept "---\n" and "---\r\n"; anything else
+    // is malformed and stays body text.
+    let after_open = text
+        .strip_prefix("---\r\n")
+        .or_else(|| text.strip_prefix("---\n"))?;
 
     // Find the closing fence on its own line.
     let mut end_idx: Option<usize> = None;
@@ -87,35 +72,113 @@ pub fn parse_note(raw: &str) -> ParsedNote {
         }
         cursor += line.len();
     }
-
-    let Some(end) = end_idx else {
-        // No closing fence — treat the whole thing as body.
-        return ParsedNote {
-            color: None,
-            extra: BTreeMap::new(),
-            body: text.to_string(),
-        };
-    };
+    let end = end_idx?;
 
     let yaml_block = &after_open[..end];
     // Skip past the closing fence line (and its trailing newline).
-    let after_close_start = end;
-    let after_close = &after_open[after_close_start..];
-    let body_start = after_close
+    let after_close = &after_open[end..];
+    let body = after_close
         .strip_prefix("---\r\n")
         .or_else(|| after_close.strip_prefix("---\n"))
         .or_else(|| after_close.strip_prefix("...\r\n"))
         .or_else(|| after_close.strip_prefix("...\n"))
         .unwrap_or("");
+    Some((yaml_block, body))
+}
+
+/// Parse a note's raw text into frontmatter + body. If the text does not
+/// begin with a `---` fence, the entire text is returned as the body.
+pub fn parse_note(raw: &str) -> ParsedNote {
+    let text = strip_bom(raw);
+    let Some((yaml_block, body)) = split_frontmatter(text) else {
+        return ParsedNote {
+            color: None,
+            extra: BTreeMap::new(),
+            body: text.to_string(),
+        };
+    };
 
     let parsed: RawFrontmatter = serde_yaml::from_str(yaml_block).unwrap_or_default();
     ParsedNote {
         color: parsed.color.filter(|s| !s.is_empty()),
         extra: parsed.extra,
-        body: body_start.to_string(),
+        body: body.to_string(),
+    }
+}
+
+/// Byte offset in `raw` where the body [`parse_note`] would return starts,
+/// past any byte-order mark and frontmatter block.
+pub fn body_offset(raw: &str) -> usize {
+    let text = strip_bom(raw);
+    let body = split_frontmatter(text).map_or(text, |(_, body)| body);
+    raw.len() - body.len()
+}
+
+/// Read the frontmatter as an ordered mapping. A note without frontmatter has
+/// an empty one; a block that is not a YAML mapping is an error, so callers
+/// never rewrite metadata they could not read.
+fn frontmatter_mapping(yaml_block: &str) -> Result<Mapping, String> {
+    match serde_yaml::from_str::<serde_yaml::Value>(yaml_block) {
+        Ok(serde_yaml::Value::Mapping(mapping)) => Ok(mapping),
+        Ok(serde_yaml::Value::Null) => Ok(Mapping::new()),
+        _ => Err("Frontmatter is not a YAML mapping".to_string()),
     }
 }
 
+/// Every frontmatter key except `color`, in file order.
+pub fn read_properties(raw: &str) -> Result<Mapping, String> {
+    let Some((yaml_block, _)) = split_frontmatter(strip_bom(raw)) else {
+        return Ok(Mapping::new());
+    };
+    let mut mapping = frontmatter_mapping(yaml_block)?;
+    mapping.shift_remove("color");
+    Ok(mapping)
+}
+
+/// Merge property changes into a note's frontmatter. Existing keys keep their
+/// position, new keys are appended, and `None` removes a key; the body is kept
+/// byte-for-byte. `color` is owned by the app's color picker and is refused
+/// here. Unlike [`serialize_note`], this never reorders keys.
+pub fn merge_properties(
+    raw: &str,
+    changes: &[(String, Option<serde_yaml::Value>)],
+) -> Result<String, String> {
+    let bom = if raw.starts_with('\u{FEFF}') {
+        "\u{FEFF}"
+    } else {
+        ""
+    };
+    let text = strip_bom(raw);
+    let (mut mapping, body) = match split_frontmatter(text) {
+        Some((yaml_block, body)) => (frontmatter_mapping(yaml_block)?, body),
+        None => (Mapping::new(), text),
+    };
+    for (key, value) in changes {
+        if key == "color" {
+            return Err("color is set with the note color picker, not as a property".to_string());
+        }
+        match value {
+            Some(value) => match mapping.get_mut(key.as_str()) {
+                Some(existing) => *existing = value.clone(),
+                None => {
+                    mapping.insert(serde_yaml::Value::String(key.clone()), value.clone());
+                }
+            },
+            None => {
+                mapping.shift_remove(key.as_str());
+            }
+        }
+    }
+    if mapping.is_empty() {
+        return Ok(format!("{bom}{body}"));
+    }
+    let yaml = serde_yaml::to_string(&mapping).map_err(|e| e.to_string())?;
+    Ok(format!(
+        "{bom}---\n{}\n---\n{body}",
+        yaml.trim_end_matches('\n')
+    ))
+}
+
 /// Serialize a note back to disk format. If `color` is `None` and `extra` is
 /// empty, the frontmatter block is omitted entirely so we don't pollute every
 /// file.
@@ -228,6 +291,56 @@ mod tests {
         assert_eq!(p.body, "x");
     }
 
+    #[test]
+    fn body_offset_points_past_bom_and_frontmatter() {
+        for raw in [
+            "\u{FEFF}---\ncolor: green\n---\nx",
+            "---\r\ntitle: a\r\n---\r\nbody\r\n",
+            "\u{FEFF}plain",
+            "---\nunclosed: yes\nbody",
+            "---\nonly: block\n---",
+        ] {
+            assert_eq!(&raw[body_offset(raw)..], parse_note(raw).body, "{raw:?}");
+        }
+    }
+
+    #[test]
+    fn merge_properties_keeps_key_order_color_and_body() {
+        let raw = "---\ntitle: Plan\ncolor: blue\nstatus: draft\ntags:\n- a\n---\n# Body\n\n---\nnot frontmatter\n";
+        let merged = merge_properties(
+            raw,
+            &[
+                ("status".to_string(), Some(serde_yaml::Value::from("done"))),
+                ("tags".to_string(), None),
+                ("due".to_string(), Some(serde_yaml::Value::from(3))),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            merged,
+            "---\ntitle: Plan\ncolor: blue\nstatus: done\ndue: 3\n---\n# Body\n\n---\nnot frontmatter\n"
+        );
+        let keys: Vec<_> = read_properties(&merged)
+            .unwrap()
+            .keys()
+            .map(|key| key.as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(keys, ["title", "status", "due"]);
+    }
+
+    #[test]
+    fn merge_properties_adds_and_drops_the_fence_and_refuses_bad_input() {
+        let added = merge_properties("body", &[("owner".to_string(), Some("me".into()))]).unwrap();
+        assert_eq!(added, "---\nowner: me\n---\nbody");
+        assert_eq!(
+            merge_properties(&added, &[("owner".to_string(), None)]).unwrap(),
+            "body"
+        );
+        assert!(merge_properties("body", &[("color".to_string(), Some("red".into()))]).is_err());
+        assert!(merge_properties("---\n- a list\n---\nbody", &[]).is_err());
+        assert!(read_properties("---\n[broken\n---\nbody").is_err());
+    }
+
     #[test]
     fn empty_color_treated_as_none() {
         let p = parse_note("---\ncolor: \n---\nbody");
@@ -51,6 +51,15 @@ pub(crate) mod forge_watcher;
 /// Best-effort attribution for note writes made through MCP.
 pub(crate) mod agent_writes;
 
+/// MCP writes held for review while agents may only propose changes.
+pub(crate) mod agent_proposals;
+
+/// Pre-write snapshots of notes MCP sessions change, so an agent can be undone.
+pub(crate) mod agent_snapshots;
+
+/// Append-only, rotated log of every MCP tool call.
+pub(crate) mod mcp_audit;
+
 /// Publish a note to WordPress.com over an account the user signs in to.
 pub(crate) mod wordpress;
 
@@ -59,9 +68,15 @@ pub(crate) mod backlinks_index;
 pub(crate) mod commands;
 pub(crate) mod paths;
 pub(crate) mod persist;
+/// Persistent full-text index that answers keyword search.
+pub(crate) mod search_index;
 /// Local semantic (vector) search: embeddings index + query engine.
 pub(crate) mod semantic;
+/// Approximate nearest-neighbour index for large semantic indexes.
+pub(crate) mod semantic_ann;
 pub(crate) mod templates_data;
+/// Line-based unified diffs for reporting note changes.
+pub(crate) mod text_diff;
 pub(crate) mod types;
 pub(crate) mod validation;
 pub(crate) mod wiki;
@@ -86,7 +101,11 @@ use commands::forges::{
 use commands::graph::get_note_graph;
 use commands::import_obsidian::{analyze_obsidian_vault, import_obsidian_vault};
 use commands::locking::{is_note_locked, lock_note, permanently_unlock_note, unlock_note};
-use commands::mcp_settings::{get_app_binary_path, get_mcp_writes_enabled, set_mcp_writes_enabled};
+use commands::mcp_settings::{
+    get_app_binary_path, get_mcp_client_policies, get_mcp_http_token, get_mcp_write_mode,
+    get_mcp_writes_enabled, rotate_mcp_http_token, set_mcp_client_policies, set_mcp_write_mode,
+    set_mcp_writes_enabled,
+};
 use commands::misc::{
     ensure_directories, get_all_note_colors, get_note_color, get_notes_directory,
     open_forge_in_finder, rescan_forge, save_image, set_note_color, write_binary_file,
@@ -285,6 +304,9 @@ pub fn run() {
             tauri::async_runtime::spawn_blocking(move || {
                 idx.rebuild_from_disk();
             });
+            // Load the full-text index and reconcile it with the notes in the
+            // background; search scans every note until it is ready.
+            search_index::open(paths::get_notes_dir());
             // Spawn the file watcher into a slot that survives Forge switches.
             // The slot is managed unconditionally, even if this spawn fails, so
             // a later switch still has somewhere to install its watcher.
@@ -315,12 +337,34 @@ pub fn run() {
             wordpress_sites,
             wordpress_publish,
             agent_writes::take_agent_write,
+            agent_proposals::list_agent_proposals,
+            agent_proposals::get_agent_proposal_diff,
+            agent_proposals::accept_agent_proposal,
+            agent_proposals::reject_agent_proposal,
+            agent_snapshots::list_agent_sessions,
+            agent_snapshots::restore_agent_changes,
+            mcp_audit::list_mcp_audit_entries,
             ensure_directories,
             get_app_binary_path,
             get_mcp_writes_enabled,
             set_mcp_writes_enabled,
+            get_mcp_write_mode,
+            set_mcp_write_mode,
+            get_mcp_http_token,
+            rotate_mcp_http_token,
+            get_mcp_client_policies,
+            set_mcp_client_policies,
             list_notes,
             search_notes_content,
+            commands::replace::preview_replace,
+            commands::replace::apply_replace,
+            commands::replace::list_replace_batches,
+            commands::replace::undo_replace,
+            commands::saved_searches::list_saved_searches,
+            commands::saved_searches::create_saved_search,
+            commands::saved_searches::update_saved_search,
+            commands::saved_searches::delete_saved_search,
+            commands::saved_searches::run_saved_search,
             // Semantic (vector) search commands
             semantic_status,
             semantic_models,
@@ -441,7 +485,9 @@ mod tests {
     use std::fs;
     use std::path::{Path, PathBuf};
 
-    use crate::commands::search::search_notes_content_in;
+    use crate::commands::search::{search_in_mode, search_notes_content_in};
+    use crate::semantic::{Passage, SemanticHit};
+    use crate::types::{ContentMatch, SearchMode, SearchOptions, SearchSignal};
     use crate::validation::{is_safe_filename, validate_path_within_base};
 
     // ---- is_safe_filename --------------------------------------------------
@@ -623,7 +669,14 @@ mod tests {
     fn search_notes_content_finds_matches() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "fox", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         let names: Vec<_> = results.iter().map(|r| r.filename.clone()).collect();
         assert!(names.contains(&"alpha.md".to_string()));
         assert!(names.contains(&"2026-04-24.md".to_string()));
@@ -635,7 +688,14 @@ mod tests {
     fn search_notes_content_excludes_locked_files() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "fox", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         for r in &results {
             assert!(
                 !r.filename.ends_with(".locked"),
@@ -655,7 +715,14 @@ mod tests {
     fn search_notes_content_excludes_index_dir() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "fox", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         assert!(!results.is_empty());
         for r in &results {
             assert!(
@@ -687,8 +754,22 @@ mod tests {
     fn search_notes_content_is_case_insensitive() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let lower = search_notes_content_in(&base, &base.join(".trash"), "fox", 100);
-        let upper = search_notes_content_in(&base, &base.join(".trash"), "FOX", 100);
+        let lower = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
+        let upper = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "FOX",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         assert_eq!(lower.len(), upper.len());
         fs::remove_dir_all(&base).ok();
     }
@@ -697,7 +778,14 @@ mod tests {
     fn search_notes_content_sorts_by_match_count() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "beta", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "beta",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         assert!(!results.is_empty());
         // beta.md has 2 matches, alpha.md has 1. beta.md must come first.
         assert_eq!(results[0].filename, "beta.md");
@@ -709,7 +797,14 @@ mod tests {
     fn search_notes_content_respects_max_results() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "fox", 2);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox",
+            2,
+            SearchOptions::default(),
+        )
+        .unwrap();
         assert!(results.len() <= 2);
         fs::remove_dir_all(&base).ok();
     }
@@ -718,7 +813,14 @@ mod tests {
     fn search_notes_content_empty_query_returns_nothing() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "   ", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "   ",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         assert!(results.is_empty());
         fs::remove_dir_all(&base).ok();
     }
@@ -727,11 +829,581 @@ mod tests {
     fn search_notes_content_reports_folder_path() {
         let base = make_tmp_base();
         seed_notes(&base);
-        let results = search_notes_content_in(&base, &base.join(".trash"), "beta", 100);
+        let results = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "beta",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
         let beta = results.iter().find(|r| r.filename == "beta.md").unwrap();
         assert_eq!(beta.folder_path.as_deref(), Some("Projects"));
         assert!(!beta.is_daily);
         assert!(!beta.is_weekly);
         fs::remove_dir_all(&base).ok();
     }
+
+    fn query_paths(base: &Path, query: &str) -> Vec<String> {
+        let mut paths: Vec<String> = search_notes_content_in(
+            base,
+            &base.join(".trash"),
+            query,
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap_or_else(|e| panic!("{query:?} failed: {e}"))
+        .into_iter()
+        .map(|r| r.path)
+        .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn search_query_errors_name_the_problem() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        for (query, error) in [
+            ("\"quick brown", "Unclosed quote in search query"),
+            ("fox OR", "OR needs a term on both sides"),
+            ("OR fox", "OR needs a term on both sides"),
+            ("fox AND", "AND needs a term on both sides"),
+            ("fox NOT", "NOT needs a term after it"),
+            (
+                "(fox OR beta",
+                "Missing closing parenthesis in search query",
+            ),
+            ("fox ()", "Empty parentheses in search query"),
+            ("tag:", "tag: needs a value, as in tag:idea"),
+            ("is:monthly", "is: must be daily, weekly, or standalone"),
+            (
+                "prop:=done",
+                "prop: needs a property name, as in prop:status=done",
+            ),
+        ] {
+            let result = search_notes_content_in(
+                &base,
+                &base.join(".trash"),
+                query,
+                100,
+                SearchOptions::default(),
+            );
+            assert_eq!(result, Err(error.to_string()), "{query:?}");
+        }
+        for query in ["created:yesterday", "modified:2026-07-31..2026-07-01"] {
+            let error = search_notes_content_in(
+                &base,
+                &base.join(".trash"),
+                query,
+                100,
+                SearchOptions::default(),
+            )
+            .unwrap_err();
+            assert!(
+                error.contains("a range such as 2026-07-01..2026-07-31"),
+                "{error}"
+            );
+        }
+        // Unknown prefixes and lone parentheses are plain text.
+        assert!(query_paths(&base, "TODO: 10:30 :)").is_empty());
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_query_combines_words_phrases_and_operators() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        assert_eq!(query_paths(&base, "fox plan"), ["daily/2026-04-24.md"]);
+        assert_eq!(query_paths(&base, "fox AND plan"), ["daily/2026-04-24.md"]);
+        assert_eq!(query_paths(&base, "\"quick brown\""), ["notes/alpha.md"]);
+        assert!(query_paths(&base, "\"brown quick\"").is_empty());
+        assert_eq!(query_paths(&base, "brown quick"), ["notes/alpha.md"]);
+        assert_eq!(
+            query_paths(&base, "fox NOT plan"),
+            ["notes/alpha.md", "weekly/2026-W17.md"]
+        );
+        assert_eq!(
+            query_paths(&base, "(beta OR plan) NOT gamma"),
+            ["daily/2026-04-24.md", "notes/Projects/beta.md"]
+        );
+        // Occurrences of every term a note matched on count towards it.
+        let either = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "fox OR beta",
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(either.len(), 4);
+        let alpha = either.iter().find(|r| r.filename == "alpha.md").unwrap();
+        assert_eq!((alpha.match_count, alpha.line_number), (2, 2));
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_ranks_by_bm25_with_field_boosts() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        for (rel, body) in [
+            ("notes/Compost.md", "Turned the compost today\n"),
+            ("notes/garden.md", "Turned the compost today\n"),
+            ("notes/aa.md", "Mulch\nspread it thin\n"),
+            ("notes/zz.md", "## Mulch\nspread it thin\n"),
+            ("notes/bb.md", "seedlings on the sill\n"),
+            ("notes/yy.md", "#seedlings on the sill\n"),
+            ("notes/short.md", "trellis\n"),
+            (
+                "notes/wordy.md",
+                "trellis by the shed with the old string and pegs\n",
+            ),
+            ("notes/x/same.md", "pruning day\n"),
+            ("notes/w/same.md", "pruning day\n"),
+            ("notes/rare.md", "kohlrabi patch\n"),
+            ("notes/common.md", "the patch\n"),
+        ] {
+            fs::create_dir_all(base.join(rel).parent().unwrap()).unwrap();
+            fs::write(base.join(rel), body).unwrap();
+        }
+        let ranked = |query: &str| -> Vec<(String, f64)> {
+            search_notes_content_in(
+                &base,
+                &base.join(".trash"),
+                query,
+                100,
+                SearchOptions::default(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.path, r.score))
+            .collect()
+        };
+
+        // The same body ranks higher where the filename, a heading, or a tag
+        // has the term too, whatever the alphabet says.
+        let compost = ranked("compost");
+        assert_eq!(compost[0].0, "notes/Compost.md");
+        assert!(compost[0].1 > compost[1].1);
+        assert_eq!(ranked("mulch")[0].0, "notes/zz.md");
+        assert_eq!(ranked("seedlings")[0].0, "notes/yy.md");
+        // Shorter notes win at equal counts; rarer terms weigh more.
+        assert_eq!(ranked("trellis")[0].0, "notes/short.md");
+        let either = ranked("kohlrabi OR the");
+        let position = |path: &str| either.iter().position(|(p, _)| p == path).unwrap();
+        assert!(position("notes/rare.md") < position("notes/common.md"));
+        // Equal scores fall back to filename, then path.
+        let pruning = ranked("pruning");
+        assert_eq!(pruning[0].1, pruning[1].1);
+        assert_eq!(
+            [pruning[0].0.as_str(), pruning[1].0.as_str()],
+            ["notes/w/same.md", "notes/x/same.md"]
+        );
+        // Notes found by filters alone have nothing to score.
+        assert!(ranked("is:weekly").iter().all(|(_, score)| *score == 0.0));
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_folds_diacritics_and_tolerates_typos() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        let long_line = format!("{} target {}\n", "İ".repeat(200), "x".repeat(200));
+        for (rel, body) in [
+            ("notes/menu.md", "Ünïcode menu: Café crème\n"),
+            ("notes/street.md", "Lives on Hauptstraße\n"),
+            ("notes/typo.md", "Find the neddle in the haystack\n"),
+            ("notes/long.md", long_line.as_str()),
+        ] {
+            fs::write(base.join(rel), body).unwrap();
+        }
+        let search = |query: &str, fold_diacritics: bool, fuzzy: bool| {
+            let options = SearchOptions {
+                fold_diacritics,
+                fuzzy,
+                ..SearchOptions::default()
+            };
+            search_notes_content_in(&base, &base.join(".trash"), query, 20, options).unwrap()
+        };
+        let highlighted = |hit: &ContentMatch| -> Vec<String> {
+            let units: Vec<u16> = hit.snippet.encode_utf16().collect();
+            hit.highlights
+                .iter()
+                .map(|[start, end]| String::from_utf16(&units[*start..*end]).unwrap())
+                .collect()
+        };
+
+        // Accents only stop mattering when asked.
+        assert!(search("cafe", false, false).is_empty());
+        let cafe = search("cafe", true, false);
+        assert_eq!(cafe.len(), 1);
+        assert_eq!(cafe[0].snippet, "Ünïcode menu: Café crème");
+        assert_eq!(cafe[0].highlights, [[14, 18]]);
+        assert_eq!(highlighted(&cafe[0]), ["Café"]);
+        assert_eq!(highlighted(&search("strasse", true, false)[0]), ["straße"]);
+        assert_eq!(search("CRÈME", false, false).len(), 1);
+
+        // Typos are forgiven in proportion to the word's length.
+        assert!(search("needle", false, false).is_empty());
+        let needle = search("needle", false, true);
+        assert_eq!(needle.len(), 1);
+        assert_eq!(highlighted(&needle[0]), ["neddle"]);
+        assert_eq!(search("\"find teh neddle\"", false, true).len(), 1);
+        assert!(search("fx", false, true).is_empty());
+
+        // Snippets centre on the match even where lowercasing changes the
+        // length of the text before it.
+        let long = search("target", false, false);
+        assert!(long[0].snippet.len() < long_line.len());
+        assert_eq!(highlighted(&long[0]), ["target"]);
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_matches_regular_expressions_and_whole_words() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        for (rel, body) in [
+            (
+                "notes/tickets.md",
+                "Filed PROJ-4821 and proj-77 today\nlogin broke\n",
+            ),
+            ("notes/log.md", "Read the log, then the log_file.\n"),
+            ("notes/straße.md", "Ärger in der Straße: ERROR 500\n"),
+            ("notes/code.md", "Learning C++ by calling foo()\n"),
+        ] {
+            fs::write(base.join(rel), body).unwrap();
+        }
+        let search = |query: &str, options: SearchOptions| {
+            search_notes_content_in(&base, &base.join(".trash"), query, 20, options)
+        };
+        let regex = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        let whole_word = SearchOptions {
+            whole_word: true,
+            ..SearchOptions::default()
+        };
+
+        // Every match on the line is reported, in bytes of the line.
+        let tickets = search(r"proj-\d+", regex).unwrap();
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].match_count, 2);
+        assert_eq!(tickets[0].spans, [[6, 15], [20, 27]]);
+        assert_eq!(tickets[0].highlights, tickets[0].spans);
+        // Spans count bytes where highlights count UTF-16 units.
+        let error = search(r"error \d{3}", regex).unwrap();
+        assert_eq!(error[0].spans, [[23, 32]]);
+        assert_eq!(error[0].highlights, [[21, 30]]);
+        assert_eq!(&"Ärger in der Straße: ERROR 500"[23..32], "ERROR 500");
+        // The whole query is the pattern; operators and filters are text.
+        assert!(search("log OR tag:x", regex).unwrap().is_empty());
+        assert_eq!(search("(proj|log)(in)?", regex).unwrap().len(), 3);
+        let folded = SearchOptions {
+            fold_diacritics: true,
+            ..regex
+        };
+        assert_eq!(search("strasse", folded).unwrap().len(), 1);
+        assert_eq!(search("Ärger", folded).unwrap().len(), 1);
+
+        // Whole words end where letters, digits, and underscores do.
+        assert_eq!(search("log", SearchOptions::default()).unwrap().len(), 3);
+        let log = search("log", whole_word).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].path, "notes/log.md");
+        assert_eq!(log[0].match_count, 1);
+        assert_eq!(log[0].spans, [[9, 12]]);
+        assert_eq!(
+            search(
+                r"log\w*",
+                SearchOptions {
+                    whole_word: true,
+                    ..regex
+                }
+            )
+            .unwrap()
+            .len(),
+            3
+        );
+        assert!(search(
+            "proj",
+            SearchOptions {
+                whole_word: true,
+                ..regex
+            }
+        )
+        .unwrap()
+        .iter()
+        .all(|hit| hit.path == "notes/tickets.md"));
+
+        // Terms that start or end with punctuation match at the end of a
+        // line or before a space, but not inside a word.
+        let whole_regex = SearchOptions {
+            whole_word: true,
+            ..regex
+        };
+        assert_eq!(search(r"C\+\+", whole_regex).unwrap()[0].spans, [[9, 12]]);
+        assert_eq!(search(r"foo\(\)", whole_regex).unwrap()[0].spans, [[24, 29]]);
+        assert!(search(r"oo\(\)", whole_regex).unwrap().is_empty());
+        assert_eq!(search("\"foo()\"", whole_word).unwrap().len(), 1);
+        assert!(search("\"oo()\"", whole_word).unwrap().is_empty());
+
+        // Bad and oversized patterns are errors, not hangs.
+        let unclosed = search("(proj", regex).unwrap_err();
+        assert!(
+            unclosed.starts_with("Invalid regular expression"),
+            "{unclosed}"
+        );
+        assert!(search(&"a".repeat(1_001), regex)
+            .unwrap_err()
+            .contains("longer than"));
+        assert_eq!(
+            search(r"\w{1000}{1000}", regex).unwrap_err(),
+            "Regular expression is too complex"
+        );
+        assert!(search(
+            "x)(y",
+            SearchOptions {
+                whole_word: true,
+                ..regex
+            }
+        )
+        .is_err());
+        assert!(search(
+            "proj",
+            SearchOptions {
+                fuzzy: true,
+                ..regex
+            }
+        )
+        .is_err());
+        // A pattern that only matches empty text finds nothing.
+        assert!(search("^", regex).unwrap().is_empty());
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_hybrid_fuses_keyword_and_semantic_rankings() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        for (rel, body) in [
+            ("notes/ticket.md", "Fixed PROJ-4821 in the parser\n"),
+            ("notes/both.md", "Parser crash PROJ-4821 while loading\n"),
+            (
+                "notes/related.md",
+                "Title line\nThe tokenizer fell over on nested lists\n",
+            ),
+        ] {
+            fs::write(base.join(rel), body).unwrap();
+        }
+        let hit = |path: &str, score: f32| SemanticHit {
+            path: path.to_string(),
+            title: String::new(),
+            score,
+            signals: Vec::new(),
+            snippet: None,
+            passage: None,
+        };
+        let semantic = || {
+            vec![
+                hit("notes/both.md", 0.9),
+                hit("notes/related.md", 0.8),
+                hit("notes/gone.md", 0.7),
+                hit("../escape.md", 0.6),
+            ]
+        };
+        let search = |query: &str, mode: SearchMode| {
+            search_in_mode(
+                &base,
+                &base.join(".trash"),
+                query,
+                20,
+                mode,
+                SearchOptions::default(),
+                |_, _| Ok(semantic()),
+            )
+        };
+
+        // Found by both beats found by either; the exact ticket ID still
+        // ranks although semantic search missed it.
+        let hybrid = search("PROJ-4821", SearchMode::Hybrid).unwrap();
+        let found: Vec<(&str, &[SearchSignal])> = hybrid
+            .iter()
+            .map(|r| (r.path.as_str(), r.signals.as_slice()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "notes/both.md",
+                    &[SearchSignal::Keyword, SearchSignal::Semantic][..]
+                ),
+                ("notes/ticket.md", &[SearchSignal::Keyword][..]),
+                ("notes/related.md", &[SearchSignal::Semantic][..]),
+            ]
+        );
+        assert_eq!(hybrid[0].score, 2.0 / 61.0);
+        assert_eq!(hybrid[1].score, hybrid[2].score);
+        assert_eq!(hybrid[0].snippet, "Parser crash PROJ-4821 while loading");
+        assert_eq!(
+            (hybrid[2].line_number, hybrid[2].snippet.as_str()),
+            (1, "Title line")
+        );
+
+        // Filters are keyword-only, so semantic search isn't asked.
+        let filtered = search_in_mode(
+            &base,
+            &base.join(".trash"),
+            "PROJ-4821 is:standalone",
+            20,
+            SearchMode::Hybrid,
+            SearchOptions::default(),
+            |_, _| panic!("semantic search can't apply filters"),
+        )
+        .unwrap();
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered
+            .iter()
+            .all(|r| r.signals == [SearchSignal::Keyword]));
+
+        // Semantic results are shown by the line with a query word.
+        let semantic_only = search("tokenizer lists", SearchMode::Semantic).unwrap();
+        assert_eq!(semantic_only.len(), 2);
+        let related = &semantic_only[1];
+        assert_eq!(related.score, f64::from(0.8f32));
+        assert_eq!(related.line_number, 2);
+        assert!(related.snippet.contains("tokenizer"));
+        assert!(search("PROJ-4821", SearchMode::Keyword)
+            .unwrap()
+            .iter()
+            .all(|r| r.signals.is_empty()));
+        assert!(search_in_mode(
+            &base,
+            &base.join(".trash"),
+            "PROJ-4821",
+            20,
+            SearchMode::Hybrid,
+            SearchOptions::default(),
+            |_, _| Err("Semantic search is not ready".to_string()),
+        )
+        .is_err());
+
+        // Without a query word, the passage semantic search matched is shown.
+        let with_passage = search_in_mode(
+            &base,
+            &base.join(".trash"),
+            "PROJ-4821",
+            20,
+            SearchMode::Hybrid,
+            SearchOptions::default(),
+            |_, _| {
+                Ok(vec![SemanticHit {
+                    passage: Some(Passage {
+                        text: "The tokenizer fell over on nested lists".to_string(),
+                        start_line: 2,
+                        end_line: 2,
+                    }),
+                    ..hit("notes/related.md", 0.8)
+                }])
+            },
+        )
+        .unwrap();
+        let related = with_passage
+            .iter()
+            .find(|r| r.path == "notes/related.md")
+            .unwrap();
+        assert_eq!(
+            (related.line_number, related.snippet.as_str()),
+            (2, "The tokenizer fell over on nested lists")
+        );
+        fs::remove_dir_all(&base).ok();
+    }
+
+    #[test]
+    fn search_query_filters_on_what_a_note_is() {
+        let base = make_tmp_base();
+        seed_notes(&base);
+        fs::write(
+            base.join("notes/Projects/roadmap.md"),
+            "---\ncolor: blue\nstatus: Done\ntags: [planning]\ncreated: 2026-07-03\n---\n\nShip the #release-train\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("notes/ideas.md"),
+            "---\nstatus: draft\naliases:\n  - Brainstorm\n---\nSee https://example.com/#anchor and #Idea\n",
+        )
+        .unwrap();
+
+        assert_eq!(query_paths(&base, "tag:idea"), ["notes/ideas.md"]);
+        assert_eq!(
+            query_paths(&base, "tag:#Planning"),
+            ["notes/Projects/roadmap.md"]
+        );
+        assert_eq!(
+            query_paths(&base, "tag:release-train"),
+            ["notes/Projects/roadmap.md"]
+        );
+        assert!(query_paths(&base, "tag:anchor").is_empty());
+        assert_eq!(
+            query_paths(&base, "folder:projects"),
+            ["notes/Projects/beta.md", "notes/Projects/roadmap.md"]
+        );
+        assert_eq!(
+            query_paths(&base, "folder:notes/Projects beta"),
+            ["notes/Projects/beta.md"]
+        );
+        assert_eq!(query_paths(&base, "folder:daily"), ["daily/2026-04-24.md"]);
+        assert_eq!(
+            query_paths(&base, "color:BLUE"),
+            ["notes/Projects/roadmap.md"]
+        );
+        assert_eq!(query_paths(&base, "is:weekly"), ["weekly/2026-W17.md"]);
+        assert_eq!(query_paths(&base, "fox NOT is:standalone").len(), 2);
+        assert_eq!(
+            query_paths(&base, "created:2026-07-01..2026-07-31"),
+            ["notes/Projects/roadmap.md"]
+        );
+        assert!(!query_paths(&base, "created:<2026-07-03")
+            .contains(&"notes/Projects/roadmap.md".to_string()));
+        let today = chrono::Local::now().date_naive();
+        assert_eq!(
+            query_paths(&base, &format!("modified:>={today} beta")).len(),
+            2
+        );
+        assert!(query_paths(&base, "modified:..1999-12-31").is_empty());
+        assert_eq!(
+            query_paths(&base, "prop:status=done"),
+            ["notes/Projects/roadmap.md"]
+        );
+        assert_eq!(
+            query_paths(&base, "prop:aliases=brainstorm"),
+            ["notes/ideas.md"]
+        );
+        assert_eq!(query_paths(&base, "prop:STATUS").len(), 2);
+        assert!(query_paths(&base, "prop:owner").is_empty());
+
+        // A note found by its metadata alone shows its first line.
+        let tagged = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "tag:planning",
+            10,
+            SearchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(tagged[0].match_count, 0);
+        assert_eq!(tagged[0].line_number, 2);
+        assert_eq!(tagged[0].snippet, "Ship the #release-train");
+        let both = search_notes_content_in(
+            &base,
+            &base.join(".trash"),
+            "prop:status=done ship",
+            10,
+            SearchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(both[0].match_count, 1);
+        fs::remove_dir_all(&base).ok();
+    }
 }
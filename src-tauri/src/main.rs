@@ -1,7 +1,8 @@
 //! Binary entry point that selects a headless mode before Tauri starts.
 //!
-//! Neither `--mcp` nor the browser-messaging host may initialize the GUI: both
-//! own stdin and stdout, and a window would be a bug the caller cannot see.
+//! Neither `--mcp` (or `--mcp-http`) nor the browser-messaging host may
+//! initialize the GUI: they serve a client the caller cannot see, and a window
+//! would be a bug.
 
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
@@ -9,7 +10,7 @@
 fn main() {
     let args: Vec<String> = std::env::args().skip(1).collect();
 
-    if args.iter().any(|arg| arg == "--mcp") {
+    if args.iter().any(|arg| arg == "--mcp" || arg == "--mcp-http") {
         if let Err(error) = moldavite_lib::mcp::run_from_env() {
             eprintln!("Moldavite MCP server error: {error}");
             std::process::exit(1);
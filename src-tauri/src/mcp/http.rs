@@ -0,0 +1,756 @@
+//! Opt-in Streamable HTTP transport for the built-in MCP server.
+//!
+//! `--mcp-http <port>` serves the same JSON-RPC dispatch as stdio at
+//! `http://127.0.0.1:<port>/mcp`, for clients that cannot spawn a process. The
+//! listener binds loopback only, and every request must carry the bearer token
+//! kept in the OS credential store; a missing or wrong token is rejected before
+//! the body is read. `Host` and `Origin` must name this loopback listener, so a
+//! web page cannot reach it through DNS rebinding either.
+//!
+//! Each `initialize` opens a session addressed by `Mcp-Session-Id`. A POST gets
+//! its response as one JSON body, or `202` with none when the request was a
+//! notification or its call was cancelled; server-initiated notifications
+//! (resource updates, progress) go out over the session's single `GET`
+//! Server-Sent Events stream. POSTs already run on their own connections, so a
+//! slow tool call never holds up another request.
+//! Like the OAuth callback listener this is hand-written on `std::net` rather
+//! than pulling in an HTTP stack for one endpoint, and every connection is
+//! closed after its response.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use serde_json::Value;
+
+use super::server::{handle_message, Outbound, Session, MAX_REQUEST_BYTES};
+use super::tools::ToolContext;
+use crate::secrets::SecretStore;
+
+const TOKEN_ACCOUNT: &str = "mcp:http:bearer_token";
+const ENDPOINT: &str = "/mcp";
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_CONNECTIONS: usize = 32;
+const MAX_SESSIONS: usize = 32;
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+/// An idle event stream sends a comment this often, which is also how a
+/// client that went away is noticed and its stream released.
+const SSE_KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// The HTTP bearer token, generated and stored on first use.
+pub(crate) fn bearer_token_with(store: &impl SecretStore) -> Result<String, String> {
+    match store.get(TOKEN_ACCOUNT)? {
+        Some(token) if !token.is_empty() => Ok(token),
+        _ => rotate_bearer_token_with(store),
+    }
+}
+
+/// Replace the HTTP bearer token. Running servers keep the token they started
+/// with, so clients must be updated and the server restarted.
+pub(crate) fn rotate_bearer_token_with(store: &impl SecretStore) -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    store.set(TOKEN_ACCOUNT, &token)?;
+    Ok(token)
+}
+
+struct HttpSession {
+    session: Session,
+    outbound: mpsc::Sender<Outbound>,
+    /// Taken by the one open event stream and handed back when it ends.
+    notifications: Mutex<Option<mpsc::Receiver<Outbound>>>,
+    last_used: Mutex<Instant>,
+}
+
+impl HttpSession {
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    fn close(&self) {
+        let _ = self.outbound.send(Outbound::Close);
+    }
+}
+
+struct HttpServer {
+    token: String,
+    port: u16,
+    context: ToolContext,
+    sessions: Mutex<HashMap<String, Arc<HttpSession>>>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Accept connections until the listener fails. Each connection is served on
+/// its own thread, up to a fixed number at once.
+pub(super) fn serve(
+    listener: TcpListener,
+    token: String,
+    context: ToolContext,
+) -> Result<(), String> {
+    let port = listener
+        .local_addr()
+        .map_err(|error| format!("Failed to inspect MCP listener: {error}"))?
+        .port();
+    let server = Arc::new(HttpServer {
+        token,
+        port,
+        context,
+        sessions: Mutex::new(HashMap::new()),
+    });
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                log::warn!("[mcp http] accept failed: {}", error);
+                continue;
+            }
+        };
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, 503, &[], "Too many connections");
+            continue;
+        }
+        let server = server.clone();
+        let active_for_thread = active.clone();
+        let spawned = std::thread::Builder::new()
+            .name("mcp-http".into())
+            .spawn(move || {
+                server.handle_connection(stream);
+                active_for_thread.fetch_sub(1, Ordering::SeqCst);
+            });
+        if let Err(error) = spawned {
+            active.fetch_sub(1, Ordering::SeqCst);
+            log::warn!("[mcp http] failed to spawn connection thread: {}", error);
+        }
+    }
+    Ok(())
+}
+
+impl HttpServer {
+    fn handle_connection(&self, mut stream: TcpStream) {
+        stream.set_read_timeout(Some(READ_TIMEOUT)).ok();
+        stream.set_write_timeout(Some(READ_TIMEOUT)).ok();
+        let Ok(reader_stream) = stream.try_clone() else {
+            return;
+        };
+        let mut reader = BufReader::new(reader_stream);
+        let request = match read_head(&mut reader) {
+            Ok(request) => request,
+            Err(status) => {
+                let _ = write_response(&mut stream, status, &[], "Malformed request");
+                return;
+            }
+        };
+        if request.path.split('?').next() != Some(ENDPOINT) {
+            let _ = write_response(&mut stream, 404, &[], "Not found");
+            return;
+        }
+        if !self.is_loopback_request(&request) {
+            let _ = write_response(&mut stream, 403, &[], "Forbidden origin");
+            return;
+        }
+        if !self.is_authorized(&request) {
+            let _ = write_response(
+                &mut stream,
+                401,
+                &[("WWW-Authenticate", "Bearer")],
+                "Missing or invalid bearer token",
+            );
+            return;
+        }
+        let _ = match request.method.as_str() {
+            "POST" => self.post(&request, &mut reader, &mut stream),
+            "GET" => self.stream_notifications(&request, &mut stream),
+            "DELETE" => self.delete(&request, &mut stream),
+            _ => write_response(
+                &mut stream,
+                405,
+                &[("Allow", "GET, POST, DELETE")],
+                "Method not allowed",
+            ),
+        };
+    }
+
+    /// Both headers must name this listener. Browsers always send `Host`, and
+    /// send `Origin` on every cross-site request that could carry a body.
+    fn is_loopback_request(&self, request: &Request) -> bool {
+        let hosts = [
+            format!("127.0.0.1:{}", self.port),
+            format!("localhost:{}", self.port),
+        ];
+        let host_ok = request.header("Host").is_some_and(|host| {
+            hosts
+                .iter()
+                .any(|allowed| host.eq_ignore_ascii_case(allowed))
+        });
+        let origin_ok = request.header("Origin").map_or(true, |origin| {
+            hosts
+                .iter()
+                .any(|allowed| origin.eq_ignore_ascii_case(&format!("http://{allowed}")))
+        });
+        host_ok && origin_ok
+    }
+
+    fn is_authorized(&self, request: &Request) -> bool {
+        let Some(value) = request.header("Authorization") else {
+            return false;
+        };
+        let Some((scheme, presented)) = value.split_once(' ') else {
+            return false;
+        };
+        scheme.eq_ignore_ascii_case("Bearer") && tokens_match(&self.token, presented.trim())
+    }
+
+    fn post<R: BufRead>(
+        &self,
+        request: &Request,
+        reader: &mut R,
+        stream: &mut TcpStream,
+    ) -> std::io::Result<()> {
+        if request.header("Transfer-Encoding").is_some() {
+            return write_response(stream, 411, &[], "Content-Length is required");
+        }
+        let Some(length) = request
+            .header("Content-Length")
+            .and_then(|length| length.trim().parse::<usize>().ok())
+        else {
+            return write_response(stream, 411, &[], "Content-Length is required");
+        };
+        if length > MAX_REQUEST_BYTES {
+            return write_response(stream, 413, &[], "Request exceeds the 1 MiB limit");
+        }
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return write_response(stream, 400, &[], "Incomplete request body");
+        }
+
+        let is_initialize = serde_json::from_slice::<Value>(&body)
+            .ok()
+            .is_some_and(|message| {
+                message.get("method").and_then(Value::as_str) == Some("initialize")
+            });
+        if is_initialize {
+            let (outbound, notifications) = mpsc::channel();
+            let session = Arc::new(HttpSession {
+                session: Session::new(self.context.for_session(), outbound.clone()),
+                outbound,
+                notifications: Mutex::new(Some(notifications)),
+                last_used: Mutex::new(Instant::now()),
+            });
+            let response = handle_message(&session.session, &body);
+            let accepted = response
+                .as_ref()
+                .is_some_and(|response| response.get("result").is_some());
+            if !accepted {
+                return write_json(stream, response, &[]);
+            }
+            let session_id = new_session_id();
+            self.insert_session(session_id.clone(), session);
+            return write_json(stream, response, &[(SESSION_HEADER, &session_id)]);
+        }
+
+        let session = match self.session(request) {
+            Ok(session) => session,
+            Err((status, message)) => return write_response(stream, status, &[], message),
+        };
+        write_json(stream, handle_message(&session.session, &body), &[])
+    }
+
+    /// Hold the connection open and forward the session's server-initiated
+    /// messages as Server-Sent Events until the client leaves or the session ends.
+    fn stream_notifications(
+        &self,
+        request: &Request,
+        stream: &mut TcpStream,
+    ) -> std::io::Result<()> {
+        if !request
+            .header("Accept")
+            .is_some_and(|accept| accept.contains("text/event-stream"))
+        {
+            return write_response(stream, 406, &[], "Accept must include text/event-stream");
+        }
+        let session = match self.session(request) {
+            Ok(session) => session,
+            Err((status, message)) => return write_response(stream, status, &[], message),
+        };
+        let Some(notifications) = session
+            .notifications
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+        else {
+            return write_response(stream, 409, &[], "An event stream is already open");
+        };
+        let mut result = stream.write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        );
+        while result.is_ok() {
+            result = match notifications.recv_timeout(SSE_KEEPALIVE) {
+                Ok(Outbound::Message(message)) => {
+                    write!(stream, "event: message\ndata: {message}\n\n")
+                        .and_then(|()| stream.flush())
+                }
+                Ok(Outbound::Close) | Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+                Err(mpsc::RecvTimeoutError::Timeout) => stream
+                    .write_all(b": keepalive\n\n")
+                    .and_then(|()| stream.flush()),
+            };
+        }
+        // The client went away; a reconnecting stream picks up where this left off.
+        *session
+            .notifications
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(notifications);
+        result
+    }
+
+    fn delete(&self, request: &Request, stream: &mut TcpStream) -> std::io::Result<()> {
+        let session = match self.session(request) {
+            Ok(session) => session,
+            Err((status, message)) => return write_response(stream, status, &[], message),
+        };
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, existing| !Arc::ptr_eq(existing, &session));
+        session.close();
+        write_response(stream, 200, &[], "")
+    }
+
+    fn session(&self, request: &Request) -> Result<Arc<HttpSession>, (u16, &'static str)> {
+        let id = request
+            .header(SESSION_HEADER)
+            .ok_or((400, "Missing Mcp-Session-Id header"))?;
+        let session = self
+            .sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(id)
+            .cloned()
+            .ok_or((404, "Unknown or expired MCP session"))?;
+        session.touch();
+        Ok(session)
+    }
+
+    /// Register a session, evicting the least recently used one at capacity so
+    /// clients that never send DELETE cannot grow the table without bound.
+    fn insert_session(&self, id: String, session: Arc<HttpSession>) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        while sessions.len() >= MAX_SESSIONS {
+            let oldest = sessions
+                .iter()
+                .min_by_key(|(_, session)| {
+                    *session.last_used.lock().unwrap_or_else(|e| e.into_inner())
+                })
+                .map(|(id, _)| id.clone());
+            let Some(oldest) = oldest else {
+                break;
+            };
+            if let Some(evicted) = sessions.remove(&oldest) {
+                evicted.close();
+            }
+        }
+        sessions.insert(id, session);
+    }
+}
+
+/// Read the request line and headers without retaining more than
+/// `MAX_HEADER_BYTES`. Errors are the HTTP status to answer with.
+fn read_head<R: BufRead>(reader: &mut R) -> Result<Request, u16> {
+    let mut budget = MAX_HEADER_BYTES;
+    let mut next_line = |reader: &mut R| -> Result<String, u16> {
+        let mut line = Vec::new();
+        let read = reader
+            .by_ref()
+            .take(budget as u64 + 1)
+            .read_until(b'\n', &mut line)
+            .map_err(|_| 400u16)?;
+        if read > budget {
+            return Err(431);
+        }
+        if read == 0 {
+            return Err(400);
+        }
+        budget -= read;
+        let line = String::from_utf8(line).map_err(|_| 400u16)?;
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    };
+
+    let request_line = next_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path), Some(_version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(400);
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers: Vec::new(),
+    };
+    loop {
+        let line = next_line(reader)?;
+        if line.is_empty() {
+            return Ok(request);
+        }
+        let (name, value) = line.split_once(':').ok_or(400u16)?;
+        request
+            .headers
+            .push((name.trim().to_string(), value.trim().to_string()));
+    }
+}
+
+fn write_json(
+    stream: &mut TcpStream,
+    response: Option<Value>,
+    headers: &[(&str, &str)],
+) -> std::io::Result<()> {
+    let Some(response) = response else {
+        // Notifications, client responses, and cancelled calls are accepted
+        // without a body.
+        return write_response(stream, 202, headers, "");
+    };
+    let body = response.to_string();
+    let mut all_headers = vec![("Content-Type", "application/json")];
+    all_headers.extend_from_slice(headers);
+    write_head(stream, 200, &all_headers, body.len())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> std::io::Result<()> {
+    let mut all_headers = vec![("Content-Type", "text/plain; charset=utf-8")];
+    all_headers.extend_from_slice(headers);
+    write_head(stream, status, &all_headers, body.len())?;
+    stream.write_all(body.as_bytes())?;
+    stream.flush()
+}
+
+fn write_head(
+    stream: &mut TcpStream,
+    status: u16,
+    headers: &[(&str, &str)],
+    content_length: usize,
+) -> std::io::Result<()> {
+    let mut head = format!("HTTP/1.1 {status} {}\r\n", reason(status));
+    for (name, value) in headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {content_length}\r\nConnection: close\r\n\r\n"
+    ));
+    stream.write_all(head.as_bytes())
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        409 => "Conflict",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    }
+}
+
+/// Compare without an early exit so response timing does not reveal how much
+/// of a guessed token was right.
+fn tokens_match(expected: &str, presented: &str) -> bool {
+    let (expected, presented) = (expected.as_bytes(), presented.as_bytes());
+    expected.len() == presented.len()
+        && expected
+            .iter()
+            .zip(presented)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn new_session_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serde_json::json;
+
+    use super::*;
+
+    struct MemorySecretStore(RefCell<HashMap<String, String>>);
+
+    impl SecretStore for MemorySecretStore {
+        fn get(&self, account: &str) -> Result<Option<String>, String> {
+            Ok(self.0.borrow().get(account).cloned())
+        }
+        fn set(&self, account: &str, value: &str) -> Result<(), String> {
+            self.0
+                .borrow_mut()
+                .insert(account.to_string(), value.to_string());
+            Ok(())
+        }
+        fn delete(&self, account: &str) -> Result<(), String> {
+            self.0.borrow_mut().remove(account);
+            Ok(())
+        }
+    }
+
+    fn temp_forge(tag: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "moldavite-mcp-http-{tag}-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        for folder in ["daily", "weekly", "notes"] {
+            fs::create_dir_all(root.join(folder)).unwrap();
+        }
+        root
+    }
+
+    fn start(context: ToolContext) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || serve(listener, "secret-token".to_string(), context));
+        port
+    }
+
+    struct Reply {
+        status: u16,
+        session: Option<String>,
+        body: String,
+    }
+
+    impl Reply {
+        fn json(&self) -> Value {
+            serde_json::from_str(&self.body).unwrap()
+        }
+    }
+
+    fn send(port: u16, method: &str, headers: &[(&str, &str)], body: &str) -> Reply {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let mut raw = format!("{method} /mcp HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\n");
+        for (name, value) in headers {
+            raw.push_str(&format!("{name}: {value}\r\n"));
+        }
+        raw.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let session = head
+            .lines()
+            .find_map(|line| line.strip_prefix("Mcp-Session-Id: "))
+            .map(str::to_string);
+        Reply {
+            status,
+            session,
+            body: body.to_string(),
+        }
+    }
+
+    const AUTH: (&str, &str) = ("Authorization", "Bearer secret-token");
+
+    fn initialize(port: u16) -> String {
+        let reply = send(
+            port,
+            "POST",
+            &[AUTH],
+            &json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","clientInfo":{"name":"http-test"}}}).to_string(),
+        );
+        assert_eq!(reply.status, 200);
+        assert_eq!(reply.json()["result"]["serverInfo"]["name"], "moldavite");
+        reply.session.expect("initialize should open a session")
+    }
+
+    #[test]
+    fn bearer_token_is_generated_once_and_rotates() {
+        let store = MemorySecretStore(RefCell::new(HashMap::new()));
+        let first = bearer_token_with(&store).unwrap();
+        assert!(first.len() >= 43);
+        assert_eq!(bearer_token_with(&store).unwrap(), first);
+        let rotated = rotate_bearer_token_with(&store).unwrap();
+        assert_ne!(rotated, first);
+        assert_eq!(bearer_token_with(&store).unwrap(), rotated);
+    }
+
+    #[test]
+    fn requests_without_token_or_from_foreign_origins_are_refused() {
+        let root = temp_forge("auth");
+        let port = start(ToolContext::new(root.clone(), true, false));
+        let body = json!({"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}).to_string();
+
+        assert_eq!(send(port, "POST", &[], &body).status, 401);
+        assert_eq!(
+            send(
+                port,
+                "POST",
+                &[("Authorization", "Bearer wrong-token!")],
+                &body
+            )
+            .status,
+            401
+        );
+        assert_eq!(
+            send(
+                port,
+                "POST",
+                &[AUTH, ("Origin", "https://evil.example")],
+                &body
+            )
+            .status,
+            403
+        );
+        assert_eq!(
+            send(
+                port,
+                "POST",
+                &[AUTH, ("Origin", &format!("http://localhost:{port}"))],
+                &body
+            )
+            .status,
+            200
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn sessions_share_stdio_dispatch_and_write_gating() {
+        let root = temp_forge("dispatch");
+        fs::write(root.join("notes/existing.md"), "body").unwrap();
+        let port = start(ToolContext::new(root.clone(), false, false));
+        let session = initialize(port);
+        let with_session = [AUTH, (SESSION_HEADER, session.as_str())];
+
+        let initialized = send(
+            port,
+            "POST",
+            &with_session,
+            &json!({"jsonrpc":"2.0","method":"notifications/initialized"}).to_string(),
+        );
+        assert_eq!(initialized.status, 202);
+
+        let listed = send(
+            port,
+            "POST",
+            &with_session,
+            &json!({"jsonrpc":"2.0","id":2,"method":"tools/list"}).to_string(),
+        );
+        assert_eq!(
+            listed.json()["result"]["tools"].as_array().unwrap().len(),
+            10
+        );
+
+        let refused = send(
+            port,
+            "POST",
+            &with_session,
+            &json!({"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"create_note","arguments":{"path":"notes/no.md","content":"x"}}}).to_string(),
+        );
+        assert_eq!(refused.json()["result"]["isError"], true);
+        assert!(!root.join("notes/no.md").exists());
+
+        let missing = send(
+            port,
+            "POST",
+            &[AUTH],
+            &json!({"jsonrpc":"2.0","id":4,"method":"tools/list"}).to_string(),
+        );
+        assert_eq!(missing.status, 400);
+
+        assert_eq!(send(port, "DELETE", &with_session, "").status, 200);
+        let expired = send(
+            port,
+            "POST",
+            &with_session,
+            &json!({"jsonrpc":"2.0","id":5,"method":"tools/list"}).to_string(),
+        );
+        assert_eq!(expired.status, 404);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn resource_updates_arrive_on_the_event_stream() {
+        let root = temp_forge("sse");
+        fs::write(root.join("notes/watched.md"), "before").unwrap();
+        let port = start(ToolContext::new(root.clone(), false, false));
+        let session = initialize(port);
+        let with_session = [AUTH, (SESSION_HEADER, session.as_str())];
+        let subscribed = send(
+            port,
+            "POST",
+            &with_session,
+            &json!({"jsonrpc":"2.0","id":2,"method":"resources/subscribe","params":{"uri":"moldavite://note/notes/watched.md"}}).to_string(),
+        );
+        assert_eq!(subscribed.json()["result"], json!({}));
+
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(10)))
+            .unwrap();
+        write!(
+            stream,
+            "GET /mcp HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nAuthorization: Bearer secret-token\r\n\
+             Accept: text/event-stream\r\nMcp-Session-Id: {session}\r\n\r\n"
+        )
+        .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+        assert!(status.contains("200"));
+
+        fs::write(root.join("notes/watched.md"), "after").unwrap();
+        let event = reader
+            .lines()
+            .map_while(Result::ok)
+            .find_map(|line| line.strip_prefix("data: ").map(str::to_string))
+            .expect("an update event");
+        let event: Value = serde_json::from_str(&event).unwrap();
+        assert_eq!(event["method"], "notifications/resources/updated");
+        assert_eq!(event["params"]["uri"], "moldavite://note/notes/watched.md");
+        fs::remove_dir_all(root).unwrap();
+    }
+}
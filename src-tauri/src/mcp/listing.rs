@@ -0,0 +1,254 @@
+//! Filters and cursor paging for `list_notes`.
+//!
+//! Listings are ordered by Forge-relative path, and the cursor is the last
+//! path a page returned, so paging stays stable while notes are added or
+//! removed elsewhere in the Forge. Kinds come from the same classification
+//! full-text search uses. Cheap filters — kind, date range, modification time —
+//! run before any note is opened; the frontmatter filter reads only the notes
+//! that survive them, and never locked ones.
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, TimeZone, Utc};
+use serde_json::Value;
+
+const DEFAULT_PAGE_SIZE: usize = 200;
+const MAX_PAGE_SIZE: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum NoteKind {
+    Daily,
+    Weekly,
+    Standalone,
+}
+
+impl NoteKind {
+    pub(super) fn classify(is_daily: bool, is_weekly: bool) -> Self {
+        match (is_daily, is_weekly) {
+            (true, _) => NoteKind::Daily,
+            (_, true) => NoteKind::Weekly,
+            _ => NoteKind::Standalone,
+        }
+    }
+
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            NoteKind::Daily => "daily",
+            NoteKind::Weekly => "weekly",
+            NoteKind::Standalone => "standalone",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(super) struct ListQuery<'a> {
+    pub(super) cursor: Option<&'a str>,
+    pub(super) limit: usize,
+    kind: Option<NoteKind>,
+    modified_since: Option<SystemTime>,
+    date_from: Option<NaiveDate>,
+    date_to: Option<NaiveDate>,
+    property: Option<(&'a str, Option<&'a Value>)>,
+}
+
+impl<'a> ListQuery<'a> {
+    pub(super) fn from_arguments(arguments: &'a Value) -> Result<Self, String> {
+        let string = |name: &str| -> Result<Option<&'a str>, String> {
+            match arguments.get(name) {
+                None | Some(Value::Null) => Ok(None),
+                Some(Value::String(value)) => Ok(Some(value)),
+                Some(_) => Err(format!("{name} must be a string")),
+            }
+        };
+        let limit = match arguments.get("limit") {
+            None | Some(Value::Null) => DEFAULT_PAGE_SIZE,
+            Some(value) => value
+                .as_u64()
+                .filter(|limit| *limit > 0)
+                .map(|limit| (limit as usize).min(MAX_PAGE_SIZE))
+                .ok_or_else(|| "limit must be a positive integer".to_string())?,
+        };
+        let kind = match string("kind")? {
+            None => None,
+            Some("daily") => Some(NoteKind::Daily),
+            Some("weekly") => Some(NoteKind::Weekly),
+            Some("standalone") => Some(NoteKind::Standalone),
+            Some(_) => return Err("kind must be daily, weekly, or standalone".to_string()),
+        };
+        let date = |name: &str| -> Result<Option<NaiveDate>, String> {
+            string(name)?
+                .map(|date| {
+                    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map_err(|_| format!("{name} must be a YYYY-MM-DD date"))
+                })
+                .transpose()
+        };
+        let (date_from, date_to) = (date("dateFrom")?, date("dateTo")?);
+        if (date_from.is_some() || date_to.is_some()) && kind.is_some_and(|k| k != NoteKind::Daily)
+        {
+            return Err("dateFrom and dateTo only apply to daily notes".to_string());
+        }
+        let modified_since = string("modifiedSince")?.map(parse_instant).transpose()?;
+        let property = match arguments.get("property") {
+            None | Some(Value::Null) => None,
+            Some(Value::Object(property)) => {
+                let key = property
+                    .get("key")
+                    .and_then(Value::as_str)
+                    .filter(|key| !key.is_empty())
+                    .ok_or_else(|| "property.key must be a non-empty string".to_string())?;
+                Some((key, property.get("value")))
+            }
+            Some(_) => return Err("property must be an object with key and value".to_string()),
+        };
+        Ok(Self {
+            cursor: string("cursor")?,
+            limit,
+            kind,
+            modified_since,
+            date_from,
+            date_to,
+            property,
+        })
+    }
+
+    /// Everything except the frontmatter filter.
+    pub(super) fn matches(&self, rel: &str, kind: NoteKind, modified: Option<SystemTime>) -> bool {
+        let ranged = self.date_from.is_some() || self.date_to.is_some();
+        if self.kind.is_some_and(|wanted| wanted != kind) || (ranged && kind != NoteKind::Daily) {
+            return false;
+        }
+        if ranged {
+            let Some(date) = daily_date(rel) else {
+                return false;
+            };
+            if self.date_from.is_some_and(|from| date < from)
+                || self.date_to.is_some_and(|to| date > to)
+            {
+                return false;
+            }
+        }
+        self.modified_since.map_or(true, |since| {
+            modified.is_some_and(|modified| modified >= since)
+        })
+    }
+
+    pub(super) fn filters_properties(&self) -> bool {
+        self.property.is_some()
+    }
+
+    /// A note matches when it has the key and, if a value was given, the
+    /// property equals it or is a list containing it.
+    pub(super) fn matches_properties(&self, properties: &serde_yaml::Mapping) -> bool {
+        let Some((key, wanted)) = self.property else {
+            return true;
+        };
+        let Some(value) = properties.get(key) else {
+            return false;
+        };
+        let Some(wanted) = wanted.filter(|wanted| !wanted.is_null()) else {
+            return true;
+        };
+        let Ok(value) = serde_json::to_value(value) else {
+            return false;
+        };
+        match &value {
+            Value::Array(items) => &value == wanted || items.contains(wanted),
+            _ => &value == wanted,
+        }
+    }
+}
+
+/// RFC 3339 timestamp, or a date meaning local midnight.
+fn parse_instant(text: &str) -> Result<SystemTime, String> {
+    if let Ok(instant) = DateTime::parse_from_rfc3339(text) {
+        return Ok(instant.with_timezone(&Utc).into());
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map(|instant| instant.with_timezone(&Utc).into())
+        .ok_or_else(|| "modifiedSince must be an RFC 3339 timestamp or YYYY-MM-DD date".to_string())
+}
+
+fn daily_date(rel: &str) -> Option<NaiveDate> {
+    let stem = rel.strip_prefix("daily/")?.strip_suffix(".md")?;
+    NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()
+}
+
+pub(super) fn timestamp(modified: SystemTime) -> String {
+    DateTime::<Utc>::from(modified).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn arguments_are_validated_and_dates_bound_daily_notes() {
+        let arguments = json!({"dateFrom":"2026-07-01","dateTo":"2026-07-31","limit":5000});
+        let query = ListQuery::from_arguments(&arguments).unwrap();
+        assert_eq!(query.limit, MAX_PAGE_SIZE);
+        assert!(query.matches("daily/2026-07-12.md", NoteKind::Daily, None));
+        assert!(!query.matches("daily/2026-08-01.md", NoteKind::Daily, None));
+        assert!(!query.matches("daily/scratch.md", NoteKind::Daily, None));
+        assert!(!query.matches("notes/2026-07-12.md", NoteKind::Standalone, None));
+
+        for invalid in [
+            json!({"kind":"monthly"}),
+            json!({"kind":"weekly","dateFrom":"2026-07-01"}),
+            json!({"dateTo":"July"}),
+            json!({"modifiedSince":"yesterday"}),
+            json!({"limit":0}),
+            json!({"property":{"value":"x"}}),
+        ] {
+            assert!(ListQuery::from_arguments(&invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn modified_since_accepts_timestamps_and_dates() {
+        let arguments = json!({"modifiedSince":"2026-07-12T10:00:00+02:00"});
+        let query = ListQuery::from_arguments(&arguments).unwrap();
+        let at = |text: &str| -> SystemTime {
+            DateTime::parse_from_rfc3339(text)
+                .unwrap()
+                .with_timezone(&Utc)
+                .into()
+        };
+        assert!(query.matches(
+            "notes/a.md",
+            NoteKind::Standalone,
+            Some(at("2026-07-12T08:00:00Z"))
+        ));
+        assert!(!query.matches(
+            "notes/a.md",
+            NoteKind::Standalone,
+            Some(at("2026-07-12T07:59:59Z"))
+        ));
+        assert!(!query.matches("notes/a.md", NoteKind::Standalone, None));
+        assert!(ListQuery::from_arguments(&json!({"modifiedSince":"2026-07-12"})).is_ok());
+    }
+
+    #[test]
+    fn property_filter_matches_scalars_and_list_members() {
+        let properties: serde_yaml::Mapping =
+            serde_yaml::from_str("status: done\npriority: 2\ntags: [work, urgent]\n").unwrap();
+        let matches = |property: Value| {
+            let arguments = json!({ "property": property });
+            ListQuery::from_arguments(&arguments)
+                .unwrap()
+                .matches_properties(&properties)
+        };
+        assert!(matches(json!({"key":"status"})));
+        assert!(matches(json!({"key":"status","value":"done"})));
+        assert!(!matches(json!({"key":"status","value":"open"})));
+        assert!(matches(json!({"key":"priority","value":2})));
+        assert!(!matches(json!({"key":"priority","value":"2"})));
+        assert!(matches(json!({"key":"tags","value":"urgent"})));
+        assert!(!matches(json!({"key":"owner"})));
+    }
+}
@@ -1,24 +1,51 @@
-//! Built-in stdio Model Context Protocol server and MCP-only startup path.
+//! Built-in Model Context Protocol server and MCP-only startup path.
 //!
 //! The application binary enters this mode before Tauri is initialized when
-//! invoked with `--mcp`. The supported subset is initialization, tool listing,
-//! and tool calls over newline-delimited JSON-RPC 2.0. Every client argument is
+//! invoked with `--mcp`, or with `--mcp-http <port>` for the opt-in loopback
+//! HTTP transport. The supported subset is initialization, tools, note
+//! resources, and template prompts over JSON-RPC 2.0. Every client argument is
 //! untrusted: `server` bounds and parses messages, while `tools` validates paths,
 //! rejects symlinks, and gates every mutation on the persisted write setting.
 
+mod http;
+mod listing;
+mod patch;
+mod policy;
+mod progress;
+mod prompts;
+mod resources;
 mod server;
 mod tools;
 
-use std::path::PathBuf;
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-/// Parse MCP-only CLI arguments and run until stdin reaches EOF.
+use serde_json::Value;
+
+use crate::types::ForgeInfo;
+
+pub(crate) use http::{bearer_token_with, rotate_bearer_token_with};
+
+/// Parse MCP-only CLI arguments and run until stdin reaches EOF, or until the
+/// process is stopped when serving HTTP.
 pub fn run_from_env() -> Result<(), String> {
     let mut forge: Option<String> = None;
+    let mut http_port: Option<u16> = None;
     let mut args = std::env::args().skip(1);
     while let Some(arg) = args.next() {
         match arg.as_str() {
             "--mcp" => {}
+            "--mcp-http" => {
+                let port = args
+                    .next()
+                    .and_then(|port| port.parse::<u16>().ok())
+                    .filter(|port| *port != 0)
+                    .ok_or_else(|| "--mcp-http requires a port between 1 and 65535".to_string())?;
+                if http_port.replace(port).is_some() {
+                    return Err("--mcp-http may only be specified once".to_string());
+                }
+            }
             "--forge" => {
                 let name = args
                     .next()
@@ -32,13 +59,25 @@ pub fn run_from_env() -> Result<(), String> {
     }
 
     let forge_root = resolve_forge(forge.as_deref())?;
-    let forge_resolver: Arc<dyn Fn() -> Result<PathBuf, String> + Send + Sync> = match forge {
+    let forge_resolver: Arc<dyn Fn() -> Result<PathBuf, String> + Send + Sync> = match &forge {
         Some(name) => {
+            let name = name.clone();
             let pinned_root = forge_root.clone();
             Arc::new(move || validate_forge_root(&name, pinned_root.clone()))
         }
         None => Arc::new(|| resolve_forge(None)),
     };
+    // Client policies and read tools may name a Forge, but never past `--forge`.
+    let (named_forge, forge_list) = match forge {
+        Some(server_forge) => pinned_forge_access(server_forge, forge_root.clone()),
+        None => {
+            let named_forge: tools::NamedForgeResolver =
+                Arc::new(|name: &str| resolve_named_forge(Some(name)));
+            let forge_list: tools::ForgeLister = Arc::new(crate::commands::forges::list_forges);
+            (named_forge, forge_list)
+        }
+    };
+    crate::search_index::open_watched(forge_root.clone());
     let config = crate::persist::read_config();
     let semantic_model = config
         .semantic_model
@@ -46,8 +85,59 @@ pub fn run_from_env() -> Result<(), String> {
         .unwrap_or(crate::semantic::DEFAULT_MODEL_ID);
     let semantic_ready = config.semantic_enabled.unwrap_or(false)
         && crate::semantic::prepare_mcp_search(&forge_root, semantic_model);
-    let context = tools::ToolContext::dynamic(forge_resolver, forge_root, semantic_ready);
-    server::serve(std::io::stdin().lock(), std::io::stdout().lock(), context)
+    let context = tools::ToolContext::dynamic(
+        forge_resolver,
+        named_forge,
+        forge_list,
+        forge_root,
+        semantic_ready,
+    );
+    match http_port {
+        Some(port) => {
+            let token = bearer_token_with(&crate::secrets::KeychainSecretStore)?;
+            let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+                .map_err(|error| format!("Failed to listen on 127.0.0.1:{port}: {error}"))?;
+            eprintln!("Moldavite MCP server listening on http://127.0.0.1:{port}/mcp");
+            http::serve(listener, token, context)
+        }
+        None => server::serve(std::io::stdin().lock(), std::io::stdout(), context),
+    }
+}
+
+/// Replay a reviewed proposal's tool call against `forge_root` with writes
+/// allowed, as the client that proposed it. Used by the app, not the server.
+pub(crate) fn replay_proposal(
+    forge_root: &Path,
+    tool: &str,
+    arguments: &Value,
+    client: Option<&str>,
+) -> Result<Value, String> {
+    let context = tools::ToolContext::for_review(forge_root.to_path_buf());
+    context.set_client_name(client);
+    context.run(tool, arguments, &progress::CallControl::none())
+}
+
+/// Forge lookup and listing for a server started with `--forge`: the pinned
+/// Forge is the only one that exists, whatever a client or policy asks for.
+fn pinned_forge_access(
+    server_forge: String,
+    forge_root: PathBuf,
+) -> (tools::NamedForgeResolver, tools::ForgeLister) {
+    let info = ForgeInfo {
+        name: server_forge.clone(),
+        path: forge_root.to_string_lossy().into_owned(),
+        is_active: true,
+    };
+    let named_forge: tools::NamedForgeResolver = Arc::new(move |name: &str| {
+        if name != server_forge {
+            return Err(format!(
+                "This server is pinned to Forge '{server_forge}' and cannot use '{name}'"
+            ));
+        }
+        validate_forge_root(name, forge_root.clone())
+    });
+    let forge_list: tools::ForgeLister = Arc::new(move || Ok(vec![info.clone()]));
+    (named_forge, forge_list)
 }
 
 /// Resolve a Forge by name for callers outside MCP, falling back to the active
@@ -145,6 +235,24 @@ mod tests {
         ))
     }
 
+    #[test]
+    fn pinned_server_lists_and_resolves_only_its_forge() {
+        let root = temp_root("pinned-access");
+        let work = root.join("Work");
+        std::fs::create_dir_all(root.join("Personal")).unwrap();
+        std::fs::create_dir_all(&work).unwrap();
+        let (named_forge, forge_list) = pinned_forge_access("Work".to_string(), work.clone());
+
+        let forges = forge_list().unwrap();
+        assert_eq!(forges.len(), 1);
+        assert_eq!(forges[0].name, "Work");
+        assert_eq!(named_forge("Work").unwrap(), work);
+        assert!(named_forge("Personal")
+            .unwrap_err()
+            .contains("pinned to Forge 'Work'"));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn pinned_missing_forge_errors() {
         let root = temp_root("pinned-missing");
@@ -0,0 +1,279 @@
+//! Section-aware edits for `patch_note`.
+//!
+//! Patches apply to a note body (frontmatter is carried over by the save path
+//! untouched). A section is an ATX heading plus every line up to the next
+//! heading of the same or a higher level; `#` lines inside fenced code blocks
+//! are not headings. Edits that cannot be applied unambiguously — a heading
+//! that matches twice, find text that occurs more than once without
+//! `replaceAll` — are refused rather than guessed at.
+
+use serde_json::Value;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum Patch<'a> {
+    /// Insert `content` directly below the heading line.
+    Insert { heading: &'a str, content: &'a str },
+    /// Add `content` after the last non-blank line of the section.
+    Append { heading: &'a str, content: &'a str },
+    /// Replace everything between the heading and the next section.
+    Replace { heading: &'a str, content: &'a str },
+    /// Replace exact occurrences of `find`.
+    FindReplace {
+        find: &'a str,
+        replace: &'a str,
+        all: bool,
+    },
+}
+
+impl<'a> Patch<'a> {
+    pub(super) fn from_arguments(arguments: &'a Value) -> Result<Self, String> {
+        let string = |name: &str| -> Result<&'a str, String> {
+            arguments
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("{name} must be a string"))
+        };
+        let operation = string("operation")?;
+        match operation {
+            "insert" | "append" | "replace" => {
+                let heading = string("heading")?;
+                let content = string("content")?;
+                Ok(match operation {
+                    "insert" => Patch::Insert { heading, content },
+                    "append" => Patch::Append { heading, content },
+                    _ => Patch::Replace { heading, content },
+                })
+            }
+            "findReplace" => {
+                let find = string("find")?;
+                if find.is_empty() {
+                    return Err("find must not be empty".to_string());
+                }
+                let all = match arguments.get("replaceAll") {
+                    None | Some(Value::Null) => false,
+                    Some(Value::Bool(all)) => *all,
+                    Some(_) => return Err("replaceAll must be a boolean".to_string()),
+                };
+                Ok(Patch::FindReplace {
+                    find,
+                    replace: string("replace")?,
+                    all,
+                })
+            }
+            _ => Err("operation must be insert, append, replace, or findReplace".to_string()),
+        }
+    }
+
+    pub(super) fn apply(&self, body: &str) -> Result<String, String> {
+        match *self {
+            Patch::FindReplace { find, replace, all } => {
+                let count = body.matches(find).count();
+                match count {
+                    0 => Err("find text does not occur in the note".to_string()),
+                    1 => Ok(body.replacen(find, replace, 1)),
+                    _ if all => Ok(body.replace(find, replace)),
+                    _ => Err(format!(
+                        "find text occurs {count} times; include more surrounding text or pass replaceAll"
+                    )),
+                }
+            }
+            Patch::Insert { heading, content }
+            | Patch::Append { heading, content }
+            | Patch::Replace { heading, content } => {
+                let mut lines: Vec<String> =
+                    body.split_inclusive('\n').map(str::to_string).collect();
+                let (heading_line, section_end) = find_section(&lines, heading)?;
+                let at = match self {
+                    Patch::Insert { .. } => heading_line + 1,
+                    Patch::Append { .. } => {
+                        (heading_line + 1..section_end)
+                            .rev()
+                            .find(|&index| !lines[index].trim().is_empty())
+                            .unwrap_or(heading_line)
+                            + 1
+                    }
+                    _ => {
+                        lines.drain(heading_line + 1..section_end);
+                        heading_line + 1
+                    }
+                };
+                if !lines[at - 1].ends_with('\n') {
+                    lines[at - 1].push('\n');
+                }
+                let mut block = content.to_string();
+                let followed = at < lines.len();
+                if followed && !block.is_empty() && !block.ends_with('\n') {
+                    block.push('\n');
+                }
+                // Keep the replaced section visually separate from the next one.
+                if matches!(self, Patch::Replace { .. })
+                    && followed
+                    && !block.is_empty()
+                    && !block.ends_with("\n\n")
+                {
+                    block.push('\n');
+                }
+                lines.insert(at, block);
+                Ok(lines.concat())
+            }
+        }
+    }
+}
+
+/// Locate the requested heading and return `(heading line, end of section)`
+/// as line indexes. `heading` may carry its `#` prefix to pin the level.
+fn find_section(lines: &[String], heading: &str) -> Result<(usize, usize), String> {
+    let wanted = heading.trim();
+    let wanted_level = wanted.chars().take_while(|c| *c == '#').count();
+    let wanted_text = wanted.trim_start_matches('#').trim();
+    if wanted_text.is_empty() {
+        return Err("heading must not be empty".to_string());
+    }
+
+    let headings = headings(lines);
+    let matches: Vec<(usize, usize)> = headings
+        .iter()
+        .filter(|(_, level, text)| {
+            text == wanted_text && (wanted_level == 0 || *level == wanted_level)
+        })
+        .map(|(index, level, _)| (*index, *level))
+        .collect();
+    let (heading_line, level) = match matches.as_slice() {
+        [] => return Err(format!("No heading named \"{wanted_text}\" in the note")),
+        [only] => *only,
+        _ => {
+            return Err(format!(
+                "{} headings are named \"{wanted_text}\"; prefix the heading with its # level to choose one",
+                matches.len()
+            ))
+        }
+    };
+    let section_end = headings
+        .iter()
+        .find(|(index, other_level, _)| *index > heading_line && *other_level <= level)
+        .map_or(lines.len(), |(index, _, _)| *index);
+    Ok((heading_line, section_end))
+}
+
+/// Every ATX heading outside fenced code as `(line index, level, text)`.
+fn headings(lines: &[String]) -> Vec<(usize, usize, String)> {
+    let mut found = Vec::new();
+    let mut fence: Option<(char, usize)> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        let unindented = trimmed.trim_start_matches(' ');
+        if trimmed.len() - unindented.len() > 3 {
+            continue;
+        }
+        let marker = unindented.chars().next().unwrap_or(' ');
+        if marker == '`' || marker == '~' {
+            let run = unindented.chars().take_while(|c| *c == marker).count();
+            if run >= 3 {
+                match fence {
+                    None => fence = Some((marker, run)),
+                    Some((open, open_run))
+                        if open == marker
+                            && run >= open_run
+                            && unindented[run..].trim().is_empty() =>
+                    {
+                        fence = None
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+        }
+        if fence.is_some() || marker != '#' {
+            continue;
+        }
+        let level = unindented.chars().take_while(|c| *c == '#').count();
+        let rest = &unindented[level..];
+        if level > 6 || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+            continue;
+        }
+        // A closing run of #s is decoration, not part of the heading text.
+        let text = rest.trim();
+        let without_closing = text.trim_end_matches('#');
+        let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+            without_closing.trim_end()
+        } else {
+            text
+        };
+        found.push((index, level, text.to_string()));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const NOTE: &str =
+        "# Day\n\nIntro\n\n## Tasks\n\n- one\n- two\n\n### Later\n- maybe\n\n## Log\nstarted\n";
+
+    fn patch(arguments: Value) -> Result<String, String> {
+        Patch::from_arguments(&arguments)?.apply(NOTE)
+    }
+
+    #[test]
+    fn heading_operations_respect_section_bounds() {
+        assert_eq!(
+            patch(json!({"operation":"insert","heading":"Tasks","content":"- zero"})).unwrap(),
+            NOTE.replace("## Tasks\n", "## Tasks\n- zero\n")
+        );
+        assert_eq!(
+            patch(json!({"operation":"append","heading":"## Tasks","content":"- three\n"}))
+                .unwrap(),
+            NOTE.replace("- maybe\n", "- maybe\n- three\n")
+        );
+        assert_eq!(
+            patch(json!({"operation":"append","heading":"Log","content":"done"})).unwrap(),
+            format!("{NOTE}done")
+        );
+        assert_eq!(
+            patch(json!({"operation":"replace","heading":"Tasks","content":"- only"})).unwrap(),
+            "# Day\n\nIntro\n\n## Tasks\n- only\n\n## Log\nstarted\n"
+        );
+    }
+
+    #[test]
+    fn headings_inside_code_fences_and_ambiguous_names_are_not_guessed() {
+        let body = "## Notes\n```md\n## Notes\n```\ntext\n## Notes ##\nmore\n";
+        let ambiguous = Patch::Append {
+            heading: "Notes",
+            content: "x",
+        }
+        .apply(body)
+        .unwrap_err();
+        assert!(ambiguous.contains("2 headings"), "{ambiguous}");
+        let missing = Patch::Insert {
+            heading: "### Notes",
+            content: "x",
+        }
+        .apply(body)
+        .unwrap_err();
+        assert!(missing.contains("No heading"), "{missing}");
+    }
+
+    #[test]
+    fn find_replace_requires_a_unique_match_unless_replace_all() {
+        assert_eq!(
+            patch(json!({"operation":"findReplace","find":"- one","replace":"- 1"})).unwrap(),
+            NOTE.replace("- one", "- 1")
+        );
+        let repeated = json!({"operation":"findReplace","find":"## ","replace":"### "});
+        assert!(patch(repeated).unwrap_err().contains("occurs 3 times"));
+        assert_eq!(
+            patch(
+                json!({"operation":"findReplace","find":"## ","replace":"### ","replaceAll":true})
+            )
+            .unwrap(),
+            NOTE.replace("## ", "### ")
+        );
+        assert!(patch(json!({"operation":"findReplace","find":"absent","replace":""})).is_err());
+        assert!(patch(json!({"operation":"findReplace","find":"","replace":"x"})).is_err());
+        assert!(patch(json!({"operation":"rewrite","heading":"Log","content":"x"})).is_err());
+    }
+}
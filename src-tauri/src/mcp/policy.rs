@@ -0,0 +1,168 @@
+//! Per-client MCP access policies.
+//!
+//! A policy is chosen by the `clientInfo.name` a client sends in `initialize`,
+//! falling back to the `"*"` entry. That name is self-reported, so policies
+//! keep well-behaved clients inside the scope the user chose for them; they
+//! are not authentication. A client that renames itself lands on the `"*"`
+//! policy, which is why restricting unknown clients belongs there.
+//!
+//! Folder rules are globs over Forge-relative paths: `*` and `?` stay within
+//! one path segment, `**` spans any number of segments, and a pattern naming a
+//! folder also covers everything inside it.
+//!
+//! Deny rules match regardless of case and Unicode normalization, because
+//! macOS and Windows file systems open `notes/private` for a `notes/Private`
+//! folder. Allow rules still need the exact spelling, so the looser match can
+//! only ever refuse a path, never open one up.
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::types::McpClientPolicy;
+
+/// Policy key that applies to clients without their own entry.
+pub(super) const DEFAULT_POLICY_KEY: &str = "*";
+
+impl McpClientPolicy {
+    pub(super) fn allows_tool(&self, name: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .map_or(true, |tools| tools.iter().any(|tool| tool == name))
+    }
+
+    /// A pinned client sees one Forge and never writes to it.
+    pub(super) fn is_read_only(&self) -> bool {
+        self.pinned_forge.is_some()
+    }
+
+    pub(super) fn restricts_reads(&self) -> bool {
+        !self.read_allow.is_empty() || !self.read_deny.is_empty()
+    }
+
+    pub(super) fn restricts_writes(&self) -> bool {
+        self.restricts_reads() || !self.write_allow.is_empty() || !self.write_deny.is_empty()
+    }
+
+    pub(super) fn can_read(&self, rel: &str) -> bool {
+        permitted(&self.read_allow, &self.read_deny, rel)
+    }
+
+    pub(super) fn can_write(&self, rel: &str) -> bool {
+        !self.is_read_only()
+            && self.can_read(rel)
+            && permitted(&self.write_allow, &self.write_deny, rel)
+    }
+}
+
+fn permitted(allow: &[String], deny: &[String], rel: &str) -> bool {
+    let folded = fold(rel);
+    (allow.is_empty() || allow.iter().any(|pattern| glob_matches(pattern, rel)))
+        && !deny
+            .iter()
+            .any(|pattern| glob_matches(&fold(pattern), &folded))
+}
+
+/// The spelling case-insensitive, normalization-insensitive file systems
+/// resolve to the same file.
+fn fold(text: &str) -> String {
+    text.nfc().collect::<String>().to_lowercase()
+}
+
+/// Match `pattern` against `path` or any folder containing it.
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').collect();
+    !pattern.is_empty() && (1..=path.len()).any(|end| segments_match(&pattern, &path[..end]))
+}
+
+fn segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| segments_match(rest, &path[skip..])),
+        Some((first, rest)) => path.split_first().is_some_and(|(segment, tail)| {
+            segment_matches(first, segment) && segments_match(rest, tail)
+        }),
+    }
+}
+
+/// `*` and `?` wildcards within one segment, with the usual single-star
+/// backtracking so matching stays linear in practice.
+fn segment_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs_match_segments_folders_and_double_stars() {
+        assert!(glob_matches("notes/Work/**", "notes/Work/a.md"));
+        assert!(glob_matches("notes/Work/**", "notes/Work/deep/b.md"));
+        assert!(glob_matches("notes/Work", "notes/Work/deep/b.md"));
+        assert!(!glob_matches("notes/Work", "notes/Workshop/a.md"));
+        assert!(glob_matches("daily/2026-*.md", "daily/2026-07-12.md"));
+        assert!(!glob_matches("daily/*.md", "daily/x/2026-07-12.md"));
+        assert!(glob_matches("**/secret*", "notes/a/b/secret plans.md"));
+        assert!(glob_matches("notes/?.md", "notes/é.md"));
+        assert!(!glob_matches("", "notes/a.md"));
+    }
+
+    #[test]
+    fn writes_need_read_access_and_pins_are_read_only() {
+        let policy = McpClientPolicy {
+            allowed_tools: Some(vec!["read_note".to_string(), "write_note".to_string()]),
+            read_allow: vec!["notes/**".to_string()],
+            read_deny: vec!["notes/Private".to_string()],
+            write_allow: vec!["notes/Inbox".to_string(), "notes/Private".to_string()],
+            ..McpClientPolicy::default()
+        };
+        assert!(policy.allows_tool("write_note"));
+        assert!(!policy.allows_tool("trash_note"));
+        assert!(policy.can_read("notes/Work/a.md"));
+        assert!(!policy.can_read("daily/2026-07-12.md"));
+        assert!(!policy.can_read("notes/Private/a.md"));
+        assert!(policy.can_write("notes/Inbox/a.md"));
+        assert!(!policy.can_write("notes/Work/a.md"));
+        assert!(!policy.can_write("notes/Private/a.md"));
+        // Other spellings of a denied folder stay denied; allow rules don't
+        // stretch the same way.
+        assert!(!policy.can_read("notes/private/a.md"));
+        assert!(!policy.can_read("notes/PRIVATE/deep/a.md"));
+        assert!(!policy.can_read("NOTES/Work/a.md"));
+        assert!(!policy.can_write("notes/inbox/a.md"));
+
+        let accented = McpClientPolicy {
+            read_deny: vec!["notes/Caf\u{e9}".to_string()],
+            ..McpClientPolicy::default()
+        };
+        assert!(!accented.can_read("notes/Cafe\u{301}/a.md"));
+        assert!(!accented.can_read("notes/CAF\u{c9}/a.md"));
+        assert!(accented.can_read("notes/Cafe/a.md"));
+
+        let pinned = McpClientPolicy {
+            pinned_forge: Some("Archive".to_string()),
+            ..McpClientPolicy::default()
+        };
+        assert!(pinned.can_read("notes/a.md"));
+        assert!(!pinned.can_write("notes/a.md"));
+    }
+}
@@ -0,0 +1,185 @@
+//! Progress notifications and cancellation for in-flight tool calls.
+//!
+//! A `tools/call` whose params carry `_meta.progressToken` is sent
+//! `notifications/progress` for that token while it runs. A
+//! `notifications/cancelled` naming the call's request id asks it to stop:
+//! tools check between units of read-only work, never partway through a write,
+//! and a cancelled call gets no response, as MCP specifies. Both work the same
+//! over stdio and HTTP because the registry lives on the session.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+
+use serde_json::{json, Value};
+
+use super::server::Outbound;
+
+pub(super) const CANCELLED: &str = "Request cancelled";
+
+/// Cancellation flags of a session's running tool calls, keyed by request id.
+#[derive(Default)]
+pub(super) struct InFlight {
+    calls: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl InFlight {
+    /// Register a call, or `None` when a call with the same id is still running.
+    pub(super) fn start(&self, id: &Value) -> Option<Arc<AtomicBool>> {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        let key = id.to_string();
+        if calls.contains_key(&key) {
+            return None;
+        }
+        let cancelled = Arc::new(AtomicBool::new(false));
+        calls.insert(key, cancelled.clone());
+        Some(cancelled)
+    }
+
+    /// Unregister a call and report whether it was cancelled.
+    pub(super) fn finish(&self, id: &Value) -> bool {
+        self.calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&id.to_string())
+            .is_some_and(|cancelled| cancelled.load(Ordering::SeqCst))
+    }
+
+    /// Handle `notifications/cancelled`. Unknown or finished ids are ignored;
+    /// the notification may race the response.
+    pub(super) fn cancel(&self, params: &Value) {
+        let Some(id) = params.get("requestId") else {
+            return;
+        };
+        if let Some(cancelled) = self
+            .calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&id.to_string())
+        {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+/// What a running tool can see of its caller: where to report progress and
+/// whether it has been cancelled.
+pub(super) struct CallControl {
+    progress: Option<(Value, mpsc::Sender<Outbound>)>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CallControl {
+    /// A call nobody watches or cancels.
+    pub(super) fn none() -> Self {
+        Self {
+            progress: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Control for a `tools/call` with these params. MCP progress tokens are
+    /// strings or integers; anything else is ignored.
+    pub(super) fn for_request(
+        params: &Value,
+        outbound: mpsc::Sender<Outbound>,
+        cancelled: Arc<AtomicBool>,
+    ) -> Self {
+        let token = params
+            .get("_meta")
+            .and_then(|meta| meta.get("progressToken"))
+            .filter(|token| token.is_string() || token.is_i64() || token.is_u64())
+            .cloned();
+        Self {
+            progress: token.map(|token| (token, outbound)),
+            cancelled,
+        }
+    }
+
+    /// Report `done` units of work, out of `total` when it is known. Callers
+    /// report increasing values at a coarse step, not per item.
+    pub(super) fn progress(&self, done: usize, total: Option<usize>, message: &str) {
+        let Some((token, outbound)) = &self.progress else {
+            return;
+        };
+        let mut params = json!({ "progressToken": token, "progress": done, "message": message });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        let _ = outbound.send(Outbound::Message(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/progress",
+            "params": params
+        })));
+    }
+
+    pub(super) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// `Err` once the call has been cancelled, for `?` at safe stopping points.
+    pub(super) fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err(CANCELLED.to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancellation_reaches_only_the_named_running_call() {
+        let in_flight = InFlight::default();
+        let first = in_flight.start(&json!(1)).unwrap();
+        let second = in_flight.start(&json!("1")).unwrap();
+        assert!(in_flight.start(&json!(1)).is_none());
+
+        in_flight.cancel(&json!({ "requestId": 1, "reason": "user stopped it" }));
+        in_flight.cancel(&json!({ "requestId": 99 }));
+        assert!(first.load(Ordering::SeqCst));
+        assert!(!second.load(Ordering::SeqCst));
+        assert!(in_flight.finish(&json!(1)));
+        assert!(!in_flight.finish(&json!("1")));
+        assert!(in_flight.start(&json!(1)).is_some());
+    }
+
+    #[test]
+    fn progress_is_sent_only_for_valid_tokens() {
+        let (outbound, queue) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let control = CallControl::for_request(
+            &json!({ "_meta": { "progressToken": "scan" } }),
+            outbound.clone(),
+            cancelled.clone(),
+        );
+        control.progress(1, Some(4), "Searched Work");
+        control.progress(2, None, "Scanned 2 notes");
+        let Ok(Outbound::Message(first)) = queue.try_recv() else {
+            panic!("expected a progress notification");
+        };
+        assert_eq!(first["method"], "notifications/progress");
+        assert_eq!(
+            first["params"],
+            json!({ "progressToken": "scan", "progress": 1, "total": 4, "message": "Searched Work" })
+        );
+        let Ok(Outbound::Message(second)) = queue.try_recv() else {
+            panic!("expected a progress notification");
+        };
+        assert!(second["params"].get("total").is_none());
+
+        let untracked = CallControl::for_request(
+            &json!({ "_meta": { "progressToken": { "nested": true } } }),
+            outbound,
+            cancelled.clone(),
+        );
+        untracked.progress(1, None, "ignored");
+        assert!(queue.try_recv().is_err());
+
+        assert!(untracked.check().is_ok());
+        cancelled.store(true, Ordering::SeqCst);
+        assert_eq!(control.check(), Err(CANCELLED.to_string()));
+    }
+}
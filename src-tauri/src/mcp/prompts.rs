@@ -0,0 +1,85 @@
+//! MCP prompts backed by note templates.
+//!
+//! Every built-in template and every user template in the resolved Forge is a
+//! prompt named by its template id. `prompts/get` expands the template the same
+//! way the app does when it creates a note, except that the optional `date` and
+//! `time` arguments replace the clock, so a client can render a template for a
+//! day other than today.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime};
+use serde_json::{json, Value};
+
+use super::tools::ToolContext;
+use crate::commands::templates::{get_template_in, list_templates_in};
+use crate::templates_data::replace_template_variables_at;
+
+pub(super) fn list(context: &ToolContext) -> Result<Value, String> {
+    let templates_dir = templates_dir(&context.forge_root()?)?;
+    let prompts: Vec<Value> = list_templates_in(&templates_dir)
+        .into_iter()
+        .map(|template| {
+            json!({
+                "name": template.id,
+                "title": template.name,
+                "description": template.description,
+                "arguments": prompt_arguments()
+            })
+        })
+        .collect();
+    Ok(json!({ "prompts": prompts }))
+}
+
+pub(super) fn get(context: &ToolContext, params: &Value) -> Result<Value, String> {
+    let name = params
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "prompts/get requires a prompt name".to_string())?;
+    let arguments = params.get("arguments").cloned().unwrap_or(Value::Null);
+    let now = expansion_time(&arguments, Local::now().naive_local())?;
+    let template = get_template_in(&templates_dir(&context.forge_root()?)?, name)?;
+    let text = replace_template_variables_at(template.content, now);
+    Ok(json!({
+        "description": template.description,
+        "messages": [{ "role": "user", "content": { "type": "text", "text": text } }]
+    }))
+}
+
+fn prompt_arguments() -> Value {
+    json!([
+        { "name": "date", "description": "Date for {{date}} and {{day_of_week}} as YYYY-MM-DD; defaults to today.", "required": false },
+        { "name": "time", "description": "Time for {{time}} as HH:MM; defaults to now.", "required": false }
+    ])
+}
+
+/// The user templates folder of a Forge. A symlinked folder is refused for the
+/// same reason symlinked notes are.
+pub(super) fn templates_dir(forge_root: &Path) -> Result<PathBuf, String> {
+    let templates_dir = forge_root.join("templates");
+    if fs::symlink_metadata(&templates_dir)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+    {
+        return Err("Refusing to read a symlinked templates folder".to_string());
+    }
+    Ok(templates_dir)
+}
+
+/// Combine the optional `date` and `time` prompt arguments with `now`.
+fn expansion_time(arguments: &Value, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let date = match arguments.get("date") {
+        None | Some(Value::Null) => now.date(),
+        Some(Value::String(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| "date must be a real calendar date in YYYY-MM-DD format".to_string())?,
+        Some(_) => return Err("date must be a string".to_string()),
+    };
+    let time = match arguments.get("time") {
+        None | Some(Value::Null) => now.time(),
+        Some(Value::String(time)) => NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| "time must be a 24-hour time in HH:MM format".to_string())?,
+        Some(_) => return Err("time must be a string".to_string()),
+    };
+    Ok(date.and_time(time))
+}
@@ -0,0 +1,320 @@
+//! MCP resources: unlocked notes exposed as `moldavite://note/<path>` URIs.
+//!
+//! A resource URI carries the same Forge-relative path the tools accept, with
+//! each segment percent-encoded. Every read and subscription decodes the URI
+//! and then goes through the tool validators unchanged, so locked notes,
+//! traversal, symlinks, and folders outside the client's read policy are
+//! refused exactly as `read_note` refuses them.
+//!
+//! Subscriptions are served by a debounced watcher on the Forge that owned the
+//! most recently subscribed note. Any change to a subscribed note — including
+//! it becoming locked or being removed — emits `notifications/resources/updated`
+//! and the client is expected to re-read it.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use serde_json::{json, Value};
+
+use super::server::Outbound;
+use super::tools::{note_entries, validated_existing_note_path, ToolContext};
+
+const NOTE_URI_PREFIX: &str = "moldavite://note/";
+const NOTE_MIME_TYPE: &str = "text/markdown";
+
+pub(super) fn list(context: &ToolContext) -> Result<Value, String> {
+    let forge_root = context.forge_root()?;
+    let policy = context.policy()?;
+    let resources: Vec<Value> = note_entries(&forge_root, None)
+        .into_iter()
+        .filter(|(rel, locked)| !locked && policy.can_read(rel))
+        .map(|(rel, _)| {
+            let name = rel.rsplit('/').next().unwrap_or(&rel).to_string();
+            json!({
+                "uri": note_uri(&rel),
+                "name": name,
+                "title": rel,
+                "mimeType": NOTE_MIME_TYPE
+            })
+        })
+        .collect();
+    Ok(json!({ "resources": resources }))
+}
+
+pub(super) fn templates() -> Value {
+    json!({
+        "resourceTemplates": [{
+            "uriTemplate": format!("{NOTE_URI_PREFIX}{{path}}"),
+            "name": "note",
+            "title": "Forge note",
+            "description": "An unlocked Markdown note addressed by its Forge-relative path under daily/, weekly/, or notes/.",
+            "mimeType": NOTE_MIME_TYPE
+        }]
+    })
+}
+
+pub(super) fn read(context: &ToolContext, params: &Value) -> Result<Value, String> {
+    let (uri, rel) = requested_note(params)?;
+    context.ensure_readable(&rel)?;
+    let forge_root = context.forge_root()?;
+    let path = context.checked_existing_note(&forge_root, &rel)?;
+    let text =
+        fs::read_to_string(&path).map_err(|error| format!("Failed to read note: {error}"))?;
+    Ok(json!({
+        "contents": [{ "uri": uri, "mimeType": NOTE_MIME_TYPE, "text": text }]
+    }))
+}
+
+/// Validate the `uri` parameter and return it with its decoded note path.
+fn requested_note(params: &Value) -> Result<(String, String), String> {
+    let uri = params
+        .get("uri")
+        .and_then(Value::as_str)
+        .ok_or_else(|| "uri must be a string".to_string())?;
+    let rel = note_path_from_uri(uri)?;
+    Ok((uri.to_string(), rel))
+}
+
+pub(super) fn note_uri(rel: &str) -> String {
+    let encoded: Vec<String> = rel.split('/').map(percent_encode).collect();
+    format!("{NOTE_URI_PREFIX}{}", encoded.join("/"))
+}
+
+fn note_path_from_uri(uri: &str) -> Result<String, String> {
+    let encoded = uri
+        .strip_prefix(NOTE_URI_PREFIX)
+        .ok_or_else(|| format!("Unknown resource URI; expected {NOTE_URI_PREFIX}<path>"))?;
+    let mut segments = Vec::new();
+    for segment in encoded.split('/') {
+        let decoded = percent_decode(segment).ok_or_else(|| "Invalid resource URI".to_string())?;
+        // An encoded separator would let one segment smuggle in another.
+        if decoded.contains('/') {
+            return Err("Invalid note path".to_string());
+        }
+        segments.push(decoded);
+    }
+    validated_existing_note_path(&segments.join("/"))
+}
+
+/// Percent-encode one path segment, keeping only RFC 3986 unreserved bytes.
+fn percent_encode(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(*byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Strict inverse of [`percent_encode`]: malformed escapes and non-UTF-8 bytes
+/// are rejected rather than repaired.
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+struct WatchedForge {
+    root: PathBuf,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Per-session resource subscriptions and the watcher that serves them.
+/// Dropping this stops the watcher.
+pub(super) struct Subscriptions {
+    outbound: mpsc::Sender<Outbound>,
+    notes: Arc<Mutex<HashSet<String>>>,
+    watched: Mutex<Option<WatchedForge>>,
+}
+
+impl Subscriptions {
+    pub(super) fn new(outbound: mpsc::Sender<Outbound>) -> Self {
+        Self {
+            outbound,
+            notes: Arc::new(Mutex::new(HashSet::new())),
+            watched: Mutex::new(None),
+        }
+    }
+
+    pub(super) fn subscribe(&self, context: &ToolContext, params: &Value) -> Result<Value, String> {
+        let (_, rel) = requested_note(params)?;
+        context.ensure_readable(&rel)?;
+        let forge_root = context.forge_root()?;
+        context.checked_existing_note(&forge_root, &rel)?;
+        self.watch(&forge_root)?;
+        self.notes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(rel);
+        Ok(json!({}))
+    }
+
+    pub(super) fn unsubscribe(&self, params: &Value) -> Result<Value, String> {
+        let (_, rel) = requested_note(params)?;
+        let idle = {
+            let mut notes = self.notes.lock().unwrap_or_else(|e| e.into_inner());
+            notes.remove(&rel);
+            notes.is_empty()
+        };
+        if idle {
+            *self.watched.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+        Ok(json!({}))
+    }
+
+    /// Ensure a watcher is running on `forge_root`. Subscribing while a
+    /// different Forge is active moves the watcher, because subscribed paths
+    /// are Forge-relative and always refer to the Forge requests resolve to.
+    fn watch(&self, forge_root: &Path) -> Result<(), String> {
+        let root = fs::canonicalize(forge_root)
+            .map_err(|error| format!("Failed to resolve Forge: {error}"))?;
+        let mut watched = self.watched.lock().unwrap_or_else(|e| e.into_inner());
+        if watched.as_ref().is_some_and(|current| current.root == root) {
+            return Ok(());
+        }
+        let notes = self.notes.clone();
+        let outbound = self.outbound.clone();
+        let root_for_events = root.clone();
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(300),
+            move |result: DebounceEventResult| {
+                let events = match result {
+                    Ok(events) => events,
+                    Err(err) => {
+                        log::warn!("[mcp resources] watcher error: {}", err);
+                        return;
+                    }
+                };
+                let mut changed = HashSet::new();
+                for event in events {
+                    if let Some(rel) = subscribed_note(&root_for_events, &event.path, &notes) {
+                        changed.insert(rel);
+                    }
+                }
+                for rel in changed {
+                    let _ = outbound.send(Outbound::Message(json!({
+                        "jsonrpc": "2.0",
+                        "method": "notifications/resources/updated",
+                        "params": { "uri": note_uri(&rel) }
+                    })));
+                }
+            },
+        )
+        .map_err(|e| format!("failed to create debouncer: {}", e))?;
+        debouncer
+            .watcher()
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("failed to watch {:?}: {}", root, e))?;
+        *watched = Some(WatchedForge {
+            root,
+            _debouncer: debouncer,
+        });
+        Ok(())
+    }
+}
+
+/// Map a changed file to the subscribed note it affects. A `.locked` sibling
+/// counts as a change to the note, so clients learn a note became unreadable.
+fn subscribed_note(root: &Path, path: &Path, notes: &Mutex<HashSet<String>>) -> Option<String> {
+    let rel = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    let rel = rel.strip_suffix(".locked").unwrap_or(&rel);
+    let notes = notes.lock().unwrap_or_else(|e| e.into_inner());
+    notes.contains(rel).then(|| rel.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn note_uris_round_trip_and_reject_smuggled_separators() {
+        let rel = "notes/Projetos_日本語/café notes.md";
+        let uri = note_uri(rel);
+        assert!(uri.starts_with("moldavite://note/notes/Projetos_"));
+        assert!(!uri.contains(' '));
+        assert_eq!(note_path_from_uri(&uri).unwrap(), rel);
+
+        for invalid in [
+            "moldavite://note/notes/..%2F..%2Fetc/passwd.md",
+            "moldavite://note/notes%2F..%2Fescape.md",
+            "moldavite://note/notes/bad%ZZ.md",
+            "moldavite://note/notes/%FF.md",
+            "moldavite://note/other/x.md",
+            "file:///etc/passwd",
+        ] {
+            assert!(note_path_from_uri(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn subscribed_note_changes_emit_resource_updates() {
+        let root = std::env::temp_dir().join(format!(
+            "moldavite-mcp-resources-{}-{}",
+            std::process::id(),
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join("notes/watched.md"), "before").unwrap();
+        fs::write(root.join("notes/other.md"), "before").unwrap();
+        let context = ToolContext::new(root.clone(), false, false);
+        let (outbound, queue) = mpsc::channel();
+        let subscriptions = Subscriptions::new(outbound);
+        let uri = note_uri("notes/watched.md");
+
+        subscriptions
+            .subscribe(&context, &json!({ "uri": uri }))
+            .unwrap();
+        fs::write(root.join("notes/other.md"), "unsubscribed change").unwrap();
+        fs::write(root.join("notes/watched.md"), "after").unwrap();
+
+        let Outbound::Message(message) = queue.recv_timeout(Duration::from_secs(10)).unwrap()
+        else {
+            panic!("expected a notification");
+        };
+        assert_eq!(message["method"], "notifications/resources/updated");
+        assert_eq!(message["params"]["uri"], uri);
+        // One write can straddle two debounce windows; only the subscribed
+        // note may ever be reported.
+        while let Ok(Outbound::Message(message)) = queue.recv_timeout(Duration::from_millis(800)) {
+            assert_eq!(message["params"]["uri"], uri);
+        }
+
+        subscriptions.unsubscribe(&json!({ "uri": uri })).unwrap();
+        fs::write(root.join("notes/watched.md"), "ignored").unwrap();
+        assert!(queue.recv_timeout(Duration::from_millis(800)).is_err());
+
+        drop(subscriptions);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
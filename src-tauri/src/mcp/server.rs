@@ -1,19 +1,37 @@
 //! Bounded newline-delimited JSON-RPC transport for the built-in MCP server.
 //!
-//! The protocol surface is intentionally limited to `initialize`, `tools/list`,
-//! and `tools/call`; notifications receive no response. Input is untrusted and a
-//! request line may never retain more than 1 MiB. Oversized lines are drained so
-//! one hostile request cannot desynchronize the remainder of the session.
+//! The protocol surface is intentionally limited to `initialize`, `ping`, and
+//! the `tools/*`, `resources/*`, and `prompts/*` methods; notifications receive
+//! no response.
+//! Input is untrusted and a request line may never retain more than 1 MiB.
+//! Oversized lines are drained so one hostile request cannot desynchronize the
+//! remainder of the session.
+//!
+//! Requests that touch the Forge run on their own threads, so a slow tool call
+//! never holds up `initialize`, `ping`, `tools/list`, or a cancellation, which
+//! are answered as soon as they are read. Those requests start in arrival
+//! order, reads alongside each other and write tools alone, so a client that
+//! pipelines dependent requests still sees them applied in order; responses
+//! may arrive in any order. At most `MAX_CONCURRENT_CALLS` are held at once;
+//! beyond that the reader waits, which keeps buffered input bounded.
+//!
+//! Output goes through a single writer thread so responses and server-initiated
+//! notifications (resource updates, progress) share one newline-delimited stream.
 
 use std::io::{BufRead, Write};
+use std::sync::{mpsc, Condvar, Mutex};
 
 use serde_json::{json, Value};
 
-use super::tools::ToolContext;
+use super::progress::{CallControl, InFlight};
+use super::prompts;
+use super::resources::{self, Subscriptions};
+use super::tools::{is_write_tool, ToolContext};
 
 const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
 const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
-const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+pub(super) const MAX_REQUEST_BYTES: usize = 1024 * 1024;
+const MAX_CONCURRENT_CALLS: usize = 8;
 
 enum InputLine {
     Line(Vec<u8>),
@@ -62,13 +80,128 @@ fn read_bounded_line<R: BufRead>(reader: &mut R) -> Result<Option<InputLine>, St
     }
 }
 
+/// A message queued for the session's writer thread.
+pub(super) enum Outbound {
+    Message(Value),
+    Close,
+}
+
+/// Per-connection state shared by every request of one MCP session.
+pub(super) struct Session {
+    context: ToolContext,
+    subscriptions: Subscriptions,
+    in_flight: InFlight,
+    outbound: mpsc::Sender<Outbound>,
+}
+
+impl Session {
+    /// Server-initiated notifications for this session are queued on `outbound`.
+    pub(super) fn new(context: ToolContext, outbound: mpsc::Sender<Outbound>) -> Self {
+        Self {
+            context,
+            subscriptions: Subscriptions::new(outbound.clone()),
+            in_flight: InFlight::default(),
+            outbound,
+        }
+    }
+}
+
+#[derive(Default)]
+struct OrderState {
+    held: usize,
+    next_ticket: u64,
+    next_start: u64,
+    reading: usize,
+    writing: bool,
+}
+
+/// Arrival-order admission for one stdio session's Forge requests.
+#[derive(Default)]
+struct CallOrder {
+    state: Mutex<OrderState>,
+    changed: Condvar,
+}
+
+impl CallOrder {
+    /// Take the next ticket, waiting while `MAX_CONCURRENT_CALLS` are held.
+    fn enqueue(&self) -> u64 {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state.held >= MAX_CONCURRENT_CALLS {
+            state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.held += 1;
+        state.next_ticket += 1;
+        state.next_ticket - 1
+    }
+
+    /// Wait until every earlier call has started and nothing conflicting runs.
+    fn start(&self, ticket: u64, write: bool) -> CallTurn<'_> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        while state.next_start != ticket || state.writing || (write && state.reading > 0) {
+            state = self.changed.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.next_start += 1;
+        if write {
+            state.writing = true;
+        } else {
+            state.reading += 1;
+        }
+        self.changed.notify_all();
+        CallTurn { order: self, write }
+    }
+}
+
+/// A started call; dropping it lets later calls proceed, even after a panic.
+struct CallTurn<'a> {
+    order: &'a CallOrder,
+    write: bool,
+}
+
+impl Drop for CallTurn<'_> {
+    fn drop(&mut self) {
+        let mut state = self.order.state.lock().unwrap_or_else(|e| e.into_inner());
+        if self.write {
+            state.writing = false;
+        } else {
+            state.reading -= 1;
+        }
+        state.held -= 1;
+        self.order.changed.notify_all();
+    }
+}
+
 /// Serve requests until EOF, emitting at most one newline-delimited response per request.
-pub(super) fn serve<R: BufRead, W: Write>(
+pub(super) fn serve<R: BufRead, W: Write + Send>(
     mut reader: R,
-    mut writer: W,
+    writer: W,
     context: ToolContext,
 ) -> Result<(), String> {
-    while let Some(line) = read_bounded_line(&mut reader)? {
+    let (outbound, queue) = mpsc::channel();
+    std::thread::scope(|scope| {
+        let writer = scope.spawn(move || write_outbound(writer, queue));
+        let session = Session::new(context, outbound.clone());
+        // Every call still running at EOF finishes and answers before the
+        // inner scope ends.
+        let order = CallOrder::default();
+        let read = std::thread::scope(|calls| read_requests(&mut reader, &session, &order, calls));
+        // Stop the subscription watcher before closing the stream so no
+        // notification can race past the final response.
+        drop(session);
+        let _ = outbound.send(Outbound::Close);
+        let written = writer
+            .join()
+            .unwrap_or_else(|_| Err("MCP writer thread panicked".to_string()));
+        written.and(read)
+    })
+}
+
+fn read_requests<'scope, R: BufRead>(
+    reader: &mut R,
+    session: &'scope Session,
+    order: &'scope CallOrder,
+    calls: &'scope std::thread::Scope<'scope, '_>,
+) -> Result<(), String> {
+    while let Some(line) = read_bounded_line(reader)? {
         let response = match line {
             InputLine::Oversized => Some(error_response(
                 Value::Null,
@@ -77,29 +210,84 @@ pub(super) fn serve<R: BufRead, W: Write>(
             )),
             InputLine::Line(line) if line.iter().all(u8::is_ascii_whitespace) => continue,
             InputLine::Line(line) => match serde_json::from_slice::<Value>(&line) {
-                Ok(request) => handle_request(&context, request),
-                Err(error) => Some(error_response(
-                    Value::Null,
-                    -32700,
-                    &format!("Parse error: {error}"),
-                )),
+                Ok(request) if runs_in_order(&request) => {
+                    let id = request["id"].clone();
+                    let write = request
+                        .pointer("/params/name")
+                        .and_then(Value::as_str)
+                        .is_some_and(is_write_tool);
+                    let ticket = order.enqueue();
+                    let spawned = std::thread::Builder::new()
+                        .name("mcp-request".into())
+                        .spawn_scoped(calls, move || {
+                            let _turn = order.start(ticket, write);
+                            if let Some(response) = handle_request(session, request) {
+                                let _ = session.outbound.send(Outbound::Message(response));
+                            }
+                        });
+                    match spawned {
+                        Ok(_) => None,
+                        Err(error) => {
+                            // Retire the ticket so the requests queued behind it still run.
+                            drop(order.start(ticket, write));
+                            log::warn!("[mcp] failed to spawn request thread: {}", error);
+                            Some(error_response(id, -32603, "Failed to start the request"))
+                        }
+                    }
+                }
+                Ok(request) => handle_request(session, request),
+                Err(error) => Some(parse_error(error)),
             },
         };
         if let Some(response) = response {
-            serde_json::to_writer(&mut writer, &response)
-                .map_err(|error| format!("Failed to encode response: {error}"))?;
-            writer
-                .write_all(b"\n")
-                .map_err(|error| format!("Failed to write stdout: {error}"))?;
-            writer
-                .flush()
-                .map_err(|error| format!("Failed to flush stdout: {error}"))?;
+            session
+                .outbound
+                .send(Outbound::Message(response))
+                .map_err(|_| "Failed to write stdout: output closed".to_string())?;
         }
     }
     Ok(())
 }
 
-fn handle_request(context: &ToolContext, request: Value) -> Option<Value> {
+/// Requests that run on a worker thread in arrival order. Notifications and
+/// the methods that never read the Forge are answered inline.
+fn runs_in_order(request: &Value) -> bool {
+    request.get("id").is_some()
+        && !matches!(
+            request.get("method").and_then(Value::as_str),
+            Some("initialize" | "ping" | "tools/list")
+        )
+}
+
+fn write_outbound<W: Write>(mut writer: W, queue: mpsc::Receiver<Outbound>) -> Result<(), String> {
+    while let Ok(Outbound::Message(message)) = queue.recv() {
+        serde_json::to_writer(&mut writer, &message)
+            .map_err(|error| format!("Failed to encode response: {error}"))?;
+        writer
+            .write_all(b"\n")
+            .map_err(|error| format!("Failed to write stdout: {error}"))?;
+        writer
+            .flush()
+            .map_err(|error| format!("Failed to flush stdout: {error}"))?;
+    }
+    Ok(())
+}
+
+/// Parse one already-bounded JSON-RPC message and dispatch it. Every
+/// transport funnels its input through here.
+pub(super) fn handle_message(session: &Session, message: &[u8]) -> Option<Value> {
+    match serde_json::from_slice::<Value>(message) {
+        Ok(request) => handle_request(session, request),
+        Err(error) => Some(parse_error(error)),
+    }
+}
+
+fn parse_error(error: serde_json::Error) -> Value {
+    error_response(Value::Null, -32700, &format!("Parse error: {error}"))
+}
+
+fn handle_request(session: &Session, request: Value) -> Option<Value> {
+    let context = &session.context;
     let id = request.get("id").cloned();
     if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
         return Some(error_response(
@@ -116,9 +304,12 @@ fn handle_request(context: &ToolContext, request: Value) -> Option<Value> {
         ));
     };
 
+    let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+    if method == "notifications/cancelled" {
+        session.in_flight.cancel(&params);
+    }
     // Notifications intentionally have no response.
     let id = id?;
-    let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
     match method {
         "initialize" => {
             context.set_client_name(
@@ -140,7 +331,11 @@ fn handle_request(context: &ToolContext, request: Value) -> Option<Value> {
                 id,
                 json!({
                     "protocolVersion": protocol,
-                    "capabilities": { "tools": {} },
+                    "capabilities": {
+                        "tools": {},
+                        "resources": { "subscribe": true },
+                        "prompts": {}
+                    },
                     "serverInfo": {
                         "name": "moldavite",
                         "version": env!("CARGO_PKG_VERSION")
@@ -148,6 +343,7 @@ fn handle_request(context: &ToolContext, request: Value) -> Option<Value> {
                 }),
             ))
         }
+        "ping" => Some(success_response(id, json!({}))),
         "tools/list" => Some(success_response(
             id,
             json!({ "tools": context.tool_definitions() }),
@@ -164,8 +360,37 @@ fn handle_request(context: &ToolContext, request: Value) -> Option<Value> {
                 .get("arguments")
                 .cloned()
                 .unwrap_or_else(|| json!({}));
-            Some(success_response(id, context.call(name, &arguments)))
+            let Some(cancelled) = session.in_flight.start(&id) else {
+                return Some(error_response(
+                    id,
+                    -32600,
+                    "A request with this id is already in progress",
+                ));
+            };
+            let control = CallControl::for_request(&params, session.outbound.clone(), cancelled);
+            let result = context.call_with(name, &arguments, &control);
+            // A cancelled request gets no response at all.
+            (!session.in_flight.finish(&id)).then(|| success_response(id, result))
         }
+        "resources/list" => Some(resource_response(id, resources::list(context))),
+        "resources/templates/list" => Some(success_response(id, resources::templates())),
+        "resources/read" => Some(resource_response(id, resources::read(context, &params))),
+        "resources/subscribe" => Some(resource_response(
+            id,
+            session.subscriptions.subscribe(context, &params),
+        )),
+        "resources/unsubscribe" => Some(resource_response(
+            id,
+            session.subscriptions.unsubscribe(&params),
+        )),
+        "prompts/list" => Some(match prompts::list(context) {
+            Ok(result) => success_response(id, result),
+            Err(error) => error_response(id, -32603, &error),
+        }),
+        "prompts/get" => Some(match prompts::get(context, &params) {
+            Ok(result) => success_response(id, result),
+            Err(error) => error_response(id, -32602, &error),
+        }),
         _ => Some(error_response(id, -32601, "Method not found")),
     }
 }
@@ -174,6 +399,16 @@ fn success_response(id: Value, result: Value) -> Value {
     json!({ "jsonrpc": "2.0", "id": id, "result": result })
 }
 
+/// Resource failures are protocol errors rather than tool results; a missing
+/// note uses the MCP "resource not found" code.
+fn resource_response(id: Value, result: Result<Value, String>) -> Value {
+    match result {
+        Ok(result) => success_response(id, result),
+        Err(error) if error == "Note not found" => error_response(id, -32002, &error),
+        Err(error) => error_response(id, -32602, &error),
+    }
+}
+
 fn error_response(id: Value, code: i32, message: &str) -> Value {
     json!({
         "jsonrpc": "2.0",
@@ -184,14 +419,15 @@ fn error_response(id: Value, code: i32, message: &str) -> Value {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::fs;
     use std::io::Cursor;
-    use std::path::PathBuf;
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::path::{Path, PathBuf};
     use std::sync::{Arc, Mutex};
     use std::time::{SystemTime, UNIX_EPOCH};
 
     use super::*;
+    use crate::types::{McpClientPolicy, McpWriteMode};
 
     fn temp_forge(tag: &str) -> PathBuf {
         let root = std::env::temp_dir().join(format!(
@@ -215,14 +451,18 @@ mod tests {
         )
     }
 
+    /// Responses ordered by request id, since tool calls may answer out of
+    /// order. Id-less messages (parse errors, notifications) sort first.
     fn run(input: String, context: ToolContext) -> Vec<Value> {
         let mut output = Vec::new();
         serve(Cursor::new(input), &mut output, context).unwrap();
-        String::from_utf8(output)
+        let mut responses: Vec<Value> = String::from_utf8(output)
             .unwrap()
             .lines()
             .map(|line| serde_json::from_str(line).unwrap())
-            .collect()
+            .collect();
+        responses.sort_by_key(|response| response["id"].as_u64());
+        responses
     }
 
     #[test]
@@ -249,6 +489,7 @@ mod tests {
             ("read_note", json!({"path":"notes/target.md"})),
             ("list_notes", json!({"folder":"notes"})),
             ("get_backlinks", json!({"path":"notes/target.md"})),
+            ("get_properties", json!({"path":"notes/target.md"})),
             (
                 "create_note",
                 json!({"path":"notes/new.md","content":"new"}),
@@ -261,6 +502,14 @@ mod tests {
                 "write_note",
                 json!({"path":"notes/new.md","content":"replaced"}),
             ),
+            (
+                "patch_note",
+                json!({"path":"notes/new.md","operation":"findReplace","find":"replaced","replace":"patched"}),
+            ),
+            (
+                "set_properties",
+                json!({"path":"notes/target.md","properties":{"status":"done"}}),
+            ),
         ];
         for (offset, (name, arguments)) in calls.into_iter().enumerate() {
             input.push_str(&request(
@@ -270,15 +519,18 @@ mod tests {
             ));
         }
         let responses = run(input, ToolContext::new(root.clone(), true, false));
-        assert_eq!(responses.len(), 9);
+        assert_eq!(responses.len(), 12);
         assert_eq!(responses[0]["result"]["protocolVersion"], "2024-11-05");
-        assert_eq!(responses[1]["result"]["tools"].as_array().unwrap().len(), 7);
+        assert_eq!(
+            responses[1]["result"]["tools"].as_array().unwrap().len(),
+            19
+        );
         for response in &responses[2..] {
             assert_ne!(response["result"]["isError"], true);
         }
         assert_eq!(
             fs::read_to_string(root.join("notes/new.md")).unwrap(),
-            "replaced"
+            "patched"
         );
         assert!(fs::read_to_string(root.join("daily/2026-07-12.md"))
             .unwrap()
@@ -286,6 +538,155 @@ mod tests {
         fs::remove_dir_all(root).unwrap();
     }
 
+    #[test]
+    fn resources_expose_unlocked_notes_through_tool_validators() {
+        let root = temp_forge("resources");
+        fs::create_dir_all(root.join("notes/Projetos_日本語")).unwrap();
+        fs::write(root.join("notes/Projetos_日本語/café.md"), "olá").unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "daily body").unwrap();
+        fs::write(root.join("notes/secret.md.locked"), "ciphertext").unwrap();
+        let input = request(1, "initialize", json!({ "protocolVersion": "2025-06-18" }))
+            + &request(2, "resources/list", json!({}))
+            + &request(3, "resources/templates/list", json!({}))
+            + &request(
+                4,
+                "resources/read",
+                json!({ "uri": resources::note_uri("notes/Projetos_日本語/café.md") }),
+            )
+            + &request(
+                5,
+                "resources/read",
+                json!({ "uri": "moldavite://note/notes/secret.md" }),
+            )
+            + &request(
+                6,
+                "resources/read",
+                json!({ "uri": "moldavite://note/notes/../../etc/passwd.md" }),
+            )
+            + &request(
+                7,
+                "resources/read",
+                json!({ "uri": "moldavite://note/notes/missing.md" }),
+            )
+            + &request(
+                8,
+                "resources/subscribe",
+                json!({ "uri": "moldavite://note/notes/secret.md" }),
+            )
+            + &request(
+                9,
+                "resources/subscribe",
+                json!({ "uri": "moldavite://note/daily/2026-07-12.md" }),
+            )
+            + &request(
+                10,
+                "resources/unsubscribe",
+                json!({ "uri": "moldavite://note/daily/2026-07-12.md" }),
+            );
+        let responses = run(input, ToolContext::new(root.clone(), false, false));
+        assert_eq!(responses.len(), 10);
+        assert_eq!(
+            responses[0]["result"]["capabilities"]["resources"]["subscribe"],
+            true
+        );
+
+        let listed: Vec<&str> = responses[1]["result"]["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|resource| resource["uri"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                "moldavite://note/daily/2026-07-12.md".to_string(),
+                resources::note_uri("notes/Projetos_日本語/café.md"),
+            ]
+        );
+        assert_eq!(
+            responses[2]["result"]["resourceTemplates"][0]["uriTemplate"],
+            "moldavite://note/{path}"
+        );
+        assert_eq!(responses[3]["result"]["contents"][0]["text"], "olá");
+        assert!(responses[4]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("locked"));
+        assert!(responses[5]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid note path"));
+        assert_eq!(responses[6]["error"]["code"], -32002);
+        assert!(responses[7]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("locked"));
+        assert_eq!(responses[8]["result"], json!({}));
+        assert_eq!(responses[9]["result"], json!({}));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn prompts_cover_builtin_and_forge_templates_with_date_arguments() {
+        let root = temp_forge("prompts");
+        fs::create_dir_all(root.join("templates")).unwrap();
+        fs::write(
+            root.join("templates/standup.json"),
+            json!({
+                "id": "standup",
+                "name": "Standup",
+                "description": "Daily standup",
+                "icon": "users",
+                "isDefault": false,
+                "content": "# Standup {{date}} {{time}} ({{day_of_week}})"
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let input = request(1, "prompts/list", json!({}))
+            + &request(
+                2,
+                "prompts/get",
+                json!({
+                    "name": "standup",
+                    "arguments": { "date": "2026-07-13", "time": "09:30" }
+                }),
+            )
+            + &request(3, "prompts/get", json!({ "name": "daily-log" }))
+            + &request(
+                4,
+                "prompts/get",
+                json!({ "name": "standup", "arguments": { "date": "2026-02-30" } }),
+            )
+            + &request(5, "prompts/get", json!({ "name": "../../secret" }));
+        let responses = run(input, ToolContext::new(root.clone(), false, false));
+
+        let names: Vec<&str> = responses[0]["result"]["prompts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|prompt| prompt["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["meeting-notes", "daily-log", "project-plan", "standup"]
+        );
+        assert_eq!(
+            responses[1]["result"]["messages"][0]["content"]["text"],
+            "# Standup 2026-07-13 09:30 (Monday)"
+        );
+        let daily_log = responses[2]["result"]["messages"][0]["content"]["text"]
+            .as_str()
+            .unwrap();
+        assert!(!daily_log.contains("{{date}}"));
+        assert!(responses[3]["error"]["message"]
+            .as_str()
+            .unwrap()
+            .contains("YYYY-MM-DD"));
+        assert_eq!(responses[4]["error"]["code"], -32602);
+        fs::remove_dir_all(root).unwrap();
+    }
+
     #[test]
     fn write_gating_omits_and_refuses_write_tools() {
         let root = temp_forge("gating");
@@ -296,7 +697,10 @@ mod tests {
                 json!({"name":"create_note","arguments":{"path":"notes/no.md","content":"x"}}),
             );
         let responses = run(input, ToolContext::new(root.clone(), false, false));
-        assert_eq!(responses[0]["result"]["tools"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            responses[0]["result"]["tools"].as_array().unwrap().len(),
+            10
+        );
         assert!(responses[1]["result"]["content"][0]["text"]
             .as_str()
             .unwrap()
@@ -305,6 +709,620 @@ mod tests {
         fs::remove_dir_all(root).unwrap();
     }
 
+    #[test]
+    fn list_notes_pages_by_cursor_and_filters_by_kind_date_and_properties() {
+        let root = temp_forge("list-filters");
+        fs::create_dir_all(root.join("notes/Work")).unwrap();
+        for day in ["2026-06-30", "2026-07-01", "2026-07-15", "2026-08-01"] {
+            fs::write(root.join(format!("daily/{day}.md")), day).unwrap();
+        }
+        fs::write(root.join("weekly/2026-W28.md"), "week").unwrap();
+        fs::write(
+            root.join("notes/Work/a.md"),
+            "---\nstatus: done\ntags: [q3]\n---\nbody",
+        )
+        .unwrap();
+        fs::write(root.join("notes/b.md"), "---\nstatus: open\n---\n").unwrap();
+        fs::write(root.join("notes/c.md.locked"), "sealed").unwrap();
+        let context = ToolContext::new(root.clone(), false, false);
+        let list =
+            |arguments: Value| context.call("list_notes", &arguments)["structuredContent"].clone();
+        let paths = |listing: &Value| -> Vec<String> {
+            listing["notes"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|note| note["path"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        let mut pages = Vec::new();
+        let mut cursor = Value::Null;
+        loop {
+            let page = list(json!({"limit": 3, "cursor": cursor}));
+            pages.push(paths(&page));
+            cursor = page["nextCursor"].clone();
+            if cursor.is_null() {
+                break;
+            }
+        }
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [3, 3, 2]);
+        assert_eq!(pages.concat(), paths(&list(json!({}))));
+
+        let first = &list(json!({"folder":"notes/Work"}))["notes"][0];
+        assert_eq!(first["kind"], "standalone");
+        assert_eq!(first["size"], 36);
+        assert!(first["modified"].as_str().unwrap().ends_with('Z'));
+        assert_eq!(
+            paths(&list(
+                json!({"dateFrom":"2026-07-01","dateTo":"2026-07-31"})
+            )),
+            ["daily/2026-07-01.md", "daily/2026-07-15.md"]
+        );
+        assert_eq!(
+            paths(&list(json!({"kind":"weekly"}))),
+            ["weekly/2026-W28.md"]
+        );
+        assert_eq!(
+            paths(&list(json!({"property":{"key":"status"}}))),
+            ["notes/Work/a.md", "notes/b.md"]
+        );
+        assert_eq!(
+            paths(&list(json!({"property":{"key":"tags","value":"q3"}}))),
+            ["notes/Work/a.md"]
+        );
+        assert!(paths(&list(json!({"modifiedSince":"2999-01-01T00:00:00Z"}))).is_empty());
+        assert_eq!(
+            context.call("list_notes", &json!({"kind":"monthly"}))["isError"],
+            true
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn search_notes_takes_the_query_language_and_reports_what_does_not_parse() {
+        let root = temp_forge("query");
+        fs::write(
+            root.join("notes/a.md"),
+            "---\nstatus: done\n---\nneedle for #work\n",
+        )
+        .unwrap();
+        fs::write(root.join("notes/b.md"), "needle elsewhere\n").unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "needle today\n").unwrap();
+        let context = ToolContext::new(root.clone(), false, false);
+        let search = |query: &str| context.call("search_notes", &json!({ "query": query }));
+        let paths = |query: &str| -> Vec<String> {
+            let response = search(query);
+            assert_eq!(response["structuredContent"]["mode"], "keyword");
+            response["structuredContent"]["results"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|hit| hit["path"].as_str().unwrap().to_string())
+                .collect()
+        };
+
+        assert_eq!(paths("needle tag:work"), ["notes/a.md"]);
+        assert_eq!(paths("needle NOT is:standalone"), ["daily/2026-07-12.md"]);
+        assert_eq!(paths("prop:status=done OR elsewhere").len(), 2);
+        // The tool ranks, scores, and breaks ties exactly as the app does.
+        let in_app = crate::commands::search::search_notes_content_in(
+            &root,
+            &root.join(".trash"),
+            "needle",
+            20,
+            crate::types::SearchOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            search("needle")["structuredContent"]["results"],
+            json!(in_app)
+        );
+        let broken = search("needle OR");
+        assert_eq!(broken["isError"], true);
+        assert!(broken["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("OR needs a term on both sides"));
+        // Without a semantic index, hybrid search is keyword search and says so.
+        let hybrid = context.call(
+            "search_notes",
+            &json!({ "query": "needle", "mode": "hybrid" }),
+        );
+        assert_eq!(hybrid["structuredContent"]["mode"], "keyword");
+        assert_eq!(hybrid["structuredContent"]["results"], json!(in_app));
+        let unknown = context.call(
+            "search_notes",
+            &json!({ "query": "needle", "mode": "fuzzy" }),
+        );
+        assert_eq!(unknown["isError"], true);
+        // Matching options loosen keyword search the way the app's do.
+        let loose = context.call(
+            "search_notes",
+            &json!({ "query": "neeedle", "fuzzy": true }),
+        );
+        assert_eq!(loose["structuredContent"]["mode"], "keyword");
+        assert_eq!(
+            loose["structuredContent"]["results"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(paths("neeedle").is_empty());
+        let bad = context.call(
+            "search_notes",
+            &json!({ "query": "needle", "foldDiacritics": "yes" }),
+        );
+        assert_eq!(bad["isError"], true);
+        // A whole-word pattern: "elsewhere" doesn't end at "else".
+        let pattern = context.call(
+            "search_notes",
+            &json!({ "query": "need\\w+ (for|else)", "regex": true, "wholeWord": true }),
+        );
+        let found = &pattern["structuredContent"]["results"];
+        assert_eq!(found.as_array().unwrap().len(), 1);
+        assert_eq!(found[0]["spans"], json!([[0, 10]]));
+        let invalid = context.call("search_notes", &json!({ "query": "(", "regex": true }));
+        assert_eq!(invalid["isError"], true);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn run_saved_search_answers_as_search_notes_with_the_saved_settings() {
+        let root = temp_forge("saved-search");
+        fs::write(root.join("notes/a.md"), "TODO ship #incident\n").unwrap();
+        fs::write(root.join("notes/b.md"), "TODOs elsewhere\n").unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "TODO today\n").unwrap();
+        let dir = crate::commands::saved_searches::searches_dir(&root).unwrap();
+        crate::commands::saved_searches::create_saved_search_in(
+            &dir,
+            crate::types::SaveSearchInput {
+                name: "Open TODOs".to_string(),
+                query: "TODO NOT is:daily".to_string(),
+                mode: crate::types::SearchMode::Keyword,
+                options: crate::types::SearchOptions {
+                    whole_word: true,
+                    ..Default::default()
+                },
+            },
+        )
+        .unwrap();
+        let context = ToolContext::new(root.clone(), false, false);
+
+        let saved = context.call("run_saved_search", &json!({ "name": "open todos" }));
+        let direct = context.call(
+            "search_notes",
+            &json!({ "query": "TODO NOT is:daily", "wholeWord": true }),
+        );
+        assert_eq!(
+            saved["structuredContent"]["results"],
+            direct["structuredContent"]["results"]
+        );
+        assert_eq!(
+            saved["structuredContent"]["results"][0]["path"],
+            "notes/a.md"
+        );
+        assert_eq!(
+            saved["structuredContent"]["results"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            saved["structuredContent"]["savedSearch"]["id"],
+            "open-todos"
+        );
+        let missing = context.call("run_saved_search", &json!({ "name": "Incidents" }));
+        assert_eq!(missing["isError"], true);
+        assert!(missing["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Saved searches: Open TODOs"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn periodic_notes_resolve_by_date_or_week_and_templates_expand_for_them() {
+        let root = temp_forge("periodic");
+        fs::write(root.join("daily/2026-07-12.md"), "sunday").unwrap();
+        fs::write(root.join("weekly/2026-W28.md.locked"), "sealed").unwrap();
+        fs::create_dir_all(root.join("templates")).unwrap();
+        fs::write(
+            root.join("templates/standup.json"),
+            json!({"id":"standup","name":"Standup","description":"","icon":"users","isDefault":false,"content":"# {{day_of_week}} {{date}}\n"}).to_string(),
+        )
+        .unwrap();
+        let context = ToolContext::new(root.clone(), true, false);
+        let call = |name: &str, arguments: Value| context.call(name, &arguments);
+
+        let daily = call("get_daily_note", json!({"date":"2026-07-12"}));
+        assert_eq!(daily["structuredContent"]["exists"], true);
+        assert_eq!(daily["structuredContent"]["content"], "sunday");
+        assert_eq!(
+            call("get_daily_note", json!({"date":"2026-07-13"}))["structuredContent"],
+            json!({"path":"daily/2026-07-13.md","exists":false,"content":null,"contentHash":null})
+        );
+        // 2026-07-12 is a Sunday, so it belongs to ISO week 28.
+        let locked = call("get_weekly_note", json!({"date":"2026-07-12"}));
+        assert_eq!(locked["isError"], true);
+        assert!(locked["structuredContent"]["error"]
+            .as_str()
+            .unwrap()
+            .contains("locked"));
+        assert_eq!(
+            call("get_weekly_note", json!({"week":"2026-W29"}))["structuredContent"]["path"],
+            "weekly/2026-W29.md"
+        );
+        for invalid in [
+            json!({"week":"2026-W54"}),
+            json!({"week":"2026-29"}),
+            json!({"week":"2026-W29","date":"2026-07-12"}),
+        ] {
+            assert_eq!(call("get_weekly_note", invalid)["isError"], true);
+        }
+
+        let weekly = call(
+            "create_note_from_template",
+            json!({"template":"standup","path":"weekly/2026-W29.md"}),
+        );
+        assert_eq!(weekly["isError"], false, "{weekly}");
+        assert_eq!(
+            fs::read_to_string(root.join("weekly/2026-W29.md")).unwrap(),
+            "# Monday 2026-07-13\n"
+        );
+        let daily = call(
+            "create_note_from_template",
+            json!({"template":"daily-log","date":"2026-07-14"}),
+        );
+        assert_eq!(daily["structuredContent"]["path"], "daily/2026-07-14.md");
+        assert!(fs::read_to_string(root.join("daily/2026-07-14.md"))
+            .unwrap()
+            .contains("2026-07-14"));
+        for refused in [
+            json!({"template":"standup","path":"daily/2026-07-12.md"}),
+            json!({"template":"missing","path":"notes/new.md"}),
+            json!({"template":"standup","path":"notes/new.md","forge":"Other"}),
+        ] {
+            assert_eq!(call("create_note_from_template", refused)["isError"], true);
+        }
+        assert_eq!(
+            fs::read_to_string(root.join("daily/2026-07-12.md")).unwrap(),
+            "sunday"
+        );
+        assert!(!root.join("notes/new.md").exists());
+
+        let gated = ToolContext::new(root.clone(), false, false).call(
+            "create_note_from_template",
+            &json!({"template":"standup","path":"notes/gated.md"}),
+        );
+        assert_eq!(gated["isError"], true);
+        assert!(!root.join("notes/gated.md").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    fn policies(entries: Value) -> Result<BTreeMap<String, McpClientPolicy>, String> {
+        Ok(serde_json::from_value(entries).unwrap())
+    }
+
+    fn initialize_as(name: &str) -> String {
+        request(
+            1,
+            "initialize",
+            json!({ "protocolVersion": "2025-06-18", "clientInfo": {"name": name, "version": "1"} }),
+        )
+    }
+
+    fn tool_names(response: &Value) -> Vec<String> {
+        response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn client_policies_filter_listed_tools_and_refuse_disallowed_calls() {
+        let root = temp_forge("policy-tools");
+        fs::write(root.join("notes/a.md"), "needle").unwrap();
+        let context = ToolContext::new(root.clone(), true, false).with_policies(
+            policies(json!({
+                "chat": {"allowedTools": ["read_note", "list_notes", "create_note"]},
+                "*": {"allowedTools": ["list_notes"]}
+            })),
+            Arc::new(|name| Err(format!("no {name}"))),
+        );
+        let calls = initialize_as("chat")
+            + &request(2, "tools/list", json!({}))
+            + &request(
+                3,
+                "tools/call",
+                json!({"name":"search_notes","arguments":{"query":"needle"}}),
+            )
+            + &request(
+                4,
+                "tools/call",
+                json!({"name":"create_note","arguments":{"path":"notes/b.md","content":"ok"}}),
+            );
+        let responses = run(calls, context.for_session());
+        assert_eq!(
+            tool_names(&responses[1]),
+            ["read_note", "list_notes", "create_note"]
+        );
+        assert_eq!(responses[2]["result"]["isError"], true);
+        assert!(responses[2]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("not allowed for this client"));
+        assert_eq!(responses[3]["result"]["isError"], false);
+        assert!(root.join("notes/b.md").is_file());
+
+        // Unknown and unnamed clients fall back to the "*" policy.
+        for client in [initialize_as("renamed"), String::new()] {
+            let responses = run(
+                client + &request(2, "tools/list", json!({})),
+                context.for_session(),
+            );
+            assert_eq!(tool_names(responses.last().unwrap()), ["list_notes"]);
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn client_folder_policies_bound_reads_writes_and_link_rewrites() {
+        let root = temp_forge("policy-folders");
+        for folder in ["Inbox", "Work", "Private"] {
+            fs::create_dir_all(root.join("notes").join(folder)).unwrap();
+        }
+        fs::write(root.join("notes/Inbox/todo.md"), "needle [[plan]]").unwrap();
+        fs::write(root.join("notes/Work/plan.md"), "needle").unwrap();
+        fs::write(root.join("notes/Private/diary.md"), "needle [[todo]]").unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "needle").unwrap();
+        let context = ToolContext::new(root.clone(), true, false).with_policies(
+            policies(json!({
+                "agent": {
+                    "readAllow": ["notes/**"],
+                    "readDeny": ["notes/Private"],
+                    "writeAllow": ["notes/Inbox"]
+                }
+            })),
+            Arc::new(|name| Err(format!("no {name}"))),
+        );
+        let calls = [
+            ("list_notes", json!({})),
+            ("search_notes", json!({"query":"needle"})),
+            ("read_note", json!({"path":"notes/Private/diary.md"})),
+            ("read_note", json!({"path":"daily/2026-07-12.md"})),
+            ("get_backlinks", json!({"path":"notes/Inbox/todo.md"})),
+            (
+                "create_note",
+                json!({"path":"notes/Inbox/new.md","content":"ok"}),
+            ),
+            (
+                "create_note",
+                json!({"path":"notes/Work/new.md","content":"no"}),
+            ),
+            ("append_to_daily_note", json!({"content":"no"})),
+            (
+                "move_note",
+                json!({"path":"notes/Inbox/new.md","folder":"notes/Work"}),
+            ),
+            // diary.md links to todo.md but is not writable by this client.
+            (
+                "rename_note",
+                json!({"path":"notes/Inbox/todo.md","newName":"tasks"}),
+            ),
+            // Case-insensitive file systems open these as the denied folder.
+            ("read_note", json!({"path":"notes/private/diary.md"})),
+            ("get_properties", json!({"path":"notes/PRIVATE/diary.md"})),
+        ];
+        let mut input = initialize_as("agent");
+        for (index, (name, arguments)) in calls.into_iter().enumerate() {
+            input.push_str(&request(
+                index as u32 + 2,
+                "tools/call",
+                json!({"name":name,"arguments":arguments}),
+            ));
+        }
+        input.push_str(&request(20, "resources/list", json!({})));
+        let responses = run(input, context);
+        let result = |index: usize| &responses[index + 1]["result"];
+
+        let listed: Vec<&str> = result(0)["structuredContent"]["notes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|note| note["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(listed, ["notes/Inbox/todo.md", "notes/Work/plan.md"]);
+        let mut found: Vec<&str> = result(1)["structuredContent"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["path"].as_str().unwrap())
+            .collect();
+        found.sort();
+        assert_eq!(found, ["notes/Inbox/todo.md", "notes/Work/plan.md"]);
+        for refused in [2, 3, 6, 7, 8, 9, 10, 11] {
+            assert_eq!(result(refused)["isError"], true, "call {refused}");
+        }
+        for respelled in [10, 11] {
+            assert!(result(respelled)["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("outside the folders this client may read"));
+        }
+        assert_eq!(
+            result(4)["structuredContent"]["backlinks"],
+            json!([]),
+            "backlinks from unreadable notes stay hidden"
+        );
+        assert_eq!(result(5)["isError"], false);
+        assert!(root.join("notes/Inbox/new.md").is_file());
+        assert!(!root.join("notes/Work/new.md").exists());
+        assert!(root.join("notes/Inbox/todo.md").is_file());
+        assert_eq!(
+            fs::read_to_string(root.join("notes/Private/diary.md")).unwrap(),
+            "needle [[todo]]"
+        );
+        let resources = responses.last().unwrap()["result"]["resources"]
+            .as_array()
+            .unwrap()
+            .len();
+        assert_eq!(resources, 3);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn pinned_client_reads_its_forge_only_and_never_writes() {
+        let active = temp_forge("policy-active");
+        let archive = temp_forge("policy-archive");
+        fs::write(active.join("notes/live.md"), "live").unwrap();
+        fs::write(archive.join("notes/old.md"), "old").unwrap();
+        let archive_root = archive.clone();
+        let context = ToolContext::new(active.clone(), true, false).with_policies(
+            policies(json!({ "reader": {"pinnedForge": "Archive"} })),
+            Arc::new(move |name| match name {
+                "Archive" => Ok(archive_root.clone()),
+                _ => Err(format!("Forge '{name}' does not exist")),
+            }),
+        );
+        let input = initialize_as("reader")
+            + &request(2, "tools/list", json!({}))
+            + &request(3, "tools/call", json!({"name":"list_notes","arguments":{}}))
+            + &request(
+                4,
+                "tools/call",
+                json!({"name":"create_note","arguments":{"path":"notes/new.md","content":"x"}}),
+            );
+        let responses = run(input, context);
+        assert_eq!(tool_names(&responses[1]).len(), 10);
+        let notes = &responses[2]["result"]["structuredContent"]["notes"];
+        assert_eq!(notes.as_array().unwrap().len(), 1);
+        assert_eq!(notes[0]["path"], "notes/old.md");
+        assert!(responses[3]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("pinned read-only to Forge 'Archive'"));
+        assert!(!archive.join("notes/new.md").exists());
+        assert!(!active.join("notes/new.md").exists());
+        fs::remove_dir_all(active).unwrap();
+        fs::remove_dir_all(archive).unwrap();
+    }
+
+    #[test]
+    fn read_tools_reach_other_forges_and_search_merges_across_them() {
+        let work = temp_forge("forges-work");
+        let personal = temp_forge("forges-personal");
+        fs::write(work.join("notes/plan.md"), "needle").unwrap();
+        fs::write(personal.join("notes/trip.md"), "needle needle").unwrap();
+        fs::write(personal.join("notes/secret.md.locked"), "needle").unwrap();
+        let forges = vec![("Work", work.clone()), ("Personal", personal.clone())];
+        let context = ToolContext::new(work.clone(), true, false).with_forges(forges.clone());
+        let structured = |name: &str, arguments: Value| {
+            let result = context.call(name, &arguments);
+            assert_eq!(result["isError"], false, "{name}: {result}");
+            result["structuredContent"].clone()
+        };
+
+        assert_eq!(
+            structured("list_forges", json!({}))["forges"],
+            json!([
+                {"name": "Work", "isDefault": true},
+                {"name": "Personal", "isDefault": false}
+            ])
+        );
+        assert_eq!(
+            structured(
+                "read_note",
+                json!({"path":"notes/trip.md","forge":"Personal"})
+            )["content"],
+            "needle needle"
+        );
+        assert_eq!(
+            structured("list_notes", json!({"forge":"Personal","folder":"notes"}))["notes"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        let results = structured("search_all_forges", json!({"query":"needle"}))["results"].clone();
+        let tagged: Vec<(&str, &str)> = results
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| {
+                (
+                    hit["forge"].as_str().unwrap(),
+                    hit["path"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            tagged,
+            [("Personal", "notes/trip.md"), ("Work", "notes/plan.md")]
+        );
+
+        for (name, arguments) in [
+            (
+                "read_note",
+                json!({"path":"notes/trip.md","forge":"Elsewhere"}),
+            ),
+            (
+                "create_note",
+                json!({"path":"notes/x.md","content":"x","forge":"Personal"}),
+            ),
+        ] {
+            assert_eq!(context.call(name, &arguments)["isError"], true, "{name}");
+        }
+        assert!(!personal.join("notes/x.md").exists());
+
+        // A pinned client sees only its Forge, even through search_all_forges.
+        let pinned = ToolContext::new(work.clone(), true, false)
+            .with_policies(
+                policies(json!({ "*": {"pinnedForge": "Personal"} })),
+                Arc::new(|name| Err(format!("no {name}"))),
+            )
+            .with_forges(forges);
+        let forges = pinned.call("list_forges", &json!({}));
+        assert_eq!(
+            forges["structuredContent"]["forges"],
+            json!([{"name": "Personal", "isDefault": true}])
+        );
+        let refused = pinned.call("read_note", &json!({"path":"notes/plan.md","forge":"Work"}));
+        assert_eq!(refused["isError"], true);
+        let hits = pinned.call("search_all_forges", &json!({"query":"needle"}));
+        assert_eq!(
+            hits["structuredContent"]["results"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        fs::remove_dir_all(work).unwrap();
+        fs::remove_dir_all(personal).unwrap();
+    }
+
+    #[test]
+    fn unreadable_policies_fail_closed() {
+        let root = temp_forge("policy-broken");
+        fs::write(root.join("notes/a.md"), "a").unwrap();
+        let context = ToolContext::new(root.clone(), true, false).with_policies(
+            Err("Invalid MCP client policies in config".to_string()),
+            Arc::new(|name| Err(format!("no {name}"))),
+        );
+        assert!(context.tool_definitions().is_empty());
+        let read = context.call("read_note", &json!({"path":"notes/a.md"}));
+        assert_eq!(read["isError"], true);
+        assert!(read["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid MCP client policies"));
+        fs::remove_dir_all(root).unwrap();
+    }
+
     #[test]
     fn write_note_preserves_existing_frontmatter() {
         let root = temp_forge("frontmatter-write");
@@ -314,71 +1332,490 @@ mod tests {
         )
         .unwrap();
 
-        let response = ToolContext::new(root.clone(), true, false).call(
+        let response = ToolContext::new(root.clone(), true, false).call(
+            "write_note",
+            &json!({"path":"notes/colored.md","content":"new body"}),
+        );
+        assert_eq!(response["isError"], false);
+
+        let raw = fs::read_to_string(root.join("notes/colored.md")).unwrap();
+        let parsed = crate::frontmatter::parse_note(&raw);
+        assert_eq!(parsed.color.as_deref(), Some("blue"));
+        assert!(parsed.extra.contains_key("tags"));
+        assert_eq!(parsed.body, "new body");
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn initialized_client_is_recorded_with_the_mcp_write_body_hash() {
+        let root = temp_forge("agent-write-marker");
+        let spool = root.with_extension("agent-writes");
+        fs::write(root.join("notes/marked.md"), "old body").unwrap();
+        let context = ToolContext::new(root.clone(), true, false)
+            .with_agent_write_spool(spool.clone());
+        let input = request(
+            1,
+            "initialize",
+            json!({
+                "protocolVersion": "2025-06-18",
+                "clientInfo": {"name": "Claude Code", "version": "1"}
+            }),
+        ) + &request(
+            2,
+            "tools/call",
+            json!({
+                "name": "write_note",
+                "arguments": {
+                    "path": "notes/marked.md",
+                    "content": "---\ntags: [agent]\n---\nagent body"
+                }
+            }),
+        );
+
+        let responses = run(input, context);
+
+        assert_eq!(responses[1]["result"]["isError"], false);
+        let info = crate::agent_writes::take_agent_write_from(
+            &spool,
+            &root,
+            "notes/marked.md",
+            &crate::commands::notes::sha256_hex("agent body"),
+        )
+        .expect("MCP write should leave a matching attribution marker");
+        assert_eq!(info.client.as_deref(), Some("Claude Code"));
+        assert!(crate::agent_writes::take_agent_write_from(
+            &spool,
+            &root,
+            "notes/marked.md",
+            &crate::commands::notes::sha256_hex("agent body")
+        )
+        .is_none());
+
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(spool).unwrap();
+    }
+
+    #[test]
+    fn dry_runs_report_diffs_and_hashes_without_writing_or_marking() {
+        let root = temp_forge("dry-run");
+        let spool = root.with_extension("agent-writes");
+        let original = "---\nstatus: open\n---\nold body\n";
+        fs::write(root.join("notes/keep.md"), original).unwrap();
+        fs::write(root.join("notes/sealed.md.locked"), "sealed").unwrap();
+        let context =
+            ToolContext::new(root.clone(), true, false).with_agent_write_spool(spool.clone());
+        let call = |name: &str, arguments: Value| context.call(name, &arguments);
+        let sha = crate::commands::notes::sha256_hex;
+
+        let preview = call(
+            "write_note",
+            json!({"path":"notes/keep.md","content":"new body\n","baseHash":sha("old body\n"),"dryRun":true}),
+        )["structuredContent"]
+            .clone();
+        assert_eq!(preview["dryRun"], true);
+        assert_eq!(preview["conflict"], false);
+        assert_eq!(preview["contentHash"], sha("new body\n"));
+        assert_eq!(
+            preview["diff"],
+            "--- a/notes/keep.md\n+++ b/notes/keep.md\n@@ -1,4 +1,4 @@\n ---\n status: open\n ---\n-old body\n+new body\n"
+        );
+        let stale = call(
+            "write_note",
+            json!({"path":"notes/keep.md","content":"new body\n","baseHash":"stale","dryRun":true}),
+        );
+        assert_eq!(stale["structuredContent"]["conflict"], true);
+
+        let created = call(
+            "create_note",
+            json!({"path":"notes/Sub/new.md","content":"fresh\n","dryRun":true}),
+        );
+        assert_eq!(created["structuredContent"]["created"], true);
+        assert!(created["structuredContent"]["diff"]
+            .as_str()
+            .unwrap()
+            .contains("@@ -0,0 +1,1 @@\n+fresh\n"));
+        let appended = call(
+            "append_to_daily_note",
+            json!({"content":"- item","date":"2026-07-12","dryRun":true}),
+        );
+        assert_eq!(appended["structuredContent"]["path"], "daily/2026-07-12.md");
+        assert_eq!(appended["structuredContent"]["created"], true);
+        let templated = call(
+            "create_note_from_template",
+            json!({"template":"meeting-notes","path":"notes/meeting.md","dryRun":true}),
+        );
+        assert_eq!(templated["structuredContent"]["template"], "meeting-notes");
+
+        // Dry runs refuse exactly what the real write would refuse.
+        for (name, arguments) in [
+            (
+                "create_note",
+                json!({"path":"notes/keep.md","content":"x","dryRun":true}),
+            ),
+            (
+                "write_note",
+                json!({"path":"notes/sealed.md","content":"x","dryRun":true}),
+            ),
+            (
+                "write_note",
+                json!({"path":"notes/keep.md","content":"x","dryRun":"yes"}),
+            ),
+        ] {
+            assert_eq!(call(name, arguments)["isError"], true, "{name}");
+        }
+
+        assert_eq!(
+            fs::read_to_string(root.join("notes/keep.md")).unwrap(),
+            original
+        );
+        assert!(!root.join("notes/Sub").exists());
+        assert!(!root.join("notes/meeting.md").exists());
+        assert!(!root.join("daily/2026-07-12.md").exists());
+        assert_eq!(fs::read_dir(root.join("notes")).unwrap().count(), 2);
+        assert!(
+            !spool.exists(),
+            "dry runs must not leave agent-write markers"
+        );
+
+        let written = call(
+            "write_note",
+            json!({"path":"notes/keep.md","content":"new body\n"}),
+        );
+        assert_eq!(written["isError"], false);
+        assert_eq!(
+            call("read_note", json!({"path":"notes/keep.md"}))["structuredContent"]["contentHash"],
+            preview["contentHash"]
+        );
+        fs::remove_dir_all(root).unwrap();
+        let _ = fs::remove_dir_all(spool);
+    }
+
+    #[test]
+    fn propose_mode_holds_writes_until_the_user_accepts_them() {
+        let root = temp_forge("propose");
+        let spool = root.with_extension("proposals");
+        let plan = root.join("notes/plan.md");
+        fs::write(&plan, "draft\n").unwrap();
+        let context =
+            ToolContext::with_write_gate(root.clone(), Arc::new(|| McpWriteMode::Propose))
+                .with_proposal_spool(spool.clone());
+        context.set_client_name(Some("Claude Code"));
+        let names: Vec<Value> = context
+            .tool_definitions()
+            .iter()
+            .map(|tool| tool["name"].clone())
+            .collect();
+        assert_eq!(names.len(), 14);
+        assert!(names.contains(&json!("write_note")));
+        assert!(!names.contains(&json!("patch_note")));
+
+        let proposed = context.call(
+            "write_note",
+            &json!({"path":"notes/plan.md","content":"rewritten\n"}),
+        );
+        let result = &proposed["structuredContent"];
+        assert_eq!(result["proposed"], true, "{result}");
+        assert!(result["message"]
+            .as_str()
+            .unwrap()
+            .contains("awaiting review"));
+        assert!(result["diff"].as_str().unwrap().contains("+rewritten"));
+        assert!(result.get("dryRun").is_none());
+        let create = context.call(
+            "create_note",
+            &json!({"path":"notes/Ideas/new.md","content":"idea"}),
+        );
+        assert_eq!(create["structuredContent"]["proposed"], true);
+        let append = context.call(
+            "append_to_daily_note",
+            &json!({"date":"2026-07-12","content":"- standup"}),
+        );
+        assert_eq!(append["structuredContent"]["created"], true);
+        let preview = context.call(
+            "create_note",
+            &json!({"path":"notes/preview.md","content":"x","dryRun":true}),
+        );
+        assert_eq!(preview["structuredContent"]["dryRun"], true);
+        let patch = context.call(
+            "patch_note",
+            &json!({"path":"notes/plan.md","operation":"findReplace","find":"draft","replace":"x"}),
+        );
+        assert!(patch["structuredContent"]["error"]
+            .as_str()
+            .unwrap()
+            .contains("need approval"));
+        assert_eq!(fs::read_to_string(&plan).unwrap(), "draft\n");
+        assert!(!root.join("notes/Ideas").exists());
+        assert!(!root.join("daily/2026-07-12.md").exists());
+
+        let proposals = crate::agent_proposals::list_from(&spool, &root);
+        let tools: Vec<&str> = proposals.iter().map(|p| p.tool.as_str()).collect();
+        assert_eq!(tools, ["write_note", "create_note", "append_to_daily_note"]);
+        assert_eq!(proposals[0].client.as_deref(), Some("Claude Code"));
+        assert!(crate::agent_proposals::list_from(&spool, &root.join("notes")).is_empty());
+        let replay = |forge: &Path, tool: &str, arguments: &Value, client: Option<&str>| {
+            let review = ToolContext::new(forge.to_path_buf(), true, false);
+            review.set_client_name(client);
+            review.run(tool, arguments, &CallControl::none())
+        };
+        let ids: Vec<&str> = proposals.iter().map(|p| p.id.as_str()).collect();
+
+        let diff = crate::agent_proposals::diff_at(&spool, &root, ids[0], replay).unwrap();
+        assert!(diff.diff.contains("-draft") && diff.diff.contains("+rewritten"));
+        assert_eq!(diff.content_hash, result["contentHash"]);
+        assert!(!diff.conflict);
+        // A human edit after the proposal is kept as a conflict copy on accept.
+        fs::write(&plan, "human edit\n").unwrap();
+        assert!(
+            crate::agent_proposals::diff_at(&spool, &root, ids[0], replay)
+                .unwrap()
+                .conflict
+        );
+        let accepted = crate::agent_proposals::accept_at(&spool, &root, ids[0], replay).unwrap();
+        assert_eq!(fs::read_to_string(&plan).unwrap(), "rewritten\n");
+        let copy = accepted
+            .conflict_copy
+            .expect("stale proposal keeps a conflict copy");
+        assert_eq!(
+            fs::read_to_string(root.join("notes").join(copy)).unwrap(),
+            "human edit\n"
+        );
+
+        // A failed accept leaves the proposal pending.
+        fs::create_dir_all(root.join("notes/Ideas")).unwrap();
+        fs::write(root.join("notes/Ideas/new.md"), "mine").unwrap();
+        let clash = crate::agent_proposals::accept_at(&spool, &root, ids[1], replay).unwrap_err();
+        assert!(clash.contains("already exists"), "{clash}");
+        assert_eq!(crate::agent_proposals::list_from(&spool, &root).len(), 2);
+        crate::agent_proposals::reject_at(&spool, &root, ids[1]).unwrap();
+
+        crate::agent_proposals::accept_at(&spool, &root, ids[2], replay).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("daily/2026-07-12.md")).unwrap(),
+            "- standup"
+        );
+        assert!(crate::agent_proposals::accept_at(&spool, &root, ids[2], replay).is_err());
+        assert!(crate::agent_proposals::list_from(&spool, &root).is_empty());
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(spool).unwrap();
+    }
+
+    #[test]
+    fn each_session_can_undo_its_own_writes() {
+        let root = temp_forge("snapshots");
+        let spool = root.with_extension("snapshots");
+        let plan = root.join("notes/plan.md");
+        fs::write(&plan, "---\ncolor: red\n---\ndraft\n").unwrap();
+        let base = ToolContext::new(root.clone(), true, false).with_snapshot_spool(spool.clone());
+        let agent = base.for_session();
+        agent.set_client_name(Some("Agent A"));
+        let other = base.for_session();
+        other.set_client_name(Some("Agent B"));
+
+        let write = agent.call(
             "write_note",
-            &json!({"path":"notes/colored.md","content":"new body"}),
+            &json!({"path":"notes/plan.md","content":"rewritten\n"}),
+        );
+        assert!(write["structuredContent"].get("error").is_none(), "{write}");
+        let properties = agent.call(
+            "set_properties",
+            &json!({"path":"notes/plan.md","properties":{"status":"done"}}),
+        );
+        assert!(
+            properties["structuredContent"].get("error").is_none(),
+            "{properties}"
+        );
+        agent.call(
+            "create_note",
+            &json!({"path":"notes/new.md","content":"idea"}),
+        );
+        let failed = agent.call(
+            "create_note",
+            &json!({"path":"notes/new.md","content":"again"}),
+        );
+        assert!(failed["structuredContent"]["error"].is_string());
+        other.call(
+            "append_to_daily_note",
+            &json!({"date":"2026-07-12","content":"- standup"}),
         );
-        assert_eq!(response["isError"], false);
 
-        let raw = fs::read_to_string(root.join("notes/colored.md")).unwrap();
-        let parsed = crate::frontmatter::parse_note(&raw);
-        assert_eq!(parsed.color.as_deref(), Some("blue"));
-        assert!(parsed.extra.contains_key("tags"));
-        assert_eq!(parsed.body, "new body");
+        let sessions = crate::agent_snapshots::list_from(&spool, &root);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].client.as_deref(), Some("Agent B"));
+        let mine = &sessions[1];
+        assert_eq!(mine.client.as_deref(), Some("Agent A"));
+        let changes: Vec<(&str, usize, bool)> = mine
+            .changes
+            .iter()
+            .map(|change| (change.rel_path.as_str(), change.writes, change.created))
+            .collect();
+        assert_eq!(
+            changes,
+            [("notes/new.md", 1, true), ("notes/plan.md", 2, false)]
+        );
 
+        let restored =
+            crate::agent_snapshots::restore_at(&spool, &root, &mine.session_id, None).unwrap();
+        assert!(restored
+            .iter()
+            .all(|note| note.error.is_none() && note.conflict_copy.is_none()));
+        assert_eq!(
+            fs::read_to_string(&plan).unwrap(),
+            "---\ncolor: red\n---\ndraft\n"
+        );
+        assert!(!root.join("notes/new.md").exists());
+        assert!(root.join("daily/2026-07-12.md").exists());
+        assert_eq!(crate::agent_snapshots::list_from(&spool, &root).len(), 1);
         fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(spool).unwrap();
     }
 
     #[test]
-    fn initialized_client_is_recorded_with_the_mcp_write_body_hash() {
-        let root = temp_forge("agent-write-marker");
-        let spool = root.with_extension("agent-writes");
-        fs::write(root.join("notes/marked.md"), "old body").unwrap();
-        let context = ToolContext::new(root.clone(), true, false)
-            .with_agent_write_spool(spool.clone());
-        let input = request(
-            1,
-            "initialize",
-            json!({
-                "protocolVersion": "2025-06-18",
-                "clientInfo": {"name": "Claude Code", "version": "1"}
-            }),
-        ) + &request(
-            2,
-            "tools/call",
-            json!({
-                "name": "write_note",
-                "arguments": {
-                    "path": "notes/marked.md",
-                    "content": "---\ntags: [agent]\n---\nagent body"
-                }
-            }),
-        );
+    fn restoring_a_session_moves_renamed_notes_back_and_restores_their_links() {
+        let root = temp_forge("snapshot-renames");
+        let spool = root.with_extension("snapshots");
+        fs::create_dir_all(root.join("notes/Work")).unwrap();
+        fs::write(root.join("notes/plan.md"), "draft [[plan]]\n").unwrap();
+        fs::write(root.join("notes/index.md"), "see [[plan]]\n").unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "[[plan]] today\n").unwrap();
+        let agent = ToolContext::new(root.clone(), true, false)
+            .with_snapshot_spool(spool.clone())
+            .for_session();
+        agent.set_client_name(Some("Agent"));
 
-        let responses = run(input, context);
+        for (name, arguments) in [
+            (
+                "write_note",
+                json!({"path":"notes/plan.md","content":"rewritten [[plan]]\n"}),
+            ),
+            (
+                "rename_note",
+                json!({"path":"notes/plan.md","newName":"roadmap"}),
+            ),
+            (
+                "move_note",
+                json!({"path":"notes/roadmap.md","folder":"notes/Work"}),
+            ),
+        ] {
+            let result = agent.call(name, &arguments);
+            assert!(result["structuredContent"].get("error").is_none(), "{result}");
+        }
+        assert_eq!(
+            fs::read_to_string(root.join("notes/index.md")).unwrap(),
+            "see [[roadmap]]\n"
+        );
 
-        assert_eq!(responses[1]["result"]["isError"], false);
-        let info = crate::agent_writes::take_agent_write_from(
-            &spool,
-            &root,
-            "notes/marked.md",
-            &crate::commands::notes::sha256_hex("agent body"),
-        )
-        .expect("MCP write should leave a matching attribution marker");
-        assert_eq!(info.client.as_deref(), Some("Claude Code"));
-        assert!(crate::agent_writes::take_agent_write_from(
-            &spool,
-            &root,
-            "notes/marked.md",
-            &crate::commands::notes::sha256_hex("agent body")
-        )
-        .is_none());
+        let sessions = crate::agent_snapshots::list_from(&spool, &root);
+        let changes: Vec<(&str, usize, Option<&str>)> = sessions[0]
+            .changes
+            .iter()
+            .map(|change| {
+                (
+                    change.rel_path.as_str(),
+                    change.writes,
+                    change.renamed_from.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("daily/2026-07-12.md", 1, None),
+                ("notes/Work/roadmap.md", 2, Some("notes/plan.md")),
+                ("notes/index.md", 1, None),
+            ]
+        );
 
+        let restored =
+            crate::agent_snapshots::restore_at(&spool, &root, &sessions[0].session_id, None)
+                .unwrap();
+        assert!(restored
+            .iter()
+            .all(|note| note.error.is_none() && note.conflict_copy.is_none()));
+        assert_eq!(restored[1].moved_back_to.as_deref(), Some("notes/plan.md"));
+        assert!(!root.join("notes/Work/roadmap.md").exists());
+        for (rel, raw) in [
+            ("notes/plan.md", "draft [[plan]]\n"),
+            ("notes/index.md", "see [[plan]]\n"),
+            ("daily/2026-07-12.md", "[[plan]] today\n"),
+        ] {
+            assert_eq!(fs::read_to_string(root.join(rel)).unwrap(), raw, "{rel}");
+        }
+        assert!(crate::agent_snapshots::list_from(&spool, &root).is_empty());
         fs::remove_dir_all(root).unwrap();
         fs::remove_dir_all(spool).unwrap();
     }
 
     #[cfg(unix)]
+    #[test]
+    fn every_tool_call_is_audited_with_client_paths_and_hashes() {
+        let root = temp_forge("audit");
+        let log = root.with_extension("audit.jsonl");
+        fs::write(root.join("notes/a.md"), "old").unwrap();
+        let context = ToolContext::new(root.clone(), true, false).with_audit_log(log.clone());
+        let calls = [
+            ("read_note", json!({"path":"notes/a.md"})),
+            ("write_note", json!({"path":"notes/a.md","content":"new"})),
+            ("rename_note", json!({"path":"notes/a.md","newName":"b"})),
+            ("read_note", json!({"path":"../../etc/passwd"})),
+            ("list_notes", json!({})),
+            ("no_such_tool", json!({})),
+        ];
+        let mut input = initialize_as("Audited Agent");
+        for (index, (name, arguments)) in calls.into_iter().enumerate() {
+            input.push_str(&request(
+                index as u32 + 2,
+                "tools/call",
+                json!({"name":name,"arguments":arguments}),
+            ));
+        }
+        run(input, context);
+
+        let page = crate::mcp_audit::page_at(&log, None, None, 100);
+        let mut entries = page.entries;
+        entries.reverse();
+        let tools: Vec<&str> = entries.iter().map(|entry| entry.tool.as_str()).collect();
+        assert_eq!(
+            tools,
+            [
+                "read_note",
+                "write_note",
+                "rename_note",
+                "read_note",
+                "list_notes",
+                "no_such_tool"
+            ]
+        );
+        assert!(entries
+            .iter()
+            .all(|entry| entry.client.as_deref() == Some("Audited Agent")));
+        let hash = crate::commands::notes::sha256_hex;
+        let (old, new) = (Some(hash("old")), Some(hash("new")));
+        let note = |entry: usize, index: usize| {
+            let note = &entries[entry].notes[index];
+            (
+                note.path.as_str(),
+                note.hash_before.clone(),
+                note.hash_after.clone(),
+            )
+        };
+        assert!(entries[0].ok);
+        assert_eq!(note(0, 0), ("notes/a.md", old.clone(), old.clone()));
+        assert_eq!(note(1, 0), ("notes/a.md", old, new.clone()));
+        assert_eq!(note(2, 0), ("notes/a.md", new.clone(), None));
+        assert_eq!(note(2, 1), ("notes/b.md", None, new));
+        assert!(!entries[3].ok);
+        assert_eq!(note(3, 0), ("../../etc/passwd", None, None));
+        assert!(entries[4].ok && entries[4].notes.is_empty());
+        assert_eq!(
+            entries[5].error.as_deref(),
+            Some("Unknown tool: no_such_tool")
+        );
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_file(log).unwrap();
+    }
+
     #[test]
     fn legacy_nonportable_notes_remain_addressable_but_cannot_be_created() {
         let root = temp_forge("legacy-paths");
@@ -505,6 +1942,155 @@ mod tests {
         fs::remove_dir_all(root).unwrap();
     }
 
+    #[test]
+    fn patch_note_edits_one_section_and_reports_hash_and_diff() {
+        let root = temp_forge("patch-note");
+        let note_path = root.join("notes/plan.md");
+        fs::write(
+            &note_path,
+            "---\ncolor: blue\ncustom: kept\n---\n# Plan\n\n## Tasks\n- one\n\n## Log\nstarted\n",
+        )
+        .unwrap();
+        let context = ToolContext::new(root.clone(), true, false);
+        let read = context.call("read_note", &json!({"path": "notes/plan.md"}));
+
+        let patched = context.call(
+            "patch_note",
+            &json!({
+                "path": "notes/plan.md",
+                "operation": "append",
+                "heading": "Tasks",
+                "content": "- two",
+                "baseHash": read["structuredContent"]["contentHash"]
+            }),
+        );
+        assert_eq!(patched["isError"], false);
+        let result = &patched["structuredContent"];
+        assert!(result["conflictCopy"].is_null());
+        assert_eq!(
+            result["diff"],
+            "--- a/notes/plan.md\n+++ b/notes/plan.md\n@@ -2,6 +2,7 @@\n \n ## Tasks\n - one\n+- two\n \n ## Log\n started\n"
+        );
+        let raw = fs::read_to_string(&note_path).unwrap();
+        assert!(raw.starts_with("---\ncolor: blue\ncustom: kept\n---\n"));
+        assert_eq!(
+            crate::frontmatter::parse_note(&raw).body,
+            "# Plan\n\n## Tasks\n- one\n- two\n\n## Log\nstarted\n"
+        );
+        let reread = context.call("read_note", &json!({"path": "notes/plan.md"}));
+        assert_eq!(
+            result["contentHash"],
+            reread["structuredContent"]["contentHash"]
+        );
+
+        // A stale hash still applies the patch to the disk body, after
+        // preserving that body as a conflict copy.
+        let stale = context.call(
+            "patch_note",
+            &json!({
+                "path": "notes/plan.md",
+                "operation": "findReplace",
+                "find": "started",
+                "replace": "finished",
+                "baseHash": read["structuredContent"]["contentHash"]
+            }),
+        );
+        assert_eq!(stale["isError"], false);
+        let conflict = stale["structuredContent"]["conflictCopy"].as_str().unwrap();
+        assert!(fs::read_to_string(root.join("notes").join(conflict))
+            .unwrap()
+            .contains("- two\n"));
+        assert!(fs::read_to_string(&note_path)
+            .unwrap()
+            .ends_with("- two\n\n## Log\nfinished\n"));
+
+        let missing = context.call(
+            "patch_note",
+            &json!({"path": "notes/plan.md", "operation": "insert", "heading": "Nope", "content": "x"}),
+        );
+        assert_eq!(missing["isError"], true);
+        fs::write(root.join("notes/secret.md.locked"), "ciphertext").unwrap();
+        let locked = context.call(
+            "patch_note",
+            &json!({"path": "notes/secret.md", "operation": "findReplace", "find": "a", "replace": "b"}),
+        );
+        assert_eq!(locked["isError"], true);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn properties_round_trip_typed_values_without_touching_body_or_color() {
+        let root = temp_forge("properties");
+        let note_path = root.join("notes/task.md");
+        fs::write(
+            &note_path,
+            "---\ntitle: Task\ncolor: red\nstatus: open\nlegacy: old\n---\nBody stays.\n",
+        )
+        .unwrap();
+        let context = ToolContext::new(root.clone(), true, false);
+        let hash_before = context.call("read_note", &json!({"path": "notes/task.md"}))
+            ["structuredContent"]["contentHash"]
+            .clone();
+
+        let set = context.call(
+            "set_properties",
+            &json!({
+                "path": "notes/task.md",
+                "properties": {
+                    "status": "done",
+                    "legacy": null,
+                    "owners": ["ana", "kai"],
+                    "due": "2026-11-01",
+                    "points": 3,
+                    "blocked": false
+                }
+            }),
+        );
+        assert_eq!(set["isError"], false);
+        assert_eq!(set["structuredContent"]["written"], true);
+        assert_eq!(
+            fs::read_to_string(&note_path).unwrap(),
+            "---\ntitle: Task\ncolor: red\nstatus: done\nblocked: false\ndue: 2026-11-01\nowners:\n- ana\n- kai\npoints: 3\n---\nBody stays.\n"
+        );
+
+        let got = context.call("get_properties", &json!({"path": "notes/task.md"}));
+        let result = &got["structuredContent"];
+        assert_eq!(result["color"], "red");
+        assert_eq!(
+            result["properties"],
+            json!({
+                "title": "Task",
+                "status": "done",
+                "blocked": false,
+                "due": "2026-11-01",
+                "owners": ["ana", "kai"],
+                "points": 3
+            })
+        );
+        assert_eq!(
+            context.call("read_note", &json!({"path": "notes/task.md"}))["structuredContent"]
+                ["contentHash"],
+            hash_before
+        );
+
+        let color = context.call(
+            "set_properties",
+            &json!({"path": "notes/task.md", "properties": {"color": "blue"}}),
+        );
+        assert_eq!(color["isError"], true);
+        fs::write(root.join("notes/secret.md.locked"), "ciphertext").unwrap();
+        for call in [
+            context.call("get_properties", &json!({"path": "notes/secret.md"})),
+            context.call(
+                "set_properties",
+                &json!({"path": "notes/secret.md", "properties": {"status": "x"}}),
+            ),
+        ] {
+            assert_eq!(call["isError"], true);
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+
     #[test]
     fn read_note_body_hash_round_trips_into_write_note() {
         let root = temp_forge("read-hash-roundtrip");
@@ -605,6 +2191,93 @@ mod tests {
         fs::remove_dir_all(root).unwrap();
     }
 
+    #[test]
+    fn rename_move_and_trash_follow_the_app_file_operations() {
+        let root = temp_forge("reorganize");
+        fs::create_dir_all(root.join("notes/Archive")).unwrap();
+        fs::write(root.join("notes/plan.md"), "# Plan").unwrap();
+        fs::write(
+            root.join("notes/index.md"),
+            "See [[plan]] and [[the plan|plan]].",
+        )
+        .unwrap();
+        fs::write(root.join("daily/2026-07-12.md"), "Worked on [[plan]]").unwrap();
+        fs::write(root.join("notes/scratch.md"), "throwaway").unwrap();
+        fs::write(root.join("notes/vault.md.locked"), "ciphertext").unwrap();
+        let calls = [
+            (
+                "rename_note",
+                json!({"path":"notes/plan.md","newName":"Roadmap"}),
+            ),
+            (
+                "move_note",
+                json!({"path":"notes/Roadmap.md","folder":"notes/Archive"}),
+            ),
+            ("trash_note", json!({"path":"notes/scratch.md"})),
+            ("trash_note", json!({"path":"notes/vault.md"})),
+            (
+                "rename_note",
+                json!({"path":"notes/index.md","newName":"vault"}),
+            ),
+            (
+                "rename_note",
+                json!({"path":"notes/index.md","newName":"Archive/escape"}),
+            ),
+            (
+                "move_note",
+                json!({"path":"daily/2026-07-12.md","folder":"notes"}),
+            ),
+        ];
+        let mut input = String::new();
+        for (index, (name, arguments)) in calls.into_iter().enumerate() {
+            input.push_str(&request(
+                index as u32,
+                "tools/call",
+                json!({"name":name,"arguments":arguments}),
+            ));
+        }
+        let responses = run(input, ToolContext::new(root.clone(), true, false));
+        let result = |index: usize| -> Value {
+            serde_json::from_str(
+                responses[index]["result"]["content"][0]["text"]
+                    .as_str()
+                    .unwrap(),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(result(0)["newPath"], "notes/Roadmap.md");
+        assert_eq!(
+            fs::read_to_string(root.join("notes/index.md")).unwrap(),
+            "See [[Roadmap]] and [[the plan|Roadmap]]."
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("daily/2026-07-12.md")).unwrap(),
+            "Worked on [[Roadmap]]"
+        );
+        assert_eq!(result(1)["newPath"], "notes/Archive/Roadmap.md");
+        assert!(root.join("notes/Archive/Roadmap.md").is_file());
+        assert!(!root.join("notes/Roadmap.md").exists());
+
+        assert_eq!(result(2)["trashed"], true);
+        assert!(!root.join("notes/scratch.md").exists());
+        let metadata: Value =
+            serde_json::from_str(&fs::read_to_string(root.join(".trash/metadata.json")).unwrap())
+                .unwrap();
+        let items = metadata["items"].as_array().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0]["id"], result(2)["trashId"]);
+        assert_eq!(items[0]["original_path"], "scratch.md");
+
+        for response in &responses[3..] {
+            assert_eq!(response["result"]["isError"], true, "{response}");
+        }
+        assert!(root.join("notes/vault.md.locked").is_file());
+        assert!(root.join("notes/index.md").is_file());
+        assert!(root.join("daily/2026-07-12.md").is_file());
+        fs::remove_dir_all(root).unwrap();
+    }
+
     #[test]
     fn malformed_oversized_unknown_and_recovery_are_bounded() {
         let root = temp_forge("bad-input");
@@ -622,10 +2295,165 @@ mod tests {
             .unwrap()
             .contains("1 MiB"));
         assert_eq!(responses[2]["error"]["code"], -32601);
-        assert_eq!(responses[3]["result"]["tools"].as_array().unwrap().len(), 4);
+        assert_eq!(
+            responses[3]["result"]["tools"].as_array().unwrap().len(),
+            10
+        );
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Stdin fed a chunk at a time by the test; EOF once the sender is dropped.
+    struct Feed {
+        chunks: mpsc::Receiver<String>,
+        pending: Vec<u8>,
+    }
+
+    impl std::io::Read for Feed {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                match self.chunks.recv() {
+                    Ok(chunk) => self.pending = chunk.into_bytes(),
+                    Err(_) => return Ok(0),
+                }
+            }
+            let count = buf.len().min(self.pending.len());
+            buf[..count].copy_from_slice(&self.pending[..count]);
+            self.pending.drain(..count);
+            Ok(count)
+        }
+    }
+
+    /// Stdout that hands each complete message to the test as it is flushed.
+    struct Messages {
+        sink: mpsc::Sender<Value>,
+        buffer: Vec<u8>,
+    }
+
+    impl Write for Messages {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.buffer.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let _ = self.sink.send(serde_json::from_slice(&line).unwrap());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn slow_calls_do_not_block_ping_and_cancelled_calls_get_no_response() {
+        let root = temp_forge("slow-call");
+        fs::write(root.join("notes/slow.md"), "slow").unwrap();
+        let (release, gate) = mpsc::channel::<()>();
+        let gate = Mutex::new(gate);
+        let resolved_root = root.clone();
+        // The first request that resolves the Forge parks until released.
+        let context = ToolContext::with_forge_resolver(
+            Arc::new(move || {
+                let _ = gate
+                    .lock()
+                    .unwrap()
+                    .recv_timeout(std::time::Duration::from_secs(10));
+                Ok(resolved_root.clone())
+            }),
+            false,
+        );
+        let (input, chunks) = mpsc::channel();
+        let (sink, output) = mpsc::channel();
+        let server = std::thread::spawn(move || {
+            let stdin = std::io::BufReader::new(Feed {
+                chunks,
+                pending: Vec::new(),
+            });
+            let stdout = Messages {
+                sink,
+                buffer: Vec::new(),
+            };
+            serve(stdin, stdout, context)
+        });
+        let next = || {
+            output
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .unwrap()
+        };
+
+        input
+            .send(request(
+                1,
+                "tools/call",
+                json!({"name":"read_note","arguments":{"path":"notes/slow.md"}}),
+            ))
+            .unwrap();
+        input.send(request(2, "ping", json!({}))).unwrap();
+        assert_eq!(next(), json!({"jsonrpc":"2.0","id":2,"result":{}}));
+        input.send(request(3, "tools/list", json!({}))).unwrap();
+        assert_eq!(next()["id"], 3);
+
+        input
+            .send(format!(
+                "{}\n",
+                json!({"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":1,"reason":"user"}})
+            ))
+            .unwrap();
+        input.send(request(4, "ping", json!({}))).unwrap();
+        assert_eq!(next()["id"], 4);
+        release.send(()).unwrap();
+        input
+            .send(request(
+                5,
+                "tools/call",
+                json!({"name":"read_note","arguments":{"path":"notes/slow.md"}}),
+            ))
+            .unwrap();
+        release.send(()).unwrap();
+        let last = next();
+        assert_eq!(last["id"], 5);
+        assert_eq!(last["result"]["structuredContent"]["content"], "slow");
+        drop(input);
+        server.join().unwrap().unwrap();
+        assert!(output.try_recv().is_err(), "cancelled call must not answer");
         fs::remove_dir_all(root).unwrap();
     }
 
+    #[test]
+    fn progress_tokens_receive_notifications_while_a_call_runs() {
+        let first = temp_forge("progress-first");
+        let second = temp_forge("progress-second");
+        fs::write(first.join("notes/a.md"), "needle").unwrap();
+        fs::write(second.join("notes/b.md"), "needle needle").unwrap();
+        let context = ToolContext::new(first.clone(), false, false)
+            .with_forges(vec![("First", first.clone()), ("Second", second.clone())]);
+        let input = request(
+            1,
+            "tools/call",
+            json!({"name":"search_all_forges","arguments":{"query":"needle"},"_meta":{"progressToken":"search-1"}}),
+        );
+        let responses = run(input, context);
+        assert_eq!(responses.len(), 3);
+        let progress: Vec<(u64, u64)> = responses[..2]
+            .iter()
+            .map(|message| {
+                assert_eq!(message["method"], "notifications/progress");
+                assert_eq!(message["params"]["progressToken"], "search-1");
+                (
+                    message["params"]["progress"].as_u64().unwrap(),
+                    message["params"]["total"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(progress, [(1, 2), (2, 2)]);
+        assert_eq!(
+            responses[2]["result"]["structuredContent"]["results"][0]["forge"],
+            "Second"
+        );
+        fs::remove_dir_all(first).unwrap();
+        fs::remove_dir_all(second).unwrap();
+    }
+
     #[test]
     fn every_path_bearing_tool_rejects_traversal_and_unknown_tools_are_clean_errors() {
         let root = temp_forge("all-traversal");
@@ -663,19 +2491,17 @@ mod tests {
     #[test]
     fn write_gate_flip_applies_without_restarting_session() {
         let root = temp_forge("gate-flip");
-        let enabled = Arc::new(AtomicBool::new(true));
-        let gate = enabled.clone();
-        let context = ToolContext::with_write_gate(
-            root.clone(),
-            Arc::new(move || gate.load(Ordering::SeqCst)),
-        );
+        let mode = Arc::new(Mutex::new(McpWriteMode::Direct));
+        let gate = mode.clone();
+        let context =
+            ToolContext::with_write_gate(root.clone(), Arc::new(move || *gate.lock().unwrap()));
         let first = context.call(
             "create_note",
             &json!({"path":"notes/first.md","content":"allowed"}),
         );
         assert_eq!(first["isError"], false);
-        enabled.store(false, Ordering::SeqCst);
-        assert_eq!(context.tool_definitions().len(), 4);
+        *mode.lock().unwrap() = McpWriteMode::Off;
+        assert_eq!(context.tool_definitions().len(), 10);
         let second = context.call(
             "create_note",
             &json!({"path":"notes/second.md","content":"blocked"}),
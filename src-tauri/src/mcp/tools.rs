@@ -6,18 +6,35 @@
 //! each request and use atomic persistence. The exposed tool set must match
 //! [`ToolContext::call`], so a listed capability cannot bypass its validator.
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
-use chrono::{Local, NaiveDate};
+use chrono::{Datelike, Local, NaiveDate, Weekday};
 use serde_json::{json, Value};
 use walkdir::WalkDir;
 
-use crate::commands::notes::{save_note_with_conflict_using, sha256_hex};
-use crate::commands::search::search_notes_content_in;
-use crate::persist::write_atomic;
+use super::listing::{timestamp, ListQuery, NoteKind};
+use super::patch::Patch;
+use super::policy::DEFAULT_POLICY_KEY;
+use super::progress::{CallControl, CANCELLED};
+use super::prompts::templates_dir;
+
+use crate::backlinks_index::BacklinksIndex;
+use crate::commands::notes::{
+    is_week_stem, move_note_in, note_ref_stem, preview_note_save, rename_note_in,
+    rewrite_inbound_links_in_roots, save_note_with_conflict_using, sha256_hex,
+};
+use crate::commands::saved_searches::{find_saved_search_in, searches_dir};
+use crate::commands::search::{classify_note_path, SearchQuery};
+use crate::commands::templates::get_template_in;
+use crate::commands::trash::{next_trash_id, trash_note_on_disk};
+use crate::mcp_audit::{AuditEntry, AuditNote};
+use crate::persist::{read_trash_metadata_at, write_atomic, write_trash_metadata_at};
+use crate::templates_data::replace_template_variables_at;
+use crate::text_diff::unified_diff;
+use crate::types::{ForgeInfo, McpClientPolicy, McpWriteMode, SearchMode, SearchOptions};
 use crate::validation::{
     is_safe_existing_filename, is_safe_existing_note_path, is_safe_filename,
     validate_path_within_base,
@@ -26,14 +43,70 @@ use crate::validation::{
 const WRITE_DISABLED: &str =
     "MCP writes are disabled. Enable Settings → AI & Agents → Allow agents to write.";
 
+const READ_TOOLS: [&str; 10] = [
+    "search_notes",
+    "run_saved_search",
+    "read_note",
+    "get_daily_note",
+    "get_weekly_note",
+    "list_notes",
+    "get_backlinks",
+    "get_properties",
+    "list_forges",
+    "search_all_forges",
+];
+
+const WRITE_TOOLS: [&str; 9] = [
+    "create_note",
+    "create_note_from_template",
+    "append_to_daily_note",
+    "patch_note",
+    "set_properties",
+    "rename_note",
+    "move_note",
+    "trash_note",
+    "write_note",
+];
+
+/// Write tools that can hold their change for review in propose mode.
+const PROPOSABLE_TOOLS: [&str; 4] = [
+    "create_note",
+    "create_note_from_template",
+    "append_to_daily_note",
+    "write_note",
+];
+
+const PROPOSAL_PENDING: &str =
+    "This change is awaiting review in Moldavite. The note is unchanged unless the user accepts it.";
+
+/// Notes a scan covers between progress reports and cancellation checks.
+const PROGRESS_STEP: usize = 500;
+
+pub(super) fn is_write_tool(name: &str) -> bool {
+    WRITE_TOOLS.contains(&name)
+}
+
+type PolicySource =
+    Arc<dyn Fn() -> Result<BTreeMap<String, McpClientPolicy>, String> + Send + Sync>;
+pub(super) type NamedForgeResolver = Arc<dyn Fn(&str) -> Result<PathBuf, String> + Send + Sync>;
+pub(super) type ForgeLister = Arc<dyn Fn() -> Result<Vec<ForgeInfo>, String> + Send + Sync>;
+
 #[derive(Clone)]
 pub(super) struct ToolContext {
     forge_root: Arc<dyn Fn() -> Result<PathBuf, String> + Send + Sync>,
-    write_gate: Arc<dyn Fn() -> bool + Send + Sync>,
+    write_mode: Arc<dyn Fn() -> McpWriteMode + Send + Sync>,
     semantic_root: PathBuf,
     semantic_ready: bool,
     agent_write_spool: Option<PathBuf>,
+    proposal_spool: Option<PathBuf>,
+    snapshot_spool: Option<PathBuf>,
+    /// Groups this session's pre-write snapshots so they can be undone together.
+    session_id: String,
+    audit_log: Option<PathBuf>,
     client_name: Arc<RwLock<Option<String>>>,
+    policies: PolicySource,
+    named_forge: NamedForgeResolver,
+    forge_list: ForgeLister,
 }
 
 impl ToolContext {
@@ -42,11 +115,18 @@ impl ToolContext {
         let semantic_root = forge_root.clone();
         Self {
             forge_root: Arc::new(move || Ok(forge_root.clone())),
-            write_gate: Arc::new(move || writes_enabled),
+            write_mode: write_mode_of(writes_enabled),
             semantic_root,
             semantic_ready,
             agent_write_spool: None,
+            proposal_spool: None,
+            snapshot_spool: None,
+            session_id: crate::agent_writes::unique_stem(),
+            audit_log: None,
             client_name: Arc::new(RwLock::new(None)),
+            policies: no_policies(),
+            named_forge: no_named_forges(),
+            forge_list: no_forge_list(),
         }
     }
 
@@ -54,36 +134,68 @@ impl ToolContext {
     /// and switching the active Forge take effect in already-running MCP sessions.
     pub(super) fn dynamic(
         forge_root: Arc<dyn Fn() -> Result<PathBuf, String> + Send + Sync>,
+        named_forge: NamedForgeResolver,
+        forge_list: ForgeLister,
         semantic_root: PathBuf,
         semantic_ready: bool,
     ) -> Self {
         Self {
             forge_root,
-            write_gate: Arc::new(|| {
-                crate::persist::read_config()
-                    .mcp_writes_enabled
-                    .unwrap_or(false)
-            }),
+            write_mode: Arc::new(|| crate::persist::read_config().mcp_write_mode()),
             semantic_root,
             semantic_ready,
             agent_write_spool: crate::agent_writes::spool_dir(),
+            proposal_spool: crate::agent_proposals::spool_dir(),
+            snapshot_spool: crate::agent_snapshots::spool_dir(),
+            session_id: crate::agent_writes::unique_stem(),
+            audit_log: crate::mcp_audit::log_path(),
+            client_name: Arc::new(RwLock::new(None)),
+            policies: Arc::new(crate::persist::read_mcp_client_policies),
+            named_forge,
+            forge_list,
+        }
+    }
+
+    /// Replays a reviewed proposal against `forge_root` with writes allowed.
+    /// Policies still apply to the client that made it.
+    pub(super) fn for_review(forge_root: PathBuf) -> Self {
+        Self {
+            forge_root: Arc::new(move || Ok(forge_root.clone())),
+            write_mode: Arc::new(|| McpWriteMode::Direct),
+            semantic_root: PathBuf::new(),
+            semantic_ready: false,
+            agent_write_spool: crate::agent_writes::spool_dir(),
+            proposal_spool: None,
+            snapshot_spool: crate::agent_snapshots::spool_dir(),
+            session_id: crate::agent_writes::unique_stem(),
+            audit_log: crate::mcp_audit::log_path(),
             client_name: Arc::new(RwLock::new(None)),
+            policies: Arc::new(crate::persist::read_mcp_client_policies),
+            named_forge: Arc::new(|_| Err("Proposals act on the open Forge".to_string())),
+            forge_list: Arc::new(|| Ok(Vec::new())),
         }
     }
 
     #[cfg(test)]
     pub(super) fn with_write_gate(
         forge_root: PathBuf,
-        write_gate: Arc<dyn Fn() -> bool + Send + Sync>,
+        write_mode: Arc<dyn Fn() -> McpWriteMode + Send + Sync>,
     ) -> Self {
         let semantic_root = forge_root.clone();
         Self {
             forge_root: Arc::new(move || Ok(forge_root.clone())),
-            write_gate,
+            write_mode,
             semantic_root,
             semantic_ready: false,
             agent_write_spool: None,
+            proposal_spool: None,
+            snapshot_spool: None,
+            session_id: crate::agent_writes::unique_stem(),
+            audit_log: None,
             client_name: Arc::new(RwLock::new(None)),
+            policies: no_policies(),
+            named_forge: no_named_forges(),
+            forge_list: no_forge_list(),
         }
     }
 
@@ -94,11 +206,18 @@ impl ToolContext {
     ) -> Self {
         Self {
             forge_root,
-            write_gate: Arc::new(move || writes_enabled),
+            write_mode: write_mode_of(writes_enabled),
             semantic_root: PathBuf::new(),
             semantic_ready: false,
             agent_write_spool: None,
+            proposal_spool: None,
+            snapshot_spool: None,
+            session_id: crate::agent_writes::unique_stem(),
+            audit_log: None,
             client_name: Arc::new(RwLock::new(None)),
+            policies: no_policies(),
+            named_forge: no_named_forges(),
+            forge_list: no_forge_list(),
         }
     }
 
@@ -108,6 +227,71 @@ impl ToolContext {
         self
     }
 
+    #[cfg(test)]
+    pub(super) fn with_proposal_spool(mut self, spool: PathBuf) -> Self {
+        self.proposal_spool = Some(spool);
+        self
+    }
+
+    #[cfg(test)]
+    pub(super) fn with_snapshot_spool(mut self, spool: PathBuf) -> Self {
+        self.snapshot_spool = Some(spool);
+        self
+    }
+
+    /// Serve `forges` by name; the first is the default Forge.
+    #[cfg(test)]
+    pub(super) fn with_forges(mut self, forges: Vec<(&str, PathBuf)>) -> Self {
+        let infos: Vec<ForgeInfo> = forges
+            .iter()
+            .enumerate()
+            .map(|(index, (name, root))| ForgeInfo {
+                name: name.to_string(),
+                path: root.to_string_lossy().into_owned(),
+                is_active: index == 0,
+            })
+            .collect();
+        let roots: BTreeMap<String, PathBuf> = forges
+            .into_iter()
+            .map(|(name, root)| (name.to_string(), root))
+            .collect();
+        self.named_forge = Arc::new(move |name| {
+            roots
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("Forge '{name}' does not exist"))
+        });
+        self.forge_list = Arc::new(move || Ok(infos.clone()));
+        self
+    }
+
+    #[cfg(test)]
+    pub(super) fn with_audit_log(mut self, log: PathBuf) -> Self {
+        self.audit_log = Some(log);
+        self
+    }
+
+    #[cfg(test)]
+    pub(super) fn with_policies(
+        mut self,
+        policies: Result<BTreeMap<String, McpClientPolicy>, String>,
+        named_forge: NamedForgeResolver,
+    ) -> Self {
+        self.policies = Arc::new(move || policies.clone());
+        self.named_forge = named_forge;
+        self
+    }
+
+    /// A copy for another concurrent session: same Forge resolution and write
+    /// gate, but its own client identity and snapshot session.
+    pub(super) fn for_session(&self) -> Self {
+        Self {
+            client_name: Arc::new(RwLock::new(None)),
+            session_id: crate::agent_writes::unique_stem(),
+            ..self.clone()
+        }
+    }
+
     pub(super) fn set_client_name(&self, client_name: Option<&str>) {
         let client_name = client_name
             .map(str::trim)
@@ -122,45 +306,207 @@ impl ToolContext {
         self.client_name.read().ok()?.clone()
     }
 
-    fn writes_enabled(&self) -> bool {
-        (self.write_gate)()
+    /// The connected client's policy, re-read per request like the write
+    /// gate. Malformed policies are an error rather than no restriction.
+    pub(super) fn policy(&self) -> Result<McpClientPolicy, String> {
+        let mut policies = (self.policies)()?;
+        let client_name = self.client_name();
+        Ok(client_name
+            .as_deref()
+            .and_then(|name| policies.remove(name))
+            .or_else(|| policies.remove(DEFAULT_POLICY_KEY))
+            .unwrap_or_default())
+    }
+
+    /// Resolve the Forge this request should act on: the client's pinned
+    /// Forge when its policy has one, otherwise the server's.
+    pub(super) fn forge_root(&self) -> Result<PathBuf, String> {
+        match self.policy()?.pinned_forge {
+            Some(name) => (self.named_forge)(&name),
+            None => (self.forge_root)(),
+        }
+    }
+
+    /// The Forge a tool call acts on. Read tools may name any Forge this
+    /// client can see; write tools always act on the default one, so a stray
+    /// `forge` argument is refused rather than ignored.
+    fn request_forge_root(&self, tool: &str, arguments: &Value) -> Result<PathBuf, String> {
+        match optional_string(arguments, "forge")? {
+            None => self.forge_root(),
+            Some(_) if WRITE_TOOLS.contains(&tool) => {
+                Err("forge is only accepted by read tools".to_string())
+            }
+            Some(name) => self.named_forge_root(name),
+        }
+    }
+
+    fn named_forge_root(&self, name: &str) -> Result<PathBuf, String> {
+        match self.policy()?.pinned_forge {
+            Some(pinned) if pinned != name => Err(format!(
+                "This client is pinned to Forge '{pinned}'; it cannot use '{name}'"
+            )),
+            _ => (self.named_forge)(name),
+        }
+    }
+
+    /// Forges this client may read, honoring both the server's `--forge` pin
+    /// (through the lister) and a policy pin.
+    fn forges(&self) -> Result<Vec<ForgeInfo>, String> {
+        match self.policy()?.pinned_forge {
+            Some(name) => {
+                let root = (self.named_forge)(&name)?;
+                Ok(vec![ForgeInfo {
+                    name,
+                    path: root.to_string_lossy().into_owned(),
+                    is_active: true,
+                }])
+            }
+            None => (self.forge_list)(),
+        }
+    }
+
+    pub(super) fn ensure_readable(&self, rel: &str) -> Result<(), String> {
+        if !self.policy()?.can_read(rel) {
+            return Err(format!("{rel} is outside the folders this client may read"));
+        }
+        Ok(())
+    }
+
+    fn ensure_writable(&self, rel: &str) -> Result<(), String> {
+        let policy = self.policy()?;
+        if let Some(forge) = &policy.pinned_forge {
+            return Err(format!(
+                "This client is pinned read-only to Forge '{forge}'"
+            ));
+        }
+        if !policy.can_write(rel) {
+            return Err(format!(
+                "{rel} is outside the folders this client may write"
+            ));
+        }
+        Ok(())
     }
 
+    fn write_mode(&self) -> McpWriteMode {
+        (self.write_mode)()
+    }
+
+    /// What a proposable write tool does with its change: `dryRun` previews
+    /// it whatever the mode, and propose mode holds it for review.
+    fn staging(&self, arguments: &Value) -> Result<Staging, String> {
+        Ok(if optional_bool(arguments, "dryRun")? {
+            Staging::DryRun
+        } else if self.write_mode() == McpWriteMode::Propose {
+            Staging::Propose
+        } else {
+            Staging::Write
+        })
+    }
+
+    /// Tools this client may call right now. An unreadable policy lists none.
     pub(super) fn tool_definitions(&self) -> Vec<Value> {
+        let Ok(policy) = self.policy() else {
+            return Vec::new();
+        };
         let mut tools = read_tool_definitions();
-        if self.writes_enabled() {
-            tools.extend(write_tool_definitions());
+        if !policy.is_read_only() {
+            match self.write_mode() {
+                McpWriteMode::Off => {}
+                McpWriteMode::Direct => tools.extend(write_tool_definitions()),
+                McpWriteMode::Propose => {
+                    tools.extend(write_tool_definitions().into_iter().filter(|tool| {
+                        PROPOSABLE_TOOLS.contains(&tool["name"].as_str().unwrap_or(""))
+                    }))
+                }
+            }
         }
+        tools.retain(|tool| {
+            tool["name"]
+                .as_str()
+                .is_some_and(|name| policy.allows_tool(name))
+        });
         tools
     }
 
+    #[cfg(test)]
     pub(super) fn call(&self, name: &str, arguments: &Value) -> Value {
-        let result = match name {
-            "create_note" | "append_to_daily_note" | "write_note" if !self.writes_enabled() => {
-                Err(WRITE_DISABLED.to_string())
+        self.call_with(name, arguments, &CallControl::none())
+    }
+
+    /// Run a tool call that may report progress and be cancelled. A call
+    /// cancelled before it starts is neither run nor audited.
+    pub(super) fn call_with(&self, name: &str, arguments: &Value, control: &CallControl) -> Value {
+        if control.is_cancelled() {
+            return tool_result(json!({ "error": CANCELLED }), true);
+        }
+        match self.run(name, arguments, control) {
+            Ok(value) => tool_result(value, false),
+            Err(error) => tool_result(json!({ "error": error }), true),
+        }
+    }
+
+    /// Run and audit one tool call.
+    pub(super) fn run(
+        &self,
+        name: &str,
+        arguments: &Value,
+        control: &CallControl,
+    ) -> Result<Value, String> {
+        let known = READ_TOOLS.contains(&name) || WRITE_TOOLS.contains(&name);
+        let audit = match &self.audit_log {
+            Some(log) if known => Some((log, self.audited_notes(name, arguments))),
+            Some(log) => Some((log, (None, Vec::new()))),
+            None => None,
+        };
+        let write_mode = WRITE_TOOLS.contains(&name).then(|| self.write_mode());
+        let result = match self.policy() {
+            Err(error) if known => Err(error),
+            Ok(policy) if known && !policy.allows_tool(name) => Err(format!(
+                "{name} is not allowed for this client by its MCP policy"
+            )),
+            Ok(McpClientPolicy {
+                pinned_forge: Some(forge),
+                ..
+            }) if WRITE_TOOLS.contains(&name) => Err(format!(
+                "This client is pinned read-only to Forge '{forge}'"
+            )),
+            _ if write_mode == Some(McpWriteMode::Off) => Err(WRITE_DISABLED.to_string()),
+            _ if write_mode == Some(McpWriteMode::Propose) && !PROPOSABLE_TOOLS.contains(&name) => {
+                Err(format!(
+                    "{name} is unavailable while agent writes need approval; only {} can propose changes",
+                    PROPOSABLE_TOOLS.join(", ")
+                ))
             }
-            "search_notes"
-            | "read_note"
-            | "list_notes"
-            | "get_backlinks"
-            | "create_note"
-            | "append_to_daily_note"
-            | "write_note" => (self.forge_root)().and_then(|root| match name {
-                "search_notes" => self.search_notes(&root, arguments),
-                "read_note" => self.read_note(&root, arguments),
-                "list_notes" => self.list_notes(&root, arguments),
-                "get_backlinks" => self.get_backlinks(&root, arguments),
-                "create_note" => self.create_note(&root, arguments),
-                "append_to_daily_note" => self.append_to_daily_note(&root, arguments),
-                "write_note" => self.write_note(&root, arguments),
-                _ => unreachable!(),
-            }),
+            _ if name == "list_forges" => self.list_forges(),
+            _ if name == "search_all_forges" => self.search_all_forges(arguments, control),
+            _ if known => self
+                .request_forge_root(name, arguments)
+                .and_then(|root| match name {
+                    "search_notes" => self.search_notes(&root, arguments),
+                    "run_saved_search" => self.run_saved_search(&root, arguments),
+                    "read_note" => self.read_note(&root, arguments),
+                    "get_daily_note" => self.get_daily_note(&root, arguments),
+                    "get_weekly_note" => self.get_weekly_note(&root, arguments),
+                    "list_notes" => self.list_notes(&root, arguments, control),
+                    "get_backlinks" => self.get_backlinks(&root, arguments, control),
+                    "get_properties" => self.get_properties(&root, arguments),
+                    "create_note" => self.create_note(&root, arguments),
+                    "create_note_from_template" => self.create_note_from_template(&root, arguments),
+                    "append_to_daily_note" => self.append_to_daily_note(&root, arguments),
+                    "write_note" => self.write_note(&root, arguments),
+                    "patch_note" => self.patch_note(&root, arguments),
+                    "set_properties" => self.set_properties(&root, arguments),
+                    "rename_note" => self.rename_note(&root, arguments),
+                    "move_note" => self.move_note(&root, arguments),
+                    "trash_note" => self.trash_note(&root, arguments),
+                    _ => unreachable!(),
+                }),
             _ => Err(format!("Unknown tool: {name}")),
         };
-        match result {
-            Ok(value) => tool_result(value, false),
-            Err(error) => tool_result(json!({ "error": error }), true),
+        if let Some((log, (forge_root, notes))) = audit {
+            self.record_call(log, name, forge_root, notes, &result);
         }
+        result
     }
 
     fn search_notes(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
@@ -168,21 +514,176 @@ impl ToolContext {
         let limit = optional_u32(arguments, "limit")?
             .unwrap_or(20)
             .clamp(1, 100);
-        if self.semantic_ready
+        let mode = match optional_string(arguments, "mode")? {
+            None => None,
+            Some("keyword") => Some(SearchMode::Keyword),
+            Some("semantic") => Some(SearchMode::Semantic),
+            Some("hybrid") => Some(SearchMode::Hybrid),
+            Some(_) => return Err("mode must be keyword, semantic, or hybrid".to_string()),
+        };
+        let options = search_options(arguments)?;
+        let policy = self.policy()?;
+        let Some(parsed) = SearchQuery::parse(query, options)? else {
+            return Ok(json!({ "mode": "keyword", "results": [] }));
+        };
+        // Over-fetch when folders are hidden so filtering still fills the page.
+        let fetch = if policy.restricts_reads() { 500 } else { limit };
+        // Operators and filters need the keyword search that understands
+        // them, and without a mode plain words go to semantic search unless
+        // the caller asked how words should match.
+        let default_mode = if options == SearchOptions::default() {
+            SearchMode::Semantic
+        } else {
+            SearchMode::Keyword
+        };
+        if parsed.is_plain()
+            && self.semantic_ready
             && forge_root == self.semantic_root
             && crate::semantic::service().is_ready()
         {
-            if let Ok(results) = crate::semantic::service().search(query, limit as usize) {
-                return Ok(json!({ "mode": "semantic", "results": results }));
+            let semantic = crate::semantic::service();
+            match mode.unwrap_or(default_mode) {
+                SearchMode::Semantic => {
+                    if let Ok(mut results) = semantic.search(forge_root, query, fetch as usize) {
+                        results.retain(|hit| policy.can_read(&hit.path));
+                        results.truncate(limit as usize);
+                        // The passage that matched is the snippet to show.
+                        for hit in &mut results {
+                            hit.snippet = hit.passage.as_ref().map(|p| p.text.clone());
+                        }
+                        return Ok(json!({ "mode": "semantic", "results": results }));
+                    }
+                }
+                SearchMode::Hybrid => {
+                    let trash_dir = forge_root.join(".trash");
+                    let fused = parsed.run_hybrid(forge_root, &trash_dir, fetch, |depth| {
+                        semantic.search(forge_root, query, depth)
+                    });
+                    if let Ok(mut results) = fused {
+                        results.retain(|hit| policy.can_read(&hit.path));
+                        results.truncate(limit as usize);
+                        return Ok(json!({ "mode": "hybrid", "results": results }));
+                    }
+                }
+                SearchMode::Keyword => {}
+            }
+        }
+        let mut results = parsed.run(forge_root, &forge_root.join(".trash"), fetch);
+        results.retain(|hit| policy.can_read(&hit.path));
+        results.truncate(limit as usize);
+        Ok(json!({ "mode": "keyword", "results": results }))
+    }
+
+    /// Run a saved search as `search_notes` would with its query, mode, and
+    /// options.
+    fn run_saved_search(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let name = required_string(arguments, "name")?;
+        let search = find_saved_search_in(&searches_dir(forge_root)?, name)?;
+        let mut search_arguments = serde_json::to_value(search.options)
+            .map_err(|error| format!("Failed to encode search options: {error}"))?;
+        search_arguments["query"] = json!(search.query);
+        search_arguments["mode"] = json!(search.mode);
+        if let Some(limit) = arguments.get("limit") {
+            search_arguments["limit"] = limit.clone();
+        }
+        let mut result = self.search_notes(forge_root, &search_arguments)?;
+        result["savedSearch"] =
+            json!({ "id": search.id, "name": search.name, "query": search.query });
+        Ok(result)
+    }
+
+    fn list_forges(&self) -> Result<Value, String> {
+        let forges: Vec<Value> = self
+            .forges()?
+            .into_iter()
+            .map(|forge| json!({ "name": forge.name, "isDefault": forge.is_active }))
+            .collect();
+        Ok(json!({ "forges": forges }))
+    }
+
+    /// Keyword search over every visible Forge, merged by score with the
+    /// tie-breaks each per-Forge search uses, Forge name before filename.
+    fn search_all_forges(&self, arguments: &Value, control: &CallControl) -> Result<Value, String> {
+        let query = required_string(arguments, "query")?;
+        let limit = optional_u32(arguments, "limit")?
+            .unwrap_or(20)
+            .clamp(1, 100);
+        let options = search_options(arguments)?;
+        let policy = self.policy()?;
+        let Some(parsed) = SearchQuery::parse(query, options)? else {
+            return Ok(json!({ "mode": "keyword", "results": [] }));
+        };
+        let fetch = if policy.restricts_reads() { 500 } else { limit };
+        let forges = self.forges()?;
+        let mut hits = Vec::new();
+        for (searched, forge) in forges.iter().enumerate() {
+            control.check()?;
+            if let Ok(root) = self.named_forge_root(&forge.name) {
+                hits.extend(
+                    parsed
+                        .run(&root, &root.join(".trash"), fetch)
+                        .into_iter()
+                        .filter(|hit| policy.can_read(&hit.path))
+                        .map(|hit| (forge.name.clone(), hit)),
+                );
             }
+            control.progress(
+                searched + 1,
+                Some(forges.len()),
+                &format!("Searched {}", forge.name),
+            );
         }
-        let results = search_notes_content_in(forge_root, &forge_root.join(".trash"), query, limit);
+        hits.sort_by(|(forge_a, a), (forge_b, b)| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| b.match_count.cmp(&a.match_count))
+                .then_with(|| forge_a.cmp(forge_b))
+                .then_with(|| a.filename.cmp(&b.filename))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        hits.truncate(limit as usize);
+        let results: Vec<Value> = hits
+            .into_iter()
+            .map(|(forge, hit)| {
+                let mut result = json!(hit);
+                result["forge"] = json!(forge);
+                result
+            })
+            .collect();
         Ok(json!({ "mode": "keyword", "results": results }))
     }
 
     fn read_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
         let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
-        let path = self.checked_existing_note(forge_root, &rel)?;
+        self.ensure_readable(&rel)?;
+        self.note_content(forge_root, &rel)
+    }
+
+    fn get_daily_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        self.periodic_note(forge_root, &daily_note_rel(arguments)?)
+    }
+
+    fn get_weekly_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        self.periodic_note(forge_root, &weekly_note_rel(arguments)?)
+    }
+
+    /// A daily or weekly note that may not have been created yet is reported
+    /// with `exists: false` instead of an error; locked notes still refuse.
+    fn periodic_note(&self, forge_root: &Path, rel: &str) -> Result<Value, String> {
+        self.ensure_readable(rel)?;
+        let path = forge_root.join(rel);
+        if fs::symlink_metadata(&path).is_err() && !locked_path(&path).exists() {
+            return Ok(
+                json!({ "path": rel, "exists": false, "content": null, "contentHash": null }),
+            );
+        }
+        let mut note = self.note_content(forge_root, rel)?;
+        note["exists"] = json!(true);
+        Ok(note)
+    }
+
+    fn note_content(&self, forge_root: &Path, rel: &str) -> Result<Value, String> {
+        let path = self.checked_existing_note(forge_root, rel)?;
         let content =
             fs::read_to_string(&path).map_err(|error| format!("Failed to read note: {error}"))?;
         // Conflict detection compares note bodies, so expose a hash in that same
@@ -191,54 +692,87 @@ impl ToolContext {
         Ok(json!({ "path": rel, "content": content, "contentHash": content_hash }))
     }
 
-    fn list_notes(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+    fn list_notes(
+        &self,
+        forge_root: &Path,
+        arguments: &Value,
+        control: &CallControl,
+    ) -> Result<Value, String> {
         let folder = match arguments.get("folder") {
             None | Some(Value::Null) => None,
             Some(Value::String(folder)) => Some(validated_existing_folder(folder)?),
             Some(_) => return Err("folder must be a string".to_string()),
         };
-        let mut notes = Vec::new();
-        for entry in WalkDir::new(forge_root)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|entry| {
-                entry.depth() == 0
-                    || (!entry.file_name().to_string_lossy().starts_with('.')
-                        && (entry.depth() > 1
-                            || matches!(
-                                entry.file_name().to_string_lossy().as_ref(),
-                                "daily" | "weekly" | "notes"
-                            )))
-            })
-            .flatten()
-        {
-            if !entry.file_type().is_file() {
+        let query = ListQuery::from_arguments(arguments)?;
+        let policy = self.policy()?;
+        let mut notes: Vec<Value> = Vec::new();
+        let mut next_cursor = None;
+        let entries = note_entries(forge_root, folder.as_deref());
+        let total = entries.len();
+        for (scanned, (rel, locked)) in entries.into_iter().enumerate() {
+            if scanned % PROGRESS_STEP == 0 && scanned > 0 {
+                control.check()?;
+                control.progress(scanned, Some(total), &format!("Scanned {scanned} notes"));
+            }
+            if query.cursor.is_some_and(|cursor| rel.as_str() <= cursor) || !policy.can_read(&rel) {
                 continue;
             }
-            let Ok(rel) = entry.path().strip_prefix(forge_root) else {
+            let Some((_, is_daily, is_weekly, _)) =
+                classify_note_path(forge_root, &forge_root.join(&rel))
+            else {
+                continue;
+            };
+            let kind = NoteKind::classify(is_daily, is_weekly);
+            let file = if locked {
+                forge_root.join(format!("{rel}.locked"))
+            } else {
+                forge_root.join(&rel)
+            };
+            let Ok(metadata) = fs::symlink_metadata(&file) else {
                 continue;
             };
-            let mut rel = rel.to_string_lossy().replace('\\', "/");
-            let locked = rel.ends_with(".md.locked");
-            if locked {
-                rel.truncate(rel.len() - ".locked".len());
-            } else if !rel.ends_with(".md") {
+            let modified = metadata.modified().ok();
+            if !query.matches(&rel, kind, modified) {
                 continue;
             }
-            if let Some(folder) = folder.as_deref() {
-                if rel != folder && !rel.starts_with(&format!("{folder}/")) {
+            if query.filters_properties() {
+                let properties = (!locked)
+                    .then(|| self.checked_existing_note(forge_root, &rel).ok())
+                    .flatten()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                    .and_then(|raw| crate::frontmatter::read_properties(&raw).ok());
+                if !properties.is_some_and(|properties| query.matches_properties(&properties)) {
                     continue;
                 }
             }
-            notes.push(json!({ "path": rel, "isLocked": locked }));
+            if notes.len() == query.limit {
+                next_cursor = notes
+                    .last()
+                    .and_then(|note| note["path"].as_str())
+                    .map(str::to_string);
+                break;
+            }
+            notes.push(json!({
+                "path": rel,
+                "isLocked": locked,
+                "kind": kind.as_str(),
+                "modified": modified.map(timestamp),
+                "size": metadata.len()
+            }));
         }
-        notes.sort_by(|a, b| a["path"].as_str().cmp(&b["path"].as_str()));
-        Ok(json!({ "notes": notes }))
+        Ok(json!({ "notes": notes, "nextCursor": next_cursor }))
     }
 
-    fn get_backlinks(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+    fn get_backlinks(
+        &self,
+        forge_root: &Path,
+        arguments: &Value,
+        control: &CallControl,
+    ) -> Result<Value, String> {
         let target_rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        self.ensure_readable(&target_rel)?;
         self.checked_existing_note(forge_root, &target_rel)?;
+        let policy = self.policy()?;
         let target_filename = Path::new(&target_rel)
             .file_name()
             .and_then(|name| name.to_str())
@@ -246,6 +780,7 @@ impl ToolContext {
         let target_stem = target_filename.trim_end_matches(".md");
         let mut seen = HashSet::new();
         let mut backlinks = Vec::new();
+        let mut scanned = 0;
         for entry in WalkDir::new(forge_root)
             .follow_links(false)
             .into_iter()
@@ -259,11 +794,16 @@ impl ToolContext {
             {
                 continue;
             }
+            scanned += 1;
+            if scanned % PROGRESS_STEP == 0 {
+                control.check()?;
+                control.progress(scanned, None, &format!("Scanned {scanned} notes"));
+            }
             let Ok(source_rel) = entry.path().strip_prefix(forge_root) else {
                 continue;
             };
             let source_rel = source_rel.to_string_lossy().replace('\\', "/");
-            if source_rel == target_rel {
+            if source_rel == target_rel || !policy.can_read(&source_rel) {
                 continue;
             }
             let Ok(raw) = fs::read_to_string(entry.path()) else {
@@ -298,20 +838,83 @@ impl ToolContext {
         Ok(json!({ "path": target_rel, "backlinks": backlinks }))
     }
 
+    fn get_properties(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        self.ensure_readable(&rel)?;
+        let path = self.checked_existing_note(forge_root, &rel)?;
+        let raw =
+            fs::read_to_string(&path).map_err(|error| format!("Failed to read note: {error}"))?;
+        let color = crate::frontmatter::parse_note(&raw).color;
+        let mut properties = serde_json::Map::new();
+        for (key, value) in crate::frontmatter::read_properties(&raw)? {
+            let key = match key {
+                serde_yaml::Value::String(key) => key,
+                other => yaml_text(&other),
+            };
+            // Tagged or otherwise JSON-incompatible YAML is reported as its
+            // YAML text rather than dropped.
+            let value =
+                serde_json::to_value(&value).unwrap_or_else(|_| Value::String(yaml_text(&value)));
+            properties.insert(key, value);
+        }
+        Ok(json!({ "path": rel, "color": color, "properties": properties }))
+    }
+
     fn create_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
         let rel = validated_new_note_path(required_string(arguments, "path")?)?;
         let content = required_string(arguments, "content")?;
-        let path = self.prepare_note_destination(forge_root, &rel)?;
+        let staging = self.staging(arguments)?;
+        self.ensure_writable(&rel)?;
+        self.create_new_note(forge_root, &rel, content, staging)
+    }
+
+    /// Expand a template the way the app does when it creates a note, dated
+    /// for the note it lands in rather than for today when that note is a
+    /// daily or weekly one.
+    fn create_note_from_template(
+        &self,
+        forge_root: &Path,
+        arguments: &Value,
+    ) -> Result<Value, String> {
+        let template_id = required_string(arguments, "template")?;
+        let rel = template_note_rel(arguments)?;
+        let date = template_date(&rel, arguments)?;
+        let staging = self.staging(arguments)?;
+        self.ensure_writable(&rel)?;
+        let template = get_template_in(&templates_dir(forge_root)?, template_id)?;
+        let content =
+            replace_template_variables_at(template.content, date.and_time(Local::now().time()));
+        let mut created = self.create_new_note(forge_root, &rel, &content, staging)?;
+        created["template"] = json!(template.id);
+        Ok(created)
+    }
+
+    /// Create `rel` holding `content`. A dry run or proposal makes the same
+    /// checks and reports the note it would create instead.
+    fn create_new_note(
+        &self,
+        forge_root: &Path,
+        rel: &str,
+        content: &str,
+        staging: Staging,
+    ) -> Result<Value, String> {
+        let path = self.prepare_note_destination(forge_root, rel, staging != Staging::Write)?;
         if locked_path(&path).exists() {
             return Err("Refusing to create a locked note".to_string());
         }
         if path.exists() {
             return Err("Note already exists; use write_note to replace it".to_string());
         }
-        self.write_agent_note(forge_root, &rel, content, || {
+        if staging != Staging::Write {
+            let mut preview = dry_run_result(rel, "", content);
+            preview["created"] = json!(true);
+            let call = json!({ "path": rel, "content": content });
+            return self.staged(forge_root, staging, preview, "create_note", &call);
+        }
+        self.write_agent_note(forge_root, rel, content, || {
             write_atomic(&path, content.as_bytes(), Some(0o600))
         })?;
-        self.note_changed(forge_root, &rel);
+        self.note_changed(forge_root, rel);
         Ok(json!({ "path": rel, "created": true }))
     }
 
@@ -319,17 +922,10 @@ impl ToolContext {
     /// full-file write can still interleave with its read-modify-write sequence.
     fn append_to_daily_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
         let content = required_string(arguments, "content")?;
-        let date = match arguments.get("date") {
-            None | Some(Value::Null) => Local::now().date_naive(),
-            Some(Value::String(date)) => {
-                NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
-                    "date must be a real calendar date in YYYY-MM-DD format".to_string()
-                })?
-            }
-            Some(_) => return Err("date must be a string".to_string()),
-        };
-        let rel = format!("daily/{}.md", date.format("%Y-%m-%d"));
-        let path = self.prepare_note_destination(forge_root, &rel)?;
+        let rel = daily_note_rel(arguments)?;
+        let staging = self.staging(arguments)?;
+        self.ensure_writable(&rel)?;
+        let path = self.prepare_note_destination(forge_root, &rel, staging != Staging::Write)?;
         if locked_path(&path).exists() {
             return Err("Refusing to append to a locked note".to_string());
         }
@@ -344,6 +940,15 @@ impl ToolContext {
             existing.push('\n');
         }
         existing.push_str(content);
+        if staging != Staging::Write {
+            let before = fs::read_to_string(&path).unwrap_or_default();
+            let mut preview = dry_run_result(&rel, &before, &existing);
+            preview["created"] = json!(created);
+            // Pin the date: accepting tomorrow must still append to this note.
+            let date = rel.trim_start_matches("daily/").trim_end_matches(".md");
+            let call = json!({ "date": date, "content": content });
+            return self.staged(forge_root, staging, preview, "append_to_daily_note", &call);
+        }
         self.write_agent_note(forge_root, &rel, &existing, || {
             write_atomic(&path, existing.as_bytes(), Some(0o600))
         })?;
@@ -355,9 +960,25 @@ impl ToolContext {
         let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
         let content = required_string(arguments, "content")?;
         let base_hash = optional_string(arguments, "baseHash")?;
+        let staging = self.staging(arguments)?;
+        self.ensure_writable(&rel)?;
         // Resolve through the permissive existing-note check: a note already on
         // disk may carry a name we would refuse to create today.
         let path = self.checked_existing_note(forge_root, &rel)?;
+        if staging != Staging::Write {
+            let (conflict, existing, serialized) =
+                preview_note_save(&path, base_hash, content, None)?;
+            let mut preview = dry_run_result(&rel, &existing, &serialized);
+            preview["conflict"] = json!(conflict);
+            // Without a baseHash, the reviewed version is the base, so edits
+            // made before the proposal is accepted survive as a conflict copy.
+            let base_hash = base_hash.map_or_else(
+                || sha256_hex(&crate::frontmatter::parse_note(&existing).body),
+                str::to_string,
+            );
+            let call = json!({ "path": rel, "content": content, "baseHash": base_hash });
+            return self.staged(forge_root, staging, preview, "write_note", &call);
+        }
         let conflict_copy = save_note_with_conflict_using(
             &path,
             base_hash,
@@ -374,6 +995,305 @@ impl ToolContext {
         Ok(json!({ "path": rel, "written": true, "conflictCopy": conflict_copy }))
     }
 
+    /// Edit one section, or one exact span, of a note body. The patch applies
+    /// to the body on disk; a stale baseHash preserves that version as a
+    /// conflict copy just as `write_note` does.
+    fn patch_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        let base_hash = optional_string(arguments, "baseHash")?;
+        let patch = Patch::from_arguments(arguments)?;
+        self.ensure_writable(&rel)?;
+        let path = self.checked_existing_note(forge_root, &rel)?;
+        let raw =
+            fs::read_to_string(&path).map_err(|error| format!("Failed to read note: {error}"))?;
+        let body = crate::frontmatter::parse_note(&raw).body;
+        let patched = patch.apply(&body)?;
+        let diff = unified_diff(&body, &patched, &format!("a/{rel}"), &format!("b/{rel}"));
+        let conflict_copy = save_note_with_conflict_using(
+            &path,
+            base_hash,
+            &patched,
+            None,
+            |path, serialized| {
+                self.write_agent_note(forge_root, &rel, serialized, || {
+                    write_atomic(path, serialized.as_bytes(), Some(0o600))
+                })
+            },
+        )?
+        .map(|(conflict_name, _)| conflict_name);
+        self.note_changed(forge_root, &rel);
+        Ok(json!({
+            "path": rel,
+            "written": true,
+            "contentHash": sha256_hex(&patched),
+            "conflictCopy": conflict_copy,
+            "diff": diff
+        }))
+    }
+
+    /// Merge typed values into a note's frontmatter. Only the named keys
+    /// change; the body, `color`, and the order of existing keys are kept.
+    fn set_properties(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        let Some(properties) = arguments.get("properties").and_then(Value::as_object) else {
+            return Err("properties must be an object".to_string());
+        };
+        let mut changes = Vec::with_capacity(properties.len());
+        for (key, value) in properties {
+            if key.trim().is_empty() {
+                return Err("Property names must not be empty".to_string());
+            }
+            let value = match value {
+                Value::Null => None,
+                value => Some(serde_yaml::to_value(value).map_err(|error| error.to_string())?),
+            };
+            changes.push((key.clone(), value));
+        }
+        self.ensure_writable(&rel)?;
+        let path = self.checked_existing_note(forge_root, &rel)?;
+        let raw =
+            fs::read_to_string(&path).map_err(|error| format!("Failed to read note: {error}"))?;
+        let merged = crate::frontmatter::merge_properties(&raw, &changes)?;
+        if merged != raw {
+            self.write_agent_note(forge_root, &rel, &merged, || {
+                write_atomic(&path, merged.as_bytes(), Some(0o600))
+            })?;
+            self.note_changed(forge_root, &rel);
+        }
+        let mut result = self.get_properties(forge_root, &json!({ "path": rel }))?;
+        result["written"] = json!(merged != raw);
+        Ok(result)
+    }
+
+    /// Rename a note in place and rewrite inbound wiki-links, exactly as the
+    /// app's rename does. Changing folders is `move_note`'s job.
+    fn rename_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        let new_name = required_string(arguments, "newName")?;
+        if new_name.contains('/') || new_name.contains('\\') {
+            return Err("newName must be a file name; use move_note to change folders".to_string());
+        }
+        self.checked_existing_note(forge_root, &rel)?;
+        let (top, rest) = note_path_parts(&rel)?;
+        let new_filename = if new_name.ends_with(".md") {
+            new_name.to_string()
+        } else {
+            format!("{new_name}.md")
+        };
+        let new_rest = match rest.rsplit_once('/') {
+            Some((parent, _)) => format!("{parent}/{new_filename}"),
+            None => new_filename,
+        };
+        let new_rel = format!("{top}/{new_rest}");
+        self.ensure_writable(&rel)?;
+        self.ensure_writable(&new_rel)?;
+        let dir = forge_root.join(top);
+        if locked_path(&dir.join(&new_rest)).exists() {
+            return Err("A locked note with this name already exists".to_string());
+        }
+        self.ensure_link_rewrites_writable(forge_root, &rel, rest, &new_rest)?;
+        self.move_agent_note(forge_root, &rel, || {
+            rename_note_in(&dir, rest, &new_rest, top == "daily", top == "weekly")?;
+            Ok(new_rel.clone())
+        })?;
+
+        // The MCP process holds no live backlinks index; the app re-reads
+        // rewritten notes through its file watcher. Each rewrite is an agent
+        // write, so restoring the session puts the old links back.
+        let scratch_index = Arc::new(BacklinksIndex::new());
+        rewrite_inbound_links_in_roots(
+            &[
+                forge_root.join("daily"),
+                forge_root.join("weekly"),
+                forge_root.join("notes"),
+            ],
+            note_ref_stem(rest),
+            note_ref_stem(&new_rest),
+            &scratch_index,
+            Some(&crate::wiki::note_name_to_filename),
+            &mut |path, rewritten| {
+                let rel = path
+                    .strip_prefix(forge_root)
+                    .map_err(|_| "Invalid note path".to_string())?
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                self.write_agent_note(forge_root, &rel, rewritten, || {
+                    write_atomic(path, rewritten.as_bytes(), Some(0o600))
+                })
+            },
+        );
+        self.note_removed(forge_root, &rel);
+        self.note_changed(forge_root, &new_rel);
+        Ok(json!({ "path": rel, "newPath": new_rel }))
+    }
+
+    /// A rename rewrites links in other notes, so each of those must be
+    /// writable by this client too, or the rename is refused up front.
+    fn ensure_link_rewrites_writable(
+        &self,
+        forge_root: &Path,
+        rel: &str,
+        old_rest: &str,
+        new_rest: &str,
+    ) -> Result<(), String> {
+        let policy = self.policy()?;
+        if !policy.restricts_writes() {
+            return Ok(());
+        }
+        for (linking, locked) in note_entries(forge_root, None) {
+            if locked || linking == rel || policy.can_write(&linking) {
+                continue;
+            }
+            let Ok(raw) = fs::read_to_string(forge_root.join(&linking)) else {
+                continue;
+            };
+            let rewrites = crate::wiki::rewrite_links_for_rename(
+                &raw,
+                note_ref_stem(old_rest),
+                note_ref_stem(new_rest),
+            );
+            if rewrites.is_some() {
+                return Err(format!(
+                    "Renaming would update links in {linking}, which this client may not write"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a standalone note to another folder under notes/. A name clash in
+    /// the destination gets the app's numbered suffix rather than an error.
+    fn move_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        let Some(note_path) = rel.strip_prefix("notes/") else {
+            return Err("Only notes under notes/ can be moved between folders".to_string());
+        };
+        let folder = validated_existing_folder(required_string(arguments, "folder")?)?;
+        let to_folder = match folder.as_str() {
+            "notes" => None,
+            folder => Some(folder.strip_prefix("notes/").ok_or_else(|| {
+                "Notes can only be moved to notes or a folder inside it".to_string()
+            })?),
+        };
+        let filename = rel.rsplit('/').next().unwrap_or(&rel);
+        self.ensure_writable(&rel)?;
+        self.ensure_writable(&format!("{folder}/{filename}"))?;
+        self.checked_existing_note(forge_root, &rel)?;
+        let standalone_dir = forge_root.join("notes");
+        if let Some(to_folder) = to_folder {
+            reject_symlink(&standalone_dir.join(to_folder))?;
+        }
+        let new_rel = self.move_agent_note(forge_root, &rel, || {
+            let (_, _, new_relative_path, _) = move_note_in(&standalone_dir, note_path, to_folder)?;
+            Ok(format!("notes/{new_relative_path}"))
+        })?;
+        if new_rel != rel {
+            self.note_removed(forge_root, &rel);
+            self.note_changed(forge_root, &new_rel);
+        }
+        Ok(json!({ "path": rel, "newPath": new_rel }))
+    }
+
+    /// Move a note to the Forge's trash with the same metadata record the app
+    /// writes, so it shows up in Trash and can be restored from there.
+    fn trash_note(&self, forge_root: &Path, arguments: &Value) -> Result<Value, String> {
+        let rel = validated_existing_note_path(required_string(arguments, "path")?)?;
+        self.ensure_writable(&rel)?;
+        self.checked_existing_note(forge_root, &rel)?;
+        let (top, rest) = note_path_parts(&rel)?;
+        let trash_dir = forge_root.join(".trash");
+        reject_symlink(&trash_dir)?;
+        fs::create_dir_all(&trash_dir)
+            .map_err(|error| format!("Failed to create trash directory: {error}"))?;
+        let item = trash_note_on_disk(
+            &forge_root.join(top),
+            &trash_dir,
+            rest,
+            top != "notes",
+            top == "daily",
+            &next_trash_id(),
+            chrono::Utc::now().timestamp(),
+        )?;
+        let trash_id = item.id.clone();
+        let metadata_path = trash_dir.join("metadata.json");
+        let mut metadata = read_trash_metadata_at(&metadata_path);
+        metadata.items.push(item);
+        write_trash_metadata_at(&metadata_path, &metadata)?;
+        self.note_removed(forge_root, &rel);
+        Ok(json!({ "path": rel, "trashed": true, "trashId": trash_id }))
+    }
+
+    /// Notes a call names, with their body hashes before it runs. Paths are
+    /// only hashed once they pass the same validation the tools apply.
+    fn audited_notes(&self, name: &str, arguments: &Value) -> (Option<PathBuf>, Vec<AuditNote>) {
+        let forge_root = self.request_forge_root(name, arguments).ok();
+        let rel = match name {
+            "append_to_daily_note" | "get_daily_note" => daily_note_rel(arguments).ok(),
+            "get_weekly_note" => weekly_note_rel(arguments).ok(),
+            "create_note_from_template" => template_note_rel(arguments).ok(),
+            _ => arguments
+                .get("path")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        };
+        let notes = rel
+            .into_iter()
+            .map(|rel| AuditNote {
+                hash_before: forge_root
+                    .as_deref()
+                    .and_then(|root| self.note_hash(root, &rel)),
+                hash_after: None,
+                path: rel,
+            })
+            .collect();
+        (forge_root, notes)
+    }
+
+    fn record_call(
+        &self,
+        log: &Path,
+        name: &str,
+        forge_root: Option<PathBuf>,
+        mut notes: Vec<AuditNote>,
+        result: &Result<Value, String>,
+    ) {
+        if let Ok(value) = result {
+            if let Some(new_rel) = value.get("newPath").and_then(Value::as_str) {
+                notes.push(AuditNote {
+                    path: new_rel.to_string(),
+                    hash_before: None,
+                    hash_after: None,
+                });
+            }
+        }
+        if let Some(root) = forge_root.as_deref() {
+            for note in &mut notes {
+                note.hash_after = self.note_hash(root, &note.path);
+            }
+        }
+        let client = self.client_name();
+        let entry = AuditEntry::new(
+            client.as_deref(),
+            name,
+            forge_root.as_deref(),
+            notes,
+            result.as_ref().err().map(String::as_str),
+        );
+        if let Err(error) = crate::mcp_audit::append_at(log, &entry) {
+            log::warn!("[mcp] audit log: {error}");
+        }
+    }
+
+    /// Body hash of a readable note in the same space as `contentHash`.
+    fn note_hash(&self, forge_root: &Path, rel: &str) -> Option<String> {
+        let rel = validated_existing_note_path(rel).ok()?;
+        let path = self.checked_existing_note(forge_root, &rel).ok()?;
+        let content = fs::read_to_string(path).ok()?;
+        Some(sha256_hex(&crate::frontmatter::parse_note(&content).body))
+    }
+
+    /// Write a note as this session: snapshot it for undo, then write it
+    /// behind an attribution marker. A failed write drops its snapshot.
     fn write_agent_note<T, F>(
         &self,
         forge_root: &Path,
@@ -384,21 +1304,73 @@ impl ToolContext {
     where
         F: FnOnce() -> Result<T, String>,
     {
-        let Some(spool) = self.agent_write_spool.as_deref() else {
-            return write();
-        };
         let client_name = self.client_name();
-        crate::agent_writes::write_with_marker_at(
-            spool,
-            forge_root,
-            rel_path,
-            raw_content,
-            client_name.as_deref(),
-            write,
-        )
+        let snapshot = match self.snapshot_spool.as_deref() {
+            Some(spool) => Some(crate::agent_snapshots::record_at(
+                spool,
+                forge_root,
+                rel_path,
+                &self.session_id,
+                client_name.as_deref(),
+                raw_content,
+            )?),
+            None => None,
+        };
+        let result = match self.agent_write_spool.as_deref() {
+            Some(spool) => crate::agent_writes::write_with_marker_at(
+                spool,
+                forge_root,
+                rel_path,
+                raw_content,
+                client_name.as_deref(),
+                write,
+            ),
+            None => write(),
+        };
+        if let (Err(_), Some(snapshot)) = (&result, snapshot) {
+            let _ = fs::remove_file(snapshot);
+        }
+        result
+    }
+
+    /// Rename or move a note as this session. `relocate` moves it and returns
+    /// its new path; the move is then recorded for undo, and reverted if it
+    /// can't be.
+    fn move_agent_note<F>(
+        &self,
+        forge_root: &Path,
+        rel_path: &str,
+        relocate: F,
+    ) -> Result<String, String>
+    where
+        F: FnOnce() -> Result<String, String>,
+    {
+        let new_rel = relocate()?;
+        let Some(spool) = self.snapshot_spool.as_deref() else {
+            return Ok(new_rel);
+        };
+        if new_rel != rel_path {
+            let client_name = self.client_name();
+            if let Err(error) = crate::agent_snapshots::record_move_at(
+                spool,
+                forge_root,
+                rel_path,
+                &new_rel,
+                &self.session_id,
+                client_name.as_deref(),
+            ) {
+                let _ = fs::rename(forge_root.join(&new_rel), forge_root.join(rel_path));
+                return Err(error);
+            }
+        }
+        Ok(new_rel)
     }
 
-    fn checked_existing_note(&self, forge_root: &Path, rel: &str) -> Result<PathBuf, String> {
+    pub(super) fn checked_existing_note(
+        &self,
+        forge_root: &Path,
+        rel: &str,
+    ) -> Result<PathBuf, String> {
         let path = forge_root.join(rel);
         if locked_path(&path).exists() {
             return Err("This note is locked and cannot be accessed through MCP".to_string());
@@ -411,39 +1383,180 @@ impl ToolContext {
         Ok(path)
     }
 
-    fn prepare_note_destination(&self, forge_root: &Path, rel: &str) -> Result<PathBuf, String> {
+    /// Validate where a note will be written and create its missing folders.
+    /// A dry run only checks that they could be created.
+    fn prepare_note_destination(
+        &self,
+        forge_root: &Path,
+        rel: &str,
+        dry_run: bool,
+    ) -> Result<PathBuf, String> {
         let path = forge_root.join(rel);
         let parent = path
             .parent()
             .ok_or_else(|| "Invalid note path".to_string())?;
-        ensure_directory_tree(forge_root, parent)?;
+        ensure_directory_tree(forge_root, parent, !dry_run)?;
         reject_symlink(&path)?;
-        validate_path_within_base(&path, forge_root)?;
+        if !dry_run || parent.is_dir() {
+            validate_path_within_base(&path, forge_root)?;
+        }
         Ok(path)
     }
 
+    /// Finish a dry run, or hold the change for review as the write tool call
+    /// `call` that accepting it will replay.
+    fn staged(
+        &self,
+        forge_root: &Path,
+        staging: Staging,
+        mut preview: Value,
+        tool: &str,
+        call: &Value,
+    ) -> Result<Value, String> {
+        if staging == Staging::DryRun {
+            return Ok(preview);
+        }
+        let spool = self
+            .proposal_spool
+            .as_deref()
+            .ok_or_else(|| "Agent proposals need a configuration folder".to_string())?;
+        let rel = preview["path"].as_str().unwrap_or_default().to_string();
+        let client_name = self.client_name();
+        let id = crate::agent_proposals::record_at(
+            spool,
+            forge_root,
+            &rel,
+            tool,
+            call,
+            client_name.as_deref(),
+        )?;
+        if let Some(preview) = preview.as_object_mut() {
+            preview.remove("dryRun");
+        }
+        preview["proposed"] = json!(true);
+        preview["proposalId"] = json!(id);
+        preview["message"] = json!(PROPOSAL_PENDING);
+        Ok(preview)
+    }
+
     fn note_changed(&self, forge_root: &Path, rel: &str) {
         crate::semantic::note_changed_in(rel, forge_root.to_path_buf());
     }
+
+    fn note_removed(&self, forge_root: &Path, rel: &str) {
+        crate::semantic::note_removed_in(rel, forge_root.to_path_buf());
+    }
 }
 
 fn read_tool_definitions() -> Vec<Value> {
-    vec![
-        tool("search_notes", "Search unlocked notes. Uses the local semantic index when it is enabled and ready, otherwise performs keyword full-text search. The response always identifies the mode used.", json!({"type":"object","properties":{"query":{"type":"string","description":"Words or meaning to search for."},"limit":{"type":"integer","minimum":1,"maximum":100,"default":20}},"required":["query"],"additionalProperties":false})),
+    let mut tools = vec![
+        tool("search_notes", "Search unlocked notes. Without a mode, plain words use the local semantic index when it is enabled and ready; otherwise, and for any query with operators or filters, this is a keyword full-text search ranked by BM25 relevance, with filename, heading, and tag matches weighted up; each result carries its score. mode hybrid runs both searches and merges them by reciprocal rank fusion, so exact terms such as ticket IDs are found alongside related notes; each hybrid result has a keyword snippet and lists the signals (keyword, semantic) that matched it. Semantic and hybrid fall back to keyword search when the semantic index isn't ready or the query has operators or filters. foldDiacritics and fuzzy loosen keyword matching, wholeWord and regex tighten it; with any of them set and no mode, the search is keyword. Each result lists the byte spans of every match on its line. The response always identifies the mode used.", json!({"type":"object","properties":{"query":{"type":"string","description":"Words or meaning to search for. Keyword queries accept \"quoted phrases\", AND/OR/NOT with parentheses, and the filters tag:, folder:, color:, is:daily|weekly|standalone, created: and modified: (a YYYY-MM-DD day, A..B range, or >=/<= bound), and prop:key=value for frontmatter properties."},"mode":{"type":"string","enum":["keyword","semantic","hybrid"],"description":"Which search to run. Omit to use semantic search for plain words when it is available."},"foldDiacritics":{"type":"boolean","default":false,"description":"Ignore accents and similar marks in keyword matching, so cafe finds Café and strasse finds Straße."},"fuzzy":{"type":"boolean","default":false,"description":"Let each keyword of three or more letters match a word one typo away, or two for words longer than five letters."},"regex":{"type":"boolean","default":false,"description":"Read the whole query as one case-insensitive regular expression, without operators or filters. Patterns are limited to 1000 characters and run in linear time."},"wholeWord":{"type":"boolean","default":false,"description":"Only match at word boundaries, so log doesn't find login."},"limit":{"type":"integer","minimum":1,"maximum":100,"default":20}},"required":["query"],"additionalProperties":false})),
+        tool("run_saved_search", "Run one of the Forge's saved searches by name, with the query, mode, and match options it was saved with in Moldavite. Results have the same shape as search_notes, plus the saved search that ran. An unknown name is an error listing the saved searches.", json!({"type":"object","properties":{"name":{"type":"string","description":"The saved search's name, ignoring case, or its id."},"limit":{"type":"integer","minimum":1,"maximum":100,"default":20}},"required":["name"],"additionalProperties":false})),
         tool("read_note", "Read one unlocked Markdown note using a Forge-relative path such as daily/2026-07-12.md or notes/Projects/foo.md.", note_path_schema(true)),
-        tool("list_notes", "List notes and locked-note placeholders, optionally restricted to a Forge-relative folder such as daily, notes, or notes/Projects.", json!({"type":"object","properties":{"folder":{"type":"string","description":"Optional Forge-relative folder."}},"additionalProperties":false})),
+        tool("get_daily_note", "Read the daily note for a date, defaulting to today. A note that does not exist yet is reported with exists false rather than as an error.", json!({"type":"object","properties":{"date":{"type":"string","format":"date","description":"Optional YYYY-MM-DD date; defaults to today."}},"additionalProperties":false})),
+        tool("get_weekly_note", "Read the weekly note for an ISO week, or for the week containing a date, defaulting to the current week. A note that does not exist yet is reported with exists false rather than as an error.", json!({"type":"object","properties":{"week":{"type":"string","description":"Optional ISO week such as 2026-W07."},"date":{"type":"string","format":"date","description":"Optional YYYY-MM-DD date whose ISO week to read; pass either week or date."}},"additionalProperties":false})),
+        tool("list_notes", "List notes and locked-note placeholders in path order with their kind, modification time, and size in bytes. Results are paged: when nextCursor is not null, pass it as cursor to get the next page. Filters combine: folder, kind, modifiedSince, a dateFrom/dateTo range over daily notes, and a frontmatter property (key, optionally with a value the property must equal or, for lists, contain). Locked notes never match a property filter.", json!({"type":"object","properties":{"folder":{"type":"string","description":"Optional Forge-relative folder such as daily, notes, or notes/Projects."},"kind":{"type":"string","enum":["daily","weekly","standalone"]},"modifiedSince":{"type":"string","description":"RFC 3339 timestamp, or YYYY-MM-DD for local midnight."},"dateFrom":{"type":"string","format":"date","description":"Earliest daily note date, inclusive."},"dateTo":{"type":"string","format":"date","description":"Latest daily note date, inclusive."},"property":{"type":"object","properties":{"key":{"type":"string"},"value":{"description":"Optional value to match."}},"required":["key"],"additionalProperties":false},"cursor":{"type":"string","description":"nextCursor from the previous page."},"limit":{"type":"integer","minimum":1,"maximum":1000,"default":200}},"additionalProperties":false})),
         tool("get_backlinks", "Find unlocked notes that contain wiki-links to the specified note.", note_path_schema(true)),
-    ]
+        tool("get_properties", "Read the frontmatter properties of an unlocked note as typed JSON values, plus its color. Properties are every frontmatter key except color.", note_path_schema(true)),
+    ];
+    // Every per-Forge read tool can target another Forge by name.
+    for tool in &mut tools {
+        tool["inputSchema"]["properties"]["forge"] = json!({"type":"string","description":"Optional Forge name from list_forges; defaults to the server's Forge."});
+    }
+    tools.push(tool("list_forges", "List the Forges this client can read, marking the default one that tools use when no forge argument is given.", json!({"type":"object","properties":{},"additionalProperties":false})));
+    tools.push(tool("search_all_forges", "Keyword full-text search across every Forge this client can read. Results are merged by relevance score and each names its forge, so follow-up reads can pass it as the forge argument.", json!({"type":"object","properties":{"query":{"type":"string","description":"Words to search for, in the same query syntax as search_notes."},"foldDiacritics":{"type":"boolean","default":false,"description":"Ignore accents and similar marks in keyword matching, so cafe finds Café and strasse finds Straße."},"fuzzy":{"type":"boolean","default":false,"description":"Let each keyword of three or more letters match a word one typo away, or two for words longer than five letters."},"regex":{"type":"boolean","default":false,"description":"Read the whole query as one case-insensitive regular expression, without operators or filters. Patterns are limited to 1000 characters and run in linear time."},"wholeWord":{"type":"boolean","default":false,"description":"Only match at word boundaries, so log doesn't find login."},"limit":{"type":"integer","minimum":1,"maximum":100,"default":20}},"required":["query"],"additionalProperties":false})));
+    tools
 }
 
 fn write_tool_definitions() -> Vec<Value> {
     vec![
-        tool("create_note", "Create a new Markdown note. Refuses to overwrite an existing or locked note.", content_path_schema()),
-        tool("append_to_daily_note", "Append Markdown to a daily note, creating it when absent. Defaults to today's local date.", json!({"type":"object","properties":{"content":{"type":"string","description":"Markdown to append."},"date":{"type":"string","format":"date","description":"Optional YYYY-MM-DD date; defaults to today."}},"required":["content"],"additionalProperties":false})),
-        tool("write_note", "Fully replace an existing unlocked Markdown note. Pass read_note's contentHash as baseHash to preserve a changed disk version as a conflict copy before replacement. The response's conflictCopy is that sibling filename when a conflict was preserved, or null after a clean write. Omitting baseHash keeps legacy overwrite behavior. Refuses missing and locked notes.", content_path_schema()),
+        tool("create_note", "Create a new Markdown note. Refuses to overwrite an existing or locked note. Pass dryRun to preview the note without creating it.", content_path_schema()),
+        tool("create_note_from_template", "Create a new note from a built-in or Forge template, expanding {{date}}, {{time}}, and {{day_of_week}}. Template ids are the prompt names from prompts/list. Without a path, creates the daily note for date. Daily and weekly notes expand for the day they stand for unless date is given. Refuses to overwrite an existing or locked note. Pass dryRun to preview the note without creating it.", json!({"type":"object","properties":{"template":{"type":"string","description":"Template id such as meeting-notes."},"path":{"type":"string","description":"Optional Forge-relative .md path for the new note, such as weekly/2026-W07.md or notes/Meetings/standup.md."},"date":{"type":"string","format":"date","description":"Optional YYYY-MM-DD date for the template variables and, without a path, the daily note to create."},"dryRun":{"type":"boolean","default":false,"description":"Run every check and return the diff and resulting contentHash without writing."}},"required":["template"],"additionalProperties":false})),
+        tool("append_to_daily_note", "Append Markdown to a daily note, creating it when absent. Defaults to today's local date. Pass dryRun to preview the change as a diff without writing.", json!({"type":"object","properties":{"content":{"type":"string","description":"Markdown to append."},"date":{"type":"string","format":"date","description":"Optional YYYY-MM-DD date; defaults to today."},"dryRun":{"type":"boolean","default":false,"description":"Run every check and return the diff and resulting contentHash without writing."}},"required":["content"],"additionalProperties":false})),
+        tool("patch_note", "Edit part of an existing unlocked note without resending all of it. operation insert puts content directly below a heading, append adds it at the end of that heading's section, replace swaps the section's contents, and findReplace replaces exact text that must occur once unless replaceAll is true. heading may include its # prefix to pick a level. Pass read_note's contentHash as baseHash to preserve a changed disk version as a conflict copy. Returns the new contentHash and a unified diff of the change.", json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative .md note path."},"operation":{"type":"string","enum":["insert","append","replace","findReplace"]},"heading":{"type":"string","description":"Heading text for insert, append, and replace, such as Tasks or ## Tasks."},"content":{"type":"string","description":"Markdown for insert, append, and replace."},"find":{"type":"string","description":"Exact text to replace for findReplace."},"replace":{"type":"string","description":"Replacement text for findReplace."},"replaceAll":{"type":"boolean","default":false,"description":"Replace every occurrence of find instead of requiring exactly one."},"baseHash":{"type":"string","description":"The contentHash returned by read_note."}},"required":["path","operation"],"additionalProperties":false})),
+        tool("set_properties", "Merge frontmatter properties into an unlocked note without touching its body. Each key in properties is set to its JSON value (string, number, boolean, array, or object); null removes the key. Other keys, their order, and the note color are kept. color itself cannot be set here.", json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative .md note path."},"properties":{"type":"object","description":"Property names mapped to new values; null removes a property.","additionalProperties":true}},"required":["path","properties"],"additionalProperties":false})),
+        tool("rename_note", "Rename an unlocked note within its folder and rewrite [[wiki-links]] to it in every other note. newName is a file name; the .md extension is optional. Refuses to overwrite an existing or locked note.", json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative path of the note to rename."},"newName":{"type":"string","description":"New file name, without folders."}},"required":["path","newName"],"additionalProperties":false})),
+        tool("move_note", "Move an unlocked note under notes/ into another existing folder under notes/. A name clash in the destination gets a numbered suffix; the response reports the final newPath.", json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative path under notes/."},"folder":{"type":"string","description":"Destination folder: notes, or notes/<folder>."}},"required":["path","folder"],"additionalProperties":false})),
+        tool("trash_note", "Move an unlocked note to the Forge's trash, where it can be restored from the app for seven days.", note_path_schema(true)),
+        tool("write_note", "Fully replace an existing unlocked Markdown note. Pass read_note's contentHash as baseHash to preserve a changed disk version as a conflict copy before replacement. The response's conflictCopy is that sibling filename when a conflict was preserved, or null after a clean write. Omitting baseHash keeps legacy overwrite behavior. Refuses missing and locked notes. With dryRun nothing is written; the response has a unified diff against the file on disk, the resulting contentHash, and conflict, which is true when the write would preserve a conflict copy.", content_path_schema()),
     ]
 }
 
+/// Walk the note folders of a Forge and return every Markdown note as a sorted
+/// `(Forge-relative path, is_locked)` pair. Locked notes are reported under the
+/// path they would have when unlocked; hidden folders are never entered.
+pub(super) fn note_entries(forge_root: &Path, folder: Option<&str>) -> Vec<(String, bool)> {
+    let mut notes = Vec::new();
+    for entry in WalkDir::new(forge_root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || (!entry.file_name().to_string_lossy().starts_with('.')
+                    && (entry.depth() > 1
+                        || matches!(
+                            entry.file_name().to_string_lossy().as_ref(),
+                            "daily" | "weekly" | "notes"
+                        )))
+        })
+        .flatten()
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(rel) = entry.path().strip_prefix(forge_root) else {
+            continue;
+        };
+        let mut rel = rel.to_string_lossy().replace('\\', "/");
+        let locked = rel.ends_with(".md.locked");
+        if locked {
+            rel.truncate(rel.len() - ".locked".len());
+        } else if !rel.ends_with(".md") {
+            continue;
+        }
+        if let Some(folder) = folder {
+            if rel != folder && !rel.starts_with(&format!("{folder}/")) {
+                continue;
+            }
+        }
+        notes.push((rel, locked));
+    }
+    notes.sort();
+    notes
+}
+
+#[cfg(test)]
+fn write_mode_of(writes_enabled: bool) -> Arc<dyn Fn() -> McpWriteMode + Send + Sync> {
+    let mode = if writes_enabled {
+        McpWriteMode::Direct
+    } else {
+        McpWriteMode::Off
+    };
+    Arc::new(move || mode)
+}
+
+#[cfg(test)]
+fn no_policies() -> PolicySource {
+    Arc::new(|| Ok(BTreeMap::new()))
+}
+
+#[cfg(test)]
+fn no_named_forges() -> NamedForgeResolver {
+    Arc::new(|name| Err(format!("Forge '{name}' does not exist")))
+}
+
+#[cfg(test)]
+fn no_forge_list() -> ForgeLister {
+    Arc::new(|| Ok(Vec::new()))
+}
+
+fn yaml_text(value: &serde_yaml::Value) -> String {
+    serde_yaml::to_string(value)
+        .unwrap_or_default()
+        .trim_end()
+        .to_string()
+}
+
 fn tool(name: &str, description: &str, input_schema: Value) -> Value {
     json!({ "name": name, "description": description, "inputSchema": input_schema })
 }
@@ -454,7 +1567,26 @@ fn note_path_schema(required: bool) -> Value {
 }
 
 fn content_path_schema() -> Value {
-    json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative .md note path."},"content":{"type":"string","description":"Complete Markdown file content."},"baseHash":{"type":"string","description":"For write_note, the contentHash returned by read_note. Omit it to keep legacy overwrite behavior."}},"required":["path","content"],"additionalProperties":false})
+    json!({"type":"object","properties":{"path":{"type":"string","description":"Forge-relative .md note path."},"content":{"type":"string","description":"Complete Markdown file content."},"baseHash":{"type":"string","description":"For write_note, the contentHash returned by read_note. Omit it to keep legacy overwrite behavior."},"dryRun":{"type":"boolean","default":false,"description":"Run every check and return the diff and resulting contentHash without writing."}},"required":["path","content"],"additionalProperties":false})
+}
+
+/// Whether a proposable write tool writes, previews, or proposes its change.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Staging {
+    Write,
+    DryRun,
+    Propose,
+}
+
+/// What a write would do, for `dryRun` and proposals: a unified diff from the current file
+/// and the `contentHash` the note would have afterwards.
+fn dry_run_result(rel: &str, before: &str, after: &str) -> Value {
+    json!({
+        "path": rel,
+        "dryRun": true,
+        "diff": unified_diff(before, after, &format!("a/{rel}"), &format!("b/{rel}")),
+        "contentHash": sha256_hex(&crate::frontmatter::parse_note(after).body)
+    })
 }
 
 fn tool_result(value: Value, is_error: bool) -> Value {
@@ -481,6 +1613,24 @@ fn optional_string<'a>(arguments: &'a Value, name: &str) -> Result<Option<&'a st
     }
 }
 
+fn optional_bool(arguments: &Value, name: &str) -> Result<bool, String> {
+    match arguments.get(name) {
+        None | Some(Value::Null) => Ok(false),
+        Some(Value::Bool(value)) => Ok(*value),
+        Some(_) => Err(format!("{name} must be a boolean")),
+    }
+}
+
+/// The keyword matching options shared by the search tools.
+fn search_options(arguments: &Value) -> Result<SearchOptions, String> {
+    Ok(SearchOptions {
+        fold_diacritics: optional_bool(arguments, "foldDiacritics")?,
+        fuzzy: optional_bool(arguments, "fuzzy")?,
+        regex: optional_bool(arguments, "regex")?,
+        whole_word: optional_bool(arguments, "wholeWord")?,
+    })
+}
+
 fn optional_u32(arguments: &Value, name: &str) -> Result<Option<u32>, String> {
     match arguments.get(name) {
         None | Some(Value::Null) => Ok(None),
@@ -502,7 +1652,79 @@ fn note_path_parts(path: &str) -> Result<(&str, &str), String> {
     Ok((top, rest))
 }
 
-fn validated_existing_note_path(path: &str) -> Result<String, String> {
+/// The `date` argument, or today.
+fn requested_date(arguments: &Value) -> Result<NaiveDate, String> {
+    match arguments.get("date") {
+        None | Some(Value::Null) => Ok(Local::now().date_naive()),
+        Some(Value::String(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| "date must be a real calendar date in YYYY-MM-DD format".to_string()),
+        Some(_) => Err("date must be a string".to_string()),
+    }
+}
+
+/// The daily note a call targets: `date`, or today.
+fn daily_note_rel(arguments: &Value) -> Result<String, String> {
+    let date = requested_date(arguments)?;
+    Ok(format!("daily/{}.md", date.format("%Y-%m-%d")))
+}
+
+/// The weekly note a call targets: the ISO `week`, the week containing
+/// `date`, or the current week. Weekly notes are named `YYYY-Www.md`.
+fn weekly_note_rel(arguments: &Value) -> Result<String, String> {
+    let week = match optional_string(arguments, "week")? {
+        Some(_) if !matches!(arguments.get("date"), None | Some(Value::Null)) => {
+            return Err("Pass either week or date, not both".to_string())
+        }
+        Some(week) => {
+            if week_monday(week).is_none() {
+                return Err("week must be a real ISO week in YYYY-Www format".to_string());
+            }
+            week.to_string()
+        }
+        None => {
+            let week = requested_date(arguments)?.iso_week();
+            format!("{:04}-W{:02}", week.year(), week.week())
+        }
+    };
+    Ok(format!("weekly/{week}.md"))
+}
+
+/// The Monday of an ISO week stem such as `2026-W07`, when the week exists.
+fn week_monday(stem: &str) -> Option<NaiveDate> {
+    if !is_week_stem(stem) {
+        return None;
+    }
+    let year = stem[..4].parse().ok()?;
+    let week = stem[6..].parse().ok()?;
+    NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+}
+
+/// The note `create_note_from_template` creates: `path`, or the daily note
+/// for `date` when no path is given.
+fn template_note_rel(arguments: &Value) -> Result<String, String> {
+    match optional_string(arguments, "path")? {
+        Some(path) => validated_new_note_path(path),
+        None => daily_note_rel(arguments),
+    }
+}
+
+/// The day a template expands for: `date`, else the day a daily note or the
+/// Monday of the week a weekly note stands for, else today.
+fn template_date(rel: &str, arguments: &Value) -> Result<NaiveDate, String> {
+    if !matches!(arguments.get("date"), None | Some(Value::Null)) {
+        return requested_date(arguments);
+    }
+    let name = rel.rsplit('/').next().unwrap_or(rel);
+    let stem = name.trim_end_matches(".md");
+    let date = match rel.split_once('/') {
+        Some(("daily", _)) => NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok(),
+        Some(("weekly", _)) => week_monday(stem),
+        _ => None,
+    };
+    Ok(date.unwrap_or_else(|| Local::now().date_naive()))
+}
+
+pub(super) fn validated_existing_note_path(path: &str) -> Result<String, String> {
     let (top, rest) = note_path_parts(path)?;
     let valid = if top == "notes" {
         is_safe_existing_note_path(rest)
@@ -555,7 +1777,7 @@ fn reject_symlink(path: &Path) -> Result<(), String> {
     Ok(())
 }
 
-fn ensure_directory_tree(base: &Path, destination: &Path) -> Result<(), String> {
+fn ensure_directory_tree(base: &Path, destination: &Path, create: bool) -> Result<(), String> {
     let relative = destination
         .strip_prefix(base)
         .map_err(|_| "Path traversal attempt detected".to_string())?;
@@ -578,7 +1800,7 @@ fn ensure_directory_tree(base: &Path, destination: &Path) -> Result<(), String>
             if metadata.file_type().is_symlink() || !metadata.is_dir() {
                 return Err("Refusing to traverse an unsafe note folder".to_string());
             }
-        } else {
+        } else if create {
             fs::create_dir(&current)
                 .map_err(|error| format!("Failed to create note folder: {error}"))?;
             #[cfg(unix)]
@@ -0,0 +1,332 @@
+//! Append-only audit log of MCP tool calls.
+//!
+//! Every `tools/call` appends one JSON line to `mcp-audit.jsonl` beside the app
+//! config: when it happened, which client asked, the tool, the notes it named
+//! with their body hashes before and after, and whether it succeeded. The file
+//! is owner-only and opened without following symlinks. Once it reaches
+//! [`MAX_LOG_BYTES`] it is rotated to `mcp-audit.1.jsonl`, keeping
+//! [`ROTATED_LOGS`] older generations, so the log stays bounded without ever
+//! being rewritten in place.
+//!
+//! Logging is best-effort: a call that cannot be recorded still runs. Several
+//! MCP processes may append concurrently; each entry is a single write, and a
+//! rotation race can at worst drop one older generation early.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::persist::open_append_private;
+
+const LOG_FILE_STEM: &str = "mcp-audit";
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+const ROTATED_LOGS: usize = 3;
+/// Lines longer than this are not ours and are skipped when paging.
+const MAX_ENTRY_BYTES: usize = 64 * 1024;
+const MAX_FIELD_CHARS: usize = 500;
+const DEFAULT_PAGE_SIZE: usize = 50;
+const MAX_PAGE_SIZE: usize = 500;
+
+static ENTRY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// One note a call named, with its body hash before and after the call.
+/// A hash is `None` when the note did not exist or could not be read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditNote {
+    pub(crate) path: String,
+    pub(crate) hash_before: Option<String>,
+    pub(crate) hash_after: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditEntry {
+    /// Sorts in recording order across processes; also the paging cursor.
+    pub(crate) id: String,
+    pub(crate) timestamp_ms: u64,
+    pub(crate) client: Option<String>,
+    pub(crate) tool: String,
+    pub(crate) forge: Option<String>,
+    pub(crate) notes: Vec<AuditNote>,
+    pub(crate) ok: bool,
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditPage {
+    pub(crate) entries: Vec<AuditEntry>,
+    /// Pass back as `before` to fetch the next older page.
+    pub(crate) next_cursor: Option<String>,
+}
+
+/// Resolve the log beside the app config without an initialized Tauri app.
+pub(crate) fn log_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("Moldavite").join(format!("{LOG_FILE_STEM}.jsonl")))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis()
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+fn entry_id(timestamp_ms: u64) -> String {
+    let counter = ENTRY_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!(
+        "{timestamp_ms:016x}-{:08x}-{counter:016x}",
+        std::process::id()
+    )
+}
+
+fn clipped(text: &str) -> String {
+    text.chars().take(MAX_FIELD_CHARS).collect()
+}
+
+impl AuditEntry {
+    /// A new entry stamped now. Free-form fields are clipped so a hostile
+    /// client cannot make one line arbitrarily large.
+    pub(crate) fn new(
+        client: Option<&str>,
+        tool: &str,
+        forge: Option<&Path>,
+        notes: Vec<AuditNote>,
+        error: Option<&str>,
+    ) -> Self {
+        let timestamp_ms = now_ms();
+        Self {
+            id: entry_id(timestamp_ms),
+            timestamp_ms,
+            client: crate::agent_writes::normalized_client(client),
+            tool: clipped(tool),
+            forge: forge.map(|root| root.to_string_lossy().into_owned()),
+            notes: notes
+                .into_iter()
+                .map(|note| AuditNote {
+                    path: clipped(&note.path),
+                    ..note
+                })
+                .collect(),
+            ok: error.is_none(),
+            error: error.map(clipped),
+        }
+    }
+}
+
+fn rotated_path(log: &Path, generation: usize) -> PathBuf {
+    log.with_file_name(format!("{LOG_FILE_STEM}.{generation}.jsonl"))
+}
+
+fn rotate_if_full(log: &Path) -> Result<(), String> {
+    match fs::symlink_metadata(log) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            return Err("Refusing a symlinked MCP audit log".to_string());
+        }
+        Ok(metadata) if metadata.len() < MAX_LOG_BYTES => return Ok(()),
+        Ok(_) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(format!("Failed to inspect MCP audit log: {error}")),
+    }
+    for generation in (1..ROTATED_LOGS).rev() {
+        let _ = fs::rename(
+            rotated_path(log, generation),
+            rotated_path(log, generation + 1),
+        );
+    }
+    match fs::rename(log, rotated_path(log, 1)) {
+        Ok(()) => Ok(()),
+        // Another process rotated it first.
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(format!("Failed to rotate MCP audit log: {error}")),
+    }
+}
+
+/// Append `entry` as one line, rotating first when the log is full.
+pub(crate) fn append_at(log: &Path, entry: &AuditEntry) -> Result<(), String> {
+    if let Some(parent) = log.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create MCP audit log directory: {error}"))?;
+    }
+    rotate_if_full(log)?;
+    let mut line = serde_json::to_vec(entry)
+        .map_err(|error| format!("Failed to encode MCP audit entry: {error}"))?;
+    line.push(b'\n');
+    let mut file = open_append_private(log)
+        .map_err(|error| format!("Failed to open MCP audit log: {error}"))?;
+    file.write_all(&line)
+        .map_err(|error| format!("Failed to write MCP audit log: {error}"))
+}
+
+fn read_entries(path: &Path, into: &mut Vec<AuditEntry>) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return;
+    };
+    if !metadata.is_file() {
+        return;
+    }
+    let Ok(raw) = fs::read_to_string(path) else {
+        return;
+    };
+    into.extend(
+        raw.lines()
+            .filter(|line| line.len() <= MAX_ENTRY_BYTES)
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok()),
+    );
+}
+
+/// Newest-first page of entries older than the `before` cursor and, when
+/// given, recorded at or after `since_ms`. Unparseable lines are skipped.
+pub(crate) fn page_at(
+    log: &Path,
+    before: Option<&str>,
+    since_ms: Option<u64>,
+    limit: usize,
+) -> AuditPage {
+    let mut entries = Vec::new();
+    read_entries(log, &mut entries);
+    for generation in 1..=ROTATED_LOGS {
+        read_entries(&rotated_path(log, generation), &mut entries);
+    }
+    entries.retain(|entry| {
+        before.map_or(true, |cursor| entry.id.as_str() < cursor)
+            && since_ms.map_or(true, |since| entry.timestamp_ms >= since)
+    });
+    entries.sort_by(|a, b| b.id.cmp(&a.id));
+    let more = entries.len() > limit;
+    entries.truncate(limit);
+    let next_cursor = more
+        .then(|| entries.last().map(|entry| entry.id.clone()))
+        .flatten();
+    AuditPage {
+        entries,
+        next_cursor,
+    }
+}
+
+#[tauri::command]
+pub(crate) fn list_mcp_audit_entries(
+    before: Option<String>,
+    since_ms: Option<u64>,
+    limit: Option<u32>,
+) -> AuditPage {
+    let limit = limit
+        .map_or(DEFAULT_PAGE_SIZE, |limit| limit as usize)
+        .clamp(1, MAX_PAGE_SIZE);
+    match log_path() {
+        Some(log) => page_at(&log, before.as_deref(), since_ms, limit),
+        None => AuditPage {
+            entries: Vec::new(),
+            next_cursor: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(tag: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "moldavite-mcp-audit-{tag}-{}-{}",
+                std::process::id(),
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_nanos()
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry(tool: &str, error: Option<&str>) -> AuditEntry {
+        AuditEntry::new(Some("Claude Code"), tool, None, Vec::new(), error)
+    }
+
+    #[test]
+    fn entries_append_owner_only_and_page_newest_first() {
+        let temp = TempDir::new("page");
+        let log = temp.path().join("mcp-audit.jsonl");
+        for index in 0..5 {
+            append_at(&log, &entry(&format!("tool_{index}"), None)).unwrap();
+        }
+        append_at(&log, &entry("write_note", Some("Note not found"))).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&log)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                fs::metadata(&log).unwrap().permissions().mode() & 0o777,
+                0o600
+            );
+        }
+        let first = page_at(&log, None, None, 4);
+        let tools: Vec<&str> = first.entries.iter().map(|e| e.tool.as_str()).collect();
+        assert_eq!(tools, ["write_note", "tool_4", "tool_3", "tool_2"]);
+        assert!(!first.entries[0].ok);
+        assert_eq!(first.entries[0].error.as_deref(), Some("Note not found"));
+        let second = page_at(&log, first.next_cursor.as_deref(), None, 4);
+        let tools: Vec<&str> = second.entries.iter().map(|e| e.tool.as_str()).collect();
+        assert_eq!(tools, ["tool_1", "tool_0"]);
+        assert_eq!(second.next_cursor, None);
+        assert!(page_at(&log, None, Some(u64::MAX), 10).entries.is_empty());
+    }
+
+    #[test]
+    fn full_logs_rotate_and_old_generations_stay_readable() {
+        let temp = TempDir::new("rotate");
+        let log = temp.path().join("mcp-audit.jsonl");
+        append_at(&log, &entry("first", None)).unwrap();
+        let file = fs::OpenOptions::new().append(true).open(&log).unwrap();
+        file.set_len(MAX_LOG_BYTES).unwrap();
+        append_at(&log, &entry("second", None)).unwrap();
+
+        assert!(rotated_path(&log, 1).is_file());
+        assert!(fs::metadata(&log).unwrap().len() < 1024);
+        let tools: Vec<String> = page_at(&log, None, None, 10)
+            .entries
+            .into_iter()
+            .map(|entry| entry.tool)
+            .collect();
+        assert_eq!(tools, ["second", "first"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_log_is_refused() {
+        let temp = TempDir::new("symlink");
+        let target = temp.path().join("elsewhere");
+        fs::write(&target, "keep").unwrap();
+        let log = temp.path().join("mcp-audit.jsonl");
+        std::os::unix::fs::symlink(&target, &log).unwrap();
+        assert!(append_at(&log, &entry("read_note", None)).is_err());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "keep");
+    }
+}
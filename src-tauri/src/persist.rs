@@ -7,6 +7,7 @@
 //! replacement; unique temp names keep concurrent writers isolated. Failed
 //! writes remove their temp file and leave the previous destination intact.
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -18,7 +19,7 @@ use rand::RngCore;
 use regex::Regex;
 
 use crate::paths::{ensure_trash_dir, get_config_path, get_trash_metadata_path};
-use crate::types::{AppConfig, TrashMetadata};
+use crate::types::{AppConfig, McpClientPolicy, TrashMetadata};
 
 lazy_static! {
     /// Matches a trailing " (N)" counter on a name.
@@ -117,6 +118,25 @@ fn open_atomic_temp(
     ))
 }
 
+/// Open `path` for appending, creating it owner-only. A symlink at `path` is
+/// refused on Unix rather than followed.
+pub(crate) fn open_append_private(path: &Path) -> std::io::Result<fs::File> {
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600).custom_flags(O_NOFOLLOW_FLAG);
+    }
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
 /// Atomically replace `path` after writing a securely created same-directory temp file.
 pub(crate) fn write_atomic_with<F>(
     path: &Path,
@@ -186,6 +206,30 @@ pub(crate) fn read_config() -> AppConfig {
     AppConfig::default()
 }
 
+/// Read only the MCP client policies, failing instead of defaulting when they
+/// are present but malformed. [`read_config`] falls back to an empty config on
+/// any parse error, which for policies would silently lift every restriction.
+pub(crate) fn read_mcp_client_policies() -> Result<BTreeMap<String, McpClientPolicy>, String> {
+    read_mcp_client_policies_at(&get_config_path())
+}
+
+fn read_mcp_client_policies_at(
+    config_path: &Path,
+) -> Result<BTreeMap<String, McpClientPolicy>, String> {
+    let content = match fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(error) => return Err(format!("Failed to read MCP client policies: {error}")),
+    };
+    let config: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|error| format!("Failed to read MCP client policies: {error}"))?;
+    match config.get("mcpClientPolicies") {
+        None | Some(serde_json::Value::Null) => Ok(BTreeMap::new()),
+        Some(policies) => serde_json::from_value(policies.clone())
+            .map_err(|error| format!("Invalid MCP client policies in config: {error}")),
+    }
+}
+
 pub(crate) fn write_config(config: &AppConfig) -> Result<(), String> {
     let config_path = get_config_path();
 
@@ -202,9 +246,14 @@ pub(crate) fn write_config(config: &AppConfig) -> Result<(), String> {
 }
 
 pub(crate) fn read_trash_metadata() -> TrashMetadata {
-    let metadata_path = get_trash_metadata_path();
+    read_trash_metadata_at(&get_trash_metadata_path())
+}
+
+/// Read a Forge's trash metadata from an explicit path. MCP uses this for the
+/// Forge a request resolved to rather than the app's active one.
+pub(crate) fn read_trash_metadata_at(metadata_path: &Path) -> TrashMetadata {
     if metadata_path.exists() {
-        if let Ok(content) = fs::read_to_string(&metadata_path) {
+        if let Ok(content) = fs::read_to_string(metadata_path) {
             if let Ok(metadata) = serde_json::from_str::<TrashMetadata>(&content) {
                 return metadata;
             }
@@ -215,9 +264,15 @@ pub(crate) fn read_trash_metadata() -> TrashMetadata {
 
 pub(crate) fn write_trash_metadata(metadata: &TrashMetadata) -> Result<(), String> {
     ensure_trash_dir()?;
-    let metadata_path = get_trash_metadata_path();
+    write_trash_metadata_at(&get_trash_metadata_path(), metadata)
+}
+
+pub(crate) fn write_trash_metadata_at(
+    metadata_path: &Path,
+    metadata: &TrashMetadata,
+) -> Result<(), String> {
     let json = serde_json::to_string_pretty(metadata).map_err(|e| e.to_string())?;
-    write_atomic(&metadata_path, json.as_bytes(), Some(0o600))
+    write_atomic(metadata_path, json.as_bytes(), Some(0o600))
         .map_err(|e| format!("Failed to write trash metadata: {}", e))?;
     Ok(())
 }
@@ -540,4 +595,37 @@ mod tests {
         let name = generate_unique_folder_name(tmp.path(), "projects");
         assert_eq!(name, "projects (2)");
     }
+
+    #[test]
+    fn mcp_client_policies_fail_closed_when_malformed() {
+        let tmp = TempDir::new("mcp-policies");
+        let config_path = tmp.path().join("config.json");
+        assert!(read_mcp_client_policies_at(&config_path)
+            .unwrap()
+            .is_empty());
+
+        fs::write(&config_path, r#"{"activeForge":"Main"}"#).unwrap();
+        assert!(read_mcp_client_policies_at(&config_path)
+            .unwrap()
+            .is_empty());
+
+        fs::write(
+            &config_path,
+            r#"{"mcpClientPolicies":{"*":{"readDeny":["notes/Private"],"pinnedForge":"Main"}}}"#,
+        )
+        .unwrap();
+        let policies = read_mcp_client_policies_at(&config_path).unwrap();
+        assert_eq!(policies["*"].read_deny, ["notes/Private"]);
+        assert_eq!(policies["*"].pinned_forge.as_deref(), Some("Main"));
+
+        // A typo must not turn into "no restrictions", as read_config would.
+        fs::write(
+            &config_path,
+            r#"{"mcpClientPolicies":{"*":{"readDeny":"notes/Private"}}}"#,
+        )
+        .unwrap();
+        assert!(read_mcp_client_policies_at(&config_path).is_err());
+        fs::write(&config_path, "{not json").unwrap();
+        assert!(read_mcp_client_policies_at(&config_path).is_err());
+    }
 }
@@ -0,0 +1,1171 @@
+//! Persistent full-text index over a Forge's notes.
+//!
+//! An inverted index from lowercase alphanumeric terms to the notes containing
+//! them, saved at `<forge>/.index/fulltext.v2.bin` next to the semantic index.
+//! It only narrows keyword search to candidate notes: matches, counts, and
+//! snippets still come from the notes on disk, so a stale entry can cost a
+//! missed hit but never produce a wrong one.
+//!
+//! # Updates
+//!
+//! The note-command hooks that drive semantic indexing
+//! ([`crate::semantic::note_changed`] and friends) refresh this index too,
+//! synchronously, so a search right after a save already sees it. Edits made
+//! outside Moldavite arrive through the Forge watcher in the app, and through
+//! the index's own watcher in MCP mode. When a Forge is opened its saved index
+//! is reconciled against the notes by content hash, so only notes whose body
+//! changed are tokenized again. Saves are debounced and written atomically.
+//!
+//! Until the open Forge's index has been reconciled, and for any other Forge,
+//! search falls back to reading every note.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+use notify::RecursiveMode;
+use notify_debouncer_mini::new_debouncer;
+use serde::{Deserialize, Serialize};
+
+use crate::commands::search::{
+    bm25, filename_has, fold, inline_tags, is_heading, match_note, rank_matches, terms_of,
+    visible_markdown_files, word_matches, Corpus, IN_FILENAME, IN_HEADING, IN_TAG,
+};
+use crate::frontmatter;
+use crate::persist::write_atomic;
+use crate::semantic::{content_hash, is_valid_note_index_path, INDEX_DIR};
+use crate::types::{ContentMatch, SearchOptions};
+
+pub(crate) const FULLTEXT_VERSION: u32 = 2;
+
+pub(crate) const FULLTEXT_FILE: &str = "fulltext.v2.bin";
+
+/// Index files of earlier versions, removed once this one is saved.
+const OLD_FULLTEXT_FILES: [&str; 1] = ["fulltext.v1.bin"];
+
+/// A burst of changes is saved once, this long after the first of them.
+const SAVE_DELAY_MS: u64 = 2000;
+
+const WATCH_DEBOUNCE_MS: u64 = 300;
+
+// =============================================================================
+// INDEX STATE
+// =============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Doc {
+    /// Forge-relative path, e.g. `notes/Projects/foo.md` or `daily/2026-01-01.md`.
+    path: String,
+    /// SHA-256 hex of the note body (frontmatter stripped).
+    content_hash: String,
+    /// Each distinct term in the body as (term id, occurrences, the
+    /// [`IN_HEADING`] and [`IN_TAG`] fields it occurs in).
+    terms: Vec<(u32, u32, u8)>,
+    /// Terms in the body, occurrences included.
+    #[serde(skip)]
+    length: u32,
+}
+
+/// One note's entry in a term's postings.
+#[derive(Debug, Clone, Copy)]
+struct Posting {
+    doc: u32,
+    occurrences: u32,
+    fields: u8,
+}
+
+/// A note that may match a query, with what the index knows of it.
+#[derive(Debug, Clone, Copy)]
+struct Candidate {
+    doc: u32,
+    occurrences: u32,
+    fields: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    terms: Vec<String>,
+    docs: Vec<Doc>,
+}
+
+#[derive(Default)]
+struct State {
+    terms: Vec<String>,
+    term_ids: HashMap<String, u32>,
+    /// Term id → postings, sorted by doc id.
+    postings: Vec<Vec<Posting>>,
+    docs: Vec<Option<Doc>>,
+    free: Vec<u32>,
+    by_path: HashMap<String, u32>,
+    /// Sum of the lengths of the live docs.
+    total_length: u64,
+}
+
+/// Occurrences of each term in a note body, and the fields it occurs in.
+fn term_stats<'a>(body: &str, lower: &'a str) -> HashMap<&'a str, (u32, u8)> {
+    let mut stats: HashMap<&str, (u32, u8)> = HashMap::new();
+    for term in terms_of(lower) {
+        stats.entry(term).or_default().0 += 1;
+    }
+    for line in lower.lines().filter(|line| is_heading(line)) {
+        for term in terms_of(line) {
+            if let Some(entry) = stats.get_mut(term) {
+                entry.1 |= IN_HEADING;
+            }
+        }
+    }
+    for tag in inline_tags(body) {
+        for term in terms_of(&tag) {
+            if let Some(entry) = stats.get_mut(term) {
+                entry.1 |= IN_TAG;
+            }
+        }
+    }
+    stats
+}
+
+impl State {
+    /// Rebuild the postings from a saved file. `None` if it is inconsistent.
+    fn from_file(file: IndexFile) -> Option<State> {
+        let mut state = State {
+            postings: vec![Vec::new(); file.terms.len()],
+            term_ids: file
+                .terms
+                .iter()
+                .enumerate()
+                .map(|(id, term)| (term.clone(), id as u32))
+                .collect(),
+            terms: file.terms,
+            ..State::default()
+        };
+        for mut doc in file.docs {
+            if state.by_path.contains_key(&doc.path) {
+                return None;
+            }
+            let id = state.docs.len() as u32;
+            for &(term, occurrences, fields) in &doc.terms {
+                state.postings.get_mut(term as usize)?.push(Posting {
+                    doc: id,
+                    occurrences,
+                    fields,
+                });
+                doc.length = doc.length.saturating_add(occurrences);
+            }
+            state.total_length += u64::from(doc.length);
+            state.by_path.insert(doc.path.clone(), id);
+            state.docs.push(Some(doc));
+        }
+        Some(state)
+    }
+
+    /// The saved form, dropping terms no note uses any more.
+    fn to_file(&self) -> IndexFile {
+        let mut remap = vec![u32::MAX; self.terms.len()];
+        let mut terms = Vec::new();
+        for (id, term) in self.terms.iter().enumerate() {
+            if !self.postings[id].is_empty() {
+                remap[id] = terms.len() as u32;
+                terms.push(term.clone());
+            }
+        }
+        let docs = self
+            .docs
+            .iter()
+            .flatten()
+            .map(|doc| Doc {
+                path: doc.path.clone(),
+                content_hash: doc.content_hash.clone(),
+                terms: doc
+                    .terms
+                    .iter()
+                    .map(|&(term, occurrences, fields)| (remap[term as usize], occurrences, fields))
+                    .collect(),
+                length: doc.length,
+            })
+            .collect();
+        IndexFile {
+            version: FULLTEXT_VERSION,
+            terms,
+            docs,
+        }
+    }
+
+    fn hash_of(&self, path: &str) -> Option<&str> {
+        let id = *self.by_path.get(path)?;
+        self.docs[id as usize]
+            .as_ref()
+            .map(|doc| doc.content_hash.as_str())
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        let Some(id) = self.by_path.remove(path) else {
+            return false;
+        };
+        if let Some(doc) = self.docs[id as usize].take() {
+            self.total_length -= u64::from(doc.length);
+            for (term, _, _) in doc.terms {
+                let postings = &mut self.postings[term as usize];
+                if let Ok(pos) = postings.binary_search_by_key(&id, |posting| posting.doc) {
+                    postings.remove(pos);
+                }
+            }
+        }
+        self.free.push(id);
+        true
+    }
+
+    fn upsert(&mut self, path: &str, content_hash: String, body: &str) {
+        self.remove(path);
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.docs.push(None);
+                (self.docs.len() - 1) as u32
+            }
+        };
+        let lower = body.to_lowercase();
+        let mut terms = Vec::new();
+        let mut length = 0u32;
+        for (term, (occurrences, fields)) in term_stats(body, &lower) {
+            let term_id = match self.term_ids.get(term) {
+                Some(&term_id) => term_id,
+                None => {
+                    let term_id = self.terms.len() as u32;
+                    self.terms.push(term.to_string());
+                    self.term_ids.insert(term.to_string(), term_id);
+                    self.postings.push(Vec::new());
+                    term_id
+                }
+            };
+            let postings = &mut self.postings[term_id as usize];
+            let pos = postings
+                .binary_search_by_key(&id, |posting| posting.doc)
+                .unwrap_or_else(|pos| pos);
+            postings.insert(
+                pos,
+                Posting {
+                    doc: id,
+                    occurrences,
+                    fields,
+                },
+            );
+            terms.push((term_id, occurrences, fields));
+            length = length.saturating_add(occurrences);
+        }
+        self.total_length += u64::from(length);
+        self.docs[id as usize] = Some(Doc {
+            path: path.to_string(),
+            content_hash,
+            terms,
+            length,
+        });
+        self.by_path.insert(path.to_string(), id);
+    }
+
+    /// Notes that may contain `query_lower` matched as `options` say, and
+    /// whether their occurrences and fields can be told from the index alone.
+    /// `None` when the query has no alphanumeric characters to look up.
+    fn candidates(
+        &self,
+        query_lower: &str,
+        options: SearchOptions,
+    ) -> Option<(Vec<Candidate>, bool)> {
+        let exact = options == SearchOptions::default();
+        let query = if exact {
+            query_lower.to_string()
+        } else {
+            fold(query_lower, options.fold_diacritics)
+        };
+        let tokens: Vec<&str> = terms_of(&query).collect();
+        if tokens.is_empty() {
+            return None;
+        }
+        // Without separators the query can only occur inside a single term,
+        // so its occurrences and fields follow from that term's.
+        let counted = exact && tokens.len() == 1 && tokens[0].len() == query.len();
+        // Folded and fuzzy matching compare the terms as the notes are read.
+        let folded: Vec<String> = if exact {
+            Vec::new()
+        } else {
+            self.terms
+                .iter()
+                .map(|term| fold(term, options.fold_diacritics))
+                .collect()
+        };
+        let mut occurrences = vec![0u32; self.docs.len()];
+        let mut fields = vec![0u8; self.docs.len()];
+        // How many of the tokens each note has a term for so far.
+        let mut tokens_found = vec![0u32; self.docs.len()];
+        for (round, token) in tokens.iter().enumerate() {
+            let round = round as u32;
+            for (term_id, term) in self.terms.iter().enumerate() {
+                let postings = &self.postings[term_id];
+                let matched = if exact {
+                    term.contains(token)
+                } else {
+                    word_matches(&folded[term_id], token, options.fuzzy)
+                };
+                if postings.is_empty() || !matched {
+                    continue;
+                }
+                let per_term = if counted {
+                    term.matches(token).count() as u32
+                } else {
+                    0
+                };
+                for posting in postings {
+                    let doc = posting.doc as usize;
+                    if tokens_found[doc] == round {
+                        tokens_found[doc] = round + 1;
+                    }
+                    occurrences[doc] = occurrences[doc]
+                        .saturating_add(posting.occurrences.saturating_mul(per_term));
+                    fields[doc] |= posting.fields;
+                }
+            }
+        }
+        let all = tokens.len() as u32;
+        let candidates = tokens_found
+            .iter()
+            .enumerate()
+            .filter(|&(_, &found)| found == all)
+            .map(|(doc, _)| Candidate {
+                doc: doc as u32,
+                occurrences: occurrences[doc],
+                fields: fields[doc],
+            })
+            .collect();
+        Some((candidates, counted))
+    }
+
+    fn corpus(&self) -> Corpus {
+        Corpus {
+            notes: self.by_path.len() as u32,
+            terms: self.total_length,
+        }
+    }
+
+    fn path_of(&self, doc: u32) -> Option<&str> {
+        self.docs[doc as usize]
+            .as_ref()
+            .map(|doc| doc.path.as_str())
+    }
+
+    /// Put the first `page` of scored candidates, as (doc id, occurrences,
+    /// score), in result order: best score, most occurrences, filename, path.
+    fn rank(&self, scored: &mut [(u32, u32, f64)], page: usize) {
+        fn file_name(path: &str) -> &str {
+            path.rsplit('/').next().unwrap_or(path)
+        }
+        let by_rank = |&(a, count_a, score_a): &(u32, u32, f64),
+                       &(b, count_b, score_b): &(u32, u32, f64)| {
+            let (a, b) = (self.path_of(a).unwrap_or(""), self.path_of(b).unwrap_or(""));
+            score_b
+                .total_cmp(&score_a)
+                .then_with(|| count_b.cmp(&count_a))
+                .then_with(|| file_name(a).cmp(file_name(b)))
+                .then_with(|| a.cmp(b))
+        };
+        let page = page.min(scored.len());
+        if page == 0 {
+            return;
+        }
+        if scored.len() > page {
+            scored.select_nth_unstable_by(page - 1, by_rank);
+        }
+        scored[..page].sort_unstable_by(by_rank);
+    }
+}
+
+// =============================================================================
+// ONE FORGE'S INDEX
+// =============================================================================
+
+pub(crate) fn index_path(forge_root: &Path) -> PathBuf {
+    forge_root.join(INDEX_DIR).join(FULLTEXT_FILE)
+}
+
+/// Body of an indexable note, or `None` when there is no readable, unlocked,
+/// non-symlinked note at `abs`.
+fn read_body(abs: &Path) -> Option<String> {
+    let meta = fs::symlink_metadata(abs).ok()?;
+    if !meta.file_type().is_file() {
+        return None;
+    }
+    let raw = fs::read_to_string(abs).ok()?;
+    Some(frontmatter::parse_note(&raw).body)
+}
+
+fn is_indexable(rel_path: &str) -> bool {
+    rel_path.ends_with(".md") && is_valid_note_index_path(rel_path)
+}
+
+pub(crate) struct ForgeIndex {
+    root: PathBuf,
+    state: RwLock<State>,
+    /// Set once the index has been reconciled with the notes on disk.
+    ready: AtomicBool,
+    save_pending: AtomicBool,
+    /// Serializes index-file writes.
+    save_lock: Mutex<()>,
+}
+
+impl ForgeIndex {
+    /// Load the saved index for a Forge. A missing, unreadable, or
+    /// incompatible file starts an empty index that reconcile fills.
+    pub(crate) fn load(root: PathBuf) -> Self {
+        let state = fs::read(index_path(&root))
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<IndexFile>(&bytes).ok())
+            .filter(|file| file.version == FULLTEXT_VERSION)
+            .and_then(State::from_file)
+            .unwrap_or_default();
+        Self {
+            root,
+            state: RwLock::new(state),
+            ready: AtomicBool::new(false),
+            save_pending: AtomicBool::new(false),
+            save_lock: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn indexed_count(&self) -> usize {
+        self.state.read().map(|s| s.by_path.len()).unwrap_or(0)
+    }
+
+    /// Bring the index in line with every visible note on disk, re-tokenizing
+    /// only notes whose content hash changed, then mark it ready. Returns how
+    /// many notes were indexed again or dropped.
+    pub(crate) fn reconcile(&self) -> usize {
+        let trash = self.root.join(".trash");
+        let mut seen = HashSet::new();
+        let mut changed = 0;
+        for path in visible_markdown_files(&self.root, &trash) {
+            let Ok(rel) = path.strip_prefix(&self.root) else {
+                continue;
+            };
+            let rel = rel.to_string_lossy().replace('\\', "/");
+            if !is_indexable(&rel) {
+                continue;
+            }
+            if self.refresh(&rel) {
+                changed += 1;
+            }
+            seen.insert(rel);
+        }
+        let unseen: Vec<String> = match self.state.read() {
+            Ok(state) => state
+                .by_path
+                .keys()
+                .filter(|path| !seen.contains(*path))
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        // Refresh rather than drop: a note may have been created since the
+        // walk passed its folder.
+        for rel in unseen {
+            if self.refresh(&rel) {
+                changed += 1;
+            }
+        }
+        self.ready.store(true, Ordering::Release);
+        changed
+    }
+
+    /// Re-read one note, by Forge-relative path, and update or drop its entry.
+    /// Returns whether the index changed. The note is read under the write
+    /// lock so concurrent refreshes of the same note apply in order.
+    pub(crate) fn refresh(&self, rel_path: &str) -> bool {
+        if !is_indexable(rel_path) {
+            return false;
+        }
+        let abs = self.root.join(rel_path);
+        let Ok(mut state) = self.state.write() else {
+            return false;
+        };
+        match read_body(&abs) {
+            Some(body) => {
+                let hash = content_hash(&body);
+                if state.hash_of(rel_path) == Some(hash.as_str()) {
+                    return false;
+                }
+                state.upsert(rel_path, hash, &body);
+                true
+            }
+            None => state.remove(rel_path),
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        if let Ok(mut state) = self.state.write() {
+            *state = State::default();
+        }
+    }
+
+    /// Persist the index atomically (temp file + fsync + rename, 0600).
+    pub(crate) fn save(&self) -> Result<(), String> {
+        // Never recreate a Forge that was deleted while it was open.
+        if !self.root.is_dir() {
+            return Err("Forge folder no longer exists".to_string());
+        }
+        let file = self
+            .state
+            .read()
+            .map_err(|_| "index lock poisoned".to_string())?
+            .to_file();
+        let bytes = bincode::serialize(&file)
+            .map_err(|e| format!("Failed to serialize search index: {}", e))?;
+        let _guard = self.save_lock.lock();
+        let dir = self.root.join(INDEX_DIR);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create index dir: {}", e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&dir, fs::Permissions::from_mode(0o700));
+        }
+        write_atomic(&index_path(&self.root), &bytes, Some(0o600))?;
+        for old in OLD_FULLTEXT_FILES {
+            let _ = fs::remove_file(dir.join(old));
+        }
+        Ok(())
+    }
+
+    /// Keyword search for `query` as a single term, with the same matching,
+    /// counting, scoring, and ordering as a full scan, reading only the notes
+    /// the index says may match. `None` until the index is ready, or for
+    /// queries the index can't narrow.
+    pub(crate) fn search(&self, query: &str, cap: usize) -> Option<Vec<ContentMatch>> {
+        if !self.is_ready() {
+            return None;
+        }
+        let term_lower = query.to_lowercase();
+        // Counted candidates can be scored like the results, so only the
+        // first page has to be read. Otherwise every candidate is read.
+        let (page, mut rest, counted, corpus, notes_with_term) = {
+            let state = self.state.read().ok()?;
+            let (candidates, counted) = state.candidates(&term_lower, SearchOptions::default())?;
+            let corpus = state.corpus();
+            let notes_with_term = candidates.len() as u32;
+            let mut scored: Vec<(u32, u32, f64)> = candidates
+                .iter()
+                .map(|candidate| {
+                    let score = if counted {
+                        let doc = state.docs[candidate.doc as usize].as_ref();
+                        let in_filename = doc.is_some_and(|doc| {
+                            filename_has(doc.path.rsplit('/').next().unwrap_or(""), &term_lower)
+                        });
+                        bm25(
+                            corpus,
+                            notes_with_term,
+                            candidate.occurrences,
+                            doc.map_or(0, |doc| doc.length),
+                            candidate.fields | if in_filename { IN_FILENAME } else { 0 },
+                        )
+                    } else {
+                        0.0
+                    };
+                    (candidate.doc, candidate.occurrences, score)
+                })
+                .collect();
+            let page = if counted {
+                state.rank(&mut scored, cap);
+                cap.min(scored.len())
+            } else {
+                scored.len()
+            };
+            let paths: Vec<String> = scored[..page]
+                .iter()
+                .filter_map(|&(doc, _, _)| state.path_of(doc).map(str::to_string))
+                .collect();
+            (
+                paths,
+                scored.split_off(page),
+                counted,
+                corpus,
+                notes_with_term,
+            )
+        };
+        let read = |rel_path: &str| {
+            match_note(&self.root, &self.root.join(rel_path), &term_lower)
+                .map(|hit| hit.scored(corpus, &[notes_with_term]))
+        };
+        let mut results: Vec<ContentMatch> = page.iter().filter_map(|path| read(path)).collect();
+        // A stale entry left the page short: read on down the ranking.
+        if counted && results.len() < cap && !rest.is_empty() {
+            let more: Vec<String> = {
+                let state = self.state.read().ok()?;
+                let len = rest.len();
+                state.rank(&mut rest, len);
+                rest.iter()
+                    .filter_map(|&(doc, _, _)| state.path_of(doc).map(str::to_string))
+                    .collect()
+            };
+            for path in more {
+                if results.len() == cap {
+                    break;
+                }
+                results.extend(read(&path));
+            }
+        }
+        rank_matches(&mut results, cap);
+        Some(results)
+    }
+
+    /// The ranking statistics for `terms` matched as `options` say: the
+    /// corpus, and how many notes have each term. `None` until the index is
+    /// ready, or when a term has no alphanumeric characters to look up.
+    pub(crate) fn statistics(
+        &self,
+        terms: &[&str],
+        options: SearchOptions,
+    ) -> Option<(Corpus, Vec<u32>)> {
+        if !self.is_ready() {
+            return None;
+        }
+        let state = self.state.read().ok()?;
+        let notes_with_term = terms
+            .iter()
+            .map(|term| {
+                state
+                    .candidates(&term.to_lowercase(), options)
+                    .map(|(candidates, _)| candidates.len() as u32)
+            })
+            .collect::<Option<Vec<u32>>>()?;
+        Some((state.corpus(), notes_with_term))
+    }
+
+    /// Forge-relative paths of the notes that may contain, for every clause,
+    /// at least one of its texts matched as `options` say. Clauses the index
+    /// can't narrow are ignored; `None` until the index is ready, or when none
+    /// of them narrows.
+    pub(crate) fn candidate_paths(
+        &self,
+        clauses: &[Vec<String>],
+        options: SearchOptions,
+    ) -> Option<Vec<String>> {
+        if !self.is_ready() {
+            return None;
+        }
+        let state = self.state.read().ok()?;
+        // How many narrowing clauses each note satisfies so far.
+        let mut satisfied = vec![0u32; state.docs.len()];
+        let mut narrowing = 0u32;
+        'clauses: for clause in clauses {
+            let mut in_clause = vec![false; state.docs.len()];
+            for text in clause {
+                let Some((candidates, _)) = state.candidates(&text.to_lowercase(), options) else {
+                    continue 'clauses;
+                };
+                for candidate in candidates {
+                    in_clause[candidate.doc as usize] = true;
+                }
+            }
+            for (doc, hit) in in_clause.into_iter().enumerate() {
+                if hit && satisfied[doc] == narrowing {
+                    satisfied[doc] = narrowing + 1;
+                }
+            }
+            narrowing += 1;
+        }
+        if narrowing == 0 {
+            return None;
+        }
+        Some(
+            satisfied
+                .iter()
+                .enumerate()
+                .filter(|&(_, &count)| count == narrowing)
+                .filter_map(|(doc, _)| state.path_of(doc as u32).map(str::to_string))
+                .collect(),
+        )
+    }
+}
+
+// =============================================================================
+// THE OPEN FORGE (global state + hooks)
+// =============================================================================
+
+static ACTIVE: RwLock<Option<Arc<ForgeIndex>>> = RwLock::new(None);
+static OPEN_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn active_for(forge_root: &Path) -> Option<Arc<ForgeIndex>> {
+    ACTIVE
+        .read()
+        .ok()?
+        .as_ref()
+        .filter(|index| index.root == forge_root)
+        .cloned()
+}
+
+/// Make `forge_root`'s index the one searches and note hooks use, loading and
+/// reconciling it on a background thread.
+pub(crate) fn open(forge_root: PathBuf) {
+    open_with(forge_root, false);
+}
+
+/// [`open`], and keep the index current with a watcher of its own. For the
+/// MCP process, which has no Forge watcher to tell it about outside edits.
+pub(crate) fn open_watched(forge_root: PathBuf) {
+    open_with(forge_root, true);
+}
+
+fn open_with(forge_root: PathBuf, watch: bool) {
+    let generation = OPEN_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = None;
+    }
+    std::thread::spawn(move || {
+        let index = Arc::new(ForgeIndex::load(forge_root));
+        {
+            let Ok(mut active) = ACTIVE.write() else {
+                return;
+            };
+            // Another Forge was opened while this one loaded.
+            if OPEN_GENERATION.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            *active = Some(Arc::clone(&index));
+        }
+        if watch {
+            if let Err(e) = spawn_watcher(&index) {
+                log::warn!("[search index] watcher failed: {}", e);
+            }
+        }
+        let changed = index.reconcile();
+        log::info!(
+            "[search index] {} notes indexed, {} updated",
+            index.indexed_count(),
+            changed
+        );
+        if changed > 0 || !index_path(&index.root).exists() {
+            schedule_save(&index);
+        }
+    });
+}
+
+fn schedule_save(index: &Arc<ForgeIndex>) {
+    if index.save_pending.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let index = Arc::clone(index);
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(SAVE_DELAY_MS));
+        index.save_pending.store(false, Ordering::SeqCst);
+        if let Err(e) = index.save() {
+            log::warn!("[search index] failed to persist index: {}", e);
+        }
+    });
+}
+
+/// Keyword search from `forge_root`'s index, or `None` when that Forge isn't
+/// the open one or its index isn't ready yet.
+pub(crate) fn search(forge_root: &Path, query: &str, cap: usize) -> Option<Vec<ContentMatch>> {
+    active_for(forge_root)?.search(query, cap)
+}
+
+/// [`ForgeIndex::candidate_paths`] from `forge_root`'s index, or `None` when
+/// that Forge isn't the open one or its index isn't ready yet.
+pub(crate) fn candidate_paths(
+    forge_root: &Path,
+    clauses: &[Vec<String>],
+    options: SearchOptions,
+) -> Option<Vec<String>> {
+    active_for(forge_root)?.candidate_paths(clauses, options)
+}
+
+/// [`ForgeIndex::statistics`] from `forge_root`'s index, or `None` when that
+/// Forge isn't the open one or its index isn't ready yet.
+pub(crate) fn statistics(
+    forge_root: &Path,
+    terms: &[&str],
+    options: SearchOptions,
+) -> Option<(Corpus, Vec<u32>)> {
+    active_for(forge_root)?.statistics(terms, options)
+}
+
+/// A note in `forge_root` was written, created, or went away (also given as
+/// its `.md.locked` path). No-op unless that Forge is the open one.
+pub(crate) fn note_changed(forge_root: &Path, rel_path: &str) {
+    let Some(index) = active_for(forge_root) else {
+        return;
+    };
+    if index.refresh(rel_path.strip_suffix(".locked").unwrap_or(rel_path)) {
+        schedule_save(&index);
+    }
+}
+
+/// Every note in `forge_root` was deleted.
+pub(crate) fn all_notes_removed(forge_root: &Path) {
+    let Some(index) = active_for(forge_root) else {
+        return;
+    };
+    index.clear();
+    schedule_save(&index);
+}
+
+/// Refresh notes the watcher reports changed until the index is replaced.
+fn spawn_watcher(index: &Arc<ForgeIndex>) -> Result<(), String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut debouncer = new_debouncer(Duration::from_millis(WATCH_DEBOUNCE_MS), tx)
+        .map_err(|e| format!("failed to create debouncer: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(&index.root, RecursiveMode::Recursive)
+        .map_err(|e| format!("failed to watch {:?}: {}", index.root, e))?;
+    let weak = Arc::downgrade(index);
+    std::thread::Builder::new()
+        .name("search-index-watcher".into())
+        .spawn(move || {
+            let _debouncer = debouncer;
+            loop {
+                let events = match rx.recv_timeout(Duration::from_secs(1)) {
+                    Ok(Ok(events)) => events,
+                    Ok(Err(e)) => {
+                        log::warn!("[search index] watcher error: {}", e);
+                        continue;
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                        if weak.strong_count() == 0 {
+                            break;
+                        }
+                        continue;
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                };
+                let Some(index) = weak.upgrade() else {
+                    break;
+                };
+                let mut changed = false;
+                for event in events {
+                    let Ok(rel) = event.path.strip_prefix(&index.root) else {
+                        continue;
+                    };
+                    let rel = rel.to_string_lossy().replace('\\', "/");
+                    changed |= index.refresh(rel.strip_suffix(".locked").unwrap_or(&rel));
+                }
+                if changed {
+                    schedule_save(&index);
+                }
+            }
+        })
+        .map_err(|e| format!("failed to spawn watcher thread: {}", e))?;
+    Ok(())
+}
+
+// =============================================================================
+// TESTS
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::search::search_notes_content_in;
+    use crate::types::SearchOptions;
+
+    struct TempForge(PathBuf);
+
+    impl TempForge {
+        fn new(tag: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "moldavite-fulltext-{}-{}-{}",
+                tag,
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0)
+            ));
+            for sub in ["notes/Projects", "daily", "weekly", ".trash", ".index"] {
+                fs::create_dir_all(root.join(sub)).unwrap();
+            }
+            Self(root)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+
+        fn write(&self, rel: &str, content: &str) {
+            fs::write(self.0.join(rel), content).unwrap();
+        }
+    }
+
+    impl Drop for TempForge {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn seed(forge: &TempForge) {
+        forge.write(
+            "notes/alpha.md",
+            "---\ncolor: red\n---\nFirst line\nThe quick brown fox\nalpha beta gamma\n",
+        );
+        forge.write(
+            "notes/Projects/beta.md",
+            "beta appears once here\nno match on this line\nand beta again, Beta!\n",
+        );
+        forge.write(
+            "daily/2026-04-24.md",
+            "Daily log\nDiscussed the fox plan -- foxes\n",
+        );
+        forge.write("weekly/2026-W17.md", "Weekly review\nfox sightings up\n");
+        forge.write(
+            "notes/Σίσυφος.md",
+            "ΣΊΣΥΦΟΣ pushes the rock; σίσυφος again\n",
+        );
+        forge.write("notes/secret.md.locked", "fox fox fox fox");
+        forge.write(".trash/old.md", "fox fox fox");
+        forge.write(".index/sneaky.md", "fox fox fox fox fox");
+    }
+
+    fn search_both(forge: &TempForge, index: &ForgeIndex, query: &str) -> Vec<ContentMatch> {
+        let root = forge.path();
+        // Quoted, the whole query is one term, as the index takes it.
+        let phrase = format!("\"{query}\"");
+        let scanned = search_notes_content_in(
+            root,
+            &root.join(".trash"),
+            &phrase,
+            100,
+            SearchOptions::default(),
+        )
+        .unwrap();
+        let indexed = index.search(query, 100).expect("index answers");
+        assert_eq!(indexed, scanned, "index and scan disagree on {query:?}");
+        indexed
+    }
+
+    #[test]
+    fn index_search_matches_a_full_scan() {
+        let forge = TempForge::new("parity");
+        seed(&forge);
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        assert!(
+            index.search("fox", 10).is_none(),
+            "not ready before reconcile"
+        );
+        assert_eq!(index.reconcile(), 5);
+
+        let fox = search_both(&forge, &index, "fox");
+        let paths: Vec<&str> = fox.iter().map(|hit| hit.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "daily/2026-04-24.md",
+                "weekly/2026-W17.md",
+                "notes/alpha.md"
+            ]
+        );
+        assert_eq!(fox[0].match_count, 2);
+        assert_eq!(search_both(&forge, &index, "BETA")[0].match_count, 3);
+        for query in [
+            "quick brown",
+            "fox plan -- fox",
+            "a",
+            "σίσυφος",
+            "e, b",
+            "red",
+            "zzz",
+        ] {
+            search_both(&forge, &index, query);
+        }
+        assert_eq!(index.search("fox", 1).unwrap().len(), 1);
+        // Nothing to look up: the caller scans instead.
+        assert!(index.search("--", 10).is_none());
+    }
+
+    #[test]
+    fn index_scores_boosted_fields_like_a_scan() {
+        let forge = TempForge::new("boosts");
+        forge.write("notes/Compost.md", "Turned the compost\n");
+        forge.write("notes/garden.md", "Turned the compost\n");
+        forge.write("notes/aa.md", "Mulch\nspread thin\n");
+        forge.write("notes/zz.md", "## Mulch\nspread thin\n");
+        forge.write(
+            "notes/bb.md",
+            "seedlings on the sill, see https://x.io/#seedlings\n",
+        );
+        forge.write("notes/yy.md", "#seedlings on the sill\n");
+        let old_index = forge.path().join(INDEX_DIR).join("fulltext.v1.bin");
+        fs::create_dir_all(old_index.parent().unwrap()).unwrap();
+        fs::write(&old_index, b"old").unwrap();
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        index.reconcile();
+
+        for (query, best) in [
+            ("compost", "notes/Compost.md"),
+            ("mulch", "notes/zz.md"),
+            ("seedlings", "notes/yy.md"),
+        ] {
+            let results = search_both(&forge, &index, query);
+            assert_eq!(results[0].path, best, "{query}");
+            assert!(results[0].score > results[1].score, "{query}");
+        }
+        index.save().unwrap();
+        assert!(index_path(forge.path()).exists());
+        assert!(!old_index.exists());
+    }
+
+    #[test]
+    fn candidate_paths_satisfy_every_clause_that_narrows() {
+        let forge = TempForge::new("clauses");
+        seed(&forge);
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        let clauses = |clauses: &[&[&str]]| -> Vec<Vec<String>> {
+            clauses
+                .iter()
+                .map(|clause| clause.iter().map(|text| text.to_string()).collect())
+                .collect()
+        };
+        let fox_and_plan_or_beta = clauses(&[&["fox"], &["plan", "beta"]]);
+        assert!(index
+            .candidate_paths(&fox_and_plan_or_beta, SearchOptions::default())
+            .is_none());
+        index.reconcile();
+
+        let mut paths = index
+            .candidate_paths(&fox_and_plan_or_beta, SearchOptions::default())
+            .unwrap();
+        paths.sort();
+        assert_eq!(paths, ["daily/2026-04-24.md", "notes/alpha.md"]);
+        // A clause with nothing to look up narrows nothing.
+        assert_eq!(
+            index.candidate_paths(
+                &clauses(&[&["--", "beta"], &["quick brown"]]),
+                SearchOptions::default()
+            ),
+            Some(vec!["notes/alpha.md".to_string()])
+        );
+        assert!(index
+            .candidate_paths(&clauses(&[&["--"]]), SearchOptions::default())
+            .is_none());
+        assert!(index
+            .candidate_paths(&[], SearchOptions::default())
+            .is_none());
+    }
+
+    #[test]
+    fn candidate_paths_widen_for_folded_and_fuzzy_words() {
+        let forge = TempForge::new("loose");
+        forge.write("notes/cafe.md", "Met at the Café on Straße 9\n");
+        forge.write("notes/parcel.md", "Did you recieve the parcel?\n");
+        forge.write("notes/other.md", "nothing here\n");
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        index.reconcile();
+        let paths = |word: &str, options: SearchOptions| {
+            let mut paths = index
+                .candidate_paths(&[vec![word.to_string()]], options)
+                .unwrap();
+            paths.sort();
+            paths
+        };
+        let folded = SearchOptions {
+            fold_diacritics: true,
+            ..SearchOptions::default()
+        };
+        let fuzzy = SearchOptions {
+            fuzzy: true,
+            ..SearchOptions::default()
+        };
+
+        assert!(paths("cafe", SearchOptions::default()).is_empty());
+        assert_eq!(paths("cafe", folded), ["notes/cafe.md"]);
+        assert_eq!(paths("strasse", folded), ["notes/cafe.md"]);
+        assert!(paths("receive", SearchOptions::default()).is_empty());
+        assert_eq!(paths("receive", fuzzy), ["notes/parcel.md"]);
+        // An accent is one typo away, so fuzzy matching finds it too.
+        assert!(paths("cafe", fuzzy).contains(&"notes/cafe.md".to_string()));
+        assert!(paths("xyz", fuzzy).is_empty());
+    }
+
+    #[test]
+    fn refresh_follows_edits_locks_and_deletes() {
+        let forge = TempForge::new("refresh");
+        seed(&forge);
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        index.reconcile();
+
+        // An edit the index missed costs that note its hit, and the page is
+        // still filled from further down the ranking.
+        forge.write("daily/2026-04-24.md", "no longer about that\n");
+        let page = index.search("fox", 1).unwrap();
+        assert_eq!(page[0].path, "weekly/2026-W17.md");
+        assert!(index.refresh("daily/2026-04-24.md"));
+
+        forge.write("notes/alpha.md", "now about owls\n");
+        assert!(index.refresh("notes/alpha.md"));
+        assert!(
+            !index.refresh("notes/alpha.md"),
+            "unchanged body is a no-op"
+        );
+        assert_eq!(search_both(&forge, &index, "owl").len(), 1);
+        assert_eq!(search_both(&forge, &index, "fox").len(), 1);
+
+        fs::rename(
+            forge.path().join("weekly/2026-W17.md"),
+            forge.path().join("weekly/2026-W17.md.locked"),
+        )
+        .unwrap();
+        assert!(index.refresh("weekly/2026-W17.md"));
+        fs::remove_file(forge.path().join("daily/2026-04-24.md")).unwrap();
+        assert!(index.refresh("daily/2026-04-24.md"));
+        assert!(!index.refresh("daily/2026-04-24.md"));
+        assert!(search_both(&forge, &index, "fox").is_empty());
+        assert!(!index.refresh("notes/../secret.md"));
+        assert!(!index.refresh(".index/sneaky.md"));
+        assert_eq!(index.indexed_count(), 3);
+    }
+
+    #[test]
+    fn saved_index_reconciles_only_changed_notes() {
+        let forge = TempForge::new("persist");
+        seed(&forge);
+        let index = ForgeIndex::load(forge.path().to_path_buf());
+        index.reconcile();
+        index.save().unwrap();
+
+        let reloaded = ForgeIndex::load(forge.path().to_path_buf());
+        assert_eq!(reloaded.indexed_count(), 5);
+        assert_eq!(reloaded.reconcile(), 0);
+
+        // Offline edits: one changed, one added, one deleted, one only
+        // touched in its frontmatter.
+        forge.write("notes/Projects/beta.md", "rewritten\n");
+        forge.write("notes/new.md", "a new fox\n");
+        fs::remove_file(forge.path().join("weekly/2026-W17.md")).unwrap();
+        forge.write(
+            "notes/alpha.md",
+            "---\ncolor: blue\n---\nFirst line\nThe quick brown fox\nalpha beta gamma\n",
+        );
+        let reloaded = ForgeIndex::load(forge.path().to_path_buf());
+        assert_eq!(reloaded.reconcile(), 3);
+        search_both(&forge, &reloaded, "fox");
+        search_both(&forge, &reloaded, "beta");
+
+        fs::write(index_path(forge.path()), b"not an index").unwrap();
+        let rebuilt = ForgeIndex::load(forge.path().to_path_buf());
+        assert_eq!(rebuilt.indexed_count(), 0);
+        assert_eq!(rebuilt.reconcile(), 5);
+        search_both(&forge, &rebuilt, "fox");
+    }
+
+    #[test]
+    fn hooks_ignore_forges_that_are_not_open() {
+        let forge = TempForge::new("hooks");
+        seed(&forge);
+        note_changed(forge.path(), "notes/alpha.md");
+        all_notes_removed(forge.path());
+        assert!(search(forge.path(), "fox", 10).is_none());
+        assert!(!index_path(forge.path()).exists());
+    }
+}
@@ -12,15 +12,21 @@
 //! # Index
 //!
 //! The per-Forge index lives at `<forge>/.index/embeddings.v1.bin`
-//! (bincode, written with `persist::write_atomic`). One vector per note:
-//! long notes are chunked into ~350-word windows (≈512 MiniLM tokens) and
-//! the note vector is the L2-normalized mean of the chunk vectors.
+//! (bincode, written with `persist::write_atomic`). Note bodies are chunked
+//! into ~350-word windows (≈512 MiniLM tokens) and every chunk keeps its
+//! own vector, so a search scores a note by its best passage and can say
+//! where that passage is. Each note also keeps the L2-normalized mean of its
+//! chunk vectors, which related notes compare. A version 1 index kept only
+//! the mean; it is rejected on load and rebuilt.
 //! Locked notes (`.md.locked`) are encrypted and are never read or indexed;
 //! when a note becomes locked its entry is removed.
 //!
-//! Search is a brute-force cosine scan over the in-memory vectors — at
-//! 384 dims × 10k notes that is milliseconds, so no vector-DB dependency
-//! is warranted.
+//! Search is a brute-force cosine scan over the in-memory chunk vectors —
+//! at 384 dims × 10k notes that is milliseconds, so no vector-DB dependency
+//! is warranted. Forges with more chunks than that add an approximate
+//! index ([`crate::semantic_ann`]) that narrows the scan. The passage text
+//! is read back from the note when a hit is returned, so the index holds no
+//! note text.
 //!
 //! # Incremental updates
 //!
@@ -29,7 +35,9 @@
 //! [`notes_removed`], which no-op unless the feature is enabled and the
 //! index is ready. Changed notes are re-embedded on a debounced background
 //! thread so saves are never blocked. Content hashes make full reconciles
-//! cheap: unchanged notes are never re-embedded.
+//! cheap: unchanged notes are never re-embedded. The same hooks keep the
+//! full-text index ([`crate::search_index`]) current whether or not this
+//! feature is on.
 
 use std::collections::HashMap;
 use std::fs;
@@ -42,13 +50,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::frontmatter;
 use crate::persist::write_atomic;
+use crate::semantic_ann::{self, IvfIndex};
+use crate::types::{ContentMatch, SearchSignal};
 
 // =============================================================================
 // CONSTANTS
 // =============================================================================
 
 /// On-disk index format version. Bump on breaking changes to force rebuilds.
-pub(crate) const INDEX_VERSION: u32 = 1;
+/// Version 2 added per-chunk vectors.
+pub(crate) const INDEX_VERSION: u32 = 2;
 /// Default embedding model for new and upgraded installations.
 pub(crate) const DEFAULT_MODEL_ID: &str = "all-minilm-l6-v2";
 /// All curated models currently produce 384-dimensional vectors.
@@ -247,6 +258,20 @@ pub(crate) struct IndexEntry {
     pub(crate) title: String,
     /// L2-normalized note vector (mean of chunk vectors).
     pub(crate) embedding: Vec<f32>,
+    /// L2-normalized vector of each [`chunk_passages`] window, in body order.
+    pub(crate) chunks: Vec<Vec<f32>>,
+}
+
+impl IndexEntry {
+    fn new(path: String, content_hash: String, title: String, vectors: NoteVectors) -> Self {
+        IndexEntry {
+            path,
+            content_hash,
+            title,
+            embedding: vectors.mean,
+            chunks: vectors.chunks,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -257,6 +282,26 @@ struct IndexFile {
     entries: Vec<IndexEntry>,
 }
 
+/// The leading fields of [`IndexFile`], read first so an index in another
+/// format is turned away without decoding its entries.
+#[derive(Deserialize)]
+struct IndexHeader {
+    version: u32,
+    model: String,
+    dim: u32,
+}
+
+/// A window of a note body: its words, joined by single spaces, and the
+/// body lines they span, 1-based and inclusive like
+/// [`ContentMatch::line_number`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Passage {
+    pub(crate) text: String,
+    pub(crate) start_line: usize,
+    pub(crate) end_line: usize,
+}
+
 /// A search / related-notes hit.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -264,6 +309,29 @@ pub(crate) struct SemanticHit {
     pub(crate) path: String,
     pub(crate) title: String,
     pub(crate) score: f32,
+    /// Set when `semantic_search` ran in keyword or hybrid mode: the line
+    /// shown for the note and, for hybrid, the searches that found it.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) signals: Vec<SearchSignal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) snippet: Option<String>,
+    /// For a semantic search, the note's passage most similar to the query,
+    /// unless the note changed since it was indexed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) passage: Option<Passage>,
+}
+
+impl From<ContentMatch> for SemanticHit {
+    fn from(result: ContentMatch) -> Self {
+        SemanticHit {
+            title: title_from_rel_path(&result.path),
+            path: result.path,
+            score: result.score as f32,
+            signals: result.signals,
+            snippet: Some(result.snippet),
+            passage: None,
+        }
+    }
 }
 
 pub(crate) fn index_path(forge_root: &Path) -> PathBuf {
@@ -301,10 +369,18 @@ pub(crate) fn save_index(
 pub(crate) fn load_index(forge_root: &Path, model_id: &str) -> Option<Vec<IndexEntry>> {
     let model = model_info(model_id).ok()?;
     let bytes = fs::read(index_path(forge_root)).ok()?;
-    let file: IndexFile = bincode::deserialize(&bytes).ok()?;
-    if file.version != INDEX_VERSION || file.model != model.id || file.dim != model.dims {
+    let header: IndexHeader = bincode::deserialize(&bytes).ok()?;
+    if header.version != INDEX_VERSION || header.model != model.id || header.dim != model.dims {
+        if header.version < INDEX_VERSION {
+            log::info!(
+                "[semantic] index format v{} predates v{}; rebuilding",
+                header.version,
+                INDEX_VERSION
+            );
+        }
         return None;
     }
+    let file: IndexFile = bincode::deserialize(&bytes).ok()?;
     Some(file.entries)
 }
 
@@ -320,13 +396,41 @@ pub(crate) fn content_hash(body: &str) -> String {
     format!("{:x}", hasher.finalize())
 }
 
-/// Split a note body into ~[`CHUNK_WORDS`]-word windows.
-pub(crate) fn chunk_text(body: &str) -> Vec<String> {
-    let words: Vec<&str> = body.split_whitespace().collect();
-    words.chunks(CHUNK_WORDS).map(|c| c.join(" ")).collect()
+/// Split a note body into ~[`CHUNK_WORDS`]-word windows. A window can start
+/// or end partway through a long line.
+pub(crate) fn chunk_passages(body: &str) -> Vec<Passage> {
+    let mut passages = Vec::new();
+    let mut words: Vec<&str> = Vec::with_capacity(CHUNK_WORDS);
+    let mut start_line = 0;
+    let mut end_line = 0;
+    for (idx, line) in body.lines().enumerate() {
+        for word in line.split_whitespace() {
+            if words.is_empty() {
+                start_line = idx + 1;
+            }
+            words.push(word);
+            end_line = idx + 1;
+            if words.len() == CHUNK_WORDS {
+                passages.push(Passage {
+                    text: words.join(" "),
+                    start_line,
+                    end_line,
+                });
+                words.clear();
+            }
+        }
+    }
+    if !words.is_empty() {
+        passages.push(Passage {
+            text: words.join(" "),
+            start_line,
+            end_line,
+        });
+    }
+    passages
 }
 
-fn l2_normalize(v: &mut [f32]) -> bool {
+pub(crate) fn l2_normalize(v: &mut [f32]) -> bool {
     let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
     if !norm.is_finite() || norm <= f32::EPSILON {
         return false;
@@ -337,19 +441,40 @@ fn l2_normalize(v: &mut [f32]) -> bool {
     true
 }
 
-/// Embed a note body: chunk, embed each chunk, mean-pool, L2-normalize.
-/// Returns `Ok(None)` for empty/degenerate bodies (nothing to index).
+/// The vectors of one note body.
+pub(crate) struct NoteVectors {
+    /// L2-normalized mean of the chunk vectors.
+    pub(crate) mean: Vec<f32>,
+    /// One L2-normalized vector per [`chunk_passages`] window, in order. A
+    /// degenerate chunk keeps a zero vector so the order still lines up.
+    pub(crate) chunks: Vec<Vec<f32>>,
+}
+
+/// Embed a note body as a single vector: chunk, embed each chunk, mean-pool,
+/// L2-normalize. Returns `Ok(None)` for empty/degenerate bodies.
 pub(crate) fn embed_note_body(
     embedder: &dyn Embedder,
     body: &str,
 ) -> Result<Option<Vec<f32>>, String> {
-    let chunks = chunk_text(body);
+    Ok(embed_note_chunks(embedder, body)?.map(|vectors| vectors.mean))
+}
+
+/// Embed a note body for the index: every chunk's vector and their mean.
+/// Returns `Ok(None)` for empty/degenerate bodies (nothing to index).
+pub(crate) fn embed_note_chunks(
+    embedder: &dyn Embedder,
+    body: &str,
+) -> Result<Option<NoteVectors>, String> {
+    let chunks: Vec<String> = chunk_passages(body)
+        .into_iter()
+        .map(|passage| passage.text)
+        .collect();
     if chunks.is_empty() {
         return Ok(None);
     }
-    let vectors = embedder.embed(&chunks)?;
-    if vectors.is_empty() {
-        return Ok(None);
+    let mut vectors = embedder.embed(&chunks)?;
+    if vectors.len() != chunks.len() {
+        return Err("Embedder returned the wrong number of vectors".to_string());
     }
     let dim = vectors[0].len();
     let mut mean = vec![0f32; dim];
@@ -368,12 +493,18 @@ pub(crate) fn embed_note_body(
     if !l2_normalize(&mut mean) {
         return Ok(None);
     }
-    Ok(Some(mean))
+    for v in vectors.iter_mut() {
+        l2_normalize(v);
+    }
+    Ok(Some(NoteVectors {
+        mean,
+        chunks: vectors,
+    }))
 }
 
 /// Cosine similarity. Vectors in the index are L2-normalized, so this is a
 /// plain dot product.
-fn dot(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
     a.iter().zip(b).map(|(x, y)| x * y).sum()
 }
 
@@ -395,6 +526,9 @@ pub(crate) fn top_k_similar(
             path: e.path.clone(),
             title: e.title.clone(),
             score: dot(&e.embedding, query),
+            signals: Vec::new(),
+            snippet: None,
+            passage: None,
         })
         .collect();
     hits.sort_by(|a, b| {
@@ -406,6 +540,77 @@ pub(crate) fn top_k_similar(
     hits
 }
 
+/// A note's chunk most similar to a query.
+pub(crate) struct PassageMatch<'a> {
+    pub(crate) entry: &'a IndexEntry,
+    /// Position of the chunk in [`IndexEntry::chunks`].
+    pub(crate) chunk: usize,
+    pub(crate) score: f32,
+}
+
+/// Return the top-k notes by their most similar chunk, ordered like
+/// [`top_k_similar`]. Of equally similar chunks the first wins.
+pub(crate) fn top_k_passages<'a>(
+    entries: &'a [IndexEntry],
+    query: &[f32],
+    limit: usize,
+) -> Vec<PassageMatch<'a>> {
+    let matches = entries
+        .iter()
+        .filter_map(|entry| best_chunk(entry, query, 0..entry.chunks.len()))
+        .collect();
+    rank_passages(matches, limit)
+}
+
+/// The most similar to `query` of the chunks of `entry` at `chunks`, the
+/// first of equals.
+pub(crate) fn best_chunk<'a>(
+    entry: &'a IndexEntry,
+    query: &[f32],
+    chunks: impl Iterator<Item = usize>,
+) -> Option<PassageMatch<'a>> {
+    chunks
+        .filter(|&chunk| entry.chunks[chunk].len() == query.len())
+        .map(|chunk| (chunk, dot(&entry.chunks[chunk], query)))
+        .max_by(|a, b| a.1.total_cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(chunk, score)| PassageMatch {
+            entry,
+            chunk,
+            score,
+        })
+}
+
+/// Order passage matches best first, ties by path, and keep `limit`.
+pub(crate) fn rank_passages(mut matches: Vec<PassageMatch>, limit: usize) -> Vec<PassageMatch> {
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.entry.path.cmp(&b.entry.path))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+/// Read chunk `chunk` of an indexed note back from disk. `None` when the
+/// note can't be read or its body no longer hashes to `body_hash`, since its
+/// chunks may have moved.
+pub(crate) fn read_passage(
+    forge_root: &Path,
+    rel_path: &str,
+    body_hash: &str,
+    chunk: usize,
+) -> Option<Passage> {
+    if !is_valid_note_index_path(rel_path) {
+        return None;
+    }
+    let raw = fs::read_to_string(forge_root.join(rel_path)).ok()?;
+    let body = frontmatter::parse_note(&raw).body;
+    if content_hash(&body) != body_hash {
+        return None;
+    }
+    chunk_passages(&body).into_iter().nth(chunk)
+}
+
 // =============================================================================
 // NOTE SCANNING + RECONCILE
 // =============================================================================
@@ -507,13 +712,14 @@ fn scan_standalone(dir: &Path, rel: &str, out: &mut Vec<NoteSource>) {
 
 /// Build an up-to-date entry list for the Forge, reusing embeddings from
 /// `existing` when the content hash is unchanged (so only new/edited notes
-/// hit the model). `progress(done, total)` is called after every note and
-/// must return `true` to continue — returning `false` aborts with
-/// [`CANCELLED`].
+/// hit the model), and bring `ann` in line with it. `progress(done, total)`
+/// is called after every note and must return `true` to continue —
+/// returning `false` aborts with [`CANCELLED`].
 pub(crate) fn reconcile_index(
     forge_root: &Path,
     embedder: &dyn Embedder,
     existing: &[IndexEntry],
+    ann: &mut Option<IvfIndex>,
     mut progress: impl FnMut(usize, usize) -> bool,
 ) -> Result<Vec<IndexEntry>, String> {
     let by_path: HashMap<&str, &IndexEntry> =
@@ -529,15 +735,11 @@ pub(crate) fn reconcile_index(
                 content_hash: hash,
                 title: src.title,
                 embedding: prev.embedding.clone(),
+                chunks: prev.chunks.clone(),
             }),
             _ => {
-                if let Some(embedding) = embed_note_body(embedder, &src.body)? {
-                    entries.push(IndexEntry {
-                        path: src.rel_path,
-                        content_hash: hash,
-                        title: src.title,
-                        embedding,
-                    });
+                if let Some(vectors) = embed_note_chunks(embedder, &src.body)? {
+                    entries.push(IndexEntry::new(src.rel_path, hash, src.title, vectors));
                 }
             }
         }
@@ -545,22 +747,28 @@ pub(crate) fn reconcile_index(
             return Err(CANCELLED.to_string());
         }
     }
+    semantic_ann::sync_ann(ann, &entries);
     Ok(entries)
 }
 
-/// Re-embed (or drop) a single note in `entries`. Returns `Ok(true)` if the
-/// entry list changed. A missing file (deleted, trashed, or replaced by a
-/// `.locked` ciphertext) or an empty body removes the entry.
+/// Re-embed (or drop) a single note in `entries`, and place (or drop) its
+/// chunks in `ann`. Returns `Ok(true)` if the entry list changed. A missing
+/// file (deleted, trashed, or replaced by a `.locked` ciphertext) or an
+/// empty body removes the entry.
 pub(crate) fn refresh_entry(
     forge_root: &Path,
     embedder: &dyn Embedder,
     entries: &mut Vec<IndexEntry>,
+    mut ann: Option<&mut IvfIndex>,
     rel_path: &str,
 ) -> Result<bool, String> {
     if !is_valid_note_index_path(rel_path) {
         return Ok(false);
     }
-    let remove = |entries: &mut Vec<IndexEntry>| {
+    let mut remove = |entries: &mut Vec<IndexEntry>| {
+        if let Some(ann) = ann.as_deref_mut() {
+            ann.remove(rel_path);
+        }
         let before = entries.len();
         entries.retain(|e| e.path != rel_path);
         entries.len() != before
@@ -583,15 +791,18 @@ pub(crate) fn refresh_entry(
     {
         return Ok(false);
     }
-    let Some(embedding) = embed_note_body(embedder, &body)? else {
+    let Some(vectors) = embed_note_chunks(embedder, &body)? else {
         return Ok(remove(entries));
     };
-    let entry = IndexEntry {
-        path: rel_path.to_string(),
-        content_hash: hash,
-        title: title_from_rel_path(rel_path),
-        embedding,
-    };
+    let entry = IndexEntry::new(
+        rel_path.to_string(),
+        hash,
+        title_from_rel_path(rel_path),
+        vectors,
+    );
+    if let Some(ann) = ann {
+        ann.insert(&entry);
+    }
     if let Some(existing) = entries.iter_mut().find(|e| e.path == rel_path) {
         *existing = entry;
     } else {
@@ -661,6 +872,9 @@ impl Phase {
 /// the enable flow in `commands::semantic`.
 pub(crate) struct SemanticService {
     entries: RwLock<Vec<IndexEntry>>,
+    /// Approximate index over `entries`, for Forges large enough to need it.
+    /// Always locked after `entries`.
+    ann: RwLock<Option<IvfIndex>>,
     phase: RwLock<Phase>,
     embedder: RwLock<Option<Arc<dyn Embedder>>>,
     /// Debounce bookkeeping for [`note_changed`]: path → latest generation.
@@ -677,6 +891,7 @@ static SERVICE: OnceLock<SemanticService> = OnceLock::new();
 pub(crate) fn service() -> &'static SemanticService {
     SERVICE.get_or_init(|| SemanticService {
         entries: RwLock::new(Vec::new()),
+        ann: RwLock::new(None),
         phase: RwLock::new(Phase::Disabled),
         embedder: RwLock::new(None),
         pending: Mutex::new(HashMap::new()),
@@ -718,9 +933,12 @@ impl SemanticService {
         }
     }
 
-    pub(crate) fn replace_entries(&self, new_entries: Vec<IndexEntry>) {
+    pub(crate) fn replace_entries(&self, new_entries: Vec<IndexEntry>, new_ann: Option<IvfIndex>) {
         if let Ok(mut e) = self.entries.write() {
             *e = new_entries;
+            if let Ok(mut a) = self.ann.write() {
+                *a = new_ann;
+            }
         }
     }
 
@@ -728,7 +946,7 @@ impl SemanticService {
     /// a re-enable only re-embeds notes that changed in the meantime).
     pub(crate) fn disable(&self) {
         self.set_phase(Phase::Disabled);
-        self.replace_entries(Vec::new());
+        self.replace_entries(Vec::new(), None);
         if let Ok(mut e) = self.embedder.write() {
             *e = None;
         }
@@ -739,7 +957,7 @@ impl SemanticService {
 
     /// The active Forge changed: in-memory entries belong to the old vault.
     pub(crate) fn reset_for_forge_switch(&self) {
-        self.replace_entries(Vec::new());
+        self.replace_entries(Vec::new(), None);
         if let Ok(mut p) = self.pending.lock() {
             p.clear();
         }
@@ -783,18 +1001,27 @@ impl SemanticService {
 
     /// Snapshot the in-memory entries and persist them for the given Forge.
     fn persist_entries(&self, forge_root: &Path) {
-        let snapshot = match self.entries.read() {
-            Ok(e) => e.clone(),
+        let (snapshot, ann) = match self.entries.read() {
+            Ok(e) => (e.clone(), self.ann.read().ok().and_then(|a| a.clone())),
             Err(_) => return,
         };
         let _guard = self.save_lock.lock();
-        if let Err(e) = save_index(forge_root, &snapshot, &configured_model_id()) {
+        let model_id = configured_model_id();
+        if let Err(e) = save_index(forge_root, &snapshot, &model_id)
+            .and_then(|()| semantic_ann::save_ann(forge_root, ann.as_ref(), &model_id))
+        {
             log::warn!("[semantic] failed to persist index: {}", e);
         }
     }
 
-    /// Embed `query` and return the `limit` most similar notes.
-    pub(crate) fn search(&self, query: &str, limit: usize) -> Result<Vec<SemanticHit>, String> {
+    /// Embed `query` and return the `limit` notes with the most similar
+    /// passages, each hit carrying its passage as read from `forge_root`.
+    pub(crate) fn search(
+        &self,
+        forge_root: &Path,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SemanticHit>, String> {
         if !self.is_ready() {
             return Err("Semantic search is not ready".to_string());
         }
@@ -808,11 +1035,43 @@ impl SemanticService {
         let Some(qv) = embed_note_body(embedder.as_ref(), query)? else {
             return Ok(Vec::new());
         };
-        let entries = self
-            .entries
-            .read()
-            .map_err(|_| "index lock poisoned".to_string())?;
-        Ok(top_k_similar(&entries, &qv, limit.clamp(1, 100), None))
+        // Passages are read after the lock is released so a slow disk never
+        // holds up re-embeds.
+        let found: Vec<(SemanticHit, String, usize)> = {
+            let entries = self
+                .entries
+                .read()
+                .map_err(|_| "index lock poisoned".to_string())?;
+            let ann = self
+                .ann
+                .read()
+                .map_err(|_| "index lock poisoned".to_string())?;
+            let limit = limit.clamp(1, 100);
+            match ann.as_ref() {
+                Some(ann) => ann.top_k_passages(&entries, &qv, limit),
+                None => top_k_passages(&entries, &qv, limit),
+            }
+            .into_iter()
+            .map(|found| {
+                let hit = SemanticHit {
+                    path: found.entry.path.clone(),
+                    title: found.entry.title.clone(),
+                    score: found.score,
+                    signals: Vec::new(),
+                    snippet: None,
+                    passage: None,
+                };
+                (hit, found.entry.content_hash.clone(), found.chunk)
+            })
+            .collect()
+        };
+        Ok(found
+            .into_iter()
+            .map(|(mut hit, hash, chunk)| {
+                hit.passage = read_passage(forge_root, &hit.path, &hash, chunk);
+                hit
+            })
+            .collect())
     }
 
     /// Nearest neighbours of an already-indexed note, excluding itself.
@@ -855,6 +1114,7 @@ pub(crate) fn note_changed(rel_path: &str) {
 /// The MCP process has no GUI Forge switcher, so it must not resolve the
 /// active Forge again inside the background task.
 pub(crate) fn note_changed_in(rel_path: &str, forge_root: PathBuf) {
+    crate::search_index::note_changed(&forge_root, rel_path);
     let svc = service();
     if !svc.is_ready() || !is_valid_note_index_path(rel_path) {
         return;
@@ -874,7 +1134,17 @@ pub(crate) fn note_changed_in(rel_path: &str, forge_root: PathBuf) {
             let Ok(mut entries) = svc.entries.write() else {
                 return;
             };
-            refresh_entry(&forge_root, embedder.as_ref(), &mut entries, &rel).unwrap_or_else(|e| {
+            let Ok(mut ann) = svc.ann.write() else {
+                return;
+            };
+            refresh_entry(
+                &forge_root,
+                embedder.as_ref(),
+                &mut entries,
+                ann.as_mut(),
+                &rel,
+            )
+            .unwrap_or_else(|e| {
                 log::warn!("[semantic] re-embed of {} failed: {}", rel, e);
                 false
             })
@@ -903,12 +1173,15 @@ pub(crate) fn prepare_mcp_search(forge_root: &Path, model_id: &str) -> bool {
     if entries.is_empty() {
         return false;
     }
+    // A missing or outdated ANN index only costs speed, so MCP mode doesn't
+    // train one; the app does on its next build.
+    let ann = semantic_ann::load_ann(forge_root, model_id);
     let embedder: Arc<dyn Embedder> = match init_fastembed_embedder(model_id) {
         Ok(embedder) => Arc::new(embedder),
         Err(_) => return false,
     };
     svc.set_embedder(embedder);
-    svc.replace_entries(entries);
+    svc.replace_entries(entries, ann);
     svc.set_phase(Phase::Ready);
     true
 }
@@ -923,6 +1196,10 @@ pub(crate) fn prepare_mcp_search(_forge_root: &Path, _model_id: &str) -> bool {
 /// Several notes changed at once (folder restore). One background thread,
 /// no debounce.
 pub(crate) fn notes_changed(rel_paths: Vec<String>) {
+    let notes_dir = crate::paths::get_notes_dir();
+    for rel in &rel_paths {
+        crate::search_index::note_changed(&notes_dir, rel);
+    }
     let svc = service();
     if !svc.is_ready() || rel_paths.is_empty() {
         return;
@@ -941,7 +1218,16 @@ pub(crate) fn notes_changed(rel_paths: Vec<String>) {
             let Ok(mut entries) = svc.entries.write() else {
                 return;
             };
-            match refresh_entry(&forge_root, embedder.as_ref(), &mut entries, rel) {
+            let Ok(mut ann) = svc.ann.write() else {
+                return;
+            };
+            match refresh_entry(
+                &forge_root,
+                embedder.as_ref(),
+                &mut entries,
+                ann.as_mut(),
+                rel,
+            ) {
                 Ok(changed) => any_changed |= changed,
                 Err(e) => log::warn!("[semantic] re-embed of {} failed: {}", rel, e),
             }
@@ -957,8 +1243,20 @@ pub(crate) fn note_removed(rel_path: &str) {
     notes_removed(vec![rel_path.to_string()]);
 }
 
+/// MCP-mode variant of [`note_removed`] for an explicitly selected Forge.
+pub(crate) fn note_removed_in(rel_path: &str, forge_root: PathBuf) {
+    notes_removed_in(vec![rel_path.to_string()], forge_root);
+}
+
 /// Several notes went away at once (folder trash).
 pub(crate) fn notes_removed(rel_paths: Vec<String>) {
+    notes_removed_in(rel_paths, crate::paths::get_notes_dir());
+}
+
+fn notes_removed_in(rel_paths: Vec<String>, forge_root: PathBuf) {
+    for rel in &rel_paths {
+        crate::search_index::note_changed(&forge_root, rel);
+    }
     let svc = service();
     if !svc.is_ready() || rel_paths.is_empty() {
         return;
@@ -972,18 +1270,26 @@ pub(crate) fn notes_removed(rel_paths: Vec<String>) {
             let Ok(mut entries) = svc.entries.write() else {
                 return;
             };
+            if let Ok(mut ann) = svc.ann.write() {
+                if let Some(ann) = ann.as_mut() {
+                    for rel in &rel_paths {
+                        ann.remove(rel);
+                    }
+                }
+            }
             let before = entries.len();
             entries.retain(|e| !rel_paths.iter().any(|p| p == &e.path));
             entries.len() != before
         };
         if removed {
-            svc.persist_entries(&crate::paths::get_notes_dir());
+            svc.persist_entries(&forge_root);
         }
     });
 }
 
 /// Every note was deleted (`clear_all_notes`).
 pub(crate) fn all_notes_removed() {
+    crate::search_index::all_notes_removed(&crate::paths::get_notes_dir());
     let svc = service();
     if !svc.is_ready() {
         return;
@@ -993,7 +1299,7 @@ pub(crate) fn all_notes_removed() {
         if !svc.is_ready() {
             return;
         }
-        svc.replace_entries(Vec::new());
+        svc.replace_entries(Vec::new(), None);
         svc.persist_entries(&crate::paths::get_notes_dir());
     });
 }
@@ -1078,6 +1384,7 @@ mod tests {
             path: path.to_string(),
             content_hash: content_hash(path),
             title: title_from_rel_path(path),
+            chunks: vec![embedding.clone()],
             embedding,
         }
     }
@@ -1085,17 +1392,39 @@ mod tests {
     // ---- chunking + embedding ----------------------------------------------
 
     #[test]
-    fn chunk_text_empty_body_yields_no_chunks() {
-        assert!(chunk_text("").is_empty());
-        assert!(chunk_text("   \n\t ").is_empty());
+    fn chunk_passages_empty_body_yields_no_chunks() {
+        assert!(chunk_passages("").is_empty());
+        assert!(chunk_passages("   \n\t ").is_empty());
     }
 
     #[test]
-    fn chunk_text_splits_long_notes_into_windows() {
+    fn chunk_passages_splits_long_notes_into_windows() {
         let body = vec!["word"; 800].join(" ");
-        let chunks = chunk_text(&body);
+        let chunks = chunk_passages(&body);
         assert_eq!(chunks.len(), 3); // 350 + 350 + 100
-        assert!(chunk_text("short note").len() == 1);
+        assert!(chunks.iter().all(|c| (c.start_line, c.end_line) == (1, 1)));
+        assert!(chunk_passages("short note").len() == 1);
+    }
+
+    #[test]
+    fn chunk_passages_report_the_lines_each_window_spans() {
+        let short = chunk_passages("\nalpha beta\n\n  gamma  \n\n");
+        assert_eq!(
+            short,
+            vec![Passage {
+                text: "alpha beta gamma".to_string(),
+                start_line: 2,
+                end_line: 4,
+            }]
+        );
+
+        // 200 lines of two words: windows of 350 words end mid-line.
+        let body: Vec<String> = (1..=200).map(|n| format!("line {n}")).collect();
+        let chunks = chunk_passages(&body.join("\n"));
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 175));
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (176, 200));
+        assert!(chunks[1].text.starts_with("line 176 line 177"));
     }
 
     #[test]
@@ -1113,6 +1442,19 @@ mod tests {
         assert!(fake.embed_calls() > 1, "long note should embed >1 chunk");
     }
 
+    #[test]
+    fn embed_note_chunks_keeps_a_unit_vector_per_chunk() {
+        let fake = FakeEmbedder::new();
+        let body = vec!["alpha beta"; 500].join(" ");
+        let vectors = embed_note_chunks(&fake, &body).unwrap().unwrap();
+        assert_eq!(vectors.chunks.len(), chunk_passages(&body).len());
+        for chunk in &vectors.chunks {
+            let norm: f32 = chunk.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4);
+        }
+        assert_eq!(Some(vectors.mean), embed_note_body(&fake, &body).unwrap());
+    }
+
     #[test]
     fn embed_note_body_empty_returns_none() {
         let fake = FakeEmbedder::new();
@@ -1174,12 +1516,14 @@ mod tests {
                 path: "notes/rust.md".into(),
                 content_hash: h1,
                 title: "rust".into(),
+                chunks: vec![v1.clone()],
                 embedding: v1,
             },
             IndexEntry {
                 path: "notes/bread.md".into(),
                 content_hash: h2,
                 title: "bread".into(),
+                chunks: vec![v2.clone()],
                 embedding: v2,
             },
         ];
@@ -1191,6 +1535,177 @@ mod tests {
         assert!(hits[0].score > hits[1].score);
     }
 
+    #[test]
+    fn best_passage_finds_a_topic_buried_in_a_long_note() {
+        let forge = TempForge::new("passages");
+        let filler = |words: &str| vec![words; CHUNK_WORDS / 5].join(" ");
+        // A long log that covers the topic in one paragraph, and a short note
+        // on nothing but one of the query's words.
+        let log = format!(
+            "{}\n\n{}\n\n{}\n{}",
+            filler("standup standup standup standup standup"),
+            filler("sourdough starter hydration flour levain"),
+            filler("budget budget budget budget budget"),
+            filler("roadmap roadmap roadmap roadmap roadmap")
+        );
+        fs::write(forge.path().join("notes/log.md"), &log).unwrap();
+        fs::write(forge.path().join("notes/short.md"), "sourdough").unwrap();
+        let fake = FakeEmbedder::new();
+        let entries = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
+        let query = embed_note_body(&fake, "sourdough starter hydration")
+            .unwrap()
+            .unwrap();
+
+        let by_mean = top_k_similar(&entries, &query, 2, None);
+        assert_eq!(by_mean[0].path, "notes/short.md");
+
+        let found = top_k_passages(&entries, &query, 2);
+        assert_eq!(found[0].entry.path, "notes/log.md");
+        assert!(found[0].score > found[1].score);
+        let passage = read_passage(
+            forge.path(),
+            &found[0].entry.path,
+            &found[0].entry.content_hash,
+            found[0].chunk,
+        )
+        .unwrap();
+        assert!(passage.text.starts_with("sourdough starter hydration"));
+        assert_eq!((passage.start_line, passage.end_line), (3, 3));
+
+        // Once the note changes, its stored chunk may point elsewhere.
+        fs::write(forge.path().join("notes/log.md"), "rewritten").unwrap();
+        assert!(read_passage(
+            forge.path(),
+            &found[0].entry.path,
+            &found[0].entry.content_hash,
+            found[0].chunk,
+        )
+        .is_none());
+    }
+
+    // ---- approximate (IVF) index ----------------------------------------------
+
+    /// Deterministic pseudo-random numbers for generated notes.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            ((self.0 >> 33) % n as u64) as usize
+        }
+
+        /// `count` words, mostly from `topic`'s vocabulary.
+        fn words(&mut self, topic: usize, count: usize) -> String {
+            (0..count)
+                .map(|_| {
+                    if self.below(10) < 7 {
+                        format!("t{}w{}", topic, self.below(25))
+                    } else {
+                        format!("common{}", self.below(200))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        }
+    }
+
+    /// Notes on 40 topics; every fourth is a long one that ends on another
+    /// topic, so it has two chunks.
+    fn topical_entries(fake: &FakeEmbedder, notes: usize) -> Vec<IndexEntry> {
+        let mut rng = Lcg(7);
+        (0..notes)
+            .map(|i| {
+                let topic = rng.below(40);
+                let mut body = rng.words(topic, if i % 4 == 0 { CHUNK_WORDS } else { 40 });
+                if i % 4 == 0 {
+                    let second = rng.below(40);
+                    body.push('\n');
+                    body.push_str(&rng.words(second, 40));
+                }
+                let vectors = embed_note_chunks(fake, &body).unwrap().unwrap();
+                IndexEntry::new(
+                    format!("notes/n{i}.md"),
+                    content_hash(&body),
+                    format!("n{i}"),
+                    vectors,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ivf_search_recalls_what_the_exact_scan_finds() {
+        let fake = FakeEmbedder::new();
+        let entries = topical_entries(&fake, 1200);
+        let ann = IvfIndex::train(&entries).unwrap();
+        let mut rng = Lcg(99);
+        let (mut found, mut wanted) = (0, 0);
+        for _ in 0..50 {
+            let topic = rng.below(40);
+            let text = rng.words(topic, 4);
+            let query = embed_note_body(&fake, &text).unwrap().unwrap();
+            let exact: Vec<&str> = top_k_passages(&entries, &query, 10)
+                .iter()
+                .map(|m| m.entry.path.as_str())
+                .collect();
+            let approx = ann.top_k_passages(&entries, &query, 10);
+            assert!(approx.len() <= 10);
+            wanted += exact.len();
+            found += approx
+                .iter()
+                .filter(|m| exact.contains(&m.entry.path.as_str()))
+                .count();
+        }
+        let recall = found as f64 / wanted as f64;
+        assert!(recall >= 0.9, "recall@10 was {recall:.3}");
+    }
+
+    #[test]
+    fn refresh_entry_and_reconcile_keep_the_ivf_index_current() {
+        let forge = TempForge::new("ivf");
+        seed_vault(&forge);
+        let fake = FakeEmbedder::new();
+        let mut entries =
+            reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
+        let mut ann = IvfIndex::train(&entries);
+        let query = embed_note_body(&fake, "kiln glaze").unwrap().unwrap();
+
+        fs::write(
+            forge.path().join("notes/kiln.md"),
+            "pottery kiln glaze firing",
+        )
+        .unwrap();
+        assert!(refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            ann.as_mut(),
+            "notes/kiln.md"
+        )
+        .unwrap());
+        let hits = ann.as_ref().unwrap().top_k_passages(&entries, &query, 1);
+        assert_eq!(hits[0].entry.path, "notes/kiln.md");
+
+        fs::remove_file(forge.path().join("notes/kiln.md")).unwrap();
+        assert!(refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            ann.as_mut(),
+            "notes/kiln.md"
+        )
+        .unwrap());
+        let hits = ann.as_ref().unwrap().top_k_passages(&entries, &query, 10);
+        assert!(hits.iter().all(|m| m.entry.path != "notes/kiln.md"));
+
+        // A Forge this small is searched exactly, so reconcile drops it.
+        reconcile_index(forge.path(), &fake, &entries, &mut ann, |_, _| true).unwrap();
+        assert!(ann.is_none());
+    }
+
     // ---- index file round trip ----------------------------------------------
 
     #[cfg(not(all(target_os = "macos", target_arch = "x86_64")))]
@@ -1257,6 +1772,54 @@ mod tests {
         assert!(load_index(forge.path(), "bge-small-en-v1.5").is_some());
     }
 
+    #[test]
+    fn version_1_index_without_chunk_vectors_is_rebuilt() {
+        #[derive(Serialize)]
+        struct V1Entry {
+            path: String,
+            content_hash: String,
+            title: String,
+            embedding: Vec<f32>,
+        }
+        #[derive(Serialize)]
+        struct V1File {
+            version: u32,
+            model: String,
+            dim: u32,
+            entries: Vec<V1Entry>,
+        }
+        let forge = TempForge::new("v1index");
+        seed_vault(&forge);
+        let fake = FakeEmbedder::new();
+        let current = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
+        let old = V1File {
+            version: 1,
+            model: DEFAULT_MODEL_ID.to_string(),
+            dim: EMBED_DIM,
+            entries: current
+                .iter()
+                .map(|e| V1Entry {
+                    path: e.path.clone(),
+                    content_hash: e.content_hash.clone(),
+                    title: e.title.clone(),
+                    embedding: e.embedding.clone(),
+                })
+                .collect(),
+        };
+        fs::create_dir_all(forge.path().join(INDEX_DIR)).unwrap();
+        fs::write(index_path(forge.path()), bincode::serialize(&old).unwrap()).unwrap();
+        assert!(load_index(forge.path(), DEFAULT_MODEL_ID).is_none());
+
+        // The build then starts from nothing and re-embeds every note.
+        let calls = fake.embed_calls();
+        let rebuilt = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
+        assert!(fake.embed_calls() > calls);
+        assert_eq!(rebuilt, current);
+        assert!(rebuilt.iter().all(|e| !e.chunks.is_empty()));
+        save_index(forge.path(), &rebuilt, DEFAULT_MODEL_ID).unwrap();
+        assert_eq!(load_index(forge.path(), DEFAULT_MODEL_ID), Some(rebuilt));
+    }
+
     #[test]
     fn load_index_missing_or_garbage_returns_none() {
         let forge = TempForge::new("missing");
@@ -1294,7 +1857,7 @@ mod tests {
         let forge = TempForge::new("reconcile");
         seed_vault(&forge);
         let fake = FakeEmbedder::new();
-        let entries = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let entries = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
         let mut paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
         paths.sort();
         assert_eq!(
@@ -1320,19 +1883,19 @@ mod tests {
         let forge = TempForge::new("incremental");
         seed_vault(&forge);
         let fake = FakeEmbedder::new();
-        let first = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let first = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
         let calls_after_first = fake.embed_calls();
         assert!(calls_after_first >= 4);
 
         // Nothing changed → no new embed calls.
-        let second = reconcile_index(forge.path(), &fake, &first, |_, _| true).unwrap();
+        let second = reconcile_index(forge.path(), &fake, &first, &mut None, |_, _| true).unwrap();
         assert_eq!(fake.embed_calls(), calls_after_first);
         assert_eq!(second.len(), first.len());
 
         // Edit one note, delete another → exactly one re-embed, one drop.
         fs::write(forge.path().join("notes/rust.md"), "rust async runtimes").unwrap();
         fs::remove_file(forge.path().join("daily/2026-07-12.md")).unwrap();
-        let third = reconcile_index(forge.path(), &fake, &second, |_, _| true).unwrap();
+        let third = reconcile_index(forge.path(), &fake, &second, &mut None, |_, _| true).unwrap();
         assert_eq!(fake.embed_calls(), calls_after_first + 1);
         assert_eq!(third.len(), first.len() - 1);
         assert!(!third.iter().any(|e| e.path == "daily/2026-07-12.md"));
@@ -1357,7 +1920,7 @@ mod tests {
         }
         let fake = FakeEmbedder::new();
         let started = std::time::Instant::now();
-        let entries = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let entries = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
         let elapsed = started.elapsed();
         assert_eq!(entries.len(), 1000);
         assert_eq!(fake.embed_calls(), 1000);
@@ -1384,17 +1947,39 @@ mod tests {
 
         for revision in 0..25 {
             fs::write(&a, format!("revision {revision}")).unwrap();
-            refresh_entry(forge.path(), &fake, &mut entries, "notes/a.md").unwrap();
+            refresh_entry(forge.path(), &fake, &mut entries, None, "notes/a.md").unwrap();
         }
         fs::rename(&a, &b).unwrap();
-        refresh_entry(forge.path(), &fake, &mut entries, "notes/a.md").unwrap();
-        refresh_entry(forge.path(), &fake, &mut entries, "notes/Projects/β.md").unwrap();
+        refresh_entry(forge.path(), &fake, &mut entries, None, "notes/a.md").unwrap();
+        refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            None,
+            "notes/Projects/β.md",
+        )
+        .unwrap();
         fs::rename(&b, &trashed).unwrap();
-        refresh_entry(forge.path(), &fake, &mut entries, "notes/Projects/β.md").unwrap();
+        refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            None,
+            "notes/Projects/β.md",
+        )
+        .unwrap();
         fs::rename(&trashed, &b).unwrap();
-        refresh_entry(forge.path(), &fake, &mut entries, "notes/Projects/β.md").unwrap();
+        refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            None,
+            "notes/Projects/β.md",
+        )
+        .unwrap();
 
-        let rebuilt = reconcile_index(forge.path(), &fake, &entries, |_, _| true).unwrap();
+        let rebuilt =
+            reconcile_index(forge.path(), &fake, &entries, &mut None, |_, _| true).unwrap();
         assert_eq!(rebuilt.len(), 1);
         assert_eq!(rebuilt[0].path, "notes/Projects/β.md");
         assert_eq!(rebuilt[0].content_hash, content_hash("revision 24"));
@@ -1410,7 +1995,8 @@ mod tests {
         for corrupt in [b"\x01\x02".as_slice(), b"truncated-index".as_slice()] {
             fs::write(index_path(forge.path()), corrupt).unwrap();
             assert!(load_index(forge.path(), DEFAULT_MODEL_ID).is_none());
-            let rebuilt = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+            let rebuilt =
+                reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
             save_index(forge.path(), &rebuilt, DEFAULT_MODEL_ID).unwrap();
             assert_eq!(load_index(forge.path(), DEFAULT_MODEL_ID).unwrap(), rebuilt);
         }
@@ -1418,7 +2004,7 @@ mod tests {
         let entries = load_index(forge.path(), DEFAULT_MODEL_ID).unwrap();
         save_index(forge.path(), &entries, "bge-small-en-v1.5").unwrap();
         assert!(load_index(forge.path(), DEFAULT_MODEL_ID).is_none());
-        let rebuilt = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let rebuilt = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
         save_index(forge.path(), &rebuilt, DEFAULT_MODEL_ID).unwrap();
         assert_eq!(load_index(forge.path(), DEFAULT_MODEL_ID).unwrap(), rebuilt);
     }
@@ -1432,11 +2018,11 @@ mod tests {
         let mut entries = Vec::new();
         for revision in 0..20 {
             fs::write(&plain, format!("secret plaintext {revision}")).unwrap();
-            refresh_entry(forge.path(), &fake, &mut entries, "notes/secret.md").unwrap();
+            refresh_entry(forge.path(), &fake, &mut entries, None, "notes/secret.md").unwrap();
             assert_eq!(entries.len(), 1);
             fs::remove_file(&plain).unwrap();
             fs::write(&locked, format!("ciphertext-{revision}")).unwrap();
-            refresh_entry(forge.path(), &fake, &mut entries, "notes/secret.md").unwrap();
+            refresh_entry(forge.path(), &fake, &mut entries, None, "notes/secret.md").unwrap();
             assert!(entries.is_empty());
             fs::remove_file(&locked).unwrap();
         }
@@ -1449,7 +2035,7 @@ mod tests {
         seed_vault(&forge);
         let fake = FakeEmbedder::new();
         let mut seen: Vec<(usize, usize)> = Vec::new();
-        reconcile_index(forge.path(), &fake, &[], |done, total| {
+        reconcile_index(forge.path(), &fake, &[], &mut None, |done, total| {
             seen.push((done, total));
             true
         })
@@ -1457,7 +2043,7 @@ mod tests {
         assert_eq!(seen.len(), 4);
         assert_eq!(seen.last(), Some(&(4, 4)));
 
-        let err = reconcile_index(forge.path(), &fake, &[], |_, _| false).unwrap_err();
+        let err = reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| false).unwrap_err();
         assert_eq!(err, CANCELLED);
     }
 
@@ -1468,30 +2054,31 @@ mod tests {
         let forge = TempForge::new("refresh");
         seed_vault(&forge);
         let fake = FakeEmbedder::new();
-        let mut entries = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let mut entries =
+            reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
 
         // Unchanged → false.
-        assert!(!refresh_entry(forge.path(), &fake, &mut entries, "notes/rust.md").unwrap());
+        assert!(!refresh_entry(forge.path(), &fake, &mut entries, None, "notes/rust.md").unwrap());
 
         // Edited → true, hash updated.
         fs::write(forge.path().join("notes/rust.md"), "tokio and async-std").unwrap();
-        assert!(refresh_entry(forge.path(), &fake, &mut entries, "notes/rust.md").unwrap());
+        assert!(refresh_entry(forge.path(), &fake, &mut entries, None, "notes/rust.md").unwrap());
         let rust = entries.iter().find(|e| e.path == "notes/rust.md").unwrap();
         assert_eq!(rust.content_hash, content_hash("tokio and async-std"));
 
         // New note → inserted.
         fs::write(forge.path().join("notes/new.md"), "fresh content").unwrap();
-        assert!(refresh_entry(forge.path(), &fake, &mut entries, "notes/new.md").unwrap());
+        assert!(refresh_entry(forge.path(), &fake, &mut entries, None, "notes/new.md").unwrap());
         assert!(entries.iter().any(|e| e.path == "notes/new.md"));
 
         // Deleted from disk → removed from index.
         fs::remove_file(forge.path().join("notes/new.md")).unwrap();
-        assert!(refresh_entry(forge.path(), &fake, &mut entries, "notes/new.md").unwrap());
+        assert!(refresh_entry(forge.path(), &fake, &mut entries, None, "notes/new.md").unwrap());
         assert!(!entries.iter().any(|e| e.path == "notes/new.md"));
 
         // Emptied → removed.
         fs::write(forge.path().join("notes/rust.md"), "  \n").unwrap();
-        assert!(refresh_entry(forge.path(), &fake, &mut entries, "notes/rust.md").unwrap());
+        assert!(refresh_entry(forge.path(), &fake, &mut entries, None, "notes/rust.md").unwrap());
         assert!(!entries.iter().any(|e| e.path == "notes/rust.md"));
     }
 
@@ -1500,18 +2087,26 @@ mod tests {
         let forge = TempForge::new("locked");
         seed_vault(&forge);
         let fake = FakeEmbedder::new();
-        let mut entries = reconcile_index(forge.path(), &fake, &[], |_, _| true).unwrap();
+        let mut entries =
+            reconcile_index(forge.path(), &fake, &[], &mut None, |_, _| true).unwrap();
         assert!(entries.iter().any(|e| e.path == "notes/rust.md"));
 
         // Simulate lock_note: plaintext removed, ciphertext appears.
         fs::remove_file(forge.path().join("notes/rust.md")).unwrap();
         fs::write(forge.path().join("notes/rust.md.locked"), "ciphertext").unwrap();
 
-        assert!(refresh_entry(forge.path(), &fake, &mut entries, "notes/rust.md").unwrap());
+        assert!(refresh_entry(forge.path(), &fake, &mut entries, None, "notes/rust.md").unwrap());
         assert!(!entries.iter().any(|e| e.path == "notes/rust.md"));
         // And the ciphertext itself can never be addressed for indexing:
         // hidden/locked names fail path validation inside refresh_entry.
-        assert!(!refresh_entry(forge.path(), &fake, &mut entries, "notes/../etc/passwd").unwrap());
+        assert!(!refresh_entry(
+            forge.path(),
+            &fake,
+            &mut entries,
+            None,
+            "notes/../etc/passwd"
+        )
+        .unwrap());
     }
 
     // ---- path helpers -----------------------------------------------------------
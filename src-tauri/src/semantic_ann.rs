@@ -0,0 +1,415 @@
+//! Approximate nearest-neighbour index over the semantic chunk vectors.
+//!
+//! An inverted-file (IVF) index: spherical k-means splits the chunk vectors
+//! into about √n lists, each with a centroid, and a query only scores the
+//! chunks in the lists whose centroids are closest to it. It is saved at
+//! `<forge>/.index/ann.v1.bin` next to the semantic index, and only exists
+//! once a Forge has [`ANN_MIN_CHUNKS`] chunks; below that the exact scan of
+//! [`crate::semantic::top_k_passages`] is fast enough and never misses.
+//!
+//! # Updates
+//!
+//! [`crate::semantic::refresh_entry`] puts a re-embedded note's chunks in
+//! their nearest lists and drops a removed note's.
+//! [`crate::semantic::reconcile_index`] does the same for every note that
+//! changed, builds the index when a Forge crosses the threshold, and trains
+//! the lists again once the Forge has doubled since they were trained. A
+//! note the index has no lists for, or lists from another version of its
+//! body, is scanned in full, so a stale index costs time but not hits.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::persist::write_atomic;
+use crate::semantic::{
+    best_chunk, dot, l2_normalize, model_info, rank_passages, IndexEntry, PassageMatch, INDEX_DIR,
+};
+
+/// On-disk ANN format version. Bump on breaking changes to force a retrain.
+pub(crate) const ANN_VERSION: u32 = 1;
+/// File (in [`INDEX_DIR`]) holding the ANN index.
+pub(crate) const ANN_FILE: &str = "ann.v1.bin";
+/// Chunks a Forge needs before searches go through the ANN index.
+pub(crate) const ANN_MIN_CHUNKS: usize = 20_000;
+/// Cap on the number of lists, however large the Forge.
+const MAX_LISTS: usize = 4096;
+/// Training sees at most this many chunks per list.
+const TRAIN_CHUNKS_PER_LIST: usize = 64;
+const TRAIN_ITERATIONS: usize = 8;
+/// Lists a query always probes, however few there are in total.
+const MIN_PROBES: usize = 8;
+
+/// The list each chunk of one note is in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NoteLists {
+    /// Hash of the body the chunks were embedded from.
+    content_hash: String,
+    lists: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct IvfIndex {
+    /// L2-normalized centroid of each list.
+    centroids: Vec<Vec<f32>>,
+    /// By note path.
+    notes: HashMap<String, NoteLists>,
+    /// Chunks the centroids were trained on.
+    trained_chunks: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AnnFile {
+    version: u32,
+    model: String,
+    dim: u32,
+    index: IvfIndex,
+}
+
+/// The leading fields of [`AnnFile`].
+#[derive(Deserialize)]
+struct AnnHeader {
+    version: u32,
+    model: String,
+    dim: u32,
+}
+
+pub(crate) fn ann_path(forge_root: &Path) -> PathBuf {
+    forge_root.join(INDEX_DIR).join(ANN_FILE)
+}
+
+/// Persist the ANN index atomically, or remove the file when the Forge has
+/// none.
+pub(crate) fn save_ann(
+    forge_root: &Path,
+    ann: Option<&IvfIndex>,
+    model_id: &str,
+) -> Result<(), String> {
+    let Some(index) = ann else {
+        return match fs::remove_file(ann_path(forge_root)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to remove ANN index: {}", e))
+            }
+            _ => Ok(()),
+        };
+    };
+    let model = model_info(model_id)?;
+    fs::create_dir_all(forge_root.join(INDEX_DIR))
+        .map_err(|e| format!("Failed to create index dir: {}", e))?;
+    let file = AnnFile {
+        version: ANN_VERSION,
+        model: model.id.to_string(),
+        dim: model.dims,
+        index: index.clone(),
+    };
+    let bytes =
+        bincode::serialize(&file).map_err(|e| format!("Failed to serialize ANN index: {}", e))?;
+    write_atomic(&ann_path(forge_root), &bytes, Some(0o600))
+}
+
+/// Load the ANN index for a Forge. `None` if it is missing, unreadable, or
+/// from another version or model; [`sync_ann`] then trains a new one if the
+/// Forge is large enough.
+pub(crate) fn load_ann(forge_root: &Path, model_id: &str) -> Option<IvfIndex> {
+    let model = model_info(model_id).ok()?;
+    let bytes = fs::read(ann_path(forge_root)).ok()?;
+    let header: AnnHeader = bincode::deserialize(&bytes).ok()?;
+    if header.version != ANN_VERSION || header.model != model.id || header.dim != model.dims {
+        return None;
+    }
+    let file: AnnFile = bincode::deserialize(&bytes).ok()?;
+    Some(file.index)
+}
+
+/// Bring `ann` in line with `entries`: drop it below [`ANN_MIN_CHUNKS`],
+/// train it when it is missing or the Forge has doubled since training, and
+/// otherwise place the chunks of new and changed notes.
+pub(crate) fn sync_ann(ann: &mut Option<IvfIndex>, entries: &[IndexEntry]) {
+    sync_ann_from(ann, entries, ANN_MIN_CHUNKS);
+}
+
+fn sync_ann_from(ann: &mut Option<IvfIndex>, entries: &[IndexEntry], min_chunks: usize) {
+    let chunks: usize = entries.iter().map(|e| e.chunks.len()).sum();
+    if chunks < min_chunks {
+        *ann = None;
+        return;
+    }
+    match ann {
+        Some(index) if chunks <= index.trained_chunks.saturating_mul(2) => index.update(entries),
+        _ => *ann = IvfIndex::train(entries),
+    }
+}
+
+impl IvfIndex {
+    /// Train lists on the chunk vectors of `entries` and place every chunk.
+    /// `None` when there are no chunks to train on.
+    pub(crate) fn train(entries: &[IndexEntry]) -> Option<Self> {
+        let dim = entries.iter().flat_map(|e| e.chunks.first()).next()?.len();
+        let vectors: Vec<&[f32]> = entries
+            .iter()
+            .flat_map(|e| e.chunks.iter())
+            .filter(|v| v.len() == dim)
+            .map(|v| v.as_slice())
+            .collect();
+        let count = vectors.len();
+        let lists = ((count as f64).sqrt().round() as usize).clamp(1, MAX_LISTS);
+        // An evenly spaced sample keeps training deterministic and bounded.
+        let stride = count.div_ceil(lists * TRAIN_CHUNKS_PER_LIST).max(1);
+        let sample: Vec<&[f32]> = vectors.iter().copied().step_by(stride).collect();
+        let mut centroids = seed_centroids(&sample, lists);
+        for _ in 0..TRAIN_ITERATIONS {
+            let mut sums = vec![vec![0f32; dim]; lists];
+            for v in &sample {
+                let list = nearest(&centroids, v);
+                for (s, x) in sums[list].iter_mut().zip(v.iter()) {
+                    *s += x;
+                }
+            }
+            // A list that drew no chunks keeps its old centroid.
+            for (centroid, mut sum) in centroids.iter_mut().zip(sums) {
+                if l2_normalize(&mut sum) {
+                    *centroid = sum;
+                }
+            }
+        }
+        let mut index = IvfIndex {
+            centroids,
+            notes: HashMap::new(),
+            trained_chunks: count,
+        };
+        for entry in entries {
+            index.insert(entry);
+        }
+        Some(index)
+    }
+
+    /// Place (or re-place) the chunks of one note.
+    pub(crate) fn insert(&mut self, entry: &IndexEntry) {
+        let lists = entry
+            .chunks
+            .iter()
+            .map(|v| nearest(&self.centroids, v) as u32)
+            .collect();
+        self.notes.insert(
+            entry.path.clone(),
+            NoteLists {
+                content_hash: entry.content_hash.clone(),
+                lists,
+            },
+        );
+    }
+
+    pub(crate) fn remove(&mut self, path: &str) {
+        self.notes.remove(path);
+    }
+
+    /// Drop notes that are gone and place the chunks of new or changed ones.
+    fn update(&mut self, entries: &[IndexEntry]) {
+        let by_path: HashMap<&str, &IndexEntry> =
+            entries.iter().map(|e| (e.path.as_str(), e)).collect();
+        self.notes
+            .retain(|path, _| by_path.contains_key(path.as_str()));
+        for entry in entries {
+            if self.lists_of(entry).is_none() {
+                self.insert(entry);
+            }
+        }
+    }
+
+    /// The lists of `entry`'s chunks, if they were placed from its current body.
+    fn lists_of(&self, entry: &IndexEntry) -> Option<&[u32]> {
+        self.notes
+            .get(&entry.path)
+            .filter(|n| n.content_hash == entry.content_hash && n.lists.len() == entry.chunks.len())
+            .map(|n| n.lists.as_slice())
+    }
+
+    /// Lists a query probes: an eighth of them, and at least [`MIN_PROBES`].
+    fn probes(&self) -> usize {
+        (self.centroids.len() / 8)
+            .max(MIN_PROBES)
+            .min(self.centroids.len())
+    }
+
+    /// Like [`crate::semantic::top_k_passages`], but only scores chunks in
+    /// the lists nearest `query`, and every chunk of notes placed from an
+    /// older body.
+    pub(crate) fn top_k_passages<'a>(
+        &self,
+        entries: &'a [IndexEntry],
+        query: &[f32],
+        limit: usize,
+    ) -> Vec<PassageMatch<'a>> {
+        let mut by_score: Vec<(usize, f32)> = self
+            .centroids
+            .iter()
+            .map(|c| dot(c, query))
+            .enumerate()
+            .collect();
+        by_score.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let mut probed = vec![false; self.centroids.len()];
+        for &(list, _) in by_score.iter().take(self.probes()) {
+            probed[list] = true;
+        }
+        let matches = entries
+            .iter()
+            .filter_map(|entry| match self.lists_of(entry) {
+                Some(lists) => best_chunk(
+                    entry,
+                    query,
+                    (0..lists.len()).filter(|&i| probed[lists[i] as usize]),
+                ),
+                None => best_chunk(entry, query, 0..entry.chunks.len()),
+            })
+            .collect();
+        rank_passages(matches, limit)
+    }
+}
+
+/// Farthest-first seeding: each centroid is the sample chunk least like
+/// the ones already chosen, the first of equals.
+fn seed_centroids(sample: &[&[f32]], lists: usize) -> Vec<Vec<f32>> {
+    let mut centroids = vec![sample[0].to_vec()];
+    let mut closest: Vec<f32> = sample.iter().map(|v| dot(v, sample[0])).collect();
+    while centroids.len() < lists {
+        let Some((next, _)) = closest.iter().enumerate().min_by(|a, b| a.1.total_cmp(b.1)) else {
+            break;
+        };
+        centroids.push(sample[next].to_vec());
+        for (similarity, v) in closest.iter_mut().zip(sample) {
+            *similarity = similarity.max(dot(v, sample[next]));
+        }
+    }
+    centroids
+}
+
+/// The centroid most similar to `v`, the first of equals.
+fn nearest(centroids: &[Vec<f32>], v: &[f32]) -> usize {
+    let mut best = (0, f32::NEG_INFINITY);
+    for (i, c) in centroids.iter().enumerate() {
+        let score = dot(c, v);
+        if score > best.1 {
+            best = (i, score);
+        }
+    }
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semantic::{content_hash, DEFAULT_MODEL_ID, EMBED_DIM};
+
+    struct TempForge(PathBuf);
+
+    impl TempForge {
+        fn new(tag: &str) -> Self {
+            let root = std::env::temp_dir().join(format!(
+                "moldavite-ann-{}-{}-{}",
+                tag,
+                std::process::id(),
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0)
+            ));
+            fs::create_dir_all(&root).unwrap();
+            Self(root)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempForge {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// `count` one-chunk notes, each a unit vector along one of 16 axes.
+    fn entries(count: usize) -> Vec<IndexEntry> {
+        (0..count)
+            .map(|i| {
+                let mut v = vec![0f32; EMBED_DIM as usize];
+                v[i % 16] = 1.0;
+                IndexEntry {
+                    path: format!("notes/{i}.md"),
+                    content_hash: content_hash(&i.to_string()),
+                    title: i.to_string(),
+                    embedding: v.clone(),
+                    chunks: vec![v],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sync_builds_above_the_threshold_and_retrains_when_the_forge_doubles() {
+        let mut ann = None;
+        sync_ann_from(&mut ann, &entries(10), 20);
+        assert!(ann.is_none());
+
+        sync_ann_from(&mut ann, &entries(30), 20);
+        let index = ann.as_ref().unwrap();
+        assert_eq!(index.trained_chunks, 30);
+        assert_eq!(index.notes.len(), 30);
+
+        // Growth short of double only places the new notes.
+        let mut grown = entries(50);
+        sync_ann_from(&mut ann, &grown, 20);
+        let index = ann.as_ref().unwrap();
+        assert_eq!(index.trained_chunks, 30);
+        assert_eq!(index.notes.len(), 50);
+        assert!(grown.iter().all(|e| index.lists_of(e).is_some()));
+
+        // A changed note is placed again and a removed one forgotten.
+        grown[0].content_hash = content_hash("edited");
+        grown.pop();
+        let index = ann.as_mut().unwrap();
+        assert!(index.lists_of(&grown[0]).is_none());
+        index.update(&grown);
+        assert!(index.lists_of(&grown[0]).is_some());
+        assert!(!index.notes.contains_key("notes/49.md"));
+
+        sync_ann_from(&mut ann, &entries(70), 20);
+        assert_eq!(ann.as_ref().unwrap().trained_chunks, 70);
+
+        sync_ann_from(&mut ann, &entries(19), 20);
+        assert!(ann.is_none());
+    }
+
+    #[test]
+    fn lists_split_clusters_and_probes_find_every_member() {
+        let all = entries(256);
+        let index = IvfIndex::train(&all).unwrap();
+        assert_eq!(index.centroids.len(), 16);
+        // Notes on the same axis share a list.
+        for entry in &all[16..] {
+            let twin = &all[entry.title.parse::<usize>().unwrap() % 16];
+            assert_eq!(index.lists_of(entry), index.lists_of(twin));
+        }
+        let hits = index.top_k_passages(&all, &all[3].chunks[0], 16);
+        assert!(hits.iter().all(|m| m.entry.chunks[0] == all[3].chunks[0]));
+        // Only the eight probed lists are scanned.
+        let scanned = index.top_k_passages(&all, &all[3].chunks[0], all.len());
+        assert_eq!(scanned.len(), 8 * 16);
+    }
+
+    #[test]
+    fn ann_file_round_trips_and_goes_away_with_the_index() {
+        let forge = TempForge::new("roundtrip");
+        let index = IvfIndex::train(&entries(40)).unwrap();
+        save_ann(forge.path(), Some(&index), DEFAULT_MODEL_ID).unwrap();
+        assert_eq!(load_ann(forge.path(), DEFAULT_MODEL_ID), Some(index));
+        assert!(load_ann(forge.path(), "bge-small-en-v1.5").is_none());
+
+        save_ann(forge.path(), None, DEFAULT_MODEL_ID).unwrap();
+        assert!(!ann_path(forge.path()).exists());
+        save_ann(forge.path(), None, DEFAULT_MODEL_ID).unwrap();
+        assert!(load_ann(forge.path(), DEFAULT_MODEL_ID).is_none());
+    }
+}
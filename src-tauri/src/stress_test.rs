@@ -26,6 +26,8 @@ use std::time::Instant;
 
 use crate::commands::search::search_notes_content_in;
 use crate::persist::write_atomic;
+use crate::search_index::ForgeIndex;
+use crate::types::SearchOptions;
 use crate::wiki::rewrite_links_for_rename;
 
 struct TempVault(PathBuf);
@@ -84,7 +86,14 @@ fn stress_search_over_1000_note_vault() {
     }
 
     let started = Instant::now();
-    let results = search_notes_content_in(base, &base.join(".trash"), "moldavite-needle", 50);
+    let results = search_notes_content_in(
+        base,
+        &base.join(".trash"),
+        "moldavite-needle",
+        50,
+        SearchOptions::default(),
+    )
+    .unwrap();
     let elapsed = started.elapsed();
 
     assert_eq!(results.len(), 10, "expected exactly the 10 seeded matches");
@@ -196,3 +205,68 @@ fn stress_rewrite_links_across_large_corpus() {
         "link rewrite across 500 notes took {elapsed:?} — order-of-magnitude regression"
     );
 }
+
+#[test]
+fn stress_indexed_search_over_50000_note_vault() {
+    let vault = TempVault::new("fulltext");
+    let base = vault.path();
+
+    let started = Instant::now();
+    for i in 0..50_000 {
+        let dir = if i % 5 == 0 {
+            "notes/Projects"
+        } else {
+            "notes"
+        };
+        let content = lorem_note(i, i % 5_000 == 0); // 10 notes carry the needle
+        fs::write(base.join(dir).join(format!("note-{i}.md")), content).unwrap();
+    }
+    eprintln!("[stress] writing 50000 notes took {:?}", started.elapsed());
+
+    let started = Instant::now();
+    let index = ForgeIndex::load(base.to_path_buf());
+    assert_eq!(index.reconcile(), 50_000);
+    index.save().unwrap();
+    eprintln!("[stress] indexing 50000 notes took {:?}", started.elapsed());
+
+    let started = Instant::now();
+    let reloaded = ForgeIndex::load(base.to_path_buf());
+    assert_eq!(
+        reloaded.reconcile(),
+        0,
+        "unchanged notes are not indexed again"
+    );
+    eprintln!(
+        "[stress] reloading and reconciling took {:?}",
+        started.elapsed()
+    );
+
+    let started = Instant::now();
+    let results = reloaded.search("moldavite-needle", 50).unwrap();
+    let elapsed = started.elapsed();
+    assert_eq!(results.len(), 10, "expected exactly the 10 seeded matches");
+    eprintln!("[stress] indexed search over 50000 notes took {elapsed:?}");
+    assert!(
+        elapsed.as_secs() < REGRESSION_BUDGET_SECS,
+        "indexed search over 50000 notes took {elapsed:?} — order-of-magnitude regression"
+    );
+
+    // A term in every note only reads the notes on the page.
+    let started = Instant::now();
+    let common = reloaded.search("fox", 20).unwrap();
+    let elapsed = started.elapsed();
+    assert_eq!(common.len(), 20);
+    assert!(common.iter().all(|hit| hit.match_count == 8));
+    eprintln!("[stress] indexed search for a common term took {elapsed:?}");
+    assert!(elapsed.as_secs() < REGRESSION_BUDGET_SECS);
+
+    let scanned = search_notes_content_in(
+        base,
+        &base.join(".trash"),
+        "moldavite-needle",
+        50,
+        SearchOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(results, scanned, "index and scan disagree");
+}
@@ -41,7 +41,7 @@ pub(crate) fn replace_template_variables(content: String) -> String {
     replace_template_variables_at(content, Local::now().naive_local())
 }
 
-fn replace_template_variables_at(content: String, now: NaiveDateTime) -> String {
+pub(crate) fn replace_template_variables_at(content: String, now: NaiveDateTime) -> String {
     let date = now.format("%Y-%m-%d").to_string();
     let time = now.format("%H:%M").to_string();
     let day_of_week = now.format("%A").to_string();
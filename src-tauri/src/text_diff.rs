@@ -0,0 +1,205 @@
+//! Line-based unified diffs for reporting what a note write changed.
+//!
+//! Output follows `diff -u`: three lines of context, `@@ -a,b +c,d @@` hunk
+//! headers, and a `\ No newline at end of file` marker after a final line
+//! without one. Common leading and trailing lines are trimmed before the LCS
+//! pass, so a local edit to a long note stays cheap; when the changed middle is
+//! still too large for the quadratic table, it is reported as one replacement.
+
+const CONTEXT_LINES: usize = 3;
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LineOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Render a unified diff from `old` to `new`, or an empty string when they are
+/// identical.
+pub(crate) fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = line_ops(&old_lines, &new_lines);
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| **op != LineOp::Equal)
+        .map(|(index, _)| index)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    // Line positions in each side before every op, plus the end position.
+    let mut old_pos = Vec::with_capacity(ops.len() + 1);
+    let mut new_pos = Vec::with_capacity(ops.len() + 1);
+    let (mut old_at, mut new_at) = (0, 0);
+    for op in &ops {
+        old_pos.push(old_at);
+        new_pos.push(new_at);
+        match op {
+            LineOp::Equal => {
+                old_at += 1;
+                new_at += 1;
+            }
+            LineOp::Delete => old_at += 1,
+            LineOp::Insert => new_at += 1,
+        }
+    }
+    old_pos.push(old_at);
+    new_pos.push(new_at);
+
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &change in &changes {
+        let start = change.saturating_sub(CONTEXT_LINES);
+        let end = (change + 1 + CONTEXT_LINES).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    for (start, end) in hunks {
+        let old_len = old_pos[end] - old_pos[start];
+        let new_len = new_pos[end] - new_pos[start];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_pos[start], old_len),
+            hunk_range(new_pos[start], new_len)
+        ));
+        for index in start..end {
+            let (marker, line) = match ops[index] {
+                LineOp::Equal => (' ', old_lines[old_pos[index]]),
+                LineOp::Delete => ('-', old_lines[old_pos[index]]),
+                LineOp::Insert => ('+', new_lines[new_pos[index]]),
+            };
+            out.push(marker);
+            out.push_str(line);
+            if !line.ends_with('\n') {
+                out.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    out
+}
+
+/// `diff -u` numbers an empty range by the line before it.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{start},0")
+    } else {
+        format!("{},{len}", start + 1)
+    }
+}
+
+fn line_ops(old: &[&str], new: &[&str]) -> Vec<LineOp> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut ops = vec![LineOp::Equal; prefix];
+    if old_middle.len().saturating_mul(new_middle.len()) <= MAX_LCS_CELLS {
+        ops.extend(lcs_ops(old_middle, new_middle));
+    } else {
+        ops.extend(std::iter::repeat(LineOp::Delete).take(old_middle.len()));
+        ops.extend(std::iter::repeat(LineOp::Insert).take(new_middle.len()));
+    }
+    ops.extend(std::iter::repeat(LineOp::Equal).take(suffix));
+    ops
+}
+
+/// Edit script from a longest-common-subsequence table. Deletions are emitted
+/// before insertions so a changed line reads as `-old` then `+new`.
+fn lcs_ops(old: &[&str], new: &[&str]) -> Vec<LineOp> {
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i] == new[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            ops.push(LineOp::Equal);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            ops.push(LineOp::Delete);
+            i += 1;
+        } else {
+            ops.push(LineOp::Insert);
+            j += 1;
+        }
+    }
+    ops.extend(std::iter::repeat(LineOp::Delete).take(old.len() - i));
+    ops.extend(std::iter::repeat(LineOp::Insert).take(new.len() - j));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_text_has_no_diff() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "a/x", "b/x"), "");
+    }
+
+    #[test]
+    fn separate_edits_get_separate_hunks_with_context() {
+        let old: String = (1..=20).map(|n| format!("line {n}\n")).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "line 18\nadded\n");
+        assert_eq!(
+            unified_diff(&old, &new, "a/n.md", "b/n.md"),
+            "--- a/n.md\n+++ b/n.md\n\
+             @@ -1,5 +1,5 @@\n line 1\n-line 2\n+line two\n line 3\n line 4\n line 5\n\
+             @@ -16,5 +16,6 @@\n line 16\n line 17\n line 18\n+added\n line 19\n line 20\n"
+        );
+    }
+
+    #[test]
+    fn empty_sides_and_missing_final_newline_follow_diff_u() {
+        assert_eq!(
+            unified_diff("", "new\n", "a/n.md", "b/n.md"),
+            "--- a/n.md\n+++ b/n.md\n@@ -0,0 +1,1 @@\n+new\n"
+        );
+        assert_eq!(
+            unified_diff("keep\nend", "keep\nend\n", "a/n.md", "b/n.md"),
+            "--- a/n.md\n+++ b/n.md\n@@ -1,2 +1,2 @@\n keep\n-end\n\\ No newline at end of file\n+end\n"
+        );
+    }
+
+    #[test]
+    fn oversized_middle_falls_back_to_one_replacement() {
+        let old: String = (0..3000).map(|n| format!("old {n}\n")).collect();
+        let new: String = (0..3000).map(|n| format!("new {n}\n")).collect();
+        let diff = unified_diff(&old, &new, "a/n.md", "b/n.md");
+        assert!(diff.starts_with("--- a/n.md\n+++ b/n.md\n@@ -1,3000 +1,3000 @@\n-old 0\n"));
+        assert_eq!(
+            diff.lines().filter(|line| line.starts_with('-')).count(),
+            3001
+        );
+        assert_eq!(
+            diff.lines().filter(|line| line.starts_with('+')).count(),
+            3001
+        );
+    }
+}
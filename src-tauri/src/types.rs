@@ -5,6 +5,8 @@
 //! standalone path), while persisted configuration fields remain backward
 //! compatible through defaults for options introduced after initial releases.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -136,6 +138,58 @@ pub(crate) struct AppConfig {
     /// default for users upgrading from an older config.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub(crate) mcp_writes_enabled: Option<bool>,
+    /// Supersedes `mcp_writes_enabled` once set. That flag is still written
+    /// alongside it, true only for direct writes, so an older build never
+    /// writes what this one would only have proposed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) mcp_write_mode: Option<McpWriteMode>,
+    /// Per-client MCP restrictions keyed by the `clientInfo.name` a client
+    /// sends when it connects; `"*"` applies to clients without their own
+    /// entry. Policies only narrow what `mcp_writes_enabled` allows.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) mcp_client_policies: BTreeMap<String, McpClientPolicy>,
+}
+
+/// What MCP write tools do. `Propose` stores a change as a pending proposal
+/// that the user accepts or rejects in the app; the note is untouched until then.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum McpWriteMode {
+    Off,
+    Direct,
+    Propose,
+}
+
+impl AppConfig {
+    pub(crate) fn mcp_write_mode(&self) -> McpWriteMode {
+        self.mcp_write_mode
+            .unwrap_or(match self.mcp_writes_enabled {
+                Some(true) => McpWriteMode::Direct,
+                _ => McpWriteMode::Off,
+            })
+    }
+}
+
+/// What one MCP client may do. Folder rules are globs over Forge-relative
+/// paths (`notes/Work/**`, `daily/*.md`); an empty allow list allows
+/// everything not denied, and writing also requires read access.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpClientPolicy {
+    /// Tool names the client may list and call. `None` allows every tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) allowed_tools: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) read_allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) read_deny: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) write_allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) write_deny: Vec<String>,
+    /// Forge the client is confined to, read-only, whatever Forge is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) pinned_forge: Option<String>,
 }
 
 fn default_semantic_model() -> Option<String> {
@@ -151,6 +205,8 @@ impl Default for AppConfig {
             semantic_enabled: None,
             semantic_model: default_semantic_model(),
             mcp_writes_enabled: None,
+            mcp_write_mode: None,
+            mcp_client_policies: BTreeMap::new(),
         }
     }
 }
@@ -197,17 +253,95 @@ pub(crate) struct NoteWriteResult {
     pub(crate) conflict_copy: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct ContentMatch {
     pub(crate) filename: String,
     pub(crate) path: String,
     pub(crate) snippet: String,
     pub(crate) line_number: usize,
+    /// Where the matched text shows in `snippet`, as [start, end) UTF-16
+    /// offsets. Empty for notes matched by filters alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) highlights: Vec<[usize; 2]>,
+    /// Every match on line `line_number`, as [start, end) byte offsets into
+    /// that line of the body. Empty for notes matched by filters alone.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) spans: Vec<[usize; 2]>,
     pub(crate) match_count: u32,
+    /// BM25 relevance, higher first. Only comparable between results of the
+    /// same search.
+    pub(crate) score: f64,
     pub(crate) is_daily: bool,
     pub(crate) is_weekly: bool,
     pub(crate) folder_path: Option<String>,
+    /// Which searches found the note. Only hybrid and semantic searches
+    /// report it; a keyword search leaves it empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) signals: Vec<SearchSignal>,
+}
+
+/// How keyword search compares the words and phrases of a query with notes.
+/// Case never matters.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct SearchOptions {
+    /// Ignore accents and similar marks, so "cafe" finds "Café".
+    pub(crate) fold_diacritics: bool,
+    /// Let each word match a word a few typos away.
+    pub(crate) fuzzy: bool,
+    /// Read the whole query as one regular expression instead of words,
+    /// phrases, operators, and filters.
+    pub(crate) regex: bool,
+    /// Only match at word boundaries, so "log" doesn't find "login".
+    pub(crate) whole_word: bool,
+}
+
+/// Which search answers a query. Hybrid runs keyword and semantic search
+/// together and fuses their rankings.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SearchMode {
+    #[default]
+    Keyword,
+    Semantic,
+    Hybrid,
+}
+
+/// One of the searches a hybrid result was found by.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SearchSignal {
+    Keyword,
+    Semantic,
+}
+
+/// A named search kept as JSON in a Forge's `searches` folder.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SavedSearch {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) query: String,
+    #[serde(default)]
+    pub(crate) mode: SearchMode,
+    #[serde(default)]
+    pub(crate) options: SearchOptions,
+    /// How many notes the search finds, while no note has changed since it
+    /// was counted. Never stored.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub(crate) result_count: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SaveSearchInput {
+    pub(crate) name: String,
+    pub(crate) query: String,
+    #[serde(default)]
+    pub(crate) mode: SearchMode,
+    #[serde(default)]
+    pub(crate) options: SearchOptions,
 }
 
 #[cfg(test)]
@@ -233,4 +367,15 @@ mod tests {
             Some("multilingual-e5-small")
         );
     }
+
+    #[test]
+    fn mcp_write_mode_falls_back_to_the_legacy_flag() {
+        let legacy: AppConfig = serde_json::from_str(r#"{"mcpWritesEnabled":true}"#).unwrap();
+        assert_eq!(legacy.mcp_write_mode(), McpWriteMode::Direct);
+        assert_eq!(AppConfig::default().mcp_write_mode(), McpWriteMode::Off);
+
+        let proposing: AppConfig =
+            serde_json::from_str(r#"{"mcpWritesEnabled":false,"mcpWriteMode":"propose"}"#).unwrap();
+        assert_eq!(proposing.mcp_write_mode(), McpWriteMode::Propose);
+    }
 }
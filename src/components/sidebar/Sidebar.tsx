@@ -46,7 +46,7 @@ import { ForgeSwitcher } from './ForgeSwitcher';
 import { ManageForgesModal } from './ManageForgesModal';
 import { SidebarTagList } from './SidebarTagList';
 import { BacklinksSection } from './BacklinksSection';
-import { SidebarSearch } from './SidebarSearch';
+import { SavedSearches, SearchMatchOptions, SidebarSearch } from './SidebarSearch';
 import { SidebarSearchResults } from './SidebarSearchResults';
 import {
   SearchModeChips,
@@ -331,11 +331,18 @@ export function Sidebar({
     };
   }, [autoFocusSearch]);
 
+  // Forge switches reload the window, so the active Forge's saved searches
+  // load once; the Forge watcher refreshes their counts.
+  const loadSavedSearches = useSearchStore((s) => s.loadSavedSearches);
+  useEffect(() => {
+    void loadSavedSearches();
+  }, [loadSavedSearches]);
+
   // If the semantic index becomes unavailable (feature disabled, Forge
-  // switch, rebuild) while the user is in semantic mode, fall back to
-  // keyword search — the chips disappear along with it.
+  // switch, rebuild) while the user is in semantic or hybrid mode, fall back
+  // to keyword search — the chips disappear along with it.
   useEffect(() => {
-    if (searchMode === 'semantic' && !semanticReady) {
+    if (searchMode !== 'keyword' && !semanticReady) {
       searchStore.setMode('keyword');
     }
   }, [searchMode, semanticReady, searchStore]);
@@ -855,6 +862,16 @@ export function Sidebar({
       {isSearchActive && semanticReady && (
         <SearchModeChips mode={searchMode} onModeChange={searchStore.setMode} />
       )}
+      {isSearchActive && searchMode !== 'semantic' && (
+        <SearchMatchOptions options={searchStore.options} onChange={searchStore.setOptions} />
+      )}
+      <SavedSearches
+        searches={searchStore.savedSearches}
+        canSave={isSearchActive}
+        onRun={searchStore.applySavedSearch}
+        onSave={searchStore.saveSearch}
+        onDelete={searchStore.deleteSavedSearch}
+      />
       {isSearchActive &&
         semanticEnabled &&
         (semanticState === 'indexing' || semanticState === 'downloading') && (
@@ -869,6 +886,7 @@ export function Sidebar({
               query={searchQuery}
               hits={semanticResults}
               loading={searchLoading}
+              error={searchStore.error}
               selectedIndex={searchSelectedIndex}
               onSelect={searchStore.setSelectedIndex}
               onOpen={openSemanticHit}
@@ -879,6 +897,7 @@ export function Sidebar({
               query={searchQuery}
               results={searchResults}
               loading={searchLoading}
+              error={searchStore.error}
               selectedIndex={searchSelectedIndex}
               onSelect={searchStore.setSelectedIndex}
               onOpen={openSearchMatch}
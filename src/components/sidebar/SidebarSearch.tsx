@@ -1,4 +1,6 @@
-import { forwardRef } from 'react';
+import React, { forwardRef, useState } from 'react';
+import type { SearchOptions } from '@/stores';
+import type { SavedSearch } from '@/lib/savedSearches';
 
 interface SidebarSearchProps {
   query: string;
@@ -48,3 +50,160 @@ export const SidebarSearch = forwardRef<HTMLInputElement, SidebarSearchProps>(
     );
   }
 );
+
+/**
+ * Toggles for how keyword search matches words, shown under the search input
+ * whenever keyword matching is in play.
+ */
+export function SearchMatchOptions({
+  options,
+  onChange,
+}: {
+  options: SearchOptions;
+  onChange: (options: Partial<SearchOptions>) => void;
+}) {
+  const toggle = (key: keyof SearchOptions, label: string, title: string) => (
+    <button
+      type="button"
+      onClick={() => onChange({ [key]: !options[key] })}
+      aria-pressed={options[key]}
+      title={title}
+      className="text-[11px] transition-colors focus-ring"
+      style={{
+        color: options[key] ? 'var(--text-primary)' : 'var(--text-muted)',
+        fontWeight: options[key] ? 500 : 400,
+      }}
+    >
+      {label}
+    </button>
+  );
+
+  return (
+    <div
+      className="flex flex-wrap items-center gap-2 px-3 pb-1"
+      role="group"
+      aria-label="Match options"
+    >
+      {toggle('foldDiacritics', 'Ignore accents', 'Match “cafe” with “Café”')}
+      <span aria-hidden="true" style={{ color: 'var(--border-strong)' }}>
+        ·
+      </span>
+      {toggle('fuzzy', 'Allow typos', 'Match words a typo or two away')}
+      <span aria-hidden="true" style={{ color: 'var(--border-strong)' }}>
+        ·
+      </span>
+      {toggle('wholeWord', 'Whole word', 'Match “log” but not “login”')}
+      <span aria-hidden="true" style={{ color: 'var(--border-strong)' }}>
+        ·
+      </span>
+      {toggle('regex', 'Regex', 'Search with a regular expression')}
+    </div>
+  );
+}
+
+/**
+ * The Forge's saved searches under the search input. Each runs when clicked
+ * and shows how many notes it finds; while a search is active it can be saved
+ * under a name.
+ */
+export function SavedSearches({
+  searches,
+  canSave,
+  onRun,
+  onSave,
+  onDelete,
+}: {
+  searches: SavedSearch[];
+  canSave: boolean;
+  onRun: (search: SavedSearch) => void;
+  onSave: (name: string) => Promise<void>;
+  onDelete: (id: string) => Promise<void>;
+}) {
+  const [naming, setNaming] = useState(false);
+  const [name, setName] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  const report = (err: unknown) => setError(err instanceof Error ? err.message : String(err));
+
+  const submit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    try {
+      await onSave(name.trim());
+      setNaming(false);
+      setName('');
+      setError(null);
+    } catch (err) {
+      report(err);
+    }
+  };
+
+  if (searches.length === 0 && !canSave) return null;
+
+  return (
+    <div className="px-3 pb-1">
+      {canSave &&
+        (naming ? (
+          <form onSubmit={submit}>
+            <input
+              type="text"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+              onKeyDown={(e) => {
+                if (e.key === 'Escape') {
+                  setNaming(false);
+                  setError(null);
+                }
+              }}
+              placeholder="Name this search"
+              aria-label="Saved search name"
+              autoFocus
+              className="search-input w-full py-1 text-[11px] focus:outline-none"
+            />
+          </form>
+        ) : (
+          <button
+            type="button"
+            onClick={() => setNaming(true)}
+            className="text-[11px] transition-colors focus-ring"
+            style={{ color: 'var(--text-muted)' }}
+          >
+            Save search
+          </button>
+        ))}
+      {error && (
+        <p className="text-[11px] mt-1" role="alert" style={{ color: 'var(--text-muted)' }}>
+          {error}
+        </p>
+      )}
+      {searches.length > 0 && (
+        <ul className="flex flex-wrap gap-x-3 gap-y-1 mt-1" aria-label="Saved searches">
+          {searches.map((search) => (
+            <li key={search.id} className="group flex items-center gap-1">
+              <button
+                type="button"
+                onClick={() => onRun(search)}
+                title={search.query}
+                className="text-[11px] transition-colors focus-ring"
+                style={{ color: 'var(--text-primary)' }}
+              >
+                {search.name}
+                {search.resultCount !== undefined && (
+                  <span style={{ color: 'var(--text-muted)' }}> {search.resultCount}</span>
+                )}
+              </button>
+              <button
+                type="button"
+                onClick={() => void onDelete(search.id).catch(report)}
+                aria-label={`Delete saved search ${search.name}`}
+                className="text-[11px] opacity-0 group-hover:opacity-100 focus:opacity-100 transition-opacity"
+                style={{ color: 'var(--text-muted)' }}
+              >
+                ×
+              </button>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
@@ -6,9 +6,50 @@ function escapeRegExp(str: string): string {
   return str.replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
 }
 
-function renderHighlighted(text: string, term: string): React.ReactNode {
-  if (!term) return text;
-  const regex = new RegExp(`(${escapeRegExp(term)})`, 'gi');
+/**
+ * The words and quoted phrases of a search query, leaving out operators and
+ * `field:value` filters, which never appear in a snippet.
+ */
+function highlightTerms(query: string): string[] {
+  const terms: string[] = [];
+  for (const [, phrase, word] of query.matchAll(/"([^"]*)"|(\S+)/g)) {
+    if (phrase !== undefined) {
+      if (phrase) terms.push(phrase);
+    } else if (
+      !['AND', 'OR', 'NOT'].includes(word) &&
+      !/^\(*(tag|folder|color|is|created|modified|prop):/i.test(word)
+    ) {
+      const bare = word.replace(/^\(+|\)+$/g, '');
+      if (bare) terms.push(bare);
+    }
+  }
+  return terms;
+}
+
+/**
+ * Marks the matched text of a snippet: the spans the backend reports when it
+ * has them, which also cover accent-folded and typo matches, or else every
+ * occurrence of the query's terms.
+ */
+function renderHighlighted(
+  text: string,
+  query: string,
+  highlights?: [number, number][]
+): React.ReactNode {
+  if (highlights && highlights.length > 0) {
+    const parts: React.ReactNode[] = [];
+    let at = 0;
+    for (const [start, end] of highlights) {
+      if (start > at) parts.push(<React.Fragment key={`t${at}`}>{text.slice(at, start)}</React.Fragment>);
+      parts.push(<mark key={`m${start}`}>{text.slice(start, end)}</mark>);
+      at = end;
+    }
+    if (at < text.length) parts.push(<React.Fragment key={`t${at}`}>{text.slice(at)}</React.Fragment>);
+    return parts;
+  }
+  const terms = highlightTerms(query);
+  if (terms.length === 0) return text;
+  const regex = new RegExp(`(${terms.map(escapeRegExp).join('|')})`, 'gi');
   const parts = text.split(regex);
   return parts.map((part, i) =>
     i % 2 === 1 ? <mark key={i}>{part}</mark> : <React.Fragment key={i}>{part}</React.Fragment>
@@ -27,6 +68,8 @@ interface SidebarSearchResultsProps {
   query: string;
   results: ContentMatch[];
   loading: boolean;
+  /** Shown instead of the empty state when the query was rejected. */
+  error: string | null;
   selectedIndex: number;
   onSelect: (index: number) => void;
   onOpen: (match: ContentMatch) => void;
@@ -37,6 +80,7 @@ export function SidebarSearchResults({
   query,
   results,
   loading,
+  error,
   selectedIndex,
   onSelect,
   onOpen,
@@ -76,9 +120,14 @@ export function SidebarSearchResults({
                     in {folder}
                   </span>
                 )}
+                {match.signals && !match.signals.includes('keyword') && (
+                  <span className="text-[11px] shrink-0" style={{ color: 'var(--text-muted)' }}>
+                    related
+                  </span>
+                )}
               </span>
               <p className="text-xs mt-0.5 truncate" style={{ color: 'var(--text-muted)' }}>
-                {renderHighlighted(match.snippet, query)}
+                {renderHighlighted(match.snippet, query, match.highlights)}
               </p>
             </button>
           );
@@ -86,7 +135,7 @@ export function SidebarSearchResults({
         {!loading && results.length === 0 && (
           <SignatureEmptyState className="px-3 py-2 text-xs">
             <div>
-              <span>No results for “{query}”.</span>{' '}
+              <span>{error ? `${error}.` : `No results for “${query}”.`}</span>{' '}
               <button onClick={onClear} style={{ color: 'var(--text-secondary)' }}>
                 Clear search
               </button>
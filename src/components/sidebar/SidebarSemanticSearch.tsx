@@ -4,7 +4,7 @@ import type { SemanticHit } from '@/lib/semantic';
 import { SignatureEmptyState } from '@/components/ui/SignatureMark';
 
 /**
- * Keyword / Semantic mode chips shown under the sidebar search input.
+ * Keyword / Semantic / Hybrid mode chips shown under the sidebar search input.
  * Only rendered when the semantic index is ready (the caller gates this).
  */
 export function SearchModeChips({
@@ -39,6 +39,10 @@ export function SearchModeChips({
         ·
       </span>
       {chip('semantic', 'Semantic')}
+      <span aria-hidden="true" style={{ color: 'var(--border-strong)' }}>
+        ·
+      </span>
+      {chip('hybrid', 'Hybrid')}
     </div>
   );
 }
@@ -64,8 +68,8 @@ interface SidebarSemanticResultsProps {
 
 /**
  * Semantic-mode result list. Mirrors `SidebarSearchResults` styling but
- * renders `SemanticHit`s (title + forge-relative path + similarity score);
- * semantic matches have no text snippet to highlight.
+ * renders `SemanticHit`s (title + forge-relative path + similarity score),
+ * each with the passage that matched; there are no query words to highlight.
  */
 export function SidebarSemanticResults({
   query,
@@ -111,6 +115,15 @@ export function SidebarSemanticResults({
                   {Math.round(hit.score * 100)}%
                 </span>
               </span>
+              {hit.passage && (
+                <p
+                  className="text-xs mt-0.5 line-clamp-2"
+                  style={{ color: 'var(--text-secondary)' }}
+                  title={`Lines ${hit.passage.startLine}–${hit.passage.endLine}`}
+                >
+                  {hit.passage.text}
+                </p>
+              )}
               {folder && (
                 <p className="text-xs mt-0.5 truncate" style={{ color: 'var(--text-muted)' }}>
                   in {folder}
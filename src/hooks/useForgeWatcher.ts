@@ -19,7 +19,7 @@ import {
   takeAgentWrite,
 } from '@/lib';
 import { getPendingAutosaveNoteId, resetAutosaveBaseline } from '@/lib/autosaveFlush';
-import { useForgeStore, useNoteStore, useToastStore } from '@/stores';
+import { useForgeStore, useNoteStore, useSearchStore, useToastStore } from '@/stores';
 import type { Note } from '@/types';
 
 /**
@@ -178,6 +178,8 @@ export function useForgeWatcher(): void {
             .catch((err) => {
               console.error('[useForgeWatcher] refresh failed:', err);
             });
+          // The backend dropped its cached saved-search counts for this change.
+          void useSearchStore.getState().loadSavedSearches();
         }
         if (shouldRefreshForges) {
           void useForgeStore
@@ -72,6 +72,7 @@ daily/       Daily notes, one per day (YYYY-MM-DD.md, e.g. 2026-07-12.md)
 weekly/      Weekly notes, one per ISO week (YYYY-Www.md, e.g. 2026-W28.md)
 notes/       Standalone notes; subfolders allowed (notes/projects/roadmap.md)
 templates/   Note templates as JSON files (managed in-app; avoid editing)
+searches/    Saved searches as JSON files (managed in-app; avoid editing)
 images/      Images pasted or inserted into notes
 .trash/      App-managed trash (7-day retention) — do not touch
 .plugins/    Installed Moldavite plugins — do not touch
@@ -162,3 +163,173 @@ export async function getMcpWritesEnabled(): Promise<boolean> {
 export async function setMcpWritesEnabled(enabled: boolean): Promise<void> {
   return await safeInvoke<void>('set_mcp_writes_enabled', { enabled });
 }
+
+/** `propose` holds agent writes for review instead of writing them. */
+export type McpWriteMode = 'off' | 'direct' | 'propose';
+
+export async function getMcpWriteMode(): Promise<McpWriteMode> {
+  return await safeInvoke<McpWriteMode>('get_mcp_write_mode');
+}
+
+export async function setMcpWriteMode(mode: McpWriteMode): Promise<void> {
+  return await safeInvoke<void>('set_mcp_write_mode', { mode });
+}
+
+/** Bearer token for `--mcp-http` clients; created on first request. */
+export async function getMcpHttpToken(): Promise<string> {
+  return await safeInvoke<string>('get_mcp_http_token');
+}
+
+/** Replace the `--mcp-http` bearer token. Running HTTP servers keep the old one. */
+export async function rotateMcpHttpToken(): Promise<string> {
+  return await safeInvoke<string>('rotate_mcp_http_token');
+}
+
+/** What one MCP client may do; folder rules are Forge-relative globs. */
+export interface McpClientPolicy {
+  allowedTools?: string[];
+  readAllow?: string[];
+  readDeny?: string[];
+  writeAllow?: string[];
+  writeDeny?: string[];
+  pinnedForge?: string;
+}
+
+/** Policies keyed by MCP client name; `"*"` applies to every other client. */
+export async function getMcpClientPolicies(): Promise<Record<string, McpClientPolicy>> {
+  return await safeInvoke<Record<string, McpClientPolicy>>('get_mcp_client_policies');
+}
+
+/** Replace all MCP client policies. Running servers apply them on the next request. */
+export async function setMcpClientPolicies(
+  policies: Record<string, McpClientPolicy>
+): Promise<void> {
+  return await safeInvoke<void>('set_mcp_client_policies', { policies });
+}
+
+/** One note an MCP call named, with its body hash before and after the call. */
+export interface McpAuditNote {
+  path: string;
+  hashBefore: string | null;
+  hashAfter: string | null;
+}
+
+/** One recorded MCP tool call. */
+export interface McpAuditEntry {
+  id: string;
+  timestampMs: number;
+  client: string | null;
+  tool: string;
+  forge: string | null;
+  notes: McpAuditNote[];
+  ok: boolean;
+  error: string | null;
+}
+
+export interface McpAuditPage {
+  entries: McpAuditEntry[];
+  nextCursor: string | null;
+}
+
+/**
+ * Newest-first page of the MCP audit log. Pass the previous page's
+ * `nextCursor` as `before` to continue, and `sinceMs` to stop at a time.
+ */
+export async function listMcpAuditEntries(
+  options: { before?: string; sinceMs?: number; limit?: number } = {}
+): Promise<McpAuditPage> {
+  return await safeInvoke<McpAuditPage>('list_mcp_audit_entries', {
+    before: options.before ?? null,
+    sinceMs: options.sinceMs ?? null,
+    limit: options.limit ?? null,
+  });
+}
+
+/** An agent write waiting for review in the active Forge. */
+export interface AgentProposal {
+  id: string;
+  relPath: string;
+  tool: string;
+  client: string | null;
+  proposedAtMs: number;
+}
+
+/** A proposal's change against the note as it is now. */
+export interface AgentProposalDiff {
+  diff: string;
+  contentHash: string;
+  /** The note changed since; accepting keeps that version as a conflict copy. */
+  conflict: boolean;
+}
+
+export interface AcceptedProposal {
+  relPath: string;
+  conflictCopy: string | null;
+}
+
+/** Pending proposals for the active Forge, oldest first. */
+export async function listAgentProposals(): Promise<AgentProposal[]> {
+  return await safeInvoke<AgentProposal[]>('list_agent_proposals');
+}
+
+export async function getAgentProposalDiff(id: string): Promise<AgentProposalDiff> {
+  return await safeInvoke<AgentProposalDiff>('get_agent_proposal_diff', { id });
+}
+
+/** Apply a proposal, re-checking it against the note as it is now. */
+export async function acceptAgentProposal(id: string): Promise<AcceptedProposal> {
+  return await safeInvoke<AcceptedProposal>('accept_agent_proposal', { id });
+}
+
+export async function rejectAgentProposal(id: string): Promise<void> {
+  return await safeInvoke<void>('reject_agent_proposal', { id });
+}
+
+/** A note one MCP session changed. */
+export interface AgentChange {
+  relPath: string;
+  writes: number;
+  /** The session created the note, so restoring it removes the note. */
+  created: boolean;
+  /** Where the note was before the session renamed or moved it. */
+  renamedFrom: string | null;
+  lastWriteMs: number;
+}
+
+/** One MCP session's writes in the active Forge. */
+export interface AgentSession {
+  sessionId: string;
+  client: string | null;
+  firstWriteMs: number;
+  lastWriteMs: number;
+  changes: AgentChange[];
+}
+
+export interface RestoredNote {
+  relPath: string;
+  removed: boolean;
+  /** Where a renamed or moved note was put back. */
+  movedBackTo: string | null;
+  /** The note changed after the agent's last write and was kept under this name. */
+  conflictCopy: string | null;
+  error: string | null;
+}
+
+/** Agent sessions with undoable writes in the active Forge, newest first. */
+export async function listAgentSessions(): Promise<AgentSession[]> {
+  return await safeInvoke<AgentSession[]>('list_agent_sessions');
+}
+
+/**
+ * Put notes back as they were before a session's first write. Leave out
+ * `paths` to undo everything the session changed.
+ */
+export async function restoreAgentChanges(
+  sessionId: string,
+  paths?: string[]
+): Promise<RestoredNote[]> {
+  return await safeInvoke<RestoredNote[]>('restore_agent_changes', {
+    sessionId,
+    paths: paths ?? null,
+  });
+}
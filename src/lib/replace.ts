@@ -0,0 +1,82 @@
+/**
+ * IPC wrappers for Forge-wide find and replace.
+ *
+ * A replace is previewed first: `previewReplace` lists each note it would
+ * change with a unified diff and the hash of the file it was computed from.
+ * `applyReplace` then writes only the picked notes that still match that
+ * hash, and returns a batch id that `undoReplace` uses to put them back.
+ * Locked notes are never changed, and frontmatter only when
+ * `includeFrontmatter` is set. See src-tauri/src/commands/replace.rs.
+ */
+
+import { safeInvoke as invoke } from './ipc';
+import type { RestoredNote } from './agents';
+
+/** What to replace, and where. Send the same request to preview and apply. */
+export interface ReplaceRequest {
+  find: string;
+  /** With `regex`, `$1` or `${name}` insert capture groups. */
+  replace: string;
+  regex?: boolean;
+  caseSensitive?: boolean;
+  wholeWord?: boolean;
+  /** Forge-relative folder, e.g. "notes/Projects" or "daily"; the whole Forge when omitted. */
+  folder?: string | null;
+  includeFrontmatter?: boolean;
+}
+
+export interface NotePreview {
+  relPath: string;
+  replacements: number;
+  /** Hash of the file as previewed; pass it back to apply the note. */
+  fileHash: string;
+  diff: string;
+}
+
+export interface ReplacePreview {
+  notes: NotePreview[];
+  /** Locked notes in scope, which are never changed. */
+  locked: string[];
+}
+
+export interface ReplaceOutcome {
+  /** Undoes the applied notes; null when nothing was written. */
+  batchId: string | null;
+  applied: { relPath: string; replacements: number }[];
+  /** Notes left alone, e.g. because they changed since the preview or are locked. */
+  skipped: { relPath: string; reason: string }[];
+}
+
+/** An applied replace that can still be undone. */
+export interface ReplaceBatch {
+  batchId: string;
+  appliedAtMs: number;
+  find: string;
+  replace: string;
+  notes: string[];
+}
+
+export async function previewReplace(request: ReplaceRequest): Promise<ReplacePreview> {
+  return invoke<ReplacePreview>('preview_replace', { request });
+}
+
+/** Write the notes picked from a preview of the same request. */
+export async function applyReplace(
+  request: ReplaceRequest,
+  notes: Pick<NotePreview, 'relPath' | 'fileHash'>[]
+): Promise<ReplaceOutcome> {
+  return invoke<ReplaceOutcome>('apply_replace', {
+    request,
+    notes: notes.map(({ relPath, fileHash }) => ({ relPath, fileHash })),
+  });
+}
+
+/** Replaces in the active Forge that can be undone, newest first. */
+export async function listReplaceBatches(): Promise<ReplaceBatch[]> {
+  return invoke<ReplaceBatch[]>('list_replace_batches');
+}
+
+/** Put back every note a replace changed. */
+export async function undoReplace(batchId: string): Promise<RestoredNote[]> {
+  return invoke<RestoredNote[]>('undo_replace', { batchId });
+}
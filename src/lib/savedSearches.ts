@@ -0,0 +1,51 @@
+/**
+ * IPC wrappers for named saved searches, stored per Forge.
+ *
+ * Each saved search keeps a query with its mode and match options. The
+ * backend lists them by name with a result count it caches until a note in
+ * the Forge changes. MCP clients run the same searches with
+ * `run_saved_search`. See src-tauri/src/commands/saved_searches.rs.
+ */
+
+import { safeInvoke as invoke } from './ipc';
+import type { ContentMatch, SearchMode, SearchOptions } from '@/stores/searchStore';
+
+/** Shape of the Rust `SavedSearch` struct (camelCase over IPC). */
+export interface SavedSearch {
+  id: string;
+  name: string;
+  query: string;
+  mode: SearchMode;
+  options: SearchOptions;
+  /** Notes the search finds (at most 500); absent when it can't run right now. */
+  resultCount?: number;
+}
+
+export interface SaveSearchInput {
+  name: string;
+  query: string;
+  mode: SearchMode;
+  options: SearchOptions;
+}
+
+/** The active Forge's saved searches, by name. */
+export async function listSavedSearches(): Promise<SavedSearch[]> {
+  return invoke<SavedSearch[]>('list_saved_searches');
+}
+
+export async function createSavedSearch(input: SaveSearchInput): Promise<SavedSearch> {
+  return invoke<SavedSearch>('create_saved_search', { input });
+}
+
+/** Change a saved search; it keeps its id. */
+export async function updateSavedSearch(id: string, input: SaveSearchInput): Promise<SavedSearch> {
+  return invoke<SavedSearch>('update_saved_search', { id, input });
+}
+
+export async function deleteSavedSearch(id: string): Promise<void> {
+  return invoke<void>('delete_saved_search', { id });
+}
+
+export async function runSavedSearch(id: string, maxResults: number): Promise<ContentMatch[]> {
+  return invoke<ContentMatch[]>('run_saved_search', { id, maxResults });
+}
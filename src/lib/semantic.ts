@@ -52,8 +52,20 @@ export interface SemanticHit {
   /** Forge-relative path, e.g. "notes/Projects/foo.md" or "daily/2026-07-12.md". */
   path: string;
   title: string;
-  /** Cosine similarity in [0, 1] — higher is closer. */
+  /** Cosine similarity in [0, 1] — higher is closer. Keyword and hybrid modes carry their own scores. */
   score: number;
+  /** Set when `semantic_search` ran in keyword or hybrid mode. */
+  signals?: ('keyword' | 'semantic')[];
+  snippet?: string;
+  /** Semantic mode: the note's passage closest to the query, absent if the note changed since it was indexed. */
+  passage?: SemanticPassage;
+}
+
+/** A ~350-word window of a note body and the body lines (1-based, inclusive) it spans. */
+export interface SemanticPassage {
+  text: string;
+  startLine: number;
+  endLine: number;
 }
 
 /** Payload of the `semantic:progress` event. */
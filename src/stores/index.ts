@@ -67,7 +67,7 @@ export { useTrashStore } from './trashStore';
 export { useTagStore } from './tagStore';
 export { useTaskStatusStore } from './taskStatusStore';
 export { useSearchStore } from './searchStore';
-export type { ContentMatch, SearchMode } from './searchStore';
+export type { ContentMatch, SearchMode, SearchOptions } from './searchStore';
 export { useSemanticStore } from './semanticStore';
 export { useTimelineStore } from './timelineStore';
 export { useGraphStore } from './graphStore';
@@ -12,6 +12,7 @@ const keywordHit = {
   snippet: 'foo bar',
   lineNumber: 1,
   matchCount: 1,
+  score: 0.42,
   isDaily: false,
   isWeekly: false,
   folderPath: null,
@@ -30,9 +31,11 @@ describe('searchStore semantic mode', () => {
     useSearchStore.setState({
       query: '',
       mode: 'keyword',
+      options: { foldDiacritics: false, fuzzy: false, regex: false, wholeWord: false },
       results: [],
       semanticResults: [],
       loading: false,
+      error: null,
       selectedIndex: 0,
     });
   });
@@ -52,6 +55,58 @@ describe('searchStore semantic mode', () => {
     expect(useSearchStore.getState().results).toEqual([keywordHit]);
   });
 
+  it('hybrid mode queries search_notes_content with its mode into results', async () => {
+    useSearchStore.setState({ mode: 'hybrid' });
+    const hybridHit = { ...keywordHit, signals: ['keyword', 'semantic'] };
+    mockInvoke.mockResolvedValueOnce([hybridHit]);
+    useSearchStore.getState().setQuery('PROJ-4821');
+    await flushDebounce();
+    expect(mockInvoke).toHaveBeenCalledWith(
+      'search_notes_content',
+      expect.objectContaining({ query: 'PROJ-4821', mode: 'hybrid' })
+    );
+    expect(useSearchStore.getState().results).toEqual([hybridHit]);
+    expect(useSearchStore.getState().semanticResults).toEqual([]);
+  });
+
+  it('setOptions re-runs the query with the new matching options', async () => {
+    mockInvoke.mockResolvedValue([keywordHit]);
+    useSearchStore.getState().setQuery('cafe');
+    await flushDebounce();
+    expect(mockInvoke).toHaveBeenLastCalledWith(
+      'search_notes_content',
+      expect.objectContaining({
+        options: { foldDiacritics: false, fuzzy: false, regex: false, wholeWord: false },
+      })
+    );
+
+    useSearchStore.getState().setOptions({ foldDiacritics: true });
+    await flushDebounce();
+    expect(mockInvoke).toHaveBeenLastCalledWith(
+      'search_notes_content',
+      expect.objectContaining({
+        query: 'cafe',
+        options: { foldDiacritics: true, fuzzy: false, regex: false, wholeWord: false },
+      })
+    );
+  });
+
+  it('keeps the reason a keyword query was rejected', async () => {
+    mockInvoke.mockRejectedValueOnce('OR needs a term on both sides');
+    useSearchStore.getState().setQuery('foo OR');
+    await flushDebounce();
+    expect(useSearchStore.getState()).toMatchObject({
+      results: [],
+      loading: false,
+      error: 'OR needs a term on both sides',
+    });
+
+    mockInvoke.mockResolvedValueOnce([keywordHit]);
+    useSearchStore.getState().setQuery('foo OR bar');
+    await flushDebounce();
+    expect(useSearchStore.getState().error).toBeNull();
+  });
+
   it('semantic mode queries semantic_search into semanticResults', async () => {
     useSearchStore.setState({ mode: 'semantic' });
     mockInvoke.mockResolvedValueOnce([semanticHit]);
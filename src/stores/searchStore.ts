@@ -7,6 +7,15 @@
 import { create } from 'zustand';
 import { safeInvoke as invoke } from '@/lib/ipc';
 import { SEMANTIC_SEARCH_LIMIT, type SemanticHit } from '@/lib/semantic';
+import {
+  createSavedSearch,
+  deleteSavedSearch,
+  listSavedSearches,
+  type SavedSearch,
+} from '@/lib/savedSearches';
+
+/** A search that found a hybrid or semantic result. */
+export type SearchSignal = 'keyword' | 'semantic';
 
 /**
  * One hit returned by the backend `search_notes_content` command.
@@ -19,34 +28,68 @@ export interface ContentMatch {
   snippet: string;
   lineNumber: number;
   matchCount: number;
+  /** BM25 relevance; results arrive sorted by it, best first. */
+  score: number;
   isDaily: boolean;
   isWeekly: boolean;
   folderPath: string | null;
+  /** Which searches found the note; absent for keyword results. */
+  signals?: SearchSignal[];
+  /** `[start, end)` UTF-16 offsets of the matched text within `snippet`. */
+  highlights?: [number, number][];
+  /** `[start, end)` byte offsets of every match on line `lineNumber`. */
+  spans?: [number, number][];
+}
+
+/** How keyword search compares query words with notes; mirrors Rust `SearchOptions`. */
+export interface SearchOptions {
+  /** Ignore accents, so "cafe" finds "Café". */
+  foldDiacritics: boolean;
+  /** Let each word match a word a typo or two away. */
+  fuzzy: boolean;
+  /** Read the whole query as one regular expression. */
+  regex: boolean;
+  /** Only match at word boundaries, so "log" doesn't find "login". */
+  wholeWord: boolean;
 }
 
 /**
- * Which engine backs the sidebar search: classic full-text keyword matching
- * or the local semantic (embeddings) index. Semantic mode is only offered
- * by the UI when the semantic index is ready.
+ * Which engine backs the sidebar search: classic full-text keyword matching,
+ * the local semantic (embeddings) index, or both fused into one ranking.
+ * Semantic and hybrid modes are only offered by the UI when the semantic
+ * index is ready.
  */
-export type SearchMode = 'keyword' | 'semantic';
+export type SearchMode = 'keyword' | 'semantic' | 'hybrid';
 
 interface SearchState {
   query: string;
   mode: SearchMode;
+  options: SearchOptions;
   results: ContentMatch[];
   semanticResults: SemanticHit[];
   loading: boolean;
+  /** Why the last keyword query failed, e.g. a query-syntax error. */
+  error: string | null;
   /** Index into the active result list for arrow-key navigation. */
   selectedIndex: number;
   setQuery: (query: string) => void;
   /** Switch engines; re-runs the current query under the new mode. */
   setMode: (mode: SearchMode) => void;
+  /** Change how keyword words match; re-runs the current query. */
+  setOptions: (options: Partial<SearchOptions>) => void;
   clear: () => void;
   /** Runs the debounced search. Call from the input onChange path. */
   runSearch: (query: string) => void;
   setSelectedIndex: (index: number) => void;
   moveSelection: (delta: number) => void;
+  /** The active Forge's saved searches, by name, with result counts. */
+  savedSearches: SavedSearch[];
+  loadSavedSearches: () => Promise<void>;
+  /** Save the current query, mode, and options under `name`. */
+  saveSearch: (name: string) => Promise<void>;
+  deleteSavedSearch: (id: string) => Promise<void>;
+  /** Load a saved search into the search box and run it. */
+  applySavedSearch: (search: SavedSearch) => void;
 }
 
 const DEBOUNCE_MS = 150;
@@ -59,10 +102,13 @@ let inflightToken = 0;
 export const useSearchStore = create<SearchState>((set, get) => ({
   query: '',
   mode: 'keyword',
+  options: { foldDiacritics: false, fuzzy: false, regex: false, wholeWord: false },
   results: [],
   semanticResults: [],
   loading: false,
+  error: null,
   selectedIndex: 0,
+  savedSearches: [],
 
   setQuery: (query) => {
     set({ query });
@@ -75,6 +121,11 @@ export const useSearchStore = create<SearchState>((set, get) => ({
     get().runSearch(get().query);
   },
 
+  setOptions: (options) => {
+    set({ options: { ...get().options, ...options } });
+    get().runSearch(get().query);
+  },
+
   clear: () => {
     if (debounceTimer) {
       clearTimeout(debounceTimer);
@@ -83,7 +134,14 @@ export const useSearchStore = create<SearchState>((set, get) => ({
     // Invalidate any in-flight search so its result can't overwrite the
     // cleared state if it finishes after `clear()`.
     inflightToken += 1;
-    set({ query: '', results: [], semanticResults: [], loading: false, selectedIndex: 0 });
+    set({
+      query: '',
+      results: [],
+      semanticResults: [],
+      loading: false,
+      error: null,
+      selectedIndex: 0,
+    });
   },
 
   runSearch: (query) => {
@@ -95,7 +153,7 @@ export const useSearchStore = create<SearchState>((set, get) => ({
     const trimmed = query.trim();
     if (!trimmed) {
       inflightToken += 1;
-      set({ results: [], semanticResults: [], loading: false, selectedIndex: 0 });
+      set({ results: [], semanticResults: [], loading: false, error: null, selectedIndex: 0 });
       return;
     }
 
@@ -110,19 +168,27 @@ export const useSearchStore = create<SearchState>((set, get) => ({
             limit: SEMANTIC_SEARCH_LIMIT,
           });
           if (token !== inflightToken) return;
-          set({ semanticResults, loading: false, selectedIndex: 0 });
+          set({ semanticResults, loading: false, error: null, selectedIndex: 0 });
         } else {
           const results = await invoke<ContentMatch[]>('search_notes_content', {
             query: trimmed,
             maxResults: MAX_RESULTS,
+            mode: get().mode,
+            options: get().options,
           });
           if (token !== inflightToken) return;
-          set({ results, loading: false, selectedIndex: 0 });
+          set({ results, loading: false, error: null, selectedIndex: 0 });
         }
       } catch (error) {
         if (token !== inflightToken) return;
         console.error('[searchStore] search failed:', error);
-        set({ results: [], semanticResults: [], loading: false, selectedIndex: 0 });
+        set({
+          results: [],
+          semanticResults: [],
+          loading: false,
+          error: error instanceof Error ? error.message : String(error),
+          selectedIndex: 0,
+        });
       }
     }, DEBOUNCE_MS);
   },
@@ -136,4 +202,35 @@ export const useSearchStore = create<SearchState>((set, get) => ({
     const next = Math.max(0, Math.min(count - 1, selectedIndex + delta));
     set({ selectedIndex: next });
   },
+
+  loadSavedSearches: async () => {
+    try {
+      set({ savedSearches: await listSavedSearches() });
+    } catch (error) {
+      console.error('[searchStore] loading saved searches failed:', error);
+    }
+  },
+
+  saveSearch: async (name) => {
+    const { query, mode, options } = get();
+    await createSavedSearch({ name, query: query.trim(), mode, options });
+    await get().loadSavedSearches();
+  },
+
+  deleteSavedSearch: async (id) => {
+    await deleteSavedSearch(id);
+    await get().loadSavedSearches();
+  },
+
+  applySavedSearch: (search) => {
+    set({
+      query: search.query,
+      mode: search.mode,
+      options: search.options,
+      results: [],
+      semanticResults: [],
+      selectedIndex: 0,
+    });
+    get().runSearch(search.query);
+  },
 }));